@@ -0,0 +1,121 @@
+//! 传输性能基准测试
+//!
+//! 通过 [`MockCos`] 在本地启动模拟 COS 服务器，用 criterion 测量：
+//!
+//! - 普通上传与分块上传的吞吐量
+//! - 请求签名（预签名 URL）的耗时
+//! - 每次传输的内存峰值（通过计数分配器统计，在 criterion 的结果之后输出；
+//!   模拟服务器运行在同一进程中，峰值包含它保存的分块与对象）
+//!
+//! 运行方式：`cargo bench --features bench --bench transfer`，
+//! 可用 `--save-baseline` / `--baseline` 比较修改前后的结果。
+
+use cos_upload::testing::MockCos;
+use cos_upload::{Presigner, Uploader};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hint::black_box;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+const MB: usize = 1024 * 1024;
+
+/// 参与测量的上传：名称与文件大小，分别走普通上传与分块上传
+const UPLOADS: [(&str, usize); 2] = [("simple", 4 * MB), ("multipart", 32 * MB)];
+
+/// 统计当前与峰值堆内存占用的分配器
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+/// 从当前占用开始重新统计峰值，返回当前占用
+fn reset_peak() -> usize {
+    let current = ALLOCATED.load(Ordering::Relaxed);
+    PEAK.store(current, Ordering::Relaxed);
+    current
+}
+
+fn temp_file(size: usize) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(&vec![0x5a; size]).unwrap();
+    file
+}
+
+/// 每次都上传到同一个对象键，模拟服务器中只保留一份内容
+async fn upload(uploader: &Uploader, path: &Path, name: &str) {
+    uploader
+        .upload_file(path, &format!("bench/{}", name), None)
+        .await
+        .unwrap();
+}
+
+fn bench_uploads(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mock = runtime.block_on(MockCos::start()).unwrap();
+    let uploader = mock.uploader();
+
+    let mut group = c.benchmark_group("upload");
+    group.sample_size(10);
+    let mut peaks = Vec::new();
+    for (name, size) in UPLOADS {
+        let file = temp_file(size);
+        // 预热并建立连接后，单独测量一次传输的内存峰值
+        runtime.block_on(upload(&uploader, file.path(), name));
+        let baseline = reset_peak();
+        runtime.block_on(upload(&uploader, file.path(), name));
+        peaks.push((name, PEAK.load(Ordering::Relaxed).saturating_sub(baseline)));
+
+        group.throughput(Throughput::Bytes(size as u64));
+        group.bench_with_input(BenchmarkId::new(name, size / MB), &file, |b, file| {
+            b.to_async(&runtime)
+                .iter(|| upload(&uploader, file.path(), name));
+        });
+    }
+    group.finish();
+
+    for (name, peak) in peaks {
+        println!(
+            "upload/{:<12} 每次传输的内存峰值 {:>7.1} MB",
+            name,
+            peak as f64 / MB as f64
+        );
+    }
+}
+
+fn bench_signing(c: &mut Criterion) {
+    let runtime = Runtime::new().unwrap();
+    let mock = runtime.block_on(MockCos::start()).unwrap();
+    let presigner = Presigner::new(mock.config());
+
+    c.bench_function("presign_url", |b| {
+        b.iter(|| {
+            black_box(presigner.presign_url("PUT", "bench/object", Duration::from_secs(600)))
+        });
+    });
+}
+
+criterion_group!(benches, bench_uploads, bench_signing);
+criterion_main!(benches);
@@ -0,0 +1,366 @@
+use crate::datetime::parse_http_date;
+use crate::list::ListOptions;
+use crate::request::{header_of, CosRequest};
+use crate::scoped::check_relative_key;
+use crate::types::crc64_of;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use tar::{EntryType, Header};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tracing::info;
+
+/// tar 的块大小
+const BLOCK_SIZE: usize = 512;
+
+/// 要打包的对象
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArchiveSelection {
+    /// 按给定的顺序打包这些对象
+    Keys(Vec<String>),
+    /// 打包该前缀下的所有对象，跳过以 `/` 结尾的目录占位对象
+    Prefix(String),
+}
+
+/// 对象内容之后补齐到整块的字节数
+fn padding(size: u64) -> usize {
+    (BLOCK_SIZE - (size % BLOCK_SIZE as u64) as usize) % BLOCK_SIZE
+}
+
+/// 生成对象在 tar 中的头部
+///
+/// 对象键超过 tar 头部的 100 字节限制时，先输出一个 GNU 长文件名条目。
+fn entry_header(object_key: &str, size: u64, mtime: u64) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(BLOCK_SIZE);
+    let mut header = Header::new_gnu();
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(mtime);
+    header.set_entry_type(EntryType::Regular);
+
+    let name = object_key.as_bytes();
+    if header.set_path(object_key).is_err() {
+        let max = header.as_old().name.len();
+        if name.len() < max {
+            return Err(anyhow!("对象键无法写入 tar: {}", object_key));
+        }
+
+        let mut long_name = Header::new_gnu();
+        long_name.set_path("././@LongLink")?;
+        long_name.set_size(name.len() as u64 + 1);
+        long_name.set_mode(0o644);
+        long_name.set_entry_type(EntryType::GNULongName);
+        long_name.set_cksum();
+        out.extend_from_slice(long_name.as_bytes());
+        out.extend_from_slice(name);
+        out.push(0);
+        out.resize(out.len() + padding(name.len() as u64 + 1), 0);
+
+        // 解压工具使用长文件名条目，头部中的名称只是截断后的提示
+        header.as_old_mut().name.copy_from_slice(&name[..max]);
+    }
+    header.set_cksum();
+    out.extend_from_slice(header.as_bytes());
+    Ok(out)
+}
+
+impl Uploader {
+    /// 把一组对象打包为 tar 流式写出，不在本地暂存
+    ///
+    /// 逐个下载对象并直接写入 `writer`，适合 Web 服务提供“下载全部文件”：把 HTTP 响应体作为
+    /// `writer`，内存占用与对象大小无关。对象在 tar 中的路径即对象键，修改时间取自 `Last-Modified`。
+    ///
+    /// 每个对象写完后校验 CRC64。由于 tar 头部已经写出，失败时 `writer` 中会留下不完整的归档，
+    /// 调用方应把错误视为整个归档失败（例如中断 HTTP 响应）。
+    ///
+    /// # 参数
+    ///
+    /// * `selection` - 要打包的对象，对象键必须能作为相对路径使用（不能包含 `..` 等路径段）
+    /// * `writer` - 归档的写入目标
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回打包的对象数量
+    ///
+    /// # 错误
+    ///
+    /// 对象键不能作为路径、请求失败、写入失败或 CRC64 不一致时返回错误。
+    pub async fn download_as_tar<W: AsyncWrite + Unpin>(
+        &self,
+        selection: &ArchiveSelection,
+        writer: &mut W,
+    ) -> Result<usize> {
+        let keys = self.archive_keys(selection).await?;
+        for key in &keys {
+            self.append_tar_entry(key, writer).await?;
+        }
+        // 归档以两个全零块结尾
+        writer.write_all(&[0; BLOCK_SIZE * 2]).await?;
+        writer.flush().await?;
+
+        info!("打包 {} 个对象为 tar", keys.len());
+        Ok(keys.len())
+    }
+
+    /// 把一组对象打包为 zip 流式写出，不在本地暂存
+    ///
+    /// 与 [`Uploader::download_as_tar`] 相同，逐个下载对象并以 deflate 压缩后直接写入 `writer`；
+    /// 条目使用数据描述符记录 CRC32 与长度，无需回写已经输出的头部，因此 `writer` 不需要支持 seek。
+    /// 对象在 zip 中的路径即对象键，修改时间取自 `Last-Modified`（zip 只能表示 1980 至 2107 年）。
+    /// 需要启用 `zip` feature。
+    ///
+    /// # 参数
+    ///
+    /// * `selection` - 要打包的对象，对象键必须能作为相对路径使用（不能包含 `..` 等路径段）
+    /// * `writer` - 归档的写入目标
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回打包的对象数量
+    ///
+    /// # 错误
+    ///
+    /// 对象键不能作为路径、请求失败、压缩或写入失败、CRC64 不一致时返回错误，
+    /// 失败时 `writer` 中会留下不完整的归档。
+    #[cfg(feature = "zip")]
+    pub async fn download_as_zip<W: AsyncWrite + Unpin>(
+        &self,
+        selection: &ArchiveSelection,
+        writer: &mut W,
+    ) -> Result<usize> {
+        let keys = self.archive_keys(selection).await?;
+        let buffer = ZipBuffer::default();
+        let mut zip = zip::ZipWriter::new_stream(buffer.clone());
+        for key in &keys {
+            self.append_zip_entry(key, &mut zip, &buffer, writer)
+                .await?;
+        }
+        // 写出中央目录
+        zip.finish()?;
+        buffer.drain_to(writer).await?;
+        writer.flush().await?;
+
+        info!("打包 {} 个对象为 zip", keys.len());
+        Ok(keys.len())
+    }
+
+    /// 得到要打包的对象键，并检查它们都能作为相对路径使用
+    async fn archive_keys(&self, selection: &ArchiveSelection) -> Result<Vec<String>> {
+        let keys = match selection {
+            ArchiveSelection::Keys(keys) => keys.clone(),
+            ArchiveSelection::Prefix(prefix) => self.list_archive_keys(prefix).await?,
+        };
+        for key in &keys {
+            check_relative_key(key)?;
+        }
+        Ok(keys)
+    }
+
+    /// 列出前缀下要打包的对象键
+    async fn list_archive_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        let opts = ListOptions::new(prefix);
+        let mut cursor = None;
+        let mut keys = Vec::new();
+
+        loop {
+            let page = self.list_objects(&opts, cursor.as_ref()).await?;
+            keys.extend(
+                page.items
+                    .into_iter()
+                    .map(|object| object.key)
+                    .filter(|key| !key.ends_with('/')),
+            );
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => return Ok(keys),
+            }
+        }
+    }
+
+    /// 下载一个对象并作为一个 tar 条目写出
+    async fn append_tar_entry<W: AsyncWrite + Unpin>(
+        &self,
+        object_key: &str,
+        writer: &mut W,
+    ) -> Result<()> {
+        let mut response = self
+            .execute(CosRequest::new(Method::GET, object_key))
+            .await?;
+        let size = response
+            .content_length()
+            .ok_or_else(|| anyhow!("下载响应中缺少 Content-Length: {}", object_key))?;
+        let mtime = header_of(&response, "Last-Modified")
+            .and_then(|value| parse_http_date(&value))
+            .map_or(0, |time| time.timestamp().max(0) as u64);
+        let crc = crc64_of(response.headers());
+
+        writer
+            .write_all(&entry_header(object_key, size, mtime)?)
+            .await?;
+
+        let mut crc64 = self.hash_backend.crc64();
+        let mut written = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            crc64.update(&chunk);
+            writer.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        if written != size {
+            return Err(anyhow!(
+                "对象长度与 Content-Length 不一致: {} ({} / {})",
+                object_key,
+                written,
+                size
+            ));
+        }
+        self.check_crc64(object_key, crc64.finish(), crc)?;
+
+        writer.write_all(&[0; BLOCK_SIZE][..padding(size)]).await?;
+        Ok(())
+    }
+
+    /// 下载一个对象并作为一个 zip 条目写出
+    #[cfg(feature = "zip")]
+    async fn append_zip_entry<W: AsyncWrite + Unpin>(
+        &self,
+        object_key: &str,
+        zip: &mut zip::ZipWriter<zip::write::StreamWriter<ZipBuffer>>,
+        buffer: &ZipBuffer,
+        writer: &mut W,
+    ) -> Result<()> {
+        use std::io::Write;
+
+        let mut response = self
+            .execute(CosRequest::new(Method::GET, object_key))
+            .await?;
+        let size = response
+            .content_length()
+            .ok_or_else(|| anyhow!("下载响应中缺少 Content-Length: {}", object_key))?;
+        let mtime = header_of(&response, "Last-Modified")
+            .and_then(|value| parse_http_date(&value))
+            .and_then(|time| zip_time(&time))
+            .unwrap_or_default();
+        let crc = crc64_of(response.headers());
+
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .last_modified_time(mtime)
+            .unix_permissions(0o644)
+            .large_file(size >= u64::from(u32::MAX));
+        zip.start_file(object_key, options)?;
+
+        let mut crc64 = self.hash_backend.crc64();
+        let mut written = 0u64;
+        while let Some(chunk) = response.chunk().await? {
+            crc64.update(&chunk);
+            zip.write_all(&chunk)?;
+            written += chunk.len() as u64;
+            buffer.drain_to(writer).await?;
+        }
+        if written != size {
+            return Err(anyhow!(
+                "对象长度与 Content-Length 不一致: {} ({} / {})",
+                object_key,
+                written,
+                size
+            ));
+        }
+        self.check_crc64(object_key, crc64.finish(), crc)?;
+        Ok(())
+    }
+}
+
+/// zip 写入器的输出缓冲
+///
+/// zip 写入器只支持同步的 `Write`，先写入缓冲，每写完一段数据再转写到异步的 `writer`。
+#[cfg(feature = "zip")]
+#[derive(Clone, Default)]
+struct ZipBuffer(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+#[cfg(feature = "zip")]
+impl ZipBuffer {
+    /// 把缓冲中的数据写入 `writer` 并清空缓冲
+    async fn drain_to<W: AsyncWrite + Unpin>(&self, writer: &mut W) -> Result<()> {
+        let data = std::mem::take(&mut *self.0.lock().unwrap());
+        if !data.is_empty() {
+            writer.write_all(&data).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "zip")]
+impl std::io::Write for ZipBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// 把时间转换为 zip 条目的修改时间，超出 zip 能表示的范围时返回 `None`
+#[cfg(feature = "zip")]
+fn zip_time(time: &chrono::DateTime<chrono::Utc>) -> Option<zip::DateTime> {
+    use chrono::{Datelike, Timelike};
+
+    zip::DateTime::from_date_and_time(
+        u16::try_from(time.year()).ok()?,
+        time.month() as u8,
+        time.day() as u8,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_entry_header() {
+        let long_key = format!("{}/file.txt", "d".repeat(120));
+        let mut archive = Vec::new();
+        for (key, data) in [("a/b.txt", &b"hello"[..]), (long_key.as_str(), &b""[..])] {
+            archive.extend(entry_header(key, data.len() as u64, 1_700_000_000).unwrap());
+            archive.extend_from_slice(data);
+            archive.resize(archive.len() + padding(data.len() as u64), 0);
+        }
+        archive.resize(archive.len() + BLOCK_SIZE * 2, 0);
+
+        let mut reader = tar::Archive::new(&archive[..]);
+        let mut entries = reader.entries().unwrap();
+
+        let mut first = entries.next().unwrap().unwrap();
+        assert_eq!(first.path().unwrap().to_str(), Some("a/b.txt"));
+        assert_eq!(first.header().mtime().unwrap(), 1_700_000_000);
+        let mut content = String::new();
+        first.read_to_string(&mut content).unwrap();
+        assert_eq!(content, "hello");
+
+        let second = entries.next().unwrap().unwrap();
+        assert_eq!(second.path().unwrap().to_str(), Some(long_key.as_str()));
+        assert!(entries.next().is_none());
+    }
+
+    #[cfg(feature = "zip")]
+    #[test]
+    fn test_zip_time() {
+        let time = crate::datetime::parse_http_date("Tue, 14 Nov 2023 22:13:21 GMT").unwrap();
+        let converted = zip_time(&time).unwrap();
+        assert_eq!(converted.year(), 2023);
+        assert_eq!(converted.month(), 11);
+        assert_eq!(converted.day(), 14);
+        assert_eq!(converted.hour(), 22);
+        // zip 的秒数精度为 2 秒
+        assert_eq!(converted.second(), 20);
+
+        let old = crate::datetime::parse_http_date("Thu, 01 Jan 1970 00:00:00 GMT").unwrap();
+        assert!(zip_time(&old).is_none());
+    }
+}
@@ -0,0 +1,437 @@
+use crate::error::{map_already_exists, CosError};
+use crate::list::{Cursor, ListOptions, ObjectSummary};
+use crate::options::UploadOptions;
+use crate::request::{read_xml, CosRequest};
+use crate::types::ObjectMetadata;
+use crate::uploader::{Uploader, FORBID_OVERWRITE_HEADER};
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::DateTime;
+use futures_util::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use object_store::path::Path;
+use object_store::{
+    Attribute, CopyMode, CopyOptions, GetOptions, GetResult, GetResultPayload, ListResult,
+    MultipartUpload, ObjectMeta, PutMode, PutMultipartOptions, PutOptions, PutPayload, PutResult,
+    UploadPart,
+};
+use reqwest::Method;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// 错误中使用的存储名称
+const STORE: &str = "COS";
+
+/// 把本 crate 的错误转换为 `object_store` 的错误，404、403、对象已存在与前置条件失败映射为对应的变体
+fn store_error(error: anyhow::Error, path: &str) -> object_store::Error {
+    let path = path.to_string();
+    match error.downcast_ref::<CosError>() {
+        Some(CosError::Service { status: 404, .. }) => object_store::Error::NotFound {
+            path,
+            source: error.into(),
+        },
+        Some(CosError::Service { status: 403, .. }) => object_store::Error::PermissionDenied {
+            path,
+            source: error.into(),
+        },
+        Some(CosError::AlreadyExists { .. }) => object_store::Error::AlreadyExists {
+            path,
+            source: error.into(),
+        },
+        Some(CosError::PreconditionFailed { .. }) | Some(CosError::Service { status: 412, .. }) => {
+            object_store::Error::Precondition {
+                path,
+                source: error.into(),
+            }
+        }
+        _ => object_store::Error::Generic {
+            store: STORE,
+            source: error.into(),
+        },
+    }
+}
+
+/// `object_store` 的前缀按路径段匹配，`a/b` 只匹配 `a/b/` 之下的对象
+fn list_prefix(prefix: Option<&Path>) -> String {
+    match prefix {
+        Some(prefix) if !prefix.as_ref().is_empty() => format!("{}/", prefix),
+        _ => String::new(),
+    }
+}
+
+fn summary_meta(object: ObjectSummary) -> object_store::Result<ObjectMeta> {
+    Ok(ObjectMeta {
+        location: Path::parse(&object.key)?,
+        last_modified: object.last_modified.unwrap_or(DateTime::UNIX_EPOCH),
+        size: object.size,
+        e_tag: object.etag,
+        version: None,
+    })
+}
+
+fn head_meta(location: &Path, metadata: &ObjectMetadata) -> ObjectMeta {
+    ObjectMeta {
+        location: location.clone(),
+        last_modified: metadata.last_modified.unwrap_or(DateTime::UNIX_EPOCH),
+        size: metadata.content_length.unwrap_or(0),
+        e_tag: metadata.etag.clone(),
+        version: None,
+    }
+}
+
+/// 以 [`Uploader`] 实现的 `object_store::ObjectStore`（需要启用 `object_store` feature）
+///
+/// 可以直接交给 DataFusion、Parquet 等 Arrow 生态的工具读写 COS，签名、端点选择、
+/// CRC64 校验与对象缓存都沿用上传器的设置。对象路径即对象键（不以 `/` 开头）。
+///
+/// 不支持的能力：读取或写入指定版本、对象标签与除 Content-Type 以外的属性；
+/// 条件读取在 `HEAD` 得到的元数据上判断。
+#[derive(Clone)]
+pub struct CosObjectStore {
+    uploader: Uploader,
+}
+
+impl CosObjectStore {
+    /// 基于上传器创建
+    pub fn new(uploader: Uploader) -> Self {
+        Self { uploader }
+    }
+
+    /// 内部使用的上传器
+    pub fn uploader(&self) -> &Uploader {
+        &self.uploader
+    }
+}
+
+impl fmt::Debug for CosObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CosObjectStore")
+            .field("bucket", &self.uploader.config.bucket)
+            .field("region", &self.uploader.config.region)
+            .finish()
+    }
+}
+
+impl fmt::Display for CosObjectStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CosObjectStore({})", self.uploader.config.bucket)
+    }
+}
+
+#[async_trait]
+impl object_store::ObjectStore for CosObjectStore {
+    async fn put_opts(
+        &self,
+        location: &Path,
+        payload: PutPayload,
+        opts: PutOptions,
+    ) -> object_store::Result<PutResult> {
+        let key = location.as_ref();
+        let content_type = match opts.attributes.get(&Attribute::ContentType) {
+            Some(content_type) => content_type.to_string(),
+            None => mime_guess::from_path(key)
+                .first_or_octet_stream()
+                .to_string(),
+        };
+        let data = Bytes::from(payload);
+
+        let result = match opts.mode {
+            PutMode::Overwrite => self.uploader.put_bytes(key, data, &content_type, &[]).await,
+            PutMode::Create => self
+                .uploader
+                .put_bytes(
+                    key,
+                    data,
+                    &content_type,
+                    &[(FORBID_OVERWRITE_HEADER, "true")],
+                )
+                .await
+                .map_err(|e| map_already_exists(e, key)),
+            PutMode::Update(version) => {
+                let Some(etag) = version.e_tag else {
+                    return Err(object_store::Error::NotImplemented {
+                        operation: "不带 ETag 的条件写入".to_string(),
+                        implementer: self.to_string(),
+                    });
+                };
+                self.uploader
+                    .put_bytes_if_match(key, Some(&etag), data, &content_type, &[])
+                    .await
+            }
+        }
+        .map_err(|e| store_error(e, key))?;
+
+        Ok(PutResult {
+            e_tag: result.etag,
+            version: None,
+            extensions: Default::default(),
+        })
+    }
+
+    async fn put_multipart_opts(
+        &self,
+        location: &Path,
+        _opts: PutMultipartOptions,
+    ) -> object_store::Result<Box<dyn MultipartUpload>> {
+        let key = location.to_string();
+        let upload_id = self
+            .uploader
+            .init_multipart_upload(&key, &UploadOptions::default())
+            .await
+            .map_err(|e| store_error(e, &key))?;
+
+        Ok(Box::new(CosMultipartUpload {
+            uploader: self.uploader.clone(),
+            object_key: key,
+            upload_id,
+            next_part: 1,
+            parts: Arc::default(),
+        }))
+    }
+
+    async fn get_opts(
+        &self,
+        location: &Path,
+        options: GetOptions,
+    ) -> object_store::Result<GetResult> {
+        let key = location.as_ref();
+        if options.version.is_some() {
+            return Err(object_store::Error::NotImplemented {
+                operation: "读取指定版本".to_string(),
+                implementer: self.to_string(),
+            });
+        }
+
+        let metadata = self
+            .uploader
+            .get_object_metadata(key)
+            .await
+            .map_err(|e| store_error(e, key))?;
+        let meta = head_meta(location, &metadata);
+        options.check_preconditions(&meta)?;
+
+        let range = match &options.range {
+            Some(range) => range
+                .as_range(meta.size)
+                .map_err(|e| object_store::Error::Generic {
+                    store: STORE,
+                    source: e.into(),
+                })?,
+            None => 0..meta.size,
+        };
+        let payload = if options.head || range.is_empty() {
+            stream::empty().boxed()
+        } else {
+            let requested = options.range.is_some().then(|| range.clone());
+            let data = self
+                .uploader
+                .get_object_bytes(key, requested)
+                .await
+                .map_err(|e| store_error(e, key))?;
+            stream::once(async move { Ok(data) }).boxed()
+        };
+
+        let mut attributes = object_store::Attributes::new();
+        if let Some(content_type) = metadata.content_type {
+            attributes.insert(Attribute::ContentType, content_type.into());
+        }
+        Ok(GetResult {
+            payload: GetResultPayload::Stream(payload),
+            meta,
+            range,
+            attributes,
+            extensions: Default::default(),
+        })
+    }
+
+    fn delete_stream(
+        &self,
+        locations: BoxStream<'static, object_store::Result<Path>>,
+    ) -> BoxStream<'static, object_store::Result<Path>> {
+        let uploader = self.uploader.clone();
+        locations
+            .and_then(move |location| {
+                let uploader = uploader.clone();
+                async move {
+                    uploader
+                        .delete_object(location.as_ref())
+                        .await
+                        .map_err(|e| store_error(e, location.as_ref()))?;
+                    Ok(location)
+                }
+            })
+            .boxed()
+    }
+
+    fn list(&self, prefix: Option<&Path>) -> BoxStream<'static, object_store::Result<ObjectMeta>> {
+        let uploader = self.uploader.clone();
+        let opts = ListOptions::new(&list_prefix(prefix));
+
+        // 状态为下一页的游标，`None` 表示已经列举完
+        stream::try_unfold(Some(None::<Cursor>), move |cursor| {
+            let uploader = uploader.clone();
+            let opts = opts.clone();
+            async move {
+                let Some(cursor) = cursor else {
+                    return Ok::<_, object_store::Error>(None);
+                };
+                let page = uploader
+                    .list_objects(&opts, cursor.as_ref())
+                    .await
+                    .map_err(|e| store_error(e, &opts.prefix))?;
+                let objects: Vec<_> = page.items.into_iter().map(summary_meta).collect();
+                Ok(Some((stream::iter(objects), page.next.map(Some))))
+            }
+        })
+        .try_flatten()
+        .boxed()
+    }
+
+    async fn list_with_delimiter(&self, prefix: Option<&Path>) -> object_store::Result<ListResult> {
+        let prefix = list_prefix(prefix);
+        let opts = ListOptions::new(&prefix).with_delimiter("/");
+        let mut cursor = None;
+        let mut result = ListResult {
+            common_prefixes: Vec::new(),
+            objects: Vec::new(),
+            extensions: Default::default(),
+        };
+
+        loop {
+            let page = self
+                .uploader
+                .list_objects(&opts, cursor.as_ref())
+                .await
+                .map_err(|e| store_error(e, &prefix))?;
+            for common_prefix in page.common_prefixes {
+                result
+                    .common_prefixes
+                    .push(Path::parse(common_prefix.trim_end_matches('/'))?);
+            }
+            for object in page.items {
+                result.objects.push(summary_meta(object)?);
+            }
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => return Ok(result),
+            }
+        }
+    }
+
+    async fn copy_opts(
+        &self,
+        from: &Path,
+        to: &Path,
+        options: CopyOptions,
+    ) -> object_store::Result<()> {
+        let key = to.as_ref();
+        let mut request = CosRequest::new(Method::PUT, key).header(
+            "x-cos-copy-source",
+            self.uploader.copy_source(from.as_ref()),
+        );
+        if options.mode == CopyMode::Create {
+            request = request.header(FORBID_OVERWRITE_HEADER, "true");
+        }
+
+        let text = async {
+            let response = self.uploader.execute(request).await?;
+            read_xml(response).await
+        }
+        .await
+        .map_err(|e| store_error(map_already_exists(e, key), key))?;
+        // 复制请求可能在返回 200 的同时在响应体中携带错误
+        if text.contains("<Error>") {
+            return Err(object_store::Error::Generic {
+                store: STORE,
+                source: format!("复制对象失败: {} -> {}: {}", from, to, text).into(),
+            });
+        }
+        self.uploader.invalidate_cached(key);
+        Ok(())
+    }
+}
+
+/// `object_store` 的分块上传，分块编号按 [`MultipartUpload::put_part`] 的调用顺序分配
+struct CosMultipartUpload {
+    uploader: Uploader,
+    object_key: String,
+    upload_id: String,
+    next_part: u32,
+    parts: Arc<Mutex<Vec<(u32, String)>>>,
+}
+
+impl fmt::Debug for CosMultipartUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CosMultipartUpload")
+            .field("object_key", &self.object_key)
+            .field("upload_id", &self.upload_id)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl MultipartUpload for CosMultipartUpload {
+    fn put_part(&mut self, data: PutPayload) -> UploadPart {
+        let part_number = self.next_part;
+        self.next_part += 1;
+
+        let uploader = self.uploader.clone();
+        let object_key = self.object_key.clone();
+        let upload_id = self.upload_id.clone();
+        let parts = self.parts.clone();
+        Box::pin(async move {
+            let etag = uploader
+                .upload_part_bytes(&object_key, &upload_id, part_number, Bytes::from(data))
+                .await
+                .map_err(|e| store_error(e, &object_key))?;
+            parts.lock().unwrap().push((part_number, etag));
+            Ok(())
+        })
+    }
+
+    async fn complete(&mut self) -> object_store::Result<PutResult> {
+        let mut parts = self.parts.lock().unwrap().clone();
+        parts.sort_by_key(|(part_number, _)| *part_number);
+        let result = self
+            .uploader
+            .complete_multipart_upload(&self.object_key, &self.upload_id, &parts)
+            .await
+            .map_err(|e| store_error(e, &self.object_key))?;
+        self.uploader.invalidate_cached(&self.object_key);
+
+        Ok(PutResult {
+            e_tag: result.etag,
+            version: None,
+            extensions: Default::default(),
+        })
+    }
+
+    async fn abort(&mut self) -> object_store::Result<()> {
+        self.uploader
+            .abort_multipart_upload(&self.object_key, &self.upload_id)
+            .await
+            .map_err(|e| store_error(e, &self.object_key))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(list_prefix(None), "");
+        assert_eq!(list_prefix(Some(&Path::from("a/b"))), "a/b/");
+
+        let error = store_error(
+            CosError::Service {
+                status: 404,
+                code: "NoSuchKey".to_string(),
+                message: String::new(),
+                request_id: None,
+            }
+            .into(),
+            "a.txt",
+        );
+        assert!(matches!(error, object_store::Error::NotFound { path, .. } if path == "a.txt"));
+    }
+}
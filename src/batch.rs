@@ -0,0 +1,832 @@
+use crate::keymap::relative_key;
+use crate::manifest::{ManifestEntry, UploadManifest};
+use crate::request::{header_of, object_url_of, read_xml, CosRequest};
+use crate::types::{crc64_of, request_id_of, UploadResult};
+use crate::uploader::{Metadata, Uploader};
+use crate::xml::find_tag;
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, warn};
+
+/// 文件级重试的初始退避时间，之后每次翻倍
+const FILE_RETRY_BASE_DELAY: Duration = Duration::from_secs(1);
+/// 单个文件的最大尝试次数
+const FILE_MAX_ATTEMPTS: u32 = 3;
+/// 服务端复制（PUT Object - Copy）支持的最大对象大小
+const MAX_COPY_SIZE: u64 = 5 * 1024 * 1024 * 1024; // 5 GB
+
+/// 批量操作共享的重试预算
+///
+/// 批量中所有文件与分块的重试都从同一预算中扣除，预算耗尽后失败将不再重试，
+/// 避免注定失败的批量任务因为逐个文件重试而拖延数小时。
+#[derive(Debug)]
+pub struct RetryBudget {
+    remaining: AtomicU32,
+    used: AtomicU32,
+}
+
+impl RetryBudget {
+    /// 创建包含指定重试次数的预算
+    pub fn new(retries: u32) -> Self {
+        Self {
+            remaining: AtomicU32::new(retries),
+            used: AtomicU32::new(0),
+        }
+    }
+
+    /// 尝试消耗一次重试，预算耗尽时返回 `false`
+    pub fn try_acquire(&self) -> bool {
+        let acquired = self
+            .remaining
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |n| n.checked_sub(1))
+            .is_ok();
+        if acquired {
+            self.used.fetch_add(1, Ordering::Relaxed);
+        }
+        acquired
+    }
+
+    /// 剩余的重试次数
+    pub fn remaining(&self) -> u32 {
+        self.remaining.load(Ordering::Acquire)
+    }
+
+    /// 已使用的重试次数
+    pub fn used(&self) -> u32 {
+        self.used.load(Ordering::Relaxed)
+    }
+}
+
+/// 记录符号链接目标的自定义元数据名，即 `x-cos-meta-symlink-target`
+pub const SYMLINK_TARGET_METADATA: &str = "symlink-target";
+
+/// 目录中符号链接的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SymlinkPolicy {
+    /// 跳过符号链接
+    #[default]
+    Skip,
+    /// 跟随符号链接，上传其指向的文件或目录内容；对象键使用链接自身的路径，指向祖先目录的循环链接会被忽略
+    Follow,
+    /// 上传一个空对象，并把链接目标记录在 `x-cos-meta-symlink-target` 中
+    RecordAsMetadata,
+}
+
+/// 目录上传时对特殊条目的处理策略
+///
+/// 默认值与之前的行为一致：跳过符号链接，不为空目录创建对象，包含隐藏文件，
+/// 遍历之后、上传之前被删除的文件记入 [`BatchReport::vanished`] 而不计为失败。
+/// 套接字、管道、设备等特殊文件总是被跳过。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirUploadPolicy {
+    /// 符号链接的处理方式
+    pub symlinks: SymlinkPolicy,
+    /// 是否为空目录创建以 `/` 结尾的空对象
+    pub empty_dirs: bool,
+    /// 是否包含以 `.` 开头的隐藏文件与目录
+    pub include_hidden: bool,
+    /// 是否把遍历后消失的文件记入 [`BatchReport::vanished`]；为 `false` 时计为失败
+    pub ignore_vanished: bool,
+}
+
+impl Default for DirUploadPolicy {
+    fn default() -> Self {
+        Self {
+            symlinks: SymlinkPolicy::default(),
+            empty_dirs: false,
+            include_hidden: true,
+            ignore_vanished: true,
+        }
+    }
+}
+
+/// 批量上传选项
+#[derive(Clone)]
+pub struct BatchOptions {
+    /// 同时上传的文件数量
+    pub file_concurrency: usize,
+    /// 整个批量共享的重试次数
+    pub retry_budget: u32,
+    /// 失败率熔断阈值（0.0 ~ 1.0），超过后不再调度新的文件
+    pub max_failure_rate: f64,
+    /// 至少完成多少个文件后才开始计算失败率
+    pub min_samples: usize,
+    /// 附加到每个对象上的元数据
+    pub metadata: Option<Metadata>,
+    /// 符号链接、空目录、隐藏文件等特殊条目的处理策略
+    pub policy: DirUploadPolicy,
+    /// 上一次上传的清单，设置后只上传大小或 CRC64 与清单不一致的文件，并在报告中生成新的清单
+    ///
+    /// 第一次上传时传入空的 [`UploadManifest`] 即可得到清单。判断是否变化需要读取每个文件计算 CRC64。
+    /// 符号链接与空目录总是上传，不记入清单；本地已删除的文件只从新的清单中去掉，不会删除远端的对象。
+    pub previous_manifest: Option<Arc<UploadManifest>>,
+    /// 是否把内容相同的文件合并为一次上传（默认关闭）
+    ///
+    /// 开启后为每个文件计算 CRC64，大小与 CRC64 都相同的文件只上传第一个，其余对象键通过服务端复制创建，
+    /// 适用于包含大量重复文件的静态资源目录。复制的对象按自身的文件名设置 `Content-Type`；
+    /// 超过 5 GB、第一个文件上传失败或复制失败时照常上传。
+    pub dedupe_content: bool,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            file_concurrency: 4,
+            retry_budget: 100,
+            max_failure_rate: 0.5,
+            min_samples: 20,
+            metadata: None,
+            policy: DirUploadPolicy::default(),
+            previous_manifest: None,
+            dedupe_content: false,
+        }
+    }
+}
+
+/// 批量上传报告，批量被熔断时只包含部分结果
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// 上传成功的文件及其结果
+    pub uploaded: Vec<(PathBuf, UploadResult)>,
+    /// 上传失败的文件及失败原因
+    pub failed: Vec<(PathBuf, String)>,
+    /// 因熔断而未被调度的文件
+    pub skipped: Vec<PathBuf>,
+    /// 整个批量使用的重试次数
+    pub retries_used: u32,
+    /// 遍历之后、上传之前被删除的文件
+    pub vanished: Vec<PathBuf>,
+    /// 遍历目录时无法处理的条目（无法读取的目录、目标不存在的符号链接等）及原因
+    pub entry_errors: Vec<(PathBuf, String)>,
+    /// 熔断原因，批量完整执行时为 `None`
+    pub aborted: Option<String>,
+    /// 与上一次清单一致而未上传的文件
+    pub unchanged: Vec<PathBuf>,
+    /// 内容与批量中已上传的文件相同、通过服务端复制创建的文件及复制来源的对象键，
+    /// 这些文件同时记录在 [`BatchReport::uploaded`] 中
+    pub deduplicated: Vec<(PathBuf, String)>,
+    /// 本次上传后的清单，包含上传成功与未变化的文件；只有设置了
+    /// [`BatchOptions::previous_manifest`] 时才有
+    pub manifest: Option<UploadManifest>,
+}
+
+impl BatchReport {
+    /// 是否所有文件都上传成功
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+            && self.skipped.is_empty()
+            && self.entry_errors.is_empty()
+            && self.aborted.is_none()
+    }
+}
+
+/// 遍历目录得到的待上传条目
+#[derive(Debug, PartialEq, Eq)]
+enum DirEntry {
+    /// 普通文件（或被跟随的指向文件的符号链接）
+    File(PathBuf),
+    /// 以元数据记录的符号链接
+    Symlink { path: PathBuf, target: PathBuf },
+    /// 空目录
+    EmptyDir(PathBuf),
+}
+
+/// 单个条目的处理结果
+struct EntryOutcome {
+    object_key: String,
+    /// 上传结果，文件与上一次清单一致而跳过时为 `None`
+    upload: Option<UploadResult>,
+    /// 记入新清单的记录
+    manifest_entry: Option<ManifestEntry>,
+    /// 通过服务端复制创建时复制来源的对象键
+    copied_from: Option<String>,
+}
+
+impl EntryOutcome {
+    fn uploaded(object_key: String, upload: UploadResult) -> Self {
+        Self {
+            object_key,
+            upload: Some(upload),
+            manifest_entry: None,
+            copied_from: None,
+        }
+    }
+}
+
+/// 相同内容的第一个文件的上传状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ContentSource {
+    /// 正在上传
+    Pending,
+    /// 已上传到该对象键，可以从中复制
+    Uploaded(String),
+    /// 上传失败或写入了备用 Bucket，无法复制
+    Unavailable,
+}
+
+/// 对内容相同的文件的登记结果
+enum ContentClaim {
+    /// 第一个出现该内容的文件，上传后需要通过发送端公布结果
+    First(watch::Sender<ContentSource>),
+    /// 内容与之前的文件相同
+    Duplicate(watch::Receiver<ContentSource>),
+}
+
+/// 批量中按大小与 CRC64 登记的内容
+#[derive(Default)]
+struct ContentRegistry {
+    sources: Mutex<HashMap<ManifestEntry, watch::Receiver<ContentSource>>>,
+}
+
+impl ContentRegistry {
+    fn claim(&self, entry: ManifestEntry) -> ContentClaim {
+        let mut sources = self.sources.lock().unwrap();
+        match sources.get(&entry) {
+            Some(source) => ContentClaim::Duplicate(source.clone()),
+            None => {
+                let (sender, receiver) = watch::channel(ContentSource::Pending);
+                sources.insert(entry, receiver);
+                ContentClaim::First(sender)
+            }
+        }
+    }
+}
+
+impl DirEntry {
+    fn path(&self) -> &Path {
+        match self {
+            DirEntry::File(path) | DirEntry::EmptyDir(path) => path,
+            DirEntry::Symlink { path, .. } => path,
+        }
+    }
+}
+
+impl Uploader {
+    /// 递归上传目录下的所有文件
+    ///
+    /// 对象键为 `prefix` + 相对路径（以 `/` 分隔）。所有文件与分块的重试共享
+    /// [`BatchOptions::retry_budget`]，失败率超过 [`BatchOptions::max_failure_rate`] 时停止调度新文件，
+    /// 已在进行中的上传会继续完成，并返回包含部分结果的报告。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 要上传的本地目录
+    /// * `prefix` - 对象键前缀，例如 `"backup/2024/"`
+    /// * `opts` - 批量上传选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功遍历目录后返回批量上传报告，单个文件的失败记录在报告中而不会作为错误返回
+    pub async fn upload_dir<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        prefix: &str,
+        opts: BatchOptions,
+    ) -> Result<BatchReport> {
+        let dir = dir.as_ref();
+        let mut report = BatchReport {
+            manifest: opts
+                .previous_manifest
+                .as_ref()
+                .map(|_| UploadManifest::new()),
+            ..BatchReport::default()
+        };
+        let entries = walk_dir(dir, &opts.policy, &mut report.entry_errors).await?;
+        info!("批量上传 {} 个条目: {:?}", entries.len(), dir);
+
+        let budget = Arc::new(RetryBudget::new(opts.retry_budget));
+        let uploader = self.fork_with_budget(budget.clone());
+        let registry = opts
+            .dedupe_content
+            .then(|| Arc::new(ContentRegistry::default()));
+        let semaphore = Arc::new(Semaphore::new(opts.file_concurrency.max(1)));
+        let mut tasks: JoinSet<(PathBuf, Result<EntryOutcome>)> = JoinSet::new();
+        let ignore_vanished = opts.policy.ignore_vanished;
+        let mut pending = entries.into_iter();
+
+        for entry in pending.by_ref() {
+            let permit = semaphore.clone().acquire_owned().await?;
+
+            // 收集已完成的上传并检查熔断条件
+            while let Some(joined) = tasks.try_join_next() {
+                record(&mut report, joined?, ignore_vanished);
+            }
+            if let Some(reason) = check_breaker(&report, &opts) {
+                warn!("批量上传熔断: {}", reason);
+                report.aborted = Some(reason);
+                report.skipped.push(entry.path().to_path_buf());
+                break;
+            }
+
+            let object_key = format!("{}{}", prefix, relative_key(dir, entry.path())?);
+            let uploader = uploader.clone();
+            let budget = budget.clone();
+            let metadata = opts.metadata.clone();
+            let previous = opts.previous_manifest.clone();
+            let registry = registry.clone();
+
+            tasks.spawn(async move {
+                let _permit = permit;
+                match entry {
+                    DirEntry::File(path) => {
+                        let result = upload_file_entry(
+                            &uploader,
+                            &budget,
+                            &path,
+                            object_key,
+                            metadata,
+                            previous.as_deref(),
+                            registry.as_deref(),
+                        )
+                        .await;
+                        (path, result)
+                    }
+                    DirEntry::Symlink { path, target } => {
+                        let mut metadata = metadata.unwrap_or_default();
+                        metadata.insert(
+                            SYMLINK_TARGET_METADATA.to_string(),
+                            target.to_string_lossy().into_owned(),
+                        );
+                        let result = uploader
+                            .put_empty_object(&object_key, metadata)
+                            .await
+                            .map(|upload| EntryOutcome::uploaded(object_key, upload));
+                        (path, result)
+                    }
+                    DirEntry::EmptyDir(path) => {
+                        let object_key = format!("{}/", object_key);
+                        let result = uploader
+                            .put_empty_object(&object_key, metadata.unwrap_or_default())
+                            .await
+                            .map(|upload| EntryOutcome::uploaded(object_key, upload));
+                        (path, result)
+                    }
+                }
+            });
+        }
+
+        report
+            .skipped
+            .extend(pending.map(|entry| entry.path().to_path_buf()));
+
+        while let Some(joined) = tasks.join_next().await {
+            record(&mut report, joined?, ignore_vanished);
+        }
+
+        report.retries_used = budget.used();
+        info!(
+            "批量上传结束: 成功 {}（其中复制 {}），未变化 {}，失败 {}，跳过 {}，消失 {}，无法处理 {}，重试 {} 次",
+            report.uploaded.len(),
+            report.deduplicated.len(),
+            report.unchanged.len(),
+            report.failed.len(),
+            report.skipped.len(),
+            report.vanished.len(),
+            report.entry_errors.len(),
+            report.retries_used
+        );
+
+        Ok(report)
+    }
+
+    /// 通过服务端复制创建与批量中已上传的文件内容相同的对象
+    ///
+    /// 按本地文件名重新设置 `Content-Type`，并以批量的元数据替换源对象的元数据。
+    async fn copy_duplicate(
+        &self,
+        source_key: &str,
+        object_key: &str,
+        file_path: &Path,
+        metadata: Option<Metadata>,
+    ) -> Result<UploadResult> {
+        let content_type = mime_guess::from_path(file_path)
+            .first_or_octet_stream()
+            .to_string();
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .header("x-cos-copy-source", self.copy_source(source_key))
+            .header("x-cos-metadata-directive", "Replaced")
+            .header("Content-Type", content_type);
+        for (key, value) in metadata.unwrap_or_default() {
+            request = request.header(&format!("x-cos-meta-{}", key), value);
+        }
+
+        let response = self.execute(request).await?;
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let crc64 = crc64_of(response.headers());
+        let text = read_xml(response).await?;
+        // 复制请求可能在返回 200 的同时在响应体中携带错误
+        if text.contains("<Error>") {
+            return Err(anyhow!("复制对象失败: {}: {}", object_key, text));
+        }
+        self.invalidate_cached(object_key);
+        debug!("内容相同，已从 {} 复制: {}", source_key, object_key);
+
+        Ok(UploadResult {
+            url,
+            etag: find_tag(&text, "ETag").map(|etag| etag.to_string()),
+            request_id,
+            stats: None,
+            failover: None,
+            crc64,
+        })
+    }
+
+    /// 上传一个内容为空的对象，用于空目录与符号链接的占位
+    async fn put_empty_object(&self, object_key: &str, metadata: Metadata) -> Result<UploadResult> {
+        let mut request = CosRequest::new(Method::PUT, object_key).body(Vec::new());
+        for (key, value) in metadata {
+            request = request.header(&format!("x-cos-meta-{}", key), value);
+        }
+
+        let response = self.execute(request).await?;
+        Ok(UploadResult {
+            url: object_url_of(&response),
+            etag: header_of(&response, "ETag"),
+            request_id: request_id_of(response.headers()),
+            stats: None,
+            failover: None,
+            crc64: crc64_of(response.headers()),
+        })
+    }
+}
+
+/// 上传单个文件
+///
+/// 给定上一次的清单或内容登记时先计算文件的大小与 CRC64：与清单一致则跳过上传，
+/// 与批量中已上传的文件内容相同则改为服务端复制。
+async fn upload_file_entry(
+    uploader: &Uploader,
+    budget: &RetryBudget,
+    path: &Path,
+    object_key: String,
+    metadata: Option<Metadata>,
+    previous: Option<&UploadManifest>,
+    registry: Option<&ContentRegistry>,
+) -> Result<EntryOutcome> {
+    if previous.is_none() && registry.is_none() {
+        let upload = upload_with_budget(uploader, budget, path, &object_key, metadata).await?;
+        return Ok(EntryOutcome::uploaded(object_key, upload));
+    }
+
+    let entry = ManifestEntry {
+        size: tokio::fs::metadata(path).await?.len(),
+        crc64: uploader.file_crc64(path).await?,
+    };
+    let mut outcome = EntryOutcome {
+        object_key,
+        upload: None,
+        manifest_entry: Some(entry),
+        copied_from: None,
+    };
+    if previous.is_some_and(|previous| previous.is_unchanged(&outcome.object_key, &entry)) {
+        debug!("文件与上一次清单一致，跳过上传: {:?}", path);
+        return Ok(outcome);
+    }
+
+    let object_key = &outcome.object_key;
+    match registry.map(|registry| registry.claim(entry)) {
+        Some(ContentClaim::First(sender)) => {
+            let result = upload_with_budget(uploader, budget, path, object_key, metadata).await;
+            let source = match &result {
+                Ok(upload) if upload.failover.is_none() => {
+                    ContentSource::Uploaded(object_key.clone())
+                }
+                _ => ContentSource::Unavailable,
+            };
+            sender.send_replace(source);
+            outcome.upload = Some(result?);
+        }
+        Some(ContentClaim::Duplicate(mut receiver)) => {
+            let source = receiver
+                .wait_for(|source| *source != ContentSource::Pending)
+                .await
+                .map(|source| source.clone())
+                .unwrap_or(ContentSource::Unavailable);
+            let copied = match source {
+                ContentSource::Uploaded(source_key) if entry.size <= MAX_COPY_SIZE => {
+                    match uploader
+                        .copy_duplicate(&source_key, object_key, path, metadata.clone())
+                        .await
+                    {
+                        Ok(upload) => Some((source_key, upload)),
+                        Err(e) => {
+                            warn!(
+                                "复制内容相同的对象失败，改为上传: {} -> {}: {}",
+                                source_key, object_key, e
+                            );
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+            match copied {
+                Some((source_key, upload)) => {
+                    outcome.copied_from = Some(source_key);
+                    outcome.upload = Some(upload);
+                }
+                None => {
+                    outcome.upload = Some(
+                        upload_with_budget(uploader, budget, path, object_key, metadata).await?,
+                    );
+                }
+            }
+        }
+        None => {
+            outcome.upload =
+                Some(upload_with_budget(uploader, budget, path, object_key, metadata).await?);
+        }
+    }
+    Ok(outcome)
+}
+
+/// 上传单个文件，可重试的失败会从预算中扣除后重试
+async fn upload_with_budget(
+    uploader: &Uploader,
+    budget: &RetryBudget,
+    path: &Path,
+    object_key: &str,
+    metadata: Option<Metadata>,
+) -> Result<UploadResult> {
+    let mut attempt = 1;
+    loop {
+        match uploader
+            .upload_file(path, object_key, metadata.clone())
+            .await
+        {
+            Ok(result) => return Ok(result),
+            Err(e)
+                if attempt < FILE_MAX_ATTEMPTS
+                    && uploader.is_retryable(&e)
+                    && budget.try_acquire() =>
+            {
+                warn!(
+                    "文件上传失败，准备重试 (第 {} 次): {:?}: {}",
+                    attempt, path, e
+                );
+                tokio::time::sleep(FILE_RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// 把单个文件的结果记录到报告中
+fn record(
+    report: &mut BatchReport,
+    (path, result): (PathBuf, Result<EntryOutcome>),
+    ignore_vanished: bool,
+) {
+    match result {
+        Ok(outcome) => {
+            if let (Some(manifest), Some(entry)) = (&mut report.manifest, outcome.manifest_entry) {
+                manifest.entries.insert(outcome.object_key, entry);
+            }
+            if let Some(source_key) = outcome.copied_from {
+                report.deduplicated.push((path.clone(), source_key));
+            }
+            match outcome.upload {
+                Some(result) => report.uploaded.push((path, result)),
+                None => report.unchanged.push(path),
+            }
+        }
+        Err(e) if ignore_vanished && is_not_found(&e) => {
+            warn!("文件在上传前已被删除: {:?}", path);
+            report.vanished.push(path);
+        }
+        Err(e) => {
+            error!("文件上传失败: {:?}: {}", path, e);
+            report.failed.push((path, format!("{:#}", e)));
+        }
+    }
+}
+
+/// 检查失败率是否超过阈值，返回熔断原因
+fn check_breaker(report: &BatchReport, opts: &BatchOptions) -> Option<String> {
+    let completed = report.uploaded.len() + report.failed.len();
+    if completed == 0 || completed < opts.min_samples {
+        return None;
+    }
+
+    let failure_rate = report.failed.len() as f64 / completed as f64;
+    if failure_rate > opts.max_failure_rate {
+        Some(format!(
+            "失败率 {:.1}% 超过阈值 {:.1}%（已完成 {} 个文件）",
+            failure_rate * 100.0,
+            opts.max_failure_rate * 100.0,
+            completed
+        ))
+    } else {
+        None
+    }
+}
+
+/// 错误是否由文件不存在引起
+fn is_not_found(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<std::io::Error>()
+        .is_some_and(|e| e.kind() == std::io::ErrorKind::NotFound)
+}
+
+/// 按策略递归遍历目录，结果按路径排序以保证顺序确定
+///
+/// 根目录无法读取时返回错误，其余无法处理的条目记入 `entry_errors`。
+async fn walk_dir(
+    dir: &Path,
+    policy: &DirUploadPolicy,
+    entry_errors: &mut Vec<(PathBuf, String)>,
+) -> Result<Vec<DirEntry>> {
+    let mut entries = Vec::new();
+    // 每个待遍历的目录都带着自身及所有祖先目录的真实路径，用于识别符号链接形成的循环
+    let root = vec![tokio::fs::canonicalize(dir).await?];
+    let mut dirs = vec![(dir.to_path_buf(), root)];
+
+    while let Some((current, ancestors)) = dirs.pop() {
+        let mut read_dir = match tokio::fs::read_dir(&current).await {
+            Ok(read_dir) => read_dir,
+            Err(e) if current == dir => return Err(e.into()),
+            Err(e) => {
+                entry_errors.push((current, format!("无法读取目录: {}", e)));
+                continue;
+            }
+        };
+
+        let mut is_empty = true;
+        loop {
+            let entry = match read_dir.next_entry().await {
+                Ok(Some(entry)) => entry,
+                Ok(None) => break,
+                Err(e) => {
+                    entry_errors.push((current.clone(), format!("读取目录条目失败: {}", e)));
+                    break;
+                }
+            };
+            let path = entry.path();
+            if !policy.include_hidden && entry.file_name().to_string_lossy().starts_with('.') {
+                debug!("跳过隐藏条目: {:?}", path);
+                continue;
+            }
+            is_empty = false;
+
+            let file_type = match entry.file_type().await {
+                Ok(file_type) => file_type,
+                // 遍历过程中被删除的条目
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => {
+                    entry_errors.push((path, format!("无法读取文件类型: {}", e)));
+                    continue;
+                }
+            };
+
+            if file_type.is_symlink() {
+                match policy.symlinks {
+                    SymlinkPolicy::Skip => debug!("跳过符号链接: {:?}", path),
+                    SymlinkPolicy::RecordAsMetadata => match tokio::fs::read_link(&path).await {
+                        Ok(target) => entries.push(DirEntry::Symlink { path, target }),
+                        Err(e) => entry_errors.push((path, format!("无法读取符号链接: {}", e))),
+                    },
+                    SymlinkPolicy::Follow => match tokio::fs::metadata(&path).await {
+                        Ok(meta) if meta.is_dir() => match tokio::fs::canonicalize(&path).await {
+                            Ok(canonical) if ancestors.contains(&canonical) => {
+                                warn!("跳过形成循环的符号链接: {:?}", path)
+                            }
+                            Ok(canonical) => {
+                                dirs.push((path, with_ancestor(&ancestors, canonical)))
+                            }
+                            Err(e) => {
+                                entry_errors.push((path, format!("无法解析符号链接的目标: {}", e)))
+                            }
+                        },
+                        Ok(meta) if meta.is_file() => entries.push(DirEntry::File(path)),
+                        Ok(_) => debug!("跳过指向特殊文件的符号链接: {:?}", path),
+                        Err(e) => {
+                            entry_errors.push((path, format!("符号链接的目标不可访问: {}", e)))
+                        }
+                    },
+                }
+            } else if file_type.is_dir() {
+                let canonical = ancestors[ancestors.len() - 1].join(entry.file_name());
+                dirs.push((path, with_ancestor(&ancestors, canonical)));
+            } else if file_type.is_file() {
+                entries.push(DirEntry::File(path));
+            } else {
+                debug!("跳过特殊文件: {:?}", path);
+            }
+        }
+
+        if is_empty && policy.empty_dirs && current != dir {
+            entries.push(DirEntry::EmptyDir(current));
+        }
+    }
+
+    entries.sort_by(|a, b| a.path().cmp(b.path()));
+    Ok(entries)
+}
+
+fn with_ancestor(ancestors: &[PathBuf], canonical: PathBuf) -> Vec<PathBuf> {
+    let mut ancestors = ancestors.to_vec();
+    ancestors.push(canonical);
+    ancestors
+}
+
+/// 递归收集目录下的所有文件，按路径排序以保证顺序确定
+#[cfg(feature = "sync")]
+pub(crate) async fn collect_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(current) = dirs.pop() {
+        let mut entries = tokio::fs::read_dir(&current).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_type = entry.file_type().await?;
+            if file_type.is_dir() {
+                dirs.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_budget() {
+        let budget = RetryBudget::new(2);
+        assert!(budget.try_acquire());
+        assert!(budget.try_acquire());
+        assert!(!budget.try_acquire());
+        assert_eq!(budget.used(), 2);
+        assert_eq!(budget.remaining(), 0);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_walk_dir_policy() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("empty")).unwrap();
+        std::fs::create_dir_all(root.join("sub")).unwrap();
+        std::fs::write(root.join("sub/a.txt"), "a").unwrap();
+        std::fs::write(root.join(".hidden"), "h").unwrap();
+        std::os::unix::fs::symlink(root.join("sub"), root.join("link")).unwrap();
+        std::os::unix::fs::symlink(root, root.join("sub/loop")).unwrap();
+
+        let mut errors = Vec::new();
+        let entries = walk_dir(root, &DirUploadPolicy::default(), &mut errors)
+            .await
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                DirEntry::File(root.join(".hidden")),
+                DirEntry::File(root.join("sub/a.txt")),
+            ]
+        );
+
+        let policy = DirUploadPolicy {
+            symlinks: SymlinkPolicy::Follow,
+            empty_dirs: true,
+            include_hidden: false,
+            ..Default::default()
+        };
+        let entries = walk_dir(root, &policy, &mut errors).await.unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                DirEntry::EmptyDir(root.join("empty")),
+                DirEntry::File(root.join("link/a.txt")),
+                DirEntry::File(root.join("sub/a.txt")),
+            ]
+        );
+        assert!(errors.is_empty());
+    }
+
+    #[test]
+    fn test_breaker() {
+        let opts = BatchOptions {
+            min_samples: 2,
+            max_failure_rate: 0.5,
+            ..Default::default()
+        };
+        let mut report = BatchReport::default();
+        report.failed.push((PathBuf::from("a"), "err".to_string()));
+        assert!(check_breaker(&report, &opts).is_none());
+        report.failed.push((PathBuf::from("b"), "err".to_string()));
+        assert!(check_breaker(&report, &opts).is_some());
+    }
+}
@@ -0,0 +1,461 @@
+use crate::error::CosError;
+use crate::request::{read_xml, CosRequest};
+use crate::types::request_id_of;
+use crate::uploader::Uploader;
+use crate::xml::{escape, find_all_tags, find_tag, unescape};
+use anyhow::{anyhow, Result};
+use base64::Engine;
+use md5::{Digest, Md5};
+use reqwest::Method;
+use tracing::info;
+
+/// 服务端加密算法
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SseAlgorithm {
+    /// SSE-COS，使用 COS 托管的密钥（`AES256`）
+    Aes256,
+    /// SSE-KMS，使用 KMS 托管的密钥（`KMS`）
+    Kms,
+    /// 国密 SM4（`SM4`）
+    Sm4,
+}
+
+impl SseAlgorithm {
+    /// XML 中使用的算法名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SseAlgorithm::Aes256 => "AES256",
+            SseAlgorithm::Kms => "KMS",
+            SseAlgorithm::Sm4 => "SM4",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "AES256" => Some(SseAlgorithm::Aes256),
+            "KMS" => Some(SseAlgorithm::Kms),
+            "SM4" => Some(SseAlgorithm::Sm4),
+            _ => None,
+        }
+    }
+}
+
+/// Bucket 的默认加密配置，对应 `ServerSideEncryptionConfiguration` 中的
+/// `Rule/ApplyServerSideEncryptionByDefault`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BucketEncryption {
+    /// 加密算法
+    pub algorithm: SseAlgorithm,
+    /// KMS 主密钥 ID，仅在 [`SseAlgorithm::Kms`] 时有效，为 `None` 时使用 COS 默认的 CMK
+    pub kms_master_key_id: Option<String>,
+}
+
+impl BucketEncryption {
+    /// 创建使用指定算法的加密配置
+    pub fn new(algorithm: SseAlgorithm) -> Self {
+        Self {
+            algorithm,
+            kms_master_key_id: None,
+        }
+    }
+
+    /// 设置 KMS 主密钥 ID
+    pub fn with_kms_master_key_id(mut self, key_id: String) -> Self {
+        self.kms_master_key_id = Some(key_id);
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let key_id = self
+            .kms_master_key_id
+            .as_ref()
+            .map(|id| format!("<KMSMasterKeyID>{}</KMSMasterKeyID>", escape(id)))
+            .unwrap_or_default();
+
+        format!(
+            "<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault>\
+             <SSEAlgorithm>{}</SSEAlgorithm>{}\
+             </ApplyServerSideEncryptionByDefault></Rule></ServerSideEncryptionConfiguration>",
+            self.algorithm.as_str(),
+            key_id
+        )
+    }
+
+    fn from_xml(text: &str) -> Result<Self> {
+        let algorithm =
+            find_tag(text, "SSEAlgorithm").ok_or_else(|| anyhow!("加密配置中缺少 SSEAlgorithm"))?;
+
+        Ok(Self {
+            algorithm: SseAlgorithm::parse(algorithm)
+                .ok_or_else(|| anyhow!("未知的加密算法: {}", algorithm))?,
+            kms_master_key_id: find_tag(text, "KMSMasterKeyID")
+                .filter(|id| !id.is_empty())
+                .map(|id| id.to_string()),
+        })
+    }
+}
+
+/// 自定义域名的源站类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DomainOrigin {
+    /// 默认源站，通过 COS 的 REST 接口访问（`REST`）
+    #[default]
+    Rest,
+    /// 静态网站源站（`WEBSITE`）
+    Website,
+}
+
+impl DomainOrigin {
+    /// XML 中使用的源站类型
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainOrigin::Rest => "REST",
+            DomainOrigin::Website => "WEBSITE",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "REST" => Some(DomainOrigin::Rest),
+            "WEBSITE" => Some(DomainOrigin::Website),
+            _ => None,
+        }
+    }
+}
+
+/// 域名已绑定到其它 Bucket 时的替换方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DomainReplace {
+    /// 域名的 CNAME 已指向当前 Bucket 的默认域名时替换（`CNAME`）
+    Cname,
+    /// 通过 TXT 记录验证域名归属后替换（`TXT`）
+    Txt,
+}
+
+impl DomainReplace {
+    /// XML 中使用的替换方式
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DomainReplace::Cname => "CNAME",
+            DomainReplace::Txt => "TXT",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "CNAME" => Some(DomainReplace::Cname),
+            "TXT" => Some(DomainReplace::Txt),
+            _ => None,
+        }
+    }
+}
+
+/// Bucket 的自定义域名规则，对应 `DomainConfiguration` 中的 `DomainRule`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainRule {
+    /// 自定义域名，如 `static.example.com`
+    pub name: String,
+    /// 是否启用（`ENABLED` / `DISABLED`）
+    pub enabled: bool,
+    /// 源站类型
+    pub origin: DomainOrigin,
+    /// 域名已绑定到其它 Bucket 时的替换方式，为 `None` 时不替换
+    pub replace: Option<DomainReplace>,
+}
+
+impl DomainRule {
+    /// 创建启用的、以 REST 接口为源站的域名规则
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            enabled: true,
+            origin: DomainOrigin::Rest,
+            replace: None,
+        }
+    }
+
+    /// 设置是否启用
+    pub fn with_enabled(mut self, enabled: bool) -> Self {
+        self.enabled = enabled;
+        self
+    }
+
+    /// 设置源站类型
+    pub fn with_origin(mut self, origin: DomainOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// 设置域名已绑定到其它 Bucket 时的替换方式
+    pub fn with_replace(mut self, replace: DomainReplace) -> Self {
+        self.replace = Some(replace);
+        self
+    }
+
+    fn to_xml(&self) -> String {
+        let replace = self
+            .replace
+            .map(|replace| format!("<Replace>{}</Replace>", replace.as_str()))
+            .unwrap_or_default();
+
+        format!(
+            "<DomainRule><Status>{}</Status><Name>{}</Name><Type>{}</Type>{}</DomainRule>",
+            if self.enabled { "ENABLED" } else { "DISABLED" },
+            escape(&self.name),
+            self.origin.as_str(),
+            replace
+        )
+    }
+
+    fn from_xml(text: &str) -> Result<Self> {
+        let name = find_tag(text, "Name").ok_or_else(|| anyhow!("域名规则中缺少 Name"))?;
+        let origin = find_tag(text, "Type").unwrap_or("REST");
+
+        Ok(Self {
+            name: unescape(name),
+            enabled: find_tag(text, "Status") == Some("ENABLED"),
+            origin: DomainOrigin::parse(origin)
+                .ok_or_else(|| anyhow!("未知的域名源站类型: {}", origin))?,
+            replace: find_tag(text, "Replace").and_then(DomainReplace::parse),
+        })
+    }
+}
+
+/// 生成自定义域名配置的请求体
+fn domain_xml(rules: &[DomainRule]) -> String {
+    let mut body = String::from("<DomainConfiguration>");
+    for rule in rules {
+        body.push_str(&rule.to_xml());
+    }
+    body.push_str("</DomainConfiguration>");
+    body
+}
+
+/// 生成全球加速配置的请求体
+fn accelerate_xml(enabled: bool) -> String {
+    format!(
+        "<AccelerateConfiguration><Status>{}</Status></AccelerateConfiguration>",
+        if enabled { "Enabled" } else { "Suspended" }
+    )
+}
+
+/// 计算请求体的 `Content-MD5`（Base64 编码的 MD5）
+pub(crate) fn content_md5(body: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(Md5::digest(body))
+}
+
+impl Uploader {
+    /// 设置 Bucket 的默认加密配置
+    ///
+    /// 设置后，未显式指定加密方式的新对象都会使用该配置加密。
+    ///
+    /// # 参数
+    ///
+    /// * `config` - 加密配置
+    pub async fn put_bucket_encryption(&self, config: &BucketEncryption) -> Result<()> {
+        let body = config.to_xml();
+        let request = CosRequest::bucket(Method::PUT)
+            .param("encryption", "")
+            .header("Content-Type", "application/xml")
+            .header("Content-MD5", content_md5(body.as_bytes()))
+            .body(body);
+
+        let response = self.execute(request).await?;
+        info!(
+            "设置 Bucket 加密配置成功: {} (request_id: {:?})",
+            config.algorithm.as_str(),
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+
+    /// 查询 Bucket 的默认加密配置
+    ///
+    /// # 返回值
+    ///
+    /// Bucket 未设置加密配置时返回 `None`
+    pub async fn get_bucket_encryption(&self) -> Result<Option<BucketEncryption>> {
+        let request = CosRequest::bucket(Method::GET).param("encryption", "");
+
+        match self.execute(request).await {
+            Ok(response) => {
+                let text = read_xml(response).await?;
+                Ok(Some(BucketEncryption::from_xml(&text)?))
+            }
+            Err(e)
+                if e.downcast_ref::<CosError>().and_then(CosError::code)
+                    == Some("NoSuchEncryptionConfiguration") =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 删除 Bucket 的默认加密配置
+    pub async fn delete_bucket_encryption(&self) -> Result<()> {
+        let request = CosRequest::bucket(Method::DELETE).param("encryption", "");
+        let response = self.execute(request).await?;
+        info!(
+            "删除 Bucket 加密配置成功 (request_id: {:?})",
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+}
+
+impl Uploader {
+    /// 开启或暂停 Bucket 的全球加速
+    ///
+    /// 开启后可以通过 [`EndpointKind::Accelerate`](crate::EndpointKind::Accelerate) 使用全球加速域名上传。
+    /// 配置生效可能需要数分钟。
+    ///
+    /// # 参数
+    ///
+    /// * `enabled` - `true` 为开启（`Enabled`），`false` 为暂停（`Suspended`）
+    pub async fn put_bucket_accelerate(&self, enabled: bool) -> Result<()> {
+        let body = accelerate_xml(enabled);
+        let request = CosRequest::bucket(Method::PUT)
+            .param("accelerate", "")
+            .header("Content-Type", "application/xml")
+            .header("Content-MD5", content_md5(body.as_bytes()))
+            .body(body);
+
+        let response = self.execute(request).await?;
+        info!(
+            "设置 Bucket 全球加速成功: {} (request_id: {:?})",
+            enabled,
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+
+    /// 查询 Bucket 是否开启了全球加速
+    ///
+    /// # 返回值
+    ///
+    /// 状态为 `Enabled` 时返回 `true`，暂停或从未配置时返回 `false`
+    pub async fn get_bucket_accelerate(&self) -> Result<bool> {
+        let request = CosRequest::bucket(Method::GET).param("accelerate", "");
+        let text = read_xml(self.execute(request).await?).await?;
+        Ok(find_tag(&text, "Status") == Some("Enabled"))
+    }
+}
+
+impl Uploader {
+    /// 设置 Bucket 的自定义域名
+    ///
+    /// 设置会覆盖整个自定义域名配置，需要保留的已有规则应一并传入（可先通过
+    /// [`Uploader::get_bucket_domain`] 读取）。域名需要预先添加指向 Bucket 默认域名的 CNAME 记录，
+    /// 配置生效可能需要数分钟。
+    ///
+    /// # 参数
+    ///
+    /// * `rules` - 自定义域名规则
+    ///
+    /// # 错误
+    ///
+    /// 域名已绑定到其它 Bucket 且未设置 [`DomainRule::replace`]，或 CNAME 记录未生效时，
+    /// COS 返回的错误原样返回。
+    pub async fn put_bucket_domain(&self, rules: &[DomainRule]) -> Result<()> {
+        let body = domain_xml(rules);
+        let request = CosRequest::bucket(Method::PUT)
+            .param("domain", "")
+            .header("Content-Type", "application/xml")
+            .header("Content-MD5", content_md5(body.as_bytes()))
+            .body(body);
+
+        let response = self.execute(request).await?;
+        info!(
+            "设置 Bucket 自定义域名成功: {:?} (request_id: {:?})",
+            rules.iter().map(|rule| &rule.name).collect::<Vec<_>>(),
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+
+    /// 查询 Bucket 的自定义域名
+    ///
+    /// # 返回值
+    ///
+    /// Bucket 未设置自定义域名时返回空列表
+    pub async fn get_bucket_domain(&self) -> Result<Vec<DomainRule>> {
+        let request = CosRequest::bucket(Method::GET).param("domain", "");
+
+        match self.execute(request).await {
+            Ok(response) => {
+                let text = read_xml(response).await?;
+                find_all_tags(&text, "DomainRule")
+                    .into_iter()
+                    .map(DomainRule::from_xml)
+                    .collect()
+            }
+            Err(e)
+                if e.downcast_ref::<CosError>().and_then(CosError::code)
+                    == Some("NoSuchDomainConfiguration") =>
+            {
+                Ok(Vec::new())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// 删除 Bucket 的全部自定义域名
+    pub async fn delete_bucket_domain(&self) -> Result<()> {
+        let request = CosRequest::bucket(Method::DELETE).param("domain", "");
+        let response = self.execute(request).await?;
+        info!(
+            "删除 Bucket 自定义域名成功 (request_id: {:?})",
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encryption_xml_round_trip() {
+        let config = BucketEncryption::new(SseAlgorithm::Kms).with_kms_master_key_id("k&1".into());
+        let xml = config.to_xml();
+        assert!(xml.contains("<SSEAlgorithm>KMS</SSEAlgorithm>"));
+        assert!(xml.contains("<KMSMasterKeyID>k&amp;1</KMSMasterKeyID>"));
+
+        let parsed = BucketEncryption::from_xml(
+            "<ServerSideEncryptionConfiguration><Rule><ApplyServerSideEncryptionByDefault>\
+             <SSEAlgorithm>AES256</SSEAlgorithm>\
+             </ApplyServerSideEncryptionByDefault></Rule></ServerSideEncryptionConfiguration>",
+        )
+        .unwrap();
+        assert_eq!(parsed, BucketEncryption::new(SseAlgorithm::Aes256));
+    }
+
+    #[test]
+    fn test_domain_xml_round_trip() {
+        let rules = vec![
+            DomainRule::new("static.example.com".into()).with_replace(DomainReplace::Cname),
+            DomainRule::new("www.example.com".into())
+                .with_origin(DomainOrigin::Website)
+                .with_enabled(false),
+        ];
+        let xml = domain_xml(&rules);
+        assert!(xml.starts_with(
+            "<DomainConfiguration><DomainRule><Status>ENABLED</Status>\
+             <Name>static.example.com</Name><Type>REST</Type><Replace>CNAME</Replace></DomainRule>"
+        ));
+
+        let parsed = find_all_tags(&xml, "DomainRule")
+            .into_iter()
+            .map(DomainRule::from_xml)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(parsed, rules);
+    }
+
+    #[test]
+    fn test_content_md5() {
+        assert_eq!(content_md5(b"hello"), "XUFAKrxLKna5cZ2REBfFkg==");
+    }
+}
@@ -0,0 +1,196 @@
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// 缓存条目的键
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct CacheKey {
+    pub(crate) object_key: String,
+    pub(crate) version_id: Option<String>,
+    pub(crate) range: Option<Range<u64>>,
+}
+
+struct CacheEntry {
+    data: Bytes,
+    etag: Option<String>,
+    validated_at: Instant,
+    /// 最近一次访问的序号，用于按最近最少使用淘汰
+    tick: u64,
+}
+
+/// 查询缓存的结果
+pub(crate) enum Lookup {
+    /// 在有效期内，可以直接使用
+    Fresh(Bytes),
+    /// 超过有效期，需要用 ETag 向 COS 确认是否仍是最新的
+    Stale { data: Bytes, etag: String },
+    /// 没有缓存
+    Miss,
+}
+
+#[derive(Default)]
+struct CacheInner {
+    entries: HashMap<CacheKey, CacheEntry>,
+    /// 访问序号到键的索引，最小的即最近最少使用
+    order: BTreeMap<u64, CacheKey>,
+    tick: u64,
+    total_bytes: u64,
+}
+
+impl CacheInner {
+    fn next_tick(&mut self) -> u64 {
+        self.tick += 1;
+        self.tick
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some(entry) = self.entries.remove(key) {
+            self.order.remove(&entry.tick);
+            self.total_bytes -= entry.data.len() as u64;
+        }
+    }
+}
+
+/// 按总字节数限制大小的 LRU 对象缓存
+pub(crate) struct ObjectCache {
+    max_bytes: u64,
+    max_age: Duration,
+    inner: Mutex<CacheInner>,
+}
+
+impl ObjectCache {
+    pub(crate) fn new(max_bytes: u64, max_age: Duration) -> Self {
+        Self {
+            max_bytes,
+            max_age,
+            inner: Mutex::default(),
+        }
+    }
+
+    pub(crate) fn get(&self, key: &CacheKey) -> Lookup {
+        let mut inner = self.inner.lock().unwrap();
+        let tick = inner.next_tick();
+        let Some(entry) = inner.entries.get_mut(key) else {
+            return Lookup::Miss;
+        };
+
+        let previous = std::mem::replace(&mut entry.tick, tick);
+        let lookup = if entry.validated_at.elapsed() < self.max_age {
+            Lookup::Fresh(entry.data.clone())
+        } else {
+            match &entry.etag {
+                Some(etag) => Lookup::Stale {
+                    data: entry.data.clone(),
+                    etag: etag.clone(),
+                },
+                None => Lookup::Miss,
+            }
+        };
+        inner.order.remove(&previous);
+        inner.order.insert(tick, key.clone());
+        lookup
+    }
+
+    /// 放入缓存，超过容量时淘汰最近最少使用的条目；比容量还大的数据不缓存
+    pub(crate) fn insert(&self, key: CacheKey, data: Bytes, etag: Option<String>) {
+        let size = data.len() as u64;
+        let mut inner = self.inner.lock().unwrap();
+        inner.remove(&key);
+        if size > self.max_bytes {
+            return;
+        }
+
+        while inner.total_bytes + size > self.max_bytes {
+            let Some((_, oldest)) = inner.order.pop_first() else {
+                break;
+            };
+            if let Some(entry) = inner.entries.remove(&oldest) {
+                inner.total_bytes -= entry.data.len() as u64;
+            }
+        }
+
+        let tick = inner.next_tick();
+        inner.order.insert(tick, key.clone());
+        inner.total_bytes += size;
+        inner.entries.insert(
+            key,
+            CacheEntry {
+                data,
+                etag,
+                validated_at: Instant::now(),
+                tick,
+            },
+        );
+    }
+
+    /// COS 确认缓存仍是最新的，重新开始计算有效期
+    pub(crate) fn mark_validated(&self, key: &CacheKey) {
+        if let Some(entry) = self.inner.lock().unwrap().entries.get_mut(key) {
+            entry.validated_at = Instant::now();
+        }
+    }
+
+    /// 移除对象的所有缓存（所有版本与范围）
+    pub(crate) fn invalidate(&self, object_key: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        let keys: Vec<_> = inner
+            .entries
+            .keys()
+            .filter(|key| key.object_key == object_key)
+            .cloned()
+            .collect();
+        for key in keys {
+            inner.remove(&key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> CacheKey {
+        CacheKey {
+            object_key: name.to_string(),
+            version_id: None,
+            range: None,
+        }
+    }
+
+    #[test]
+    fn test_lru_eviction() {
+        let cache = ObjectCache::new(10, Duration::from_secs(60));
+        cache.insert(key("a"), Bytes::from_static(b"aaaa"), None);
+        cache.insert(key("b"), Bytes::from_static(b"bbbb"), None);
+        // 访问 a 之后，b 成为最近最少使用的条目
+        assert!(matches!(cache.get(&key("a")), Lookup::Fresh(_)));
+        cache.insert(key("c"), Bytes::from_static(b"cccc"), None);
+
+        assert!(matches!(cache.get(&key("a")), Lookup::Fresh(_)));
+        assert!(matches!(cache.get(&key("b")), Lookup::Miss));
+        assert!(matches!(cache.get(&key("c")), Lookup::Fresh(_)));
+
+        // 比容量还大的数据不缓存
+        cache.insert(key("big"), Bytes::from(vec![0; 11]), None);
+        assert!(matches!(cache.get(&key("big")), Lookup::Miss));
+
+        cache.invalidate("a");
+        assert!(matches!(cache.get(&key("a")), Lookup::Miss));
+    }
+
+    #[test]
+    fn test_stale_entry() {
+        let cache = ObjectCache::new(10, Duration::ZERO);
+        cache.insert(
+            key("a"),
+            Bytes::from_static(b"a"),
+            Some("\"e\"".to_string()),
+        );
+        cache.insert(key("b"), Bytes::from_static(b"b"), None);
+        assert!(matches!(cache.get(&key("a")), Lookup::Stale { etag, .. } if etag == "\"e\""));
+        // 没有 ETag 的过期条目无法确认，视为未缓存
+        assert!(matches!(cache.get(&key("b")), Lookup::Miss));
+    }
+}
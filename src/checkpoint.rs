@@ -0,0 +1,470 @@
+//! 分块上传的断点
+//!
+//! 断点记录了分块上传 ID 与已完成分块的 ETag。进程重启后凭断点继续上传时，
+//! 已完成的分块只在本地读取一遍用于计算整个文件的 CRC64，不会重新上传。
+//! 断点可以保存在 [`FileCheckpointStore`] 中，并按 [`CheckpointRetention`] 清理被放弃的断点。
+
+use crate::durable::sync_parent_dir;
+use crate::hash::sha1_hex;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use tracing::{info, warn};
+
+/// 断点文件的扩展名
+const CHECKPOINT_EXTENSION: &str = "json";
+
+/// 进行中的分块上传的断点
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MultipartCheckpoint {
+    /// 本地文件路径
+    pub file_path: PathBuf,
+    /// 对象键
+    pub object_key: String,
+    /// 分块上传 ID
+    pub upload_id: String,
+    /// 创建断点时的文件大小，续传前用来判断文件是否被修改
+    pub file_size: u64,
+    /// 创建断点时文件的修改时间（Unix 秒）
+    pub file_mtime: u64,
+    /// 分块大小，续传时沿用以保证分块边界不变
+    pub part_size: u64,
+    /// 自适应分块时已分配的各分块大小（按分块编号），续传时沿用；
+    /// 为空时所有分块都使用 `part_size`
+    pub part_sizes: Vec<u64>,
+    /// 已完成的分块编号与 ETag，按分块编号升序
+    pub completed_parts: Vec<(u32, String)>,
+}
+
+impl MultipartCheckpoint {
+    /// 分块是否已经上传完成
+    pub fn is_completed(&self, part_number: u32) -> bool {
+        self.completed_parts
+            .binary_search_by_key(&part_number, |(n, _)| *n)
+            .is_ok()
+    }
+
+    /// 记录一个已完成的分块
+    pub(crate) fn record_part(&mut self, part_number: u32, etag: String) {
+        if let Err(index) = self
+            .completed_parts
+            .binary_search_by_key(&part_number, |(n, _)| *n)
+        {
+            self.completed_parts.insert(index, (part_number, etag));
+        }
+    }
+
+    /// 本地文件的大小与修改时间是否仍与断点一致
+    pub(crate) async fn matches_file(&self) -> bool {
+        match tokio::fs::metadata(&self.file_path).await {
+            Ok(metadata) => {
+                metadata.len() == self.file_size && file_mtime(&metadata) == Some(self.file_mtime)
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 断点的 JSON 表示，可以保存到磁盘后在进程重启时继续上传
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.to_value()).expect("断点序列化失败")
+    }
+
+    /// 从 JSON 解析断点
+    ///
+    /// # 错误
+    ///
+    /// JSON 格式错误或缺少字段时返回错误。
+    pub fn from_json(text: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(text).context("解析断点失败")?;
+        Self::from_value(&value)
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        let parts: Vec<_> = self
+            .completed_parts
+            .iter()
+            .map(|(part_number, etag)| json!({ "part_number": part_number, "etag": etag }))
+            .collect();
+
+        json!({
+            "file_path": path_to_json(&self.file_path),
+            "object_key": self.object_key,
+            "upload_id": self.upload_id,
+            "file_size": self.file_size,
+            "file_mtime": self.file_mtime,
+            "part_size": self.part_size,
+            "part_sizes": self.part_sizes,
+            "completed_parts": parts,
+        })
+    }
+
+    pub(crate) fn from_value(value: &Value) -> Result<Self> {
+        let mut completed_parts = Vec::new();
+        for part in array_field(value, "completed_parts")? {
+            let part_number = u32::try_from(u64_field(part, "part_number")?)
+                .map_err(|_| anyhow!("分块编号超出范围"))?;
+            completed_parts.push((part_number, str_field(part, "etag")?.to_string()));
+        }
+        completed_parts.sort_by_key(|(part_number, _)| *part_number);
+        // 旧版本的断点没有该字段
+        let part_sizes = match value.get("part_sizes") {
+            None | Some(Value::Null) => Vec::new(),
+            Some(_) => array_field(value, "part_sizes")?
+                .iter()
+                .map(|size| size.as_u64().ok_or_else(|| anyhow!("分块大小必须是整数")))
+                .collect::<Result<_>>()?,
+        };
+
+        Ok(Self {
+            file_path: PathBuf::from(str_field(value, "file_path")?),
+            object_key: str_field(value, "object_key")?.to_string(),
+            upload_id: str_field(value, "upload_id")?.to_string(),
+            file_size: u64_field(value, "file_size")?,
+            file_mtime: u64_field(value, "file_mtime")?,
+            part_size: u64_field(value, "part_size")?,
+            part_sizes,
+            completed_parts,
+        })
+    }
+}
+
+/// 断点的保留策略
+///
+/// 用户放弃的传输会留下永远不会再使用的断点，按最长保留时间与最大数量清理。
+/// 两项都为 `None`（默认）时不清理任何断点。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CheckpointRetention {
+    /// 断点最后一次保存后的最长保留时间
+    pub max_age: Option<Duration>,
+    /// 最多保留的断点数量，超出时清理最早保存的断点
+    pub max_count: Option<usize>,
+}
+
+impl CheckpointRetention {
+    /// 创建不清理任何断点的保留策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置最长保留时间
+    pub fn with_max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(max_age);
+        self
+    }
+
+    /// 设置最多保留的断点数量
+    pub fn with_max_count(mut self, max_count: usize) -> Self {
+        self.max_count = Some(max_count);
+        self
+    }
+
+    /// 从按保存时间排列的条目中选出应清理的条目的下标
+    fn expired<T>(&self, entries: &[(SystemTime, T)], now: SystemTime) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..entries.len()).collect();
+        // 最近保存的在前
+        order.sort_by(|&a, &b| entries[b].0.cmp(&entries[a].0));
+
+        let mut expired = Vec::new();
+        let mut kept = 0;
+        for index in order {
+            let too_old = self.max_age.is_some_and(|max_age| {
+                now.duration_since(entries[index].0)
+                    .is_ok_and(|age| age > max_age)
+            });
+            let too_many = self.max_count.is_some_and(|max_count| kept >= max_count);
+            if too_old || too_many {
+                expired.push(index);
+            } else {
+                kept += 1;
+            }
+        }
+        expired
+    }
+}
+
+/// 把断点保存在本地目录中的存储，每个对象键一个文件
+///
+/// 打开时按保留策略自动清理一次断点，长期运行的进程也可以定期调用
+/// [`FileCheckpointStore::gc_checkpoints`]。清理只删除本地断点，COS 上对应的分块上传仍然存在，
+/// 可以用返回的断点调用 [`Uploader::abort_multipart_upload`](crate::Uploader::abort_multipart_upload)
+/// 释放已上传的分块。
+#[derive(Debug, Clone)]
+pub struct FileCheckpointStore {
+    dir: PathBuf,
+    retention: CheckpointRetention,
+}
+
+impl FileCheckpointStore {
+    /// 打开断点目录（不存在时创建），并按保留策略清理断点
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 保存断点文件的目录，应只用于保存断点
+    /// * `retention` - 断点的保留策略
+    ///
+    /// # 错误
+    ///
+    /// 创建或读取目录失败时返回错误。
+    pub async fn open<P: AsRef<Path>>(dir: P, retention: CheckpointRetention) -> Result<Self> {
+        let dir = dir.as_ref().to_path_buf();
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("创建断点目录失败: {:?}", dir))?;
+
+        let store = Self { dir, retention };
+        store.gc_checkpoints().await?;
+        Ok(store)
+    }
+
+    /// 断点目录
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// 对象键对应的断点文件，文件名为对象键的 SHA-1，避免对象键中的 `/` 等字符
+    fn path_of(&self, object_key: &str) -> PathBuf {
+        self.dir.join(format!(
+            "{}.{}",
+            sha1_hex(object_key.as_bytes()),
+            CHECKPOINT_EXTENSION
+        ))
+    }
+
+    /// 保存断点，覆盖同一对象键的旧断点
+    ///
+    /// 先写入临时文件并刷盘，再重命名并把目录刷盘，写入过程中进程退出或系统崩溃
+    /// 也不会留下不完整的断点。
+    pub async fn save(&self, checkpoint: &MultipartCheckpoint) -> Result<()> {
+        let path = self.path_of(&checkpoint.object_key);
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+
+        let mut file = tokio::fs::File::create(&temp).await?;
+        file.write_all(checkpoint.to_json().as_bytes()).await?;
+        file.sync_data().await?;
+        drop(file);
+        tokio::fs::rename(&temp, &path).await?;
+        sync_parent_dir(&path).await?;
+        Ok(())
+    }
+
+    /// 读取对象键的断点，不存在时返回 `None`
+    pub async fn load(&self, object_key: &str) -> Result<Option<MultipartCheckpoint>> {
+        let path = self.path_of(object_key);
+        match tokio::fs::read_to_string(&path).await {
+            Ok(text) => MultipartCheckpoint::from_json(&text).map(Some),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(anyhow::Error::new(e).context(format!("读取断点失败: {:?}", path))),
+        }
+    }
+
+    /// 删除对象键的断点，上传完成或放弃时调用；断点不存在时不报错
+    pub async fn remove(&self, object_key: &str) -> Result<()> {
+        remove_if_exists(&self.path_of(object_key)).await
+    }
+
+    /// 按保留策略清理断点
+    ///
+    /// 无法解析的断点文件永远不能用于续传，不论保留策略如何都会被删除，也不计入保留的数量。
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回被清理的断点（不含无法解析的文件），可用于中止 COS 上对应的分块上传
+    pub async fn gc_checkpoints(&self) -> Result<Vec<MultipartCheckpoint>> {
+        let mut entries = Vec::new();
+        let mut dir = tokio::fs::read_dir(&self.dir).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(CHECKPOINT_EXTENSION) {
+                continue;
+            }
+            let modified = entry.metadata().await?.modified()?;
+            let text = match tokio::fs::read_to_string(&path).await {
+                Ok(text) => text,
+                // 同时有其它任务删除了断点
+                Err(e) if e.kind() == ErrorKind::NotFound => continue,
+                Err(e) => {
+                    return Err(anyhow::Error::new(e).context(format!("读取断点失败: {:?}", path)))
+                }
+            };
+            match MultipartCheckpoint::from_json(&text) {
+                Ok(checkpoint) => entries.push((modified, (path, checkpoint))),
+                Err(e) => {
+                    warn!("删除无法解析的断点文件 {:?}: {:#}", path, e);
+                    remove_if_exists(&path).await?;
+                }
+            }
+        }
+
+        let mut removed = Vec::new();
+        for index in self.retention.expired(&entries, SystemTime::now()) {
+            let (path, checkpoint) = &entries[index].1;
+            remove_if_exists(path).await?;
+            removed.push(checkpoint.clone());
+        }
+
+        if !removed.is_empty() {
+            info!("清理了 {} 个断点: {:?}", removed.len(), self.dir);
+        }
+        Ok(removed)
+    }
+}
+
+/// 删除文件，文件不存在时不报错
+async fn remove_if_exists(path: &Path) -> Result<()> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// 文件的修改时间（Unix 秒）
+pub(crate) fn file_mtime(metadata: &std::fs::Metadata) -> Option<u64> {
+    metadata
+        .modified()
+        .ok()?
+        .duration_since(UNIX_EPOCH)
+        .ok()
+        .map(|d| d.as_secs())
+}
+
+/// 路径在 JSON 中的表示，非 UTF-8 的部分会被替换
+///
+/// 替换后无法还原原来的路径，需要据此找回文件的记录（如上传日志）应先拒绝非 UTF-8 的路径。
+pub(crate) fn path_to_json(path: &Path) -> String {
+    path.to_string_lossy().into_owned()
+}
+
+pub(crate) fn str_field<'a>(value: &'a Value, name: &str) -> Result<&'a str> {
+    value
+        .get(name)
+        .and_then(Value::as_str)
+        .ok_or_else(|| anyhow!("断点中缺少字段: {}", name))
+}
+
+pub(crate) fn u64_field(value: &Value, name: &str) -> Result<u64> {
+    value
+        .get(name)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| anyhow!("断点中缺少字段: {}", name))
+}
+
+pub(crate) fn array_field<'a>(value: &'a Value, name: &str) -> Result<&'a Vec<Value>> {
+    value
+        .get(name)
+        .and_then(Value::as_array)
+        .ok_or_else(|| anyhow!("断点中缺少字段: {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_retention() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_000_000);
+        let ago = |secs| (now - Duration::from_secs(secs), ());
+        let entries = [ago(10), ago(5000), ago(100), ago(20)];
+
+        assert!(CheckpointRetention::new().expired(&entries, now).is_empty());
+
+        let retention = CheckpointRetention::new().with_max_age(Duration::from_secs(1000));
+        assert_eq!(retention.expired(&entries, now), vec![1]);
+
+        let retention = retention.with_max_count(2);
+        assert_eq!(retention.expired(&entries, now), vec![2, 1]);
+    }
+
+    fn checkpoint(object_key: &str) -> MultipartCheckpoint {
+        MultipartCheckpoint {
+            file_path: PathBuf::from("/data").join(object_key),
+            object_key: object_key.to_string(),
+            upload_id: format!("upload-{}", object_key),
+            file_size: 12 * 1024 * 1024,
+            file_mtime: 1700000000,
+            part_size: 5 * 1024 * 1024,
+            part_sizes: Vec::new(),
+            completed_parts: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_checkpoint_store_gc() {
+        let dir = tempfile::tempdir().unwrap();
+        let store_dir = dir.path().join("checkpoints");
+        let store = FileCheckpointStore::open(&store_dir, CheckpointRetention::new())
+            .await
+            .unwrap();
+        for key in ["a.bin", "b.bin", "c.bin"] {
+            store.save(&checkpoint(key)).await.unwrap();
+        }
+        let set_age = |key: &str, secs: u64| {
+            let file = std::fs::File::options()
+                .write(true)
+                .open(store.path_of(key))
+                .unwrap();
+            file.set_modified(SystemTime::now() - Duration::from_secs(secs))
+                .unwrap();
+        };
+        set_age("a.bin", 2 * 3600);
+        set_age("c.bin", 600);
+        std::fs::write(store_dir.join("broken.json"), "{").unwrap();
+        std::fs::write(store_dir.join("notes.txt"), "not a checkpoint").unwrap();
+
+        // 不清理时只删除无法解析的文件
+        assert!(store.gc_checkpoints().await.unwrap().is_empty());
+        assert!(!store_dir.join("broken.json").exists());
+        assert_eq!(
+            store.load("a.bin").await.unwrap(),
+            Some(checkpoint("a.bin"))
+        );
+
+        // 重新打开时按保留策略清理：a 超过保留时间，c 超出保留数量
+        let retention = CheckpointRetention::new()
+            .with_max_age(Duration::from_secs(3600))
+            .with_max_count(1);
+        let store = FileCheckpointStore::open(&store_dir, retention)
+            .await
+            .unwrap();
+        assert_eq!(store.load("a.bin").await.unwrap(), None);
+        assert_eq!(
+            store.load("b.bin").await.unwrap(),
+            Some(checkpoint("b.bin"))
+        );
+        assert_eq!(store.load("c.bin").await.unwrap(), None);
+        assert!(store_dir.join("notes.txt").exists());
+
+        store.remove("b.bin").await.unwrap();
+        store.remove("b.bin").await.unwrap();
+        assert!(store.gc_checkpoints().await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_checkpoint_round_trip() {
+        let mut checkpoint = MultipartCheckpoint {
+            file_path: PathBuf::from("/data/a.bin"),
+            object_key: "backup/a.bin".to_string(),
+            upload_id: "upload-1".to_string(),
+            file_size: 12 * 1024 * 1024,
+            file_mtime: 1700000000,
+            part_size: 5 * 1024 * 1024,
+            part_sizes: vec![1024 * 1024, 4 * 1024 * 1024],
+            completed_parts: Vec::new(),
+        };
+        checkpoint.record_part(3, "\"c\"".to_string());
+        checkpoint.record_part(1, "\"a\"".to_string());
+        checkpoint.record_part(1, "\"dup\"".to_string());
+        assert!(checkpoint.is_completed(1));
+        assert!(!checkpoint.is_completed(2));
+
+        let parsed = MultipartCheckpoint::from_json(&checkpoint.to_json()).unwrap();
+        assert_eq!(parsed, checkpoint);
+        assert_eq!(
+            parsed.completed_parts,
+            vec![(1, "\"a\"".to_string()), (3, "\"c\"".to_string())]
+        );
+    }
+}
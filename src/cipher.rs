@@ -0,0 +1,149 @@
+//! 对象键的确定性加密
+//!
+//! 按 `/` 分段加密，每段的密文为 `hex(tag || 明文 ⊕ 密钥流)`：`tag` 是明文的 HMAC（截取前 16 字节），
+//! 同时作为密钥流的 IV（合成 IV 构造）。相同的明文总是得到相同的密文，因此上传、下载与
+//! 按目录列举都可以直接使用密文；解密时重新计算 `tag` 校验，其它密钥写入的对象键无法解密。
+
+use crate::config::REDACTED;
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use std::fmt;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// 每段密文中合成 IV 的字节数
+const TAG_LEN: usize = 16;
+
+/// 对象键的确定性加密，参见 [`Config::with_key_encryption`](crate::Config::with_key_encryption)
+///
+/// 加密后每段的长度变为 `2 × (16 + 明文字节数)`，需要注意 COS 对对象键长度的限制；
+/// 密文只由小写十六进制字符组成，不需要 URL 编码。加密只隐藏各段的内容，
+/// 段数、各段长度以及相同的段（例如同一用户 ID 目录）仍然可见。
+#[derive(Clone)]
+pub struct KeyEncryption {
+    tag_key: [u8; 20],
+    stream_key: [u8; 20],
+}
+
+impl KeyEncryption {
+    /// 从用户提供的密钥派生加密使用的子密钥
+    ///
+    /// # 参数
+    ///
+    /// * `secret` - 密钥，丢失后已加密的对象键将无法还原
+    pub fn new(secret: &[u8]) -> Self {
+        let derive = |label: &[u8]| -> [u8; 20] {
+            let mut mac = HmacSha1::new_from_slice(secret).expect("HMAC 可以接受任意长度的密钥");
+            mac.update(label);
+            mac.finalize().into_bytes().into()
+        };
+        Self {
+            tag_key: derive(b"cos_upload/key-encryption/tag"),
+            stream_key: derive(b"cos_upload/key-encryption/stream"),
+        }
+    }
+
+    /// 加密对象键，空路径段（例如目录对象末尾的 `/`）保持不变
+    pub fn encrypt_key(&self, object_key: &str) -> String {
+        object_key
+            .split('/')
+            .map(|segment| self.encrypt_segment(segment))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// 解密对象键
+    ///
+    /// # 返回值
+    ///
+    /// 不是由同一密钥加密的对象键返回 `None`
+    pub fn decrypt_key(&self, stored_key: &str) -> Option<String> {
+        stored_key
+            .split('/')
+            .map(|segment| self.decrypt_segment(segment))
+            .collect::<Option<Vec<_>>>()
+            .map(|segments| segments.join("/"))
+    }
+
+    fn tag(&self, plaintext: &[u8]) -> HmacSha1 {
+        let mut mac = HmacSha1::new_from_slice(&self.tag_key).expect("HMAC 可以接受任意长度的密钥");
+        mac.update(plaintext);
+        mac
+    }
+
+    /// 以 `tag` 为 IV 的密钥流与数据逐字节异或
+    fn apply_stream(&self, tag: &[u8], data: &mut [u8]) {
+        for (counter, chunk) in data.chunks_mut(20).enumerate() {
+            let mut mac =
+                HmacSha1::new_from_slice(&self.stream_key).expect("HMAC 可以接受任意长度的密钥");
+            mac.update(tag);
+            mac.update(&(counter as u32).to_be_bytes());
+            let block = mac.finalize().into_bytes();
+            for (byte, key) in chunk.iter_mut().zip(block) {
+                *byte ^= key;
+            }
+        }
+    }
+
+    fn encrypt_segment(&self, segment: &str) -> String {
+        if segment.is_empty() {
+            return String::new();
+        }
+        let tag = self.tag(segment.as_bytes()).finalize().into_bytes();
+        let mut out = tag[..TAG_LEN].to_vec();
+        let mut data = segment.as_bytes().to_vec();
+        self.apply_stream(&tag[..TAG_LEN], &mut data);
+        out.extend_from_slice(&data);
+        hex::encode(out)
+    }
+
+    fn decrypt_segment(&self, segment: &str) -> Option<String> {
+        if segment.is_empty() {
+            return Some(String::new());
+        }
+        let bytes = hex::decode(segment).ok()?;
+        if bytes.len() <= TAG_LEN {
+            return None;
+        }
+        let (tag, data) = bytes.split_at(TAG_LEN);
+        let mut data = data.to_vec();
+        self.apply_stream(tag, &mut data);
+        self.tag(&data).verify_truncated_left(tag).ok()?;
+        String::from_utf8(data).ok()
+    }
+}
+
+impl fmt::Debug for KeyEncryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "KeyEncryption({})", REDACTED)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_encryption_round_trip() {
+        let encryption = KeyEncryption::new(b"secret");
+        let stored = encryption.encrypt_key("users/张三@example.com/avatar.png");
+        assert_eq!(
+            stored,
+            encryption.encrypt_key("users/张三@example.com/avatar.png")
+        );
+        assert!(!stored.contains("example"));
+        assert_eq!(stored.split('/').count(), 3);
+        assert_eq!(
+            encryption.decrypt_key(&stored).unwrap(),
+            "users/张三@example.com/avatar.png"
+        );
+
+        // 目录按段加密，前缀关系得以保留
+        let dir = encryption.encrypt_key("users/");
+        assert!(dir.ends_with('/'));
+        assert!(stored.starts_with(&dir));
+
+        assert!(KeyEncryption::new(b"other").decrypt_key(&stored).is_none());
+        assert!(encryption.decrypt_key("users/a.png").is_none());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::options::UploadOptions;
+use crate::presign::PresignWindow;
+use crate::types::UploadResult;
+use crate::uploader::{Uploader, MAX_PARTS};
+use anyhow::{anyhow, Result};
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// 客户端上传一个分块的凭据
+///
+/// 客户端以 `PUT` 向 `url` 发送分块的内容，把响应中的 `ETag` 头部交回服务端。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientPartTicket {
+    /// 分块编号（从 1 开始）
+    pub part_number: u32,
+    /// 预签名 URL
+    pub url: String,
+    /// URL 开始生效的时间（Unix 秒）
+    pub valid_from: i64,
+    /// URL 的过期时间（Unix 秒）
+    pub expires_at: i64,
+    /// 生成凭据时服务端的时间（Unix 秒）
+    pub server_time: i64,
+}
+
+/// 分块凭据刷新后的回调，参数为重新签发的凭据
+pub type PartRefreshCallback = Arc<dyn Fn(&[ClientPartTicket]) + Send + Sync>;
+
+/// 客户端分块上传的签发策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientMultipartOptions {
+    /// 每个分块 URL 的有效时间窗口（默认 15 分钟）
+    pub window: PresignWindow,
+    /// 同时签发、尚未完成的分块数上限（默认 8）
+    pub wave_size: u32,
+    /// URL 距离过期不足该时长时重新签发（默认 3 分钟）
+    pub refresh_margin: Duration,
+}
+
+impl Default for ClientMultipartOptions {
+    fn default() -> Self {
+        Self {
+            window: PresignWindow::new(Duration::from_secs(15 * 60)),
+            wave_size: 8,
+            refresh_margin: Duration::from_secs(3 * 60),
+        }
+    }
+}
+
+impl ClientMultipartOptions {
+    /// 使用默认策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置每个分块 URL 的有效时间窗口
+    pub fn with_window(mut self, window: PresignWindow) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// 设置同时签发、尚未完成的分块数上限
+    pub fn with_wave_size(mut self, wave_size: u32) -> Self {
+        self.wave_size = wave_size.max(1);
+        self
+    }
+
+    /// 设置重新签发 URL 的提前量
+    pub fn with_refresh_margin(mut self, refresh_margin: Duration) -> Self {
+        self.refresh_margin = refresh_margin;
+        self
+    }
+}
+
+/// 客户端分块上传的进度
+#[derive(Default)]
+struct ClientMultipartState {
+    /// 下一个尚未签发的分块编号
+    next_part: u32,
+    /// 已签发、尚未完成的分块及其 URL 的过期时间
+    issued: BTreeMap<u32, i64>,
+    /// 已完成的分块及其 ETag
+    completed: BTreeMap<u32, String>,
+    on_refresh: Option<PartRefreshCallback>,
+    /// 已完成或终止，后台刷新任务据此退出
+    finished: bool,
+}
+
+struct ClientMultipartInner {
+    uploader: Uploader,
+    object_key: String,
+    upload_id: String,
+    part_count: u32,
+    forbid_overwrite: bool,
+    options: ClientMultipartOptions,
+    state: Mutex<ClientMultipartState>,
+}
+
+/// 由浏览器等客户端通过预签名 URL 直接上传分块的分块上传
+///
+/// 通过 [`Uploader::start_client_multipart_upload`] 创建，服务端持有该句柄，只向客户端下发分块的预签名 URL，
+/// 密钥始终不离开服务端。适用于持续数小时的大文件直传：
+///
+/// 1. 调用 [`ClientMultipartUpload::next_wave`] 分批签发分块 URL，同时未完成的分块数不超过
+///    [`ClientMultipartOptions::wave_size`]，避免一次签发全部 URL 而在上传到后面的分块时过期；
+/// 2. 客户端每上传完一个分块，服务端调用 [`ClientMultipartUpload::part_completed`] 记录 ETag，
+///    再签发下一批；
+/// 3. 定期调用 [`ClientMultipartUpload::refresh_expiring`]（或通过
+///    [`ClientMultipartUpload::spawn_refresher`] 在后台调用），为即将过期、尚未完成的分块重新签发 URL，
+///    并通过回调推送给客户端；
+/// 4. 全部分块完成后调用 [`ClientMultipartUpload::complete`]，放弃时调用
+///    [`ClientMultipartUpload::abort`] 释放已上传的分块。
+///
+/// 句柄可以克隆，克隆后共享同一次上传的进度。
+#[derive(Clone)]
+pub struct ClientMultipartUpload {
+    inner: Arc<ClientMultipartInner>,
+}
+
+impl fmt::Debug for ClientMultipartUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.inner.state.lock().unwrap();
+        f.debug_struct("ClientMultipartUpload")
+            .field("object_key", &self.inner.object_key)
+            .field("upload_id", &self.inner.upload_id)
+            .field("part_count", &self.inner.part_count)
+            .field("issued", &state.issued.len())
+            .field("completed", &state.completed.len())
+            .finish()
+    }
+}
+
+impl ClientMultipartUpload {
+    /// 对象键
+    pub fn object_key(&self) -> &str {
+        &self.inner.object_key
+    }
+
+    /// 分块上传 ID
+    pub fn upload_id(&self) -> &str {
+        &self.inner.upload_id
+    }
+
+    /// 分块总数
+    pub fn part_count(&self) -> u32 {
+        self.inner.part_count
+    }
+
+    /// 已完成的分块数
+    pub fn parts_completed(&self) -> u32 {
+        self.inner.state.lock().unwrap().completed.len() as u32
+    }
+
+    /// 已签发、尚未完成的分块编号
+    pub fn pending_parts(&self) -> Vec<u32> {
+        let state = self.inner.state.lock().unwrap();
+        state.issued.keys().copied().collect()
+    }
+
+    /// 设置凭据刷新后的回调，用于把重新签发的 URL 推送给客户端（如通过 WebSocket）
+    pub fn set_refresh_callback(&self, callback: PartRefreshCallback) {
+        self.inner.state.lock().unwrap().on_refresh = Some(callback);
+    }
+
+    /// 为一个分块签发 URL
+    fn ticket(&self, part_number: u32) -> ClientPartTicket {
+        let presigned = self.inner.uploader.presign_part_url(
+            &self.inner.object_key,
+            &self.inner.upload_id,
+            part_number,
+            &self.inner.options.window,
+        );
+        ClientPartTicket {
+            part_number,
+            url: presigned.url,
+            valid_from: presigned.valid_from,
+            expires_at: presigned.expires_at,
+            server_time: presigned.server_time,
+        }
+    }
+
+    /// 签发下一批分块的 URL
+    ///
+    /// 补足到最多 [`ClientMultipartOptions::wave_size`] 个未完成的分块。
+    ///
+    /// # 返回值
+    ///
+    /// 返回新签发的凭据；未完成的分块已达上限或全部分块都已签发时返回空列表
+    pub fn next_wave(&self) -> Vec<ClientPartTicket> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.finished {
+            return Vec::new();
+        }
+        let room = self
+            .inner
+            .options
+            .wave_size
+            .saturating_sub(state.issued.len() as u32);
+        let first = state.next_part;
+        let last = (first + room).min(self.inner.part_count + 1);
+        let tickets: Vec<_> = (first..last).map(|n| self.ticket(n)).collect();
+        for ticket in &tickets {
+            state.issued.insert(ticket.part_number, ticket.expires_at);
+        }
+        state.next_part = last;
+        if !tickets.is_empty() {
+            debug!(
+                "签发分块 URL: {} 第 {}-{} 块",
+                self.inner.object_key,
+                first,
+                last - 1
+            );
+        }
+        tickets
+    }
+
+    /// 记录客户端上传完成的分块
+    ///
+    /// 同一分块重复上传时以最后一次的 ETag 为准。
+    ///
+    /// # 参数
+    ///
+    /// * `part_number` - 分块编号
+    /// * `etag` - 客户端收到的 `ETag` 响应头
+    ///
+    /// # 错误
+    ///
+    /// 分块尚未签发或上传已经结束时返回错误。
+    pub fn part_completed(&self, part_number: u32, etag: &str) -> Result<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.finished {
+            return Err(anyhow!("分块上传已经结束: {}", self.inner.object_key));
+        }
+        if state.issued.remove(&part_number).is_none()
+            && !state.completed.contains_key(&part_number)
+        {
+            return Err(anyhow!(
+                "第 {} 个分块尚未签发: {}",
+                part_number,
+                self.inner.object_key
+            ));
+        }
+        state.completed.insert(part_number, etag.to_string());
+        Ok(())
+    }
+
+    /// 为即将过期、尚未完成的分块重新签发 URL
+    ///
+    /// 设置了回调时（[`ClientMultipartUpload::set_refresh_callback`]）以重新签发的凭据调用回调。
+    ///
+    /// # 返回值
+    ///
+    /// 返回重新签发的凭据，没有即将过期的分块时返回空列表
+    pub fn refresh_expiring(&self) -> Vec<ClientPartTicket> {
+        let (tickets, callback) = {
+            let mut state = self.inner.state.lock().unwrap();
+            let deadline =
+                Utc::now().timestamp() + self.inner.options.refresh_margin.as_secs() as i64;
+            let expiring: Vec<u32> = state
+                .issued
+                .iter()
+                .filter(|(_, expires_at)| **expires_at <= deadline)
+                .map(|(part_number, _)| *part_number)
+                .collect();
+            let tickets: Vec<_> = expiring.into_iter().map(|n| self.ticket(n)).collect();
+            for ticket in &tickets {
+                state.issued.insert(ticket.part_number, ticket.expires_at);
+            }
+            (tickets, state.on_refresh.clone())
+        };
+        if tickets.is_empty() {
+            return tickets;
+        }
+        debug!(
+            "重新签发即将过期的分块 URL: {} ({} 个)",
+            self.inner.object_key,
+            tickets.len()
+        );
+        if let Some(callback) = callback {
+            callback(&tickets);
+        }
+        tickets
+    }
+
+    /// 在后台定期调用 [`ClientMultipartUpload::refresh_expiring`]
+    ///
+    /// 上传完成或终止后后台任务自动退出。
+    ///
+    /// # 参数
+    ///
+    /// * `interval` - 检查间隔，应明显小于 [`ClientMultipartOptions::refresh_margin`]
+    pub fn spawn_refresher(&self, interval: Duration) -> JoinHandle<()> {
+        let upload = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                if upload.inner.state.lock().unwrap().finished {
+                    break;
+                }
+                upload.refresh_expiring();
+            }
+        })
+    }
+
+    /// 标记上传结束，后续不再签发或刷新 URL
+    fn finish(&self) -> Result<()> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.finished {
+            return Err(anyhow!("分块上传已经结束: {}", self.inner.object_key));
+        }
+        state.finished = true;
+        Ok(())
+    }
+
+    /// 完成或终止的请求失败时撤销结束标记，句柄及其克隆仍可以重试完成或终止
+    fn reopen(&self) {
+        self.inner.state.lock().unwrap().finished = false;
+    }
+
+    /// 按分块编号合并所有分块，完成上传
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回对象的上传结果
+    ///
+    /// # 错误
+    ///
+    /// 尚有分块未完成、上传已经结束或完成请求失败时返回错误。完成请求失败时上传仍未结束，
+    /// 可以通过句柄的克隆重试完成或终止。客户端交回的 ETag 与 COS 保存的分块不一致时 COS 拒绝完成请求，分块仍然保留，
+    /// 需要调用方通过 [`Uploader::abort_multipart_upload`] 释放。
+    pub async fn complete(self) -> Result<UploadResult> {
+        let parts: Vec<(u32, String)> = {
+            let state = self.inner.state.lock().unwrap();
+            if state.completed.len() as u32 != self.inner.part_count {
+                return Err(anyhow!(
+                    "尚有分块未完成 ({}/{}): {}",
+                    state.completed.len(),
+                    self.inner.part_count,
+                    self.inner.object_key
+                ));
+            }
+            state
+                .completed
+                .iter()
+                .map(|(part_number, etag)| (*part_number, etag.clone()))
+                .collect()
+        };
+        self.finish()?;
+
+        let (result, _) = self
+            .inner
+            .uploader
+            .finish_multipart_upload(
+                &self.inner.object_key,
+                &self.inner.upload_id,
+                &parts,
+                Some(self.inner.part_count),
+                self.inner.forbid_overwrite,
+            )
+            .await
+            .inspect_err(|_| self.reopen())?;
+        self.inner
+            .uploader
+            .invalidate_cached(&self.inner.object_key);
+        info!(
+            "客户端分块上传成功: {} ({} 个分块, request_id: {:?})",
+            result.url, self.inner.part_count, result.request_id
+        );
+        Ok(result)
+    }
+
+    /// 终止上传，释放已上传的分块
+    ///
+    /// 终止请求失败时上传仍未结束，可以通过句柄的克隆重试。
+    pub async fn abort(self) -> Result<()> {
+        self.finish()?;
+        self.inner
+            .uploader
+            .abort_multipart_upload(&self.inner.object_key, &self.inner.upload_id)
+            .await
+            .inspect_err(|e| {
+                warn!("终止分块上传失败: {}: {}", self.inner.object_key, e);
+                self.reopen();
+            })?;
+        info!(
+            "已终止客户端分块上传: {} ({}/{} 个分块)",
+            self.inner.object_key,
+            self.parts_completed(),
+            self.inner.part_count
+        );
+        Ok(())
+    }
+}
+
+impl Uploader {
+    /// 开始一个由客户端通过预签名 URL 上传分块的分块上传
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `part_count` - 分块总数，除最后一个分块外每个分块不能小于 1 MB
+    /// * `options` - 上传选项，其中的元数据、存储类型与禁止覆盖等设置作用于最终的对象
+    /// * `policy` - 分块 URL 的签发策略
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传句柄，通过 [`ClientMultipartUpload::next_wave`] 签发分块 URL
+    ///
+    /// # 错误
+    ///
+    /// 分块总数为 0 或超过 10000、初始化分块上传失败时返回错误。
+    pub async fn start_client_multipart_upload(
+        &self,
+        object_key: &str,
+        part_count: u32,
+        options: &UploadOptions,
+        policy: ClientMultipartOptions,
+    ) -> Result<ClientMultipartUpload> {
+        if part_count == 0 || part_count as usize > MAX_PARTS {
+            return Err(anyhow!(
+                "分块总数必须在 1 到 {} 之间: {} ({})",
+                MAX_PARTS,
+                object_key,
+                part_count
+            ));
+        }
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(
+                target.start_client_multipart_upload(object_key, part_count, options, policy),
+            )
+            .await;
+        }
+        let upload_id = self.init_multipart_upload(object_key, options).await?;
+        info!(
+            "开始客户端分块上传: {} ({} 个分块, upload_id: {})",
+            object_key, part_count, upload_id
+        );
+
+        Ok(ClientMultipartUpload {
+            inner: Arc::new(ClientMultipartInner {
+                uploader: self.clone(),
+                object_key: object_key.to_string(),
+                upload_id,
+                part_count,
+                forbid_overwrite: options.forbid_overwrite,
+                options: policy,
+                state: Mutex::new(ClientMultipartState {
+                    next_part: 1,
+                    ..ClientMultipartState::default()
+                }),
+            }),
+        })
+    }
+}
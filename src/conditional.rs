@@ -0,0 +1,206 @@
+use crate::error::{is_not_found, map_already_exists, CosError};
+#[cfg(feature = "unpack")]
+use crate::options::UploadOptions;
+use crate::request::{header_of, object_url_of, CosRequest};
+use crate::types::{crc64_of, request_id_of, UploadResult};
+use crate::uploader::{Uploader, FORBID_OVERWRITE_HEADER};
+use anyhow::Result;
+use bytes::Bytes;
+use reqwest::Method;
+use tracing::{info, warn};
+
+/// 比较两个 ETag，忽略引号与弱校验前缀
+fn etag_matches(a: &str, b: &str) -> bool {
+    fn normalize(etag: &str) -> &str {
+        etag.trim().trim_start_matches("W/").trim_matches('"')
+    }
+    normalize(a) == normalize(b)
+}
+
+impl Uploader {
+    /// 仅当对象的 ETag 与期望一致时写入（比较并交换）
+    ///
+    /// 多个服务更新同一个状态对象时，先读取对象与其 ETag，修改后以读到的 ETag 调用本方法；
+    /// 期间对象被其它写入修改时返回 [`CosError::PreconditionFailed`]，调用方重新读取后重试，
+    /// 而不是覆盖掉对方的修改。
+    ///
+    /// 写入前先以 `HEAD` 检查当前的 ETag，写入时再带上 `If-Match`（期望对象不存在时为
+    /// `x-cos-forbid-overwrite`），由 COS 在服务端确认。不支持条件写入的兼容网关只能依靠前一步的检查，
+    /// 两次请求之间仍可能发生并发写入。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `expected_etag` - 期望的当前 ETag，为 `None` 时要求对象不存在
+    /// * `data` - 新的对象内容，Content-Type 按对象键的扩展名推断
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果，其中的 ETag 可用于下一次条件写入
+    ///
+    /// # 错误
+    ///
+    /// ETag 不一致时返回 [`CosError::PreconditionFailed`]，请求失败时返回对应的错误。
+    pub async fn put_if_match(
+        &self,
+        object_key: &str,
+        expected_etag: Option<&str>,
+        data: impl Into<Bytes>,
+    ) -> Result<UploadResult> {
+        let content_type = mime_guess::from_path(object_key)
+            .first_or_octet_stream()
+            .to_string();
+        self.put_bytes_if_match(object_key, expected_etag, data.into(), &content_type, &[])
+            .await
+    }
+
+    /// 条件写入内存中的数据，`headers` 为额外的请求头
+    pub(crate) async fn put_bytes_if_match(
+        &self,
+        object_key: &str,
+        expected_etag: Option<&str>,
+        data: Bytes,
+        content_type: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<UploadResult> {
+        let precondition_failed = |actual_etag: Option<String>, request_id: Option<String>| {
+            CosError::PreconditionFailed {
+                object_key: object_key.to_string(),
+                expected_etag: expected_etag.map(str::to_string),
+                actual_etag,
+                request_id,
+            }
+        };
+
+        let current = match self.get_object_metadata(object_key).await {
+            Ok(metadata) => Some(metadata),
+            Err(e) if is_not_found(&e) => None,
+            Err(e) => return Err(e),
+        };
+        let matched = match (&current, expected_etag) {
+            (None, None) => true,
+            (Some(current), Some(expected)) => current
+                .etag
+                .as_deref()
+                .is_some_and(|etag| etag_matches(etag, expected)),
+            _ => false,
+        };
+        if !matched {
+            let current = current.as_ref();
+            return Err(precondition_failed(
+                current.and_then(|m| m.etag.clone()),
+                current.and_then(|m| m.request_id.clone()),
+            )
+            .into());
+        }
+
+        let condition = match expected_etag {
+            Some(etag) => ("If-Match", etag),
+            None => (FORBID_OVERWRITE_HEADER, "true"),
+        };
+        let headers: Vec<_> = std::iter::once(condition)
+            .chain(headers.iter().copied())
+            .collect();
+
+        match self
+            .put_bytes(object_key, data, content_type, &headers)
+            .await
+        {
+            Ok(result) => Ok(result),
+            Err(e) => {
+                let e = map_already_exists(e, object_key);
+                Err(match e.downcast_ref::<CosError>() {
+                    Some(CosError::Service {
+                        status: 412,
+                        request_id,
+                        ..
+                    })
+                    | Some(CosError::AlreadyExists { request_id, .. }) => {
+                        warn!("条件写入冲突: {}", object_key);
+                        precondition_failed(None, request_id.clone()).into()
+                    }
+                    _ => e,
+                })
+            }
+        }
+    }
+
+    /// 以一次 `PUT` 请求写入内存中的数据，`headers` 为额外的请求头
+    pub(crate) async fn put_bytes(
+        &self,
+        object_key: &str,
+        data: Bytes,
+        content_type: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<UploadResult> {
+        let mut request =
+            CosRequest::new(Method::PUT, object_key).header("Content-Type", content_type);
+        for (name, value) in headers {
+            request = request.header(name, *value);
+        }
+        self.send_put(object_key, request, data).await
+    }
+
+    /// 以一次 `PUT` 请求写入内存中的数据，并应用上传选项中的元数据、存储类型等设置
+    #[cfg(feature = "unpack")]
+    pub(crate) async fn put_bytes_with_options(
+        &self,
+        object_key: &str,
+        data: Bytes,
+        content_type: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        let mut request =
+            CosRequest::new(Method::PUT, object_key).header("Content-Type", content_type);
+        if options.forbid_overwrite {
+            request = request.header(FORBID_OVERWRITE_HEADER, "true");
+        }
+        self.send_put(object_key, options.apply(request), data)
+            .await
+            .map_err(|e| map_already_exists(e, object_key))
+    }
+
+    /// 发送写入数据的 `PUT` 请求，校验 COS 返回的 CRC64，成功后清除该对象的缓存
+    async fn send_put(
+        &self,
+        object_key: &str,
+        request: CosRequest,
+        data: Bytes,
+    ) -> Result<UploadResult> {
+        let mut crc64 = self.hash_backend.crc64();
+        crc64.update(&data);
+        let response = self.execute(request.body(data)).await?;
+        self.invalidate_cached(object_key);
+
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let etag = header_of(&response, "ETag");
+        let crc = crc64_of(response.headers());
+        self.check_crc64(object_key, crc64.finish(), crc)?;
+        info!(
+            "写入成功: {} ETag {:?} (request_id: {:?})",
+            object_key, etag, request_id
+        );
+
+        Ok(UploadResult {
+            url,
+            etag,
+            request_id,
+            stats: None,
+            failover: None,
+            crc64: crc,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_etag_matches() {
+        assert!(etag_matches("\"abc\"", "abc"));
+        assert!(etag_matches("W/\"abc\"", "\"abc\""));
+        assert!(!etag_matches("\"abc\"", "\"abd\""));
+    }
+}
@@ -1,16 +1,292 @@
-use anyhow::Result;
+#[cfg(feature = "crypto")]
+use crate::cipher::KeyEncryption;
+#[cfg(feature = "runtime")]
+use crate::error::CosError;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+#[cfg(any(feature = "runtime", feature = "presign"))]
+use std::borrow::Cow;
+#[cfg(any(feature = "runtime", feature = "presign"))]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// COS 对象键的最大长度（UTF-8 字节）
+#[cfg(feature = "runtime")]
+const MAX_OBJECT_KEY_LEN: usize = 850;
+
+/// 访问 COS 使用的域名类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum EndpointKind {
+    /// 地域域名 `{bucket}.cos.{region}.myqcloud.com`
+    #[default]
+    Regional,
+    /// 全球加速域名 `{bucket}.cos.accelerate.myqcloud.com`，需先为 Bucket 开启全球加速
+    Accelerate,
+    /// 内网域名 `{bucket}.cos-internal.{region}.tencentcos.cn`，只能在同地域的 CVM/TKE 等腾讯云内网环境中访问，
+    /// 不产生外网下行流量费用
+    ///
+    /// 内网域名无法连接时，[`Uploader`](crate::Uploader) 会自动回退到地域域名并继续使用。
+    Internal,
+}
+
+impl EndpointKind {
+    /// 该类型下 Bucket 的访问域名
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn host(&self, bucket: &str, region: &str) -> String {
+        format!("{}.{}", bucket, self.service_host(region))
+    }
+
+    /// 该类型下不含 Bucket 的服务域名，用于路径风格的请求
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    fn service_host(&self, region: &str) -> String {
+        match self {
+            EndpointKind::Regional => format!("cos.{}.myqcloud.com", region),
+            EndpointKind::Accelerate => "cos.accelerate.myqcloud.com".to_string(),
+            EndpointKind::Internal => format!("cos-internal.{}.tencentcos.cn", region),
+        }
+    }
+}
+
+/// Bucket 在请求中的寻址方式
+///
+/// 虚拟主机风格把 Bucket 放在域名中（`{bucket}.cos.{region}.myqcloud.com`），Bucket 名称含有 `.` 时
+/// 域名多出一级，无法匹配 `*.cos.{region}.myqcloud.com` 通配证书，TLS 握手失败；
+/// 路径风格把 Bucket 放在路径的第一段（`cos.{region}.myqcloud.com/{bucket}/{key}`），签名中的路径同样包含 Bucket。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum AddressingStyle {
+    /// 自动选择（默认）：兼容模式为 [`CompatibilityProfile::Generic`] 或 Bucket 名称含有 `.` 时使用路径风格，
+    /// 否则使用虚拟主机风格
+    #[default]
+    Auto,
+    /// 始终使用虚拟主机风格；设置了自定义端点时端点本身应已包含 Bucket
+    VirtualHost,
+    /// 始终使用路径风格；设置了自定义端点时端点作为服务域名，Bucket 放在路径中
+    Path,
+}
+
+/// 对接的服务端实现
+///
+/// 开发环境中常用自建的 COS 协议兼容网关（如 MinIO 风格的网关）代替腾讯云 COS，
+/// 这些网关与 COS 存在细微差异，通过兼容模式切换相应的行为。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum CompatibilityProfile {
+    /// 腾讯云 COS：Bucket 作为域名的一部分（虚拟主机风格），校验 `x-cos-hash-crc64ecma`
+    #[default]
+    Tencent,
+    /// 第三方兼容网关：Bucket 作为路径的第一段（`{endpoint}/{bucket}/{key}`），
+    /// 不校验 CRC64（这类网关通常不返回该头部，或使用不同的算法）
+    Generic,
+}
+
+impl CompatibilityProfile {
+    /// 是否校验 COS 返回的 CRC64
+    #[cfg(feature = "runtime")]
+    pub(crate) fn verifies_crc64(self) -> bool {
+        self == CompatibilityProfile::Tencent
+    }
+
+    /// 是否把 Bucket 放在请求路径中
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    fn path_style(self) -> bool {
+        self == CompatibilityProfile::Generic
+    }
+}
+
+/// 参与请求签名的头部范围
+///
+/// 部分企业代理会删除或改写请求头（例如改写 `Content-Length`），使签名失效，
+/// 此时可以缩小参与签名的头部范围。`Host` 总是参与签名。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum SignedHeaders {
+    /// 全部请求头都参与签名（默认），请求在传输中被改写时 COS 会拒绝
+    #[default]
+    All,
+    /// 最小的安全集合：`Host`、`Content-Type`、`Content-MD5` 与全部 `x-cos-*` 头部，
+    /// 不包含 `Content-Length` 等容易被代理改写的头部
+    Minimal,
+    /// 只有 `Host` 与列出的头部参与签名（名称不区分大小写）
+    Only(Vec<String>),
+}
+
+impl SignedHeaders {
+    /// 头部是否参与签名
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    fn includes(&self, name: &str) -> bool {
+        let name = name.to_ascii_lowercase();
+        match self {
+            SignedHeaders::All => true,
+            SignedHeaders::Minimal => {
+                matches!(name.as_str(), "host" | "content-type" | "content-md5")
+                    || name.starts_with("x-cos-")
+            }
+            SignedHeaders::Only(names) => {
+                name == "host" || names.iter().any(|n| n.eq_ignore_ascii_case(&name))
+            }
+        }
+    }
+
+    /// 从请求头中取出参与签名的部分
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn select(&self, headers: &HashMap<String, String>) -> HashMap<String, String> {
+        match self {
+            SignedHeaders::All => headers.clone(),
+            _ => headers
+                .iter()
+                .filter(|(name, _)| self.includes(name))
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect(),
+        }
+    }
+
+    /// 不是签入全部头部时记录警告，提醒未签名的头部被篡改时无法发现
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn warn_if_reduced(&self) {
+        match self {
+            SignedHeaders::All => {}
+            SignedHeaders::Minimal => {
+                tracing::warn!("Content-Length 等头部不参与签名，传输中被改写时 COS 无法发现")
+            }
+            SignedHeaders::Only(names) => tracing::warn!(
+                "只有 Host 与 {:?} 参与签名，其它头部（包括 x-cos-* 头部）被篡改时 COS 无法发现",
+                names
+            ),
+        }
+    }
+}
+
+/// 签名时规范化请求头的方式
+///
+/// 默认与官方文档及腾讯云 SDK 的签名算法逐字节一致：头部名转为小写，值原样进行 URL 编码。
+/// 部分对规范化要求严格的第三方 COS 兼容实现会先去掉值首尾的空白，或者保留头部名的大小写，
+/// 此时可以调整这里的选项，使 `q-header-list` 与规范请求与服务端的计算一致。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeaderCanonicalization {
+    /// 头部名是否转为小写（默认开启）；关闭时按原样的大小写排序与签名
+    pub lowercase_names: bool,
+    /// 是否去掉值首尾的空白后再编码（默认关闭）
+    pub trim_values: bool,
+}
+
+impl Default for HeaderCanonicalization {
+    fn default() -> Self {
+        Self {
+            lowercase_names: true,
+            trim_values: false,
+        }
+    }
+}
+
+impl HeaderCanonicalization {
+    /// 创建与官方文档一致的规范化方式
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置头部名是否转为小写
+    pub fn with_lowercase_names(mut self, enabled: bool) -> Self {
+        self.lowercase_names = enabled;
+        self
+    }
+
+    /// 设置是否去掉值首尾的空白
+    pub fn with_trim_values(mut self, enabled: bool) -> Self {
+        self.trim_values = enabled;
+        self
+    }
+}
 
 /// COS 配置结构体
+///
+/// 启用 `serde` feature 后可以序列化与反序列化。序列化时 `secret_key` 与 `security_token`
+/// 会被替换为 [`REDACTED`]，因此序列化结果可以安全地写入日志或任务队列，但不能用来还原凭证。
 #[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     /// 腾讯云 SecretId
     pub secret_id: String,
     /// 腾讯云 SecretKey
+    #[cfg_attr(feature = "serde", serde(serialize_with = "redact"))]
     pub secret_key: String,
     /// COS 地域
     pub region: String,
     /// COS Bucket 名称
     pub bucket: String,
+    /// Bucket 不在配置的地域时，是否自动向正确的地域重试（默认关闭）
+    ///
+    /// 关闭时返回 [`CosError::WrongRegion`](crate::CosError::WrongRegion)。
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub follow_region_redirects: bool,
+    /// 临时密钥的 SessionToken，使用临时密钥时随请求以 `x-cos-security-token` 发送
+    #[cfg_attr(feature = "serde", serde(default, serialize_with = "redact_option"))]
+    pub security_token: Option<String>,
+    /// 访问 COS 使用的域名类型（默认使用地域域名）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub endpoint: EndpointKind,
+    /// COS 返回 `SignatureDoesNotMatch` 时，是否在错误中附上 COS 期望的与本地计算的待签字符串对比（默认关闭）
+    ///
+    /// 开启后可以通过 `err.downcast_ref::<`[`SignatureMismatch`](crate::SignatureMismatch)`>()` 取出对比结果。仅用于排查问题，
+    /// 对比内容包含请求的头部与查询参数。
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub debug_signature: bool,
+    /// 自定义端点（如 `http://127.0.0.1:9000`），设置后所有请求都发往该地址，
+    /// 忽略 `endpoint` 与地域对域名的影响；未写协议时使用 HTTPS
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub custom_endpoint: Option<String>,
+    /// 对接的服务端实现（默认为腾讯云 COS）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub compatibility: CompatibilityProfile,
+    /// Bucket 的寻址方式（默认自动选择），参见 [`AddressingStyle`]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub addressing_style: AddressingStyle,
+    /// 自动加在所有对象键之前的前缀（如 `env/staging/`），用于隔离不同环境
+    ///
+    /// 上传、下载、删除、复制与预签名使用的对象键都会加上该前缀；列举时的前缀相对于它，
+    /// 返回的对象键会去掉它，调用方始终只看到逻辑上的对象键。
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub key_prefix: Option<String>,
+    /// 参与请求签名的头部范围（默认全部头部）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub signed_headers: SignedHeaders,
+    /// 账号的 APPID（如 `1250000000`）
+    ///
+    /// 设置后 `bucket` 可以只写短名称（如 `examplebucket`），请求时自动补全为 `examplebucket-1250000000`，
+    /// 参见 [`Config::bucket_name`]。
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub app_id: Option<String>,
+    /// 应用标识（如 `billing-service/2.1`），附加在 `User-Agent` 的 `cos_upload/{版本号}` 之后，
+    /// 便于在 COS 访问日志中区分来自不同服务的请求
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub app_name: Option<String>,
+    /// 签名时规范化头部名称与值的方式（默认按官方文档）
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub header_canonicalization: HeaderCanonicalization,
+    /// 对象键的确定性加密（默认不加密），参见 [`Config::with_key_encryption`]
+    ///
+    /// 不参与序列化，反序列化得到的配置需要重新设置。
+    #[cfg(feature = "crypto")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub key_encryption: Option<KeyEncryption>,
 }
 
 impl Config {
@@ -22,6 +298,9 @@ impl Config {
     /// - TENCENT_COS_REGION
     /// - TENCENT_COS_BUCKET
     ///
+    /// 使用临时密钥时可以额外设置 `TENCENT_SECURITY_TOKEN`；`TENCENT_COS_BUCKET` 只写短名称时，
+    /// 需要通过 `TENCENT_COS_APPID` 提供 APPID。
+    ///
     /// # 错误
     ///
     /// 如果任何必需的环境变量未设置，将返回错误。
@@ -31,6 +310,20 @@ impl Config {
             secret_key: std::env::var("TENCENT_SECRET_KEY")?,
             region: std::env::var("TENCENT_COS_REGION")?,
             bucket: std::env::var("TENCENT_COS_BUCKET")?,
+            follow_region_redirects: false,
+            security_token: std::env::var("TENCENT_SECURITY_TOKEN").ok(),
+            endpoint: EndpointKind::default(),
+            debug_signature: false,
+            custom_endpoint: None,
+            compatibility: CompatibilityProfile::default(),
+            addressing_style: AddressingStyle::default(),
+            key_prefix: None,
+            signed_headers: SignedHeaders::default(),
+            app_id: std::env::var("TENCENT_COS_APPID").ok(),
+            app_name: None,
+            header_canonicalization: HeaderCanonicalization::default(),
+            #[cfg(feature = "crypto")]
+            key_encryption: None,
         })
     }
 
@@ -41,6 +334,566 @@ impl Config {
             secret_key,
             region,
             bucket,
+            follow_region_redirects: false,
+            security_token: None,
+            endpoint: EndpointKind::default(),
+            debug_signature: false,
+            custom_endpoint: None,
+            compatibility: CompatibilityProfile::default(),
+            addressing_style: AddressingStyle::default(),
+            key_prefix: None,
+            signed_headers: SignedHeaders::default(),
+            app_id: None,
+            app_name: None,
+            header_canonicalization: HeaderCanonicalization::default(),
+            #[cfg(feature = "crypto")]
+            key_encryption: None,
+        }
+    }
+
+    /// 从腾讯云命令行工具（tccli）的配置文件创建配置
+    ///
+    /// 与 tccli 一样读取 `~/.tccli/{profile}.credential` 中的 `secretId`、`secretKey`
+    /// （以及临时密钥的 `token`），地域依次取自凭证文件的 `region` 与 `~/.tccli/{profile}.configure`
+    /// 中的 `_sys_param.region`。
+    ///
+    /// # 参数
+    ///
+    /// * `profile` - tccli 的配置名，为 `None` 时使用 `default`
+    /// * `bucket` - COS Bucket 名称
+    ///
+    /// # 错误
+    ///
+    /// 找不到用户主目录、凭证文件不存在或缺少必需的字段时返回错误。
+    pub fn from_tccli(profile: Option<&str>, bucket: String) -> Result<Self> {
+        let home = std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .ok_or_else(|| anyhow!("无法确定用户主目录"))?;
+
+        Self::from_tccli_dir(
+            &PathBuf::from(home).join(".tccli"),
+            profile.unwrap_or("default"),
+            bucket,
+        )
+    }
+
+    /// 从指定的 tccli 配置目录创建配置，参见 [`Config::from_tccli`]
+    pub fn from_tccli_dir(dir: &Path, profile: &str, bucket: String) -> Result<Self> {
+        let credential_path = dir.join(format!("{}.credential", profile));
+        let credential = read_json(&credential_path)?;
+        // configure 文件是可选的，只用来补充地域
+        let configure = read_json(&dir.join(format!("{}.configure", profile))).ok();
+
+        let field = |value: &Value, name: &str| {
+            value
+                .get(name)
+                .and_then(Value::as_str)
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+        };
+
+        let secret_id = field(&credential, "secretId")
+            .ok_or_else(|| anyhow!("tccli 凭证文件缺少 secretId: {:?}", credential_path))?;
+        let secret_key = field(&credential, "secretKey")
+            .ok_or_else(|| anyhow!("tccli 凭证文件缺少 secretKey: {:?}", credential_path))?;
+        let region = field(&credential, "region")
+            .or_else(|| {
+                configure
+                    .as_ref()
+                    .and_then(|c| c.get("_sys_param"))
+                    .and_then(|p| field(p, "region"))
+            })
+            .ok_or_else(|| anyhow!("tccli 配置中没有设置地域: {}", profile))?;
+
+        Ok(Self {
+            security_token: field(&credential, "token"),
+            ..Self::new(secret_id, secret_key, region, bucket)
+        })
+    }
+
+    /// 设置临时密钥的 SessionToken
+    pub fn with_security_token(mut self, token: String) -> Self {
+        self.security_token = Some(token);
+        self
+    }
+
+    /// 设置访问 COS 使用的域名类型
+    pub fn with_endpoint(mut self, endpoint: EndpointKind) -> Self {
+        self.endpoint = endpoint;
+        self
+    }
+
+    /// 设置自定义端点，参见 [`Config::custom_endpoint`]
+    pub fn with_custom_endpoint(mut self, endpoint: String) -> Self {
+        self.custom_endpoint = Some(endpoint);
+        self
+    }
+
+    /// 设置对接的服务端实现
+    pub fn with_compatibility(mut self, profile: CompatibilityProfile) -> Self {
+        self.compatibility = profile;
+        self
+    }
+
+    /// 设置 Bucket 的寻址方式，参见 [`AddressingStyle`]
+    pub fn with_addressing_style(mut self, style: AddressingStyle) -> Self {
+        self.addressing_style = style;
+        self
+    }
+
+    /// 设置账号的 APPID，参见 [`Config::app_id`](Config#structfield.app_id)
+    pub fn with_app_id(mut self, app_id: String) -> Self {
+        self.app_id = Some(app_id);
+        self
+    }
+
+    /// 设置附加在 `User-Agent` 中的应用标识
+    pub fn with_app_name(mut self, app_name: String) -> Self {
+        self.app_name = Some(app_name);
+        self
+    }
+
+    /// 带 APPID 后缀的完整 Bucket 名称
+    ///
+    /// COS 的 Bucket 名称必须以 `-{APPID}` 结尾。设置了 [`Config::app_id`](Config#structfield.app_id)
+    /// 时，`bucket` 已带有该后缀则原样返回，没有数字后缀时补全；未设置时 `bucket` 必须已带有数字后缀。
+    /// 兼容网关（[`CompatibilityProfile::Generic`]）没有 APPID 的概念，未设置 APPID 时原样返回。
+    ///
+    /// # 错误
+    ///
+    /// `bucket` 与 `app_id` 都没有提供 APPID，或 `bucket` 的数字后缀与 `app_id` 不一致时返回错误。
+    pub fn bucket_name(&self) -> Result<String> {
+        let bucket_app_id = self.bucket.rsplit_once('-').and_then(|(name, id)| {
+            (!name.is_empty() && !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+                .then_some(id)
+        });
+        if let Some(app_id) = &self.app_id {
+            return match bucket_app_id {
+                Some(id) if id == app_id => Ok(self.bucket.clone()),
+                Some(id) => Err(anyhow!(
+                    "Bucket 名称中的 APPID {} 与配置的 APPID {} 不一致: {}",
+                    id,
+                    app_id,
+                    self.bucket
+                )),
+                None => Ok(format!("{}-{}", self.bucket, app_id)),
+            };
+        }
+
+        if bucket_app_id.is_some() || self.compatibility == CompatibilityProfile::Generic {
+            Ok(self.bucket.clone())
+        } else {
+            Err(anyhow!(
+                "Bucket 名称缺少 APPID 后缀: {}（应形如 examplebucket-1250000000，或通过 Config::with_app_id 设置 APPID）",
+                self.bucket
+            ))
+        }
+    }
+
+    /// 请求中使用的 Bucket 名称，缺少 APPID 时退回配置的名称，由发送请求前的检查报告错误
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    fn resolved_bucket(&self) -> String {
+        self.bucket_name().unwrap_or_else(|_| self.bucket.clone())
+    }
+
+    /// 设置参与请求签名的头部范围，参见 [`SignedHeaders`]
+    pub fn with_signed_headers(mut self, signed_headers: SignedHeaders) -> Self {
+        self.signed_headers = signed_headers;
+        self
+    }
+
+    /// 设置签名时规范化头部的方式，参见 [`HeaderCanonicalization`]
+    pub fn with_header_canonicalization(
+        mut self,
+        canonicalization: HeaderCanonicalization,
+    ) -> Self {
+        self.header_canonicalization = canonicalization;
+        self
+    }
+
+    /// 设置自动加在所有对象键之前的前缀，参见 [`Config::key_prefix`](Config#structfield.key_prefix)
+    pub fn with_key_prefix(mut self, prefix: String) -> Self {
+        self.key_prefix = Some(prefix);
+        self
+    }
+
+    /// 用密钥对对象键进行确定性加密，使共享 Bucket 中的对象名不泄露用户标识等敏感信息
+    ///
+    /// 上传、下载、删除、复制与预签名时对象键按 `/` 分段加密（在 [`Config::key_prefix`](Config#structfield.key_prefix)
+    /// 之后），列举时按段加密前缀并解密返回的对象键，调用方始终只看到明文的对象键，
+    /// 同步与按前缀删除照常工作。限制：
+    ///
+    /// - 列举结果按密文排序；前缀不以 `/` 结尾时在客户端过滤最后一段，分组字符只能为 `/`；
+    /// - 不是由同一密钥加密的对象在列举时被跳过；
+    /// - 生命周期规则、清单与访问日志中只能看到密文。
+    ///
+    /// 参见 [`KeyEncryption`]。需要启用 `crypto` feature（默认启用）。
+    #[cfg(feature = "crypto")]
+    pub fn with_key_encryption(mut self, secret: String) -> Self {
+        self.key_encryption = Some(KeyEncryption::new(secret.as_bytes()));
+        self
+    }
+
+    /// 对象键在 COS 中（不含前缀）的形式，设置了对象键加密时为密文
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn stored_key<'a>(&self, object_key: &'a str) -> Cow<'a, str> {
+        #[cfg(feature = "crypto")]
+        if let Some(encryption) = &self.key_encryption {
+            return Cow::Owned(encryption.encrypt_key(object_key));
+        }
+        Cow::Borrowed(object_key)
+    }
+
+    /// 列举请求中的完整前缀：加上对象键前缀，设置了对象键加密时只加密以 `/` 结尾的完整路径段，
+    /// 最后一段由调用方在解密后过滤
+    #[cfg(feature = "runtime")]
+    pub(crate) fn stored_prefix(&self, prefix: &str) -> String {
+        #[cfg(feature = "crypto")]
+        if let Some(encryption) = &self.key_encryption {
+            let dir = prefix.rfind('/').map_or("", |i| &prefix[..=i]);
+            return format!("{}{}", self.key_prefix(), encryption.encrypt_key(dir));
+        }
+        format!("{}{}", self.key_prefix(), prefix)
+    }
+
+    /// 对象键前缀，未设置时为空字符串
+    #[cfg(feature = "runtime")]
+    pub(crate) fn key_prefix(&self) -> &str {
+        self.key_prefix.as_deref().unwrap_or("")
+    }
+
+    /// 指定地域下 Bucket 的访问域名
+    #[cfg(any(feature = "presign", all(feature = "runtime", test)))]
+    pub(crate) fn host_for(&self, region: &str) -> String {
+        self.host_of(self.endpoint, region)
+    }
+
+    /// 指定域名类型与地域下 Bucket 的访问域名，设置了自定义端点时始终为该端点；
+    /// 使用路径风格时为不含 Bucket 的服务域名
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn host_of(&self, kind: EndpointKind, region: &str) -> String {
+        match &self.custom_endpoint {
+            Some(endpoint) => split_scheme(endpoint).1.to_string(),
+            None if self.path_style() => kind.service_host(region),
+            None => kind.host(&self.resolved_bucket(), region),
+        }
+    }
+
+    /// 是否把 Bucket 放在请求路径中，参见 [`AddressingStyle`]
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    fn path_style(&self) -> bool {
+        match self.addressing_style {
+            AddressingStyle::Auto => {
+                self.compatibility.path_style() || self.resolved_bucket().contains('.')
+            }
+            AddressingStyle::VirtualHost => false,
+            AddressingStyle::Path => true,
+        }
+    }
+
+    /// 请求使用的协议（`https` 或 `http`）
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn scheme(&self) -> &str {
+        self.custom_endpoint
+            .as_deref()
+            .map_or("https", |endpoint| split_scheme(endpoint).0)
+    }
+
+    /// 对象在请求中的路径（含开头的 `/`），同时也是参与签名的路径
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - 对象键，Bucket 级别的请求为空字符串；非空时加上 [`Config::key_prefix`](Config#structfield.key_prefix)，
+    ///   并按需加密
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    pub(crate) fn object_path(&self, object_key: &str) -> String {
+        let prefix = match &self.key_prefix {
+            Some(prefix) if !object_key.is_empty() => prefix.as_str(),
+            _ => "",
+        };
+        let object_key = self.stored_key(object_key);
+        if self.path_style() {
+            format!("/{}/{}{}", self.resolved_bucket(), prefix, object_key)
+        } else {
+            format!("/{}{}", prefix, object_key)
+        }
+    }
+
+    /// 检查对象键能否用于对象级别的请求
+    ///
+    /// 空字符串与 `/` 会使请求落到 Bucket 根路径上，加上 [`Config::key_prefix`](Config#structfield.key_prefix)
+    /// 后超过 COS 限制（[`MAX_OBJECT_KEY_LEN`] 字节）的对象键会被 COS 拒绝，都在发出请求前返回错误。
+    #[cfg(feature = "runtime")]
+    pub(crate) fn check_object_key(&self, object_key: &str) -> Result<(), CosError> {
+        let reason = if object_key.is_empty() {
+            "对象键不能为空".to_string()
+        } else if object_key == "/" {
+            "对象键不能为 \"/\"".to_string()
+        } else {
+            let len = self.key_prefix().len() + self.stored_key(object_key).len();
+            if len <= MAX_OBJECT_KEY_LEN {
+                return Ok(());
+            }
+            format!("长度为 {} 字节，超过上限 {} 字节", len, MAX_OBJECT_KEY_LEN)
+        };
+        Err(CosError::InvalidObjectKey {
+            object_key: object_key.to_string(),
+            reason,
+        })
+    }
+
+    /// 设置签名不匹配时是否在错误中附上待签字符串的对比
+    pub fn with_signature_debug(mut self, enabled: bool) -> Self {
+        self.debug_signature = enabled;
+        self
+    }
+
+    /// 设置地域不匹配时是否自动向正确的地域重试
+    pub fn with_follow_region_redirects(mut self, follow: bool) -> Self {
+        self.follow_region_redirects = follow;
+        self
+    }
+}
+
+/// 序列化 [`Config`] 时替代密钥的占位字符串
+pub const REDACTED: &str = "******";
+
+#[cfg(feature = "serde")]
+fn redact<S: serde::Serializer>(_: &str, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(REDACTED)
+}
+
+#[cfg(feature = "serde")]
+fn redact_option<S: serde::Serializer>(
+    value: &Option<String>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(_) => serializer.serialize_some(REDACTED),
+        None => serializer.serialize_none(),
+    }
+}
+
+/// 把端点拆分为协议与域名（可含端口），未写协议时为 `https`
+#[cfg(any(feature = "runtime", feature = "presign"))]
+fn split_scheme(endpoint: &str) -> (&str, &str) {
+    let (scheme, rest) = match endpoint.split_once("://") {
+        Some(("http", rest)) => ("http", rest),
+        Some((_, rest)) => ("https", rest),
+        None => ("https", endpoint),
+    };
+    (scheme, rest.trim_end_matches('/'))
+}
+
+/// 读取并解析 JSON 文件
+fn read_json(path: &Path) -> Result<Value> {
+    let text =
+        std::fs::read_to_string(path).with_context(|| format!("读取配置文件失败: {:?}", path))?;
+    serde_json::from_str(&text).with_context(|| format!("解析配置文件失败: {:?}", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_tccli_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("dev.credential"),
+            r#"{"secretId": "id", "secretKey": "key"}"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("dev.configure"),
+            r#"{"_sys_param": {"output": "json", "region": "ap-guangzhou"}}"#,
+        )
+        .unwrap();
+
+        let config = Config::from_tccli_dir(dir.path(), "dev", "bucket-1250000000".into()).unwrap();
+        assert_eq!(config.secret_id, "id");
+        assert_eq!(config.secret_key, "key");
+        assert_eq!(config.region, "ap-guangzhou");
+        assert_eq!(config.security_token, None);
+
+        assert!(Config::from_tccli_dir(dir.path(), "missing", "b".into()).is_err());
+    }
+
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    #[test]
+    fn test_compatibility_profile() {
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        assert_eq!(config.scheme(), "https");
+        assert_eq!(
+            config.host_for("ap-guangzhou"),
+            "b.cos.ap-guangzhou.myqcloud.com"
+        );
+        assert_eq!(config.object_path("a/b.txt"), "/a/b.txt");
+
+        let config = config
+            .with_custom_endpoint("http://127.0.0.1:9000/".into())
+            .with_compatibility(CompatibilityProfile::Generic);
+        assert_eq!(config.scheme(), "http");
+        assert_eq!(config.host_for("ap-beijing"), "127.0.0.1:9000");
+        assert_eq!(config.object_path("a/b.txt"), "/b/a/b.txt");
+        assert_eq!(config.object_path(""), "/b/");
+
+        let config = config.with_key_prefix("env/staging/".into());
+        assert_eq!(config.object_path("a.txt"), "/b/env/staging/a.txt");
+        assert_eq!(config.object_path(""), "/b/");
+
+        // 对象键加密在前缀之后进行
+        #[cfg(feature = "crypto")]
+        {
+            let config = config.with_key_encryption("k".into());
+            let stored = KeyEncryption::new(b"k").encrypt_key("users/42/a.txt");
+            assert_eq!(
+                config.object_path("users/42/a.txt"),
+                format!("/b/env/staging/{}", stored)
+            );
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serialize_redacts_secrets() {
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into())
+            .with_security_token("token".into());
+        let json = serde_json::to_value(&config).unwrap();
+        assert_eq!(json["secret_id"], "id");
+        assert_eq!(json["secret_key"], REDACTED);
+        assert_eq!(json["security_token"], REDACTED);
+        assert_eq!(json["endpoint"], "regional");
+
+        let parsed: Config = serde_json::from_str(
+            r#"{"secret_id": "id", "secret_key": "key", "region": "ap-guangzhou", "bucket": "b"}"#,
+        )
+        .unwrap();
+        assert_eq!(parsed.secret_key, "key");
+        assert_eq!(parsed.endpoint, EndpointKind::Regional);
+    }
+
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    #[test]
+    fn test_addressing_style() {
+        let config = Config::new(
+            "id".into(),
+            "key".into(),
+            "ap-guangzhou".into(),
+            "logs.example-1250000000".into(),
+        );
+        assert_eq!(
+            config.host_for("ap-guangzhou"),
+            "cos.ap-guangzhou.myqcloud.com"
+        );
+        assert_eq!(
+            config.object_path("a.txt"),
+            "/logs.example-1250000000/a.txt"
+        );
+
+        let config = config.with_addressing_style(AddressingStyle::VirtualHost);
+        assert_eq!(
+            config.host_for("ap-guangzhou"),
+            "logs.example-1250000000.cos.ap-guangzhou.myqcloud.com"
+        );
+        assert_eq!(config.object_path("a.txt"), "/a.txt");
+
+        let config = config
+            .with_custom_endpoint("https://s3.example.com".into())
+            .with_addressing_style(AddressingStyle::Path);
+        assert_eq!(config.host_for("ap-guangzhou"), "s3.example.com");
+        assert_eq!(
+            config.object_path("a.txt"),
+            "/logs.example-1250000000/a.txt"
+        );
+    }
+
+    #[cfg(any(feature = "runtime", feature = "presign"))]
+    #[test]
+    fn test_signed_headers() {
+        let headers = HashMap::from([
+            ("Host".to_string(), "b".to_string()),
+            ("Content-Length".to_string(), "3".to_string()),
+            ("Content-Type".to_string(), "text/plain".to_string()),
+            ("x-cos-meta-a".to_string(), "1".to_string()),
+        ]);
+        let names = |policy: SignedHeaders| {
+            let mut names: Vec<_> = policy.select(&headers).into_keys().collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(names(SignedHeaders::All).len(), 4);
+        assert_eq!(
+            names(SignedHeaders::Minimal),
+            ["Content-Type", "Host", "x-cos-meta-a"]
+        );
+        assert_eq!(
+            names(SignedHeaders::Only(vec!["content-type".into()])),
+            ["Content-Type", "Host"]
+        );
+    }
+
+    #[test]
+    fn test_bucket_name() {
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        assert!(config.bucket_name().is_err());
+        assert_eq!(
+            config
+                .clone()
+                .with_compatibility(CompatibilityProfile::Generic)
+                .bucket_name()
+                .unwrap(),
+            "b"
+        );
+
+        let config = config.with_app_id("1250000000".into());
+        assert_eq!(config.bucket_name().unwrap(), "b-1250000000");
+        let config = Config {
+            bucket: "b-1250000000".into(),
+            ..config
+        };
+        assert_eq!(config.bucket_name().unwrap(), "b-1250000000");
+        let mismatched = Config {
+            bucket: "b-1300000000".into(),
+            ..config.clone()
+        };
+        assert!(mismatched.bucket_name().is_err());
+        let config = Config {
+            app_id: None,
+            ..config
+        };
+        assert_eq!(config.bucket_name().unwrap(), "b-1250000000");
+    }
+
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_check_object_key() {
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        assert!(config.check_object_key("a.txt").is_ok());
+        assert!(config
+            .check_object_key(&"a".repeat(MAX_OBJECT_KEY_LEN))
+            .is_ok());
+        for key in ["", "/"] {
+            assert!(matches!(
+                config.check_object_key(key),
+                Err(CosError::InvalidObjectKey { .. })
+            ));
+        }
+
+        let config = config.with_key_prefix("env/staging/".into());
+        assert!(config
+            .check_object_key(&"a".repeat(MAX_OBJECT_KEY_LEN))
+            .is_err());
+
+        // 列举时只加密完整的路径段，最后一段在解密后过滤
+        #[cfg(feature = "crypto")]
+        {
+            let config = config.with_key_encryption("k".into());
+            let stored = KeyEncryption::new(b"k").encrypt_key("users/42/a.txt");
+            let dir = config.stored_prefix("users/4");
+            assert!(dir.starts_with("env/staging/") && dir.ends_with('/'));
+            assert!(format!("env/staging/{}", stored).starts_with(&dir));
         }
     }
 }
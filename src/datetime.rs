@@ -0,0 +1,89 @@
+//! 时间解析工具
+//!
+//! COS 在响应头（`Last-Modified`、`Expires`、`x-cos-restore`）中使用 HTTP 日期，
+//! 在列举结果等 XML 响应中使用 ISO 8601。这里统一解析为 `DateTime<Utc>`，各个类型化模型共用。
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+
+/// 解析 HTTP 日期（RFC 7231）
+///
+/// 支持标准的 IMF-fixdate（`Sun, 06 Nov 1994 08:49:37 GMT`），
+/// 以及 RFC 7231 要求接收方兼容的 RFC 850 与 asctime 两种过时格式。
+///
+/// # 返回值
+///
+/// 不是合法的 HTTP 日期时返回 `None`，例如 `Expires: 0`
+pub fn parse_http_date(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc2822(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    ["%A, %d-%b-%y %H:%M:%S GMT", "%a %b %e %H:%M:%S %Y"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|time| time.and_utc())
+}
+
+/// 解析 ISO 8601 时间
+///
+/// 支持带时区的 RFC 3339（`2015-10-21T07:28:00.000Z`），小数秒可有可无；
+/// 没有时区的时间按 UTC 处理。
+///
+/// # 返回值
+///
+/// 不是合法的 ISO 8601 时间时返回 `None`
+pub fn parse_iso8601(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// 解析 HTTP 日期或 ISO 8601 时间，格式未知时使用
+///
+/// # 返回值
+///
+/// 两种格式都无法解析时返回 `None`
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    parse_iso8601(value).or_else(|| parse_http_date(value))
+}
+
+/// 格式化为 HTTP 日期（IMF-fixdate）
+#[cfg(feature = "runtime")]
+pub(crate) fn format_http_date(time: &DateTime<Utc>) -> String {
+    time.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// 格式化为 COS 列举结果使用的 ISO 8601 格式（毫秒精度，UTC）
+#[cfg(feature = "runtime")]
+pub(crate) fn format_iso8601(time: &DateTime<Utc>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%.3fZ").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_timestamps() {
+        let expected = DateTime::from_timestamp(784111777, 0);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), expected);
+        assert_eq!(parse_http_date("Sun Nov  6 08:49:37 1994"), expected);
+        assert_eq!(parse_http_date("0"), None);
+
+        assert_eq!(parse_iso8601("1994-11-06T08:49:37Z"), expected);
+        assert_eq!(parse_iso8601("1994-11-06T16:49:37+08:00"), expected);
+        assert_eq!(parse_iso8601("1994-11-06T08:49:37"), expected);
+        let millis = parse_iso8601("1994-11-06T08:49:37.250Z").unwrap();
+        assert_eq!(millis.timestamp_millis(), 784111777250);
+        assert_eq!(parse_iso8601("Sun, 06 Nov 1994 08:49:37 GMT"), None);
+
+        assert_eq!(parse_timestamp("Sun, 06 Nov 1994 08:49:37 GMT"), expected);
+        assert_eq!(parse_timestamp("1994-11-06T08:49:37.000Z"), expected);
+        assert_eq!(parse_timestamp(""), None);
+    }
+}
@@ -0,0 +1,83 @@
+use crate::options::UploadOptions;
+use crate::request::{object_url_of, read_xml, CosRequest};
+use crate::types::{crc64_of, request_id_of, UploadResult};
+use crate::uploader::Uploader;
+use crate::xml::find_tag;
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use tracing::info;
+
+impl Uploader {
+    /// 完成分块上传，并把只有在上传完数据后才知道的元数据写入对象
+    ///
+    /// 最终的行数、整体校验值等元数据往往要等数据全部写完才能得到，而初始化分块上传时就必须给出元数据。
+    /// 本方法先完成分块上传，再以 `x-cos-metadata-directive: Replaced` 把对象复制到自身，
+    /// 用 `options` 中的元数据、存储类型等替换对象原有的设置，对调用方隐藏这一复制技巧。
+    ///
+    /// 复制会替换初始化时设置的全部自定义元数据，需要保留的元数据也应放在 `options` 中；
+    /// Content-Type 保持不变。`options` 设置了过期标签时同样替换对象的标签，否则保留原有的标签。
+    /// 复制到自身要求对象不超过 5 GB。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `upload_id` - 初始化分块上传时返回的上传 ID
+    /// * `parts` - 分块编号与对应的 ETag，按分块编号升序
+    /// * `options` - 最终写入对象的元数据与其它选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回替换元数据之后对象的上传结果
+    ///
+    /// # 错误
+    ///
+    /// 完成分块上传或替换元数据失败时返回错误；替换失败时对象已经合并完成，只是缺少新的元数据。
+    pub async fn finalize_with_metadata(
+        &self,
+        object_key: &str,
+        upload_id: &str,
+        parts: &[(u32, String)],
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        self.complete_multipart_upload(object_key, upload_id, parts)
+            .await?;
+        let current = self.get_object_metadata(object_key).await?;
+
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .header("x-cos-copy-source", self.copy_source(object_key))
+            .header("x-cos-metadata-directive", "Replaced");
+        if let Some(content_type) = current.content_type {
+            request = request.header("Content-Type", content_type);
+        }
+        let mut request = options.apply(request);
+        // 复制时 COS 只在带有该指令时才使用请求中的标签，否则沿用源对象的标签
+        if request.headers.contains_key("x-cos-tagging") {
+            request = request.header("x-cos-tagging-directive", "Replaced");
+        }
+
+        let response = self.execute(request).await?;
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let crc64 = crc64_of(response.headers());
+        let text = read_xml(response).await?;
+
+        // 复制请求可能在返回 200 的同时在响应体中携带错误
+        if text.contains("<Error>") {
+            return Err(anyhow!("替换对象元数据失败: {}: {}", object_key, text));
+        }
+        self.invalidate_cached(object_key);
+        info!(
+            "完成分块上传并替换元数据: {} (request_id: {:?})",
+            object_key, request_id
+        );
+
+        Ok(UploadResult {
+            url,
+            etag: find_tag(&text, "ETag").map(|etag| etag.to_string()),
+            request_id,
+            stats: None,
+            failover: None,
+            crc64,
+        })
+    }
+}
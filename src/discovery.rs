@@ -0,0 +1,104 @@
+use crate::config::EndpointKind;
+use crate::error::CosError;
+use crate::http::client_builder;
+use anyhow::{anyhow, Result};
+use reqwest::header::HeaderMap;
+use reqwest::redirect::Policy;
+use reqwest::StatusCode;
+use std::time::Duration;
+use tracing::info;
+
+/// 探测时先访问的地域，Bucket 不在该地域时 COS 会返回实际的地域
+const DISCOVERY_REGION: &str = "ap-guangzhou";
+/// 探测请求的超时时间
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 从探测请求的响应中确定 Bucket 所在的地域
+///
+/// 优先使用 `x-cos-bucket-region`；没有该头部时，重定向说明 Bucket 在其它地域，
+/// 成功或无权限（403）说明 Bucket 就在探测的地域。
+fn region_from_response(bucket: &str, status: StatusCode, headers: &HeaderMap) -> Result<String> {
+    if let Some(region) = headers
+        .get("x-cos-bucket-region")
+        .and_then(|v| v.to_str().ok())
+        .filter(|v| !v.is_empty())
+    {
+        return Ok(region.to_string());
+    }
+    if status.is_success() || status == StatusCode::FORBIDDEN {
+        return Ok(DISCOVERY_REGION.to_string());
+    }
+
+    // HEAD 响应没有响应体，只能从响应头中解析重定向
+    match CosError::from_response(status, headers, "") {
+        CosError::WrongRegion { expected, .. } => Ok(expected),
+        CosError::Service { status: 404, .. } => Err(anyhow!("Bucket 不存在: {}", bucket)),
+        err => Err(anyhow::Error::new(err).context(format!("无法确定 Bucket 的地域: {}", bucket))),
+    }
+}
+
+/// 只根据 Bucket 名称查询其所在的地域
+///
+/// 向默认地域的 Bucket 域名发送一次不带签名的 `HEAD` 请求，从 `x-cos-bucket-region`
+/// 或重定向中解析实际的地域，不需要密钥。只知道 Bucket 名称的工具可以据此构建 [`Config`](crate::Config)。
+///
+/// # 参数
+///
+/// * `bucket` - Bucket 名称（含 APPID，如 `examplebucket-1250000000`）
+///
+/// # 返回值
+///
+/// 成功时返回地域，例如 `ap-beijing`
+///
+/// # 错误
+///
+/// Bucket 不存在、网络请求失败或响应中无法解析出地域时返回错误。
+pub async fn discover_bucket_region(bucket: &str) -> Result<String> {
+    let client = client_builder(None)
+        .redirect(Policy::none())
+        .timeout(DISCOVERY_TIMEOUT)
+        .build()?;
+    let url = format!(
+        "https://{}/",
+        EndpointKind::Regional.host(bucket, DISCOVERY_REGION)
+    );
+
+    let response = client.head(&url).send().await?;
+    let region = region_from_response(bucket, response.status(), response.headers())?;
+    info!("Bucket {} 位于地域 {}", bucket, region);
+    Ok(region)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, LOCATION};
+
+    #[test]
+    fn test_region_from_response() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            region_from_response("b", StatusCode::FORBIDDEN, &headers).unwrap(),
+            DISCOVERY_REGION
+        );
+        assert!(region_from_response("b", StatusCode::NOT_FOUND, &headers).is_err());
+
+        headers.insert(
+            LOCATION,
+            HeaderValue::from_static("https://b.cos.ap-beijing.myqcloud.com/"),
+        );
+        assert_eq!(
+            region_from_response("b", StatusCode::MOVED_PERMANENTLY, &headers).unwrap(),
+            "ap-beijing"
+        );
+
+        headers.insert(
+            "x-cos-bucket-region",
+            HeaderValue::from_static("ap-shanghai"),
+        );
+        assert_eq!(
+            region_from_response("b", StatusCode::MOVED_PERMANENTLY, &headers).unwrap(),
+            "ap-shanghai"
+        );
+    }
+}
@@ -0,0 +1,480 @@
+use crate::cache::{CacheKey, Lookup, ObjectCache};
+use crate::durable::sync_parent_dir;
+use crate::error::CosError;
+use crate::request::{header_of, CosRequest};
+use crate::task::next_transfer_id;
+use crate::types::{crc64_of, ObjectMetadata};
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use reqwest::{Method, StatusCode};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tracing::{debug, info};
+
+/// 下载中的临时文件的后缀
+pub const PARTIAL_SUFFIX: &str = ".part";
+
+/// 记录临时文件内容所属对象 ETag 的文件后缀，附加在临时文件名之后
+const ETAG_SUFFIX: &str = ".etag";
+
+/// `Range` 头部的值（闭区间）
+fn range_header(range: &Range<u64>) -> Result<String> {
+    if range.start >= range.end {
+        return Err(anyhow!("读取范围不能为空: {:?}", range));
+    }
+    Ok(format!("bytes={}-{}", range.start, range.end - 1))
+}
+
+/// 下载选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DownloadOptions {
+    /// 下载失败时是否保留 `{file}.part` 临时文件，下次下载同一路径时从中断处继续（默认关闭）
+    ///
+    /// 关闭时每次下载使用唯一的临时文件名，失败后删除；开启时临时文件名固定，
+    /// 同一目标路径不能同时有多个下载。对象的 ETag 记录在 `{file}.part.etag` 中，
+    /// 续传时通过 `If-Range` 发送，对象在两次下载之间被替换时重新下载整个对象。
+    pub keep_partial: bool,
+}
+
+impl DownloadOptions {
+    /// 创建默认的下载选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置是否保留临时文件以便续传
+    pub fn with_keep_partial(mut self, keep: bool) -> Self {
+        self.keep_partial = keep;
+        self
+    }
+}
+
+/// 读取到内存中的对象内容
+pub(crate) struct FetchedObject {
+    /// 对象保存的原始字节，不做任何解码
+    pub(crate) data: Bytes,
+    pub(crate) etag: Option<String>,
+    /// 对象的 `Content-Encoding`
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    pub(crate) content_encoding: Option<String>,
+}
+
+/// 可续传的临时文件路径：`{file}.part`
+fn partial_path(file_path: &Path) -> PathBuf {
+    let mut path = file_path.as_os_str().to_owned();
+    path.push(PARTIAL_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// 记录临时文件对应 ETag 的文件路径：`{临时文件}.etag`
+fn etag_path(temp_path: &Path) -> PathBuf {
+    let mut path = temp_path.as_os_str().to_owned();
+    path.push(ETAG_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// 读取可续传的临时文件的长度与对应的 ETag
+///
+/// 临时文件为空或没有记录 ETag 时无法确认已有内容属于哪个版本的对象，返回 `None`。
+async fn resume_point(temp_path: &Path) -> Option<(u64, String)> {
+    let len = tokio::fs::metadata(temp_path).await.ok()?.len();
+    let etag = tokio::fs::read_to_string(etag_path(temp_path)).await.ok()?;
+    let etag = etag.trim();
+    (len > 0 && !etag.is_empty()).then(|| (len, etag.to_string()))
+}
+
+/// 删除临时文件及其 ETag 记录
+async fn remove_partial(temp_path: &Path) {
+    let _ = tokio::fs::remove_file(temp_path).await;
+    let _ = tokio::fs::remove_file(etag_path(temp_path)).await;
+}
+
+/// 本次下载专用的临时文件路径：`.{file}.{进程 ID}.{序号}.part`
+fn unique_temp_path(file_path: &Path) -> PathBuf {
+    let name = file_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    file_path.with_file_name(format!(
+        ".{}.{}.{}{}",
+        name,
+        std::process::id(),
+        next_transfer_id(),
+        PARTIAL_SUFFIX
+    ))
+}
+
+impl Uploader {
+    /// 下载对象到本地文件
+    ///
+    /// 先写入目标目录中的临时文件，下载完成、校验并刷新到磁盘后再原子地重命名为目标文件，
+    /// 读取目标文件的进程不会看到写了一半的内容。边下载边增量计算 CRC64，
+    /// COS 返回了 `x-cos-hash-crc64ecma` 时进行比对。目标文件的父目录不存在时会自动创建。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `file_path` - 本地文件路径
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回 GET 响应中的对象元数据
+    ///
+    /// # 错误
+    ///
+    /// 请求失败、写入文件失败或 CRC64 不一致（[`CosError::ChecksumMismatch`](crate::CosError::ChecksumMismatch)）时返回错误。
+    pub async fn download_object<P: AsRef<Path>>(
+        &self,
+        object_key: &str,
+        file_path: P,
+    ) -> Result<ObjectMetadata> {
+        self.download_object_with_options(object_key, file_path, &DownloadOptions::default())
+            .await
+    }
+
+    /// 按下载选项下载对象到本地文件
+    ///
+    /// 与 [`Uploader::download_object`] 相同，但可以保留临时文件以便续传。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `file_path` - 本地文件路径
+    /// * `options` - 下载选项
+    pub async fn download_object_with_options<P: AsRef<Path>>(
+        &self,
+        object_key: &str,
+        file_path: P,
+        options: &DownloadOptions,
+    ) -> Result<ObjectMetadata> {
+        let file_path = file_path.as_ref();
+        debug!("下载对象: {} -> {:?}", object_key, file_path);
+
+        if let Some(parent) = file_path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+
+        let (temp_path, resume) = if options.keep_partial {
+            let path = partial_path(file_path);
+            let resume = resume_point(&path).await;
+            (path, resume)
+        } else {
+            (unique_temp_path(file_path), None)
+        };
+
+        let downloaded = self
+            .download_to(object_key, &temp_path, resume, options.keep_partial)
+            .await;
+        match downloaded {
+            Ok(metadata) => {
+                tokio::fs::rename(&temp_path, file_path).await?;
+                // 重命名记录在目录中，目录也刷盘后下载的文件才能在崩溃后保留
+                sync_parent_dir(file_path).await?;
+                if options.keep_partial {
+                    let _ = tokio::fs::remove_file(etag_path(&temp_path)).await;
+                }
+                info!(
+                    "对象下载成功: {} (request_id: {:?})",
+                    object_key, metadata.request_id
+                );
+                Ok(metadata)
+            }
+            Err(e) => {
+                // 校验失败说明临时文件中已有的内容不可信，不能用于续传
+                let corrupted = matches!(
+                    e.downcast_ref::<CosError>(),
+                    Some(CosError::ChecksumMismatch { .. })
+                );
+                if !options.keep_partial || corrupted {
+                    remove_partial(&temp_path).await;
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// 下载对象到临时文件
+    ///
+    /// `resume` 为已有内容的长度与所属对象的 ETag，从已有内容之后继续；对象的 ETag 已变化时
+    /// COS 按 `If-Range` 返回整个对象，覆盖已有内容。`record_etag` 为 `true` 时把对象的 ETag
+    /// 写入临时文件旁的记录中，供下次续传使用。
+    async fn download_to(
+        &self,
+        object_key: &str,
+        temp_path: &Path,
+        resume: Option<(u64, String)>,
+        record_etag: bool,
+    ) -> Result<ObjectMetadata> {
+        let mut request = CosRequest::new(Method::GET, object_key);
+        if let Some((offset, etag)) = &resume {
+            request = request
+                .header("Range", format!("bytes={}-", offset))
+                .header("If-Range", etag.as_str());
+        }
+        let offset = resume.as_ref().map_or(0, |(offset, _)| *offset);
+        let mut response = match self.execute(request).await {
+            Ok(response) => response,
+            // 已有内容不短于对象，重新下载整个对象
+            Err(e)
+                if offset > 0
+                    && matches!(
+                        e.downcast_ref::<CosError>(),
+                        Some(CosError::Service { status: 416, .. })
+                    ) =>
+            {
+                debug!("续传范围无效，重新下载: {}", object_key);
+                self.execute(CosRequest::new(Method::GET, object_key))
+                    .await?
+            }
+            Err(e) => return Err(e),
+        };
+        let mut metadata = ObjectMetadata::from_headers(response.headers());
+        let resumed = offset > 0 && response.status() == StatusCode::PARTIAL_CONTENT;
+        if record_etag && !resumed {
+            // 先于内容写入，下载中断后已有的内容总能对应到正确的对象版本
+            match &metadata.etag {
+                Some(etag) => tokio::fs::write(etag_path(temp_path), etag).await?,
+                None => {
+                    let _ = tokio::fs::remove_file(etag_path(temp_path)).await;
+                }
+            }
+        }
+
+        let mut crc64 = self.hash_backend.crc64();
+        let mut file = if resumed {
+            debug!("从 {} 字节处继续下载: {}", offset, object_key);
+            // 已有内容也要参与整个对象的 CRC64 计算
+            let mut file = tokio::fs::OpenOptions::new()
+                .read(true)
+                .append(true)
+                .open(temp_path)
+                .await?;
+            let mut buffer = vec![0; 1024 * 1024];
+            loop {
+                let n = file.read(&mut buffer).await?;
+                if n == 0 {
+                    break;
+                }
+                crc64.update(&buffer[..n]);
+            }
+            file
+        } else {
+            tokio::fs::File::create(temp_path).await?
+        };
+
+        while let Some(chunk) = response.chunk().await? {
+            crc64.update(&chunk);
+            file.write_all(&chunk).await?;
+        }
+        file.flush().await?;
+        file.sync_all().await?;
+
+        self.check_crc64(object_key, crc64.finish(), metadata.crc64)?;
+        // 续传时响应中的长度只是本次传输的部分
+        metadata.content_length = Some(file.metadata().await?.len());
+        Ok(metadata)
+    }
+
+    /// 开启下载内容的内存缓存
+    ///
+    /// [`Uploader::get_object_bytes`] 与 [`Uploader::get_object_version_bytes`] 的结果按
+    /// （对象键，版本，范围）缓存，总大小超过 `max_bytes` 时淘汰最近最少使用的条目。
+    /// 缓存时间不超过 `max_age` 的条目直接返回；更早的条目带上 `If-None-Match` 向 COS 确认，
+    /// 对象未变化时不再传输内容。适合大量 worker 反复读取同一批配置或清单对象的场景。
+    ///
+    /// 缓存在克隆之间共享；通过本上传器上传或删除对象时，该对象的缓存会被清除。
+    ///
+    /// # 参数
+    ///
+    /// * `max_bytes` - 缓存的最大总字节数
+    /// * `max_age` - 无需确认即可直接使用的时长，为零时每次都向 COS 确认
+    pub fn with_object_cache(mut self, max_bytes: u64, max_age: Duration) -> Self {
+        self.object_cache = Some(Arc::new(ObjectCache::new(max_bytes, max_age)));
+        self
+    }
+
+    /// 清除对象的缓存
+    pub(crate) fn invalidate_cached(&self, object_key: &str) {
+        if let Some(cache) = &self.object_cache {
+            cache.invalidate(object_key);
+        }
+    }
+
+    /// 读取对象内容到内存
+    ///
+    /// 开启了 [`Uploader::with_object_cache`] 时优先使用缓存。读取整个对象时校验 CRC64。
+    /// 并发读取同一对象时合并为一次请求，参见 [`Uploader::get_object_version_bytes`]。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `range` - 读取的字节范围（左闭右开），为 `None` 时读取整个对象
+    pub async fn get_object_bytes(
+        &self,
+        object_key: &str,
+        range: Option<Range<u64>>,
+    ) -> Result<Bytes> {
+        self.get_object_version_bytes(object_key, None, range).await
+    }
+
+    /// 读取对象指定版本的内容到内存
+    ///
+    /// 多个任务同时读取同一对象（相同版本与范围）时只发出一次请求，所有调用方共享结果，
+    /// 热点对象被大量并发读取时不会重复消耗下行流量。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `version_id` - 版本 ID，为 `None` 时读取最新版本
+    /// * `range` - 读取的字节范围（左闭右开），为 `None` 时读取整个对象
+    pub async fn get_object_version_bytes(
+        &self,
+        object_key: &str,
+        version_id: Option<&str>,
+        range: Option<Range<u64>>,
+    ) -> Result<Bytes> {
+        let key = CacheKey {
+            object_key: object_key.to_string(),
+            version_id: version_id.map(str::to_string),
+            range: range.clone(),
+        };
+        self.in_flight_reads
+            .run(key, || {
+                self.load_object_bytes(object_key, version_id, range)
+            })
+            .await
+    }
+
+    /// 读取对象内容，开启了缓存时优先使用缓存
+    async fn load_object_bytes(
+        &self,
+        object_key: &str,
+        version_id: Option<&str>,
+        range: Option<Range<u64>>,
+    ) -> Result<Bytes> {
+        let Some(cache) = &self.object_cache else {
+            let (data, _) = self
+                .fetch_object_bytes(object_key, version_id, range.as_ref(), None)
+                .await?
+                .ok_or_else(|| anyhow!("未带条件的请求不应返回 304: {}", object_key))?;
+            return Ok(data);
+        };
+
+        let key = CacheKey {
+            object_key: object_key.to_string(),
+            version_id: version_id.map(str::to_string),
+            range,
+        };
+        let cached = match cache.get(&key) {
+            Lookup::Fresh(data) => {
+                debug!("对象缓存命中: {}", object_key);
+                return Ok(data);
+            }
+            Lookup::Stale { data, etag } => Some((data, etag)),
+            Lookup::Miss => None,
+        };
+
+        let if_none_match = cached.as_ref().map(|(_, etag)| etag.as_str());
+        let fetched = self
+            .fetch_object_bytes(object_key, version_id, key.range.as_ref(), if_none_match)
+            .await?;
+        match (fetched, cached) {
+            (Some((data, etag)), _) => {
+                cache.insert(key, data.clone(), etag);
+                Ok(data)
+            }
+            (None, Some((data, _))) => {
+                debug!("对象未变化，沿用缓存: {}", object_key);
+                cache.mark_validated(&key);
+                Ok(data)
+            }
+            (None, None) => Err(anyhow!("未带条件的请求不应返回 304: {}", object_key)),
+        }
+    }
+
+    /// 读取对象内容与 ETag，带有 `If-None-Match` 且对象未变化时返回 `None`
+    pub(crate) async fn fetch_object_bytes(
+        &self,
+        object_key: &str,
+        version_id: Option<&str>,
+        range: Option<&Range<u64>>,
+        if_none_match: Option<&str>,
+    ) -> Result<Option<(Bytes, Option<String>)>> {
+        let fetched = self
+            .fetch_object(object_key, version_id, range, if_none_match)
+            .await?;
+        Ok(fetched.map(|fetched| (fetched.data, fetched.etag)))
+    }
+
+    /// 读取对象内容与相关的响应头，带有 `If-None-Match` 且对象未变化时返回 `None`
+    pub(crate) async fn fetch_object(
+        &self,
+        object_key: &str,
+        version_id: Option<&str>,
+        range: Option<&Range<u64>>,
+        if_none_match: Option<&str>,
+    ) -> Result<Option<FetchedObject>> {
+        let mut request = CosRequest::new(Method::GET, object_key);
+        if let Some(version_id) = version_id {
+            request = request.param("versionId", version_id);
+        }
+        if let Some(range) = range {
+            request = request.header("Range", range_header(range)?);
+        }
+        if let Some(etag) = if_none_match {
+            request = request.header("If-None-Match", etag);
+        }
+
+        let response = match self.execute(request).await {
+            Ok(response) => response,
+            Err(e)
+                if if_none_match.is_some()
+                    && matches!(
+                        e.downcast_ref::<CosError>(),
+                        Some(CosError::Service { status: 304, .. })
+                    ) =>
+            {
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+
+        let etag = header_of(&response, "ETag");
+        let content_encoding = header_of(&response, "Content-Encoding");
+        let crc = crc64_of(response.headers());
+        let data = response.bytes().await?;
+        // 范围读取时响应头中的 CRC64 针对整个对象，无法校验
+        if range.is_none() {
+            let mut crc64 = self.hash_backend.crc64();
+            crc64.update(&data);
+            self.check_crc64(object_key, crc64.finish(), crc)?;
+        }
+        Ok(Some(FetchedObject {
+            data,
+            etag,
+            content_encoding,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_temp_paths() {
+        let path = Path::new("/data/out/report.csv");
+        assert_eq!(partial_path(path), Path::new("/data/out/report.csv.part"));
+
+        let first = unique_temp_path(path);
+        let second = unique_temp_path(path);
+        assert_ne!(first, second);
+        assert_eq!(first.parent(), path.parent());
+        let name = first.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with(".report.csv.") && name.ends_with(".part"));
+    }
+}
@@ -0,0 +1,23 @@
+use anyhow::Result;
+#[cfg(unix)]
+use std::fs::File;
+use std::path::Path;
+#[cfg(unix)]
+use std::path::PathBuf;
+
+/// 把文件所在的目录刷盘，使其中的创建与重命名在崩溃后保留
+///
+/// 只在 Unix 上需要；Windows 不能以文件方式打开目录，NTFS 的元数据日志已保证重命名不会丢失。
+pub(crate) async fn sync_parent_dir(path: &Path) -> Result<()> {
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        tokio::task::spawn_blocking(move || File::open(dir)?.sync_all()).await??;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
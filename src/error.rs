@@ -0,0 +1,482 @@
+#[cfg(any(feature = "runtime", feature = "presign"))]
+use crate::xml::find_tag;
+use std::fmt;
+
+/// COS 操作的结构化错误
+///
+/// 各 API 仍返回 `anyhow::Result`，需要区分错误类型时可以通过
+/// `err.downcast_ref::<CosError>()` 取出。
+///
+/// 启用 `serde` feature 后以 `kind` 字段区分错误类型，例如
+/// `{"kind": "service", "status": 404, "code": "NoSuchKey", ...}`。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "kind", rename_all = "snake_case")
+)]
+pub enum CosError {
+    /// 配置的地域与 Bucket 实际所在地域不一致
+    WrongRegion {
+        /// Bucket 实际所在的地域
+        expected: String,
+        /// COS 返回的正确访问域名
+        endpoint: Option<String>,
+        /// 请求的 `x-cos-request-id`
+        request_id: Option<String>,
+    },
+    /// COS 返回的其它错误
+    Service {
+        /// HTTP 状态码
+        status: u16,
+        /// COS 错误码，例如 `NoSuchKey`
+        code: String,
+        /// 错误描述
+        message: String,
+        /// 请求的 `x-cos-request-id`
+        request_id: Option<String>,
+    },
+    /// 设置了禁止覆盖（`x-cos-forbid-overwrite`）而对象键已存在
+    AlreadyExists {
+        /// 对象键
+        object_key: String,
+        /// 请求的 `x-cos-request-id`
+        request_id: Option<String>,
+    },
+    /// 本地计算的 CRC64 与 COS 返回的不一致，对象内容可能已损坏
+    ChecksumMismatch {
+        /// 对象键
+        object_key: String,
+        /// 本地计算的 CRC64
+        local: u64,
+        /// COS 返回的 CRC64
+        remote: u64,
+    },
+    /// 条件写入（[`Uploader::put_if_match`](crate::Uploader::put_if_match)）时，对象的 ETag 与期望的不一致
+    PreconditionFailed {
+        /// 对象键
+        object_key: String,
+        /// 期望的 ETag，为 `None` 时表示期望对象不存在
+        expected_etag: Option<String>,
+        /// 对象当前的 ETag，对象不存在或 COS 未返回时为 `None`
+        actual_etag: Option<String>,
+        /// 请求的 `x-cos-request-id`
+        request_id: Option<String>,
+    },
+    /// 传输被 [`TransferHandle::cancel`](crate::TransferHandle::cancel) 取消
+    Cancelled {
+        /// 对象键
+        object_key: String,
+    },
+    /// 对象键不合法（为空、等于 `/` 或超过长度限制），请求没有发出
+    InvalidObjectKey {
+        /// 对象键
+        object_key: String,
+        /// 不合法的原因
+        reason: String,
+    },
+    /// 完成分块上传前检查分块列表，发现重复、缺失或未按升序排列的分块编号，完成请求没有发出
+    InvalidPartList {
+        /// 对象键
+        object_key: String,
+        /// 重复出现的分块编号
+        duplicates: Vec<u32>,
+        /// 缺失的分块编号（从 1 到最大编号或预期的分块数之间）
+        missing: Vec<u32>,
+        /// 分块编号是否未按升序排列
+        unordered: bool,
+    },
+    /// 上传后的读后校验（[`UploadOptions::verify_after_upload`](crate::UploadOptions::verify_after_upload)）未通过
+    VerificationFailed {
+        /// 对象键
+        object_key: String,
+        /// 未通过的原因，例如大小或某个片段的内容不一致
+        reason: String,
+    },
+    /// 内容类型不符合 [`Uploader::with_content_type_policy`](crate::Uploader::with_content_type_policy) 设置的策略，请求没有发出
+    ContentTypeDenied {
+        /// 对象键
+        object_key: String,
+        /// 被拒绝的内容类型
+        content_type: String,
+    },
+    /// 对象键不符合 [`Uploader::with_key_policy`](crate::Uploader::with_key_policy) 设置的命名规范，请求没有发出
+    KeyPolicyViolation {
+        /// 对象键
+        object_key: String,
+        /// 不符合的原因
+        reason: String,
+    },
+}
+
+#[cfg(any(feature = "runtime", feature = "presign"))]
+impl CosError {
+    /// 根据失败响应的状态码、响应头与响应体构建错误
+    pub(crate) fn from_response(
+        status: reqwest::StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        body: &str,
+    ) -> Self {
+        let request_id = find_tag(body, "RequestId")
+            .map(|v| v.to_string())
+            .or_else(|| crate::types::request_id_of(headers));
+        let code = find_tag(body, "Code")
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| status.canonical_reason().unwrap_or("Unknown").to_string());
+        let message = find_tag(body, "Message").unwrap_or("").to_string();
+
+        let is_redirect = matches!(status.as_u16(), 301 | 307)
+            || code == "PermanentRedirect"
+            || code == "TemporaryRedirect";
+
+        if is_redirect {
+            let endpoint = find_tag(body, "Endpoint")
+                .map(|v| v.to_string())
+                .or_else(|| {
+                    headers
+                        .get(reqwest::header::LOCATION)
+                        .and_then(|v| v.to_str().ok())
+                        .and_then(|v| reqwest::Url::parse(v).ok())
+                        .and_then(|url| url.host_str().map(|host| host.to_string()))
+                });
+            let expected = headers
+                .get("x-cos-bucket-region")
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string())
+                .or_else(|| endpoint.as_deref().and_then(region_from_endpoint));
+
+            if let Some(expected) = expected {
+                return CosError::WrongRegion {
+                    expected,
+                    endpoint,
+                    request_id,
+                };
+            }
+        }
+
+        CosError::Service {
+            status: status.as_u16(),
+            code,
+            message,
+            request_id,
+        }
+    }
+}
+
+#[cfg(any(feature = "runtime", feature = "presign"))]
+impl CosError {
+    /// 从其它 HTTP 执行器得到的失败响应构建错误
+    ///
+    /// 与 `Uploader::to_http_request` 配合使用，解析方式与本库发送的请求相同。
+    pub fn from_http_response(response: &http::Response<bytes::Bytes>) -> Self {
+        let body = String::from_utf8_lossy(response.body());
+        Self::from_response(response.status(), response.headers(), &body)
+    }
+}
+
+impl CosError {
+    /// COS 返回的错误码，例如 `NoSuchKey`；地域不匹配、本地校验失败等本地产生的错误返回 `None`
+    pub fn code(&self) -> Option<&str> {
+        match self {
+            CosError::Service { code, .. } => Some(code),
+            CosError::WrongRegion { .. }
+            | CosError::AlreadyExists { .. }
+            | CosError::ChecksumMismatch { .. }
+            | CosError::PreconditionFailed { .. }
+            | CosError::Cancelled { .. }
+            | CosError::InvalidObjectKey { .. }
+            | CosError::InvalidPartList { .. }
+            | CosError::VerificationFailed { .. }
+            | CosError::ContentTypeDenied { .. }
+            | CosError::KeyPolicyViolation { .. } => None,
+        }
+    }
+}
+
+/// 把禁止覆盖时 COS 返回的冲突错误转换为 [`CosError::AlreadyExists`]，其它错误原样返回
+#[cfg(feature = "runtime")]
+pub(crate) fn map_already_exists(error: anyhow::Error, object_key: &str) -> anyhow::Error {
+    match error.downcast_ref::<CosError>() {
+        Some(CosError::Service {
+            status: 409,
+            code,
+            request_id,
+            ..
+        }) if code.contains("AlreadyExist") => CosError::AlreadyExists {
+            object_key: object_key.to_string(),
+            request_id: request_id.clone(),
+        }
+        .into(),
+        _ => error,
+    }
+}
+
+/// 判断错误是否为对象不存在（404）
+#[cfg(feature = "runtime")]
+pub(crate) fn is_not_found(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<CosError>(),
+        Some(CosError::Service { status: 404, .. })
+    )
+}
+
+/// 判断错误是否值得重试：网络错误与 COS 的 5xx/429 响应
+#[cfg(feature = "runtime")]
+pub(crate) fn is_retryable(error: &anyhow::Error) -> bool {
+    if let Some(CosError::Service { status, .. }) = error.downcast_ref::<CosError>() {
+        return *status >= 500 || *status == 429;
+    }
+    error.downcast_ref::<reqwest::Error>().is_some()
+}
+
+/// 判断错误是否发生在连接建立之后、收到响应之前，例如发送请求体时连接被重置或超时
+#[cfg(feature = "runtime")]
+pub(crate) fn is_stream_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| !e.is_connect() && (e.is_request() || e.is_body() || e.is_timeout()))
+}
+
+/// 从 `{bucket}.cos.{region}.myqcloud.com` 形式的域名中解析地域
+#[cfg(any(feature = "runtime", feature = "presign"))]
+fn region_from_endpoint(endpoint: &str) -> Option<String> {
+    let host = endpoint.split('/').next()?;
+    let rest = host.split(".cos.").nth(1)?;
+    let region = rest.strip_suffix(".myqcloud.com")?;
+    Some(region.to_string())
+}
+
+impl fmt::Display for CosError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CosError::WrongRegion {
+                expected,
+                request_id,
+                ..
+            } => write!(
+                f,
+                "Bucket 不在配置的地域，实际地域为 {} (request_id: {:?})",
+                expected, request_id
+            ),
+            CosError::Service {
+                status,
+                code,
+                message,
+                request_id,
+            } => write!(
+                f,
+                "COS 请求失败 (HTTP {}, {}): {} (request_id: {:?})",
+                status, code, message, request_id
+            ),
+            CosError::AlreadyExists {
+                object_key,
+                request_id,
+            } => write!(
+                f,
+                "对象已存在，禁止覆盖: {} (request_id: {:?})",
+                object_key, request_id
+            ),
+            CosError::ChecksumMismatch {
+                object_key,
+                local,
+                remote,
+            } => write!(
+                f,
+                "CRC64 校验失败: {} (本地 {}, COS {})",
+                object_key, local, remote
+            ),
+            CosError::PreconditionFailed {
+                object_key,
+                expected_etag,
+                actual_etag,
+                request_id,
+            } => write!(
+                f,
+                "对象已被其它写入修改: {} (期望 ETag {:?}，实际 {:?}, request_id: {:?})",
+                object_key, expected_etag, actual_etag, request_id
+            ),
+            CosError::Cancelled { object_key } => write!(f, "传输已取消: {}", object_key),
+            CosError::InvalidObjectKey { object_key, reason } => {
+                write!(f, "非法的对象键 {:?}: {}", object_key, reason)
+            }
+            CosError::InvalidPartList {
+                object_key,
+                duplicates,
+                missing,
+                unordered,
+            } => {
+                write!(f, "分块列表有误，拒绝完成分块上传: {}", object_key)?;
+                if !duplicates.is_empty() {
+                    write!(f, "，重复的分块 {}", number_ranges(duplicates))?;
+                }
+                if !missing.is_empty() {
+                    write!(f, "，缺失的分块 {}", number_ranges(missing))?;
+                }
+                if *unordered {
+                    write!(f, "，分块未按编号升序排列")?;
+                }
+                Ok(())
+            }
+            CosError::VerificationFailed { object_key, reason } => {
+                write!(f, "上传后校验失败: {}: {}", object_key, reason)
+            }
+            CosError::ContentTypeDenied {
+                object_key,
+                content_type,
+            } => write!(f, "内容类型 {} 不允许上传: {}", content_type, object_key),
+            CosError::KeyPolicyViolation { object_key, reason } => {
+                write!(f, "对象键不符合命名规范 {:?}: {}", object_key, reason)
+            }
+        }
+    }
+}
+
+/// 把升序的编号合并为区间，例如 `[3, 4, 5, 9]` 输出为 `3-5, 9`
+fn number_ranges(numbers: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for &n in numbers {
+        match ranges.last_mut() {
+            Some((_, end)) if n == *end + 1 => *end = n,
+            _ => ranges.push((n, n)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl std::error::Error for CosError {}
+
+/// COS 返回 `SignatureDoesNotMatch` 时，COS 期望的与本地计算的待签字符串
+///
+/// 开启 [`Config::debug_signature`](crate::Config::debug_signature) 后作为上下文附在 [`CosError`] 上，
+/// 仍可以通过 `downcast_ref::<CosError>()` 取出原始错误。`Display` 输出逐行对比，
+/// 其中临时密钥的 SessionToken 已被隐去。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureMismatch {
+    /// COS 期望的规范请求（`FormatString`），COS 未返回时为 `None`
+    pub expected_format_string: Option<String>,
+    /// COS 期望的待签字符串（`StringToSign`），COS 未返回时为 `None`
+    pub expected_string_to_sign: Option<String>,
+    /// 本地签名使用的规范请求
+    pub format_string: String,
+    /// 本地签名使用的待签字符串
+    pub string_to_sign: String,
+}
+
+/// 逐行对比 COS 期望的与本地计算的字符串
+fn write_diff(
+    f: &mut fmt::Formatter<'_>,
+    title: &str,
+    expected: Option<&str>,
+    local: &str,
+) -> fmt::Result {
+    writeln!(f, "[{}]", title)?;
+    let local: Vec<_> = local.split('\n').collect();
+    let Some(expected) = expected else {
+        writeln!(f, "  (COS 未返回)")?;
+        for line in local {
+            writeln!(f, "    本地: {:?}", line)?;
+        }
+        return Ok(());
+    };
+
+    let expected: Vec<_> = expected.split('\n').collect();
+    for i in 0..expected.len().max(local.len()) {
+        match (expected.get(i), local.get(i)) {
+            (Some(e), Some(l)) if e == l => writeln!(f, "  = {:?}", l)?,
+            (e, l) => {
+                writeln!(
+                    f,
+                    "  ! COS : {}",
+                    e.map_or("-".to_string(), |e| format!("{:?}", e))
+                )?;
+                writeln!(
+                    f,
+                    "    本地: {}",
+                    l.map_or("-".to_string(), |l| format!("{:?}", l))
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+impl fmt::Display for SignatureMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "签名不匹配，COS 期望的与本地计算的待签字符串对比：")?;
+        write_diff(
+            f,
+            "FormatString",
+            self.expected_format_string.as_deref(),
+            &self.format_string,
+        )?;
+        write_diff(
+            f,
+            "StringToSign",
+            self.expected_string_to_sign.as_deref(),
+            &self.string_to_sign,
+        )
+    }
+}
+
+impl std::error::Error for SignatureMismatch {}
+
+#[cfg(all(test, any(feature = "runtime", feature = "presign")))]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderMap;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_parse_permanent_redirect() {
+        let body = "<Error><Code>PermanentRedirect</Code><Message>wrong region</Message>\
+                    <Endpoint>examplebucket-1250000000.cos.ap-beijing.myqcloud.com</Endpoint>\
+                    <RequestId>abc</RequestId></Error>";
+        let err = CosError::from_response(StatusCode::MOVED_PERMANENTLY, &HeaderMap::new(), body);
+        assert_eq!(
+            err,
+            CosError::WrongRegion {
+                expected: "ap-beijing".to_string(),
+                endpoint: Some("examplebucket-1250000000.cos.ap-beijing.myqcloud.com".to_string()),
+                request_id: Some("abc".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_service_error() {
+        let body = "<Error><Code>NoSuchKey</Code><Message>not found</Message></Error>";
+        let err = CosError::from_response(StatusCode::NOT_FOUND, &HeaderMap::new(), body);
+        assert!(
+            matches!(err, CosError::Service { status: 404, ref code, .. } if code == "NoSuchKey")
+        );
+    }
+
+    #[cfg(feature = "runtime")]
+    #[test]
+    fn test_map_already_exists() {
+        let body = "<Error><Code>FileAlreadyExists</Code><RequestId>abc</RequestId></Error>";
+        let err = CosError::from_response(StatusCode::CONFLICT, &HeaderMap::new(), body);
+        let err = map_already_exists(err.into(), "a.txt");
+        assert_eq!(
+            err.downcast_ref::<CosError>(),
+            Some(&CosError::AlreadyExists {
+                object_key: "a.txt".to_string(),
+                request_id: Some("abc".to_string()),
+            })
+        );
+
+        let err = CosError::from_response(StatusCode::FORBIDDEN, &HeaderMap::new(), "");
+        let err = map_already_exists(err.into(), "a.txt");
+        assert!(matches!(
+            err.downcast_ref::<CosError>(),
+            Some(CosError::Service { status: 403, .. })
+        ));
+    }
+}
@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+/// 传输过程中的事件
+///
+/// 通过 [`Uploader::with_event_channel`](crate::Uploader::with_event_channel) 开启后，
+/// 可以用 [`Uploader::subscribe_events`](crate::Uploader::subscribe_events) 订阅，
+/// 用于持久化每个对象的完整上传审计记录。
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransferEvent {
+    /// 开始上传一个分块
+    PartStarted {
+        /// 传输 ID
+        transfer_id: u64,
+        /// 对象键
+        object_key: String,
+        /// 分块编号
+        part_number: u32,
+        /// 分块大小（字节）
+        size: u64,
+    },
+    /// 分块上传完成
+    PartCompleted {
+        /// 传输 ID
+        transfer_id: u64,
+        /// 对象键
+        object_key: String,
+        /// 分块编号
+        part_number: u32,
+        /// 分块的 ETag
+        etag: String,
+        /// COS 返回的分块 CRC64（`x-cos-hash-crc64ecma`）
+        crc: Option<String>,
+    },
+    /// 分块上传失败，即将重试
+    PartRetried {
+        /// 传输 ID
+        transfer_id: u64,
+        /// 对象键
+        object_key: String,
+        /// 分块编号
+        part_number: u32,
+        /// 已失败的次数
+        attempt: u32,
+        /// 失败原因
+        error: String,
+    },
+    /// 对象上传完成（普通上传或完成分块上传）
+    UploadCompleted {
+        /// 传输 ID
+        transfer_id: u64,
+        /// 对象键
+        object_key: String,
+        /// 对象的 ETag
+        etag: Option<String>,
+        /// COS 返回的对象 CRC64（`x-cos-hash-crc64ecma`）
+        crc: Option<String>,
+        /// 最后一次请求的 `x-cos-request-id`
+        request_id: Option<String>,
+    },
+    /// 分块上传的进度，按 `Uploader::with_progress_interval` 设置的间隔发送
+    Progress {
+        /// 传输 ID
+        transfer_id: u64,
+        /// 对象键
+        object_key: String,
+        /// 已完成的字节数，含从断点继续时此前已完成的分块
+        bytes_transferred: u64,
+        /// 文件的总字节数
+        total_bytes: u64,
+        /// 平滑后的吞吐量（字节/秒），尚无采样时为 0
+        bytes_per_sec: u64,
+        /// 按平滑吞吐量估算的剩余时间，尚无采样时为 `None`
+        eta: Option<Duration>,
+    },
+    /// 影子模式下，镜像上传失败或与主上传的内容不一致（优先比较 CRC64，缺少时比较 ETag）
+    ShadowMismatch {
+        /// 对象键
+        object_key: String,
+        /// 主上传的 ETag
+        primary_etag: Option<String>,
+        /// 镜像上传的 ETag，镜像上传失败时为 `None`
+        shadow_etag: Option<String>,
+        /// 镜像上传失败的原因
+        error: Option<String>,
+    },
+}
@@ -0,0 +1,161 @@
+use crate::scoped::check_relative_key;
+use crate::types::CRC64_HEADER;
+use crate::uploader::Uploader;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// 清单文件名
+const MANIFEST_FILE: &str = "manifest.json";
+/// 对象在导出包中的目录
+const OBJECTS_DIR: &str = "objects";
+
+/// 导出包的写入位置
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleTarget {
+    /// 写入目录：对象位于 `objects/{key}`，清单位于 `manifest.json`
+    Directory(PathBuf),
+    /// 打包为 tar 文件，内部结构与目录形式相同（需启用 `tar` feature）
+    #[cfg(feature = "tar")]
+    Tar(PathBuf),
+}
+
+/// 导出包中单个对象的清单记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleEntry {
+    /// 对象键
+    pub key: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// COS 返回并已与本地计算值比对过的 CRC64
+    pub crc64: Option<String>,
+    /// 下载请求的 `x-cos-request-id`
+    pub request_id: Option<String>,
+    /// 下载完成的时间（RFC 3339）
+    pub downloaded_at: String,
+}
+
+/// 导出包清单
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BundleManifest {
+    /// Bucket 名称
+    pub bucket: String,
+    /// 清单生成的时间（RFC 3339）
+    pub created_at: String,
+    /// 导出的对象，顺序与传入的对象键一致
+    pub entries: Vec<BundleEntry>,
+}
+
+impl BundleManifest {
+    /// 清单的 JSON 表示，即写入 `manifest.json` 的内容
+    pub fn to_json(&self) -> String {
+        let entries: Vec<_> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                json!({
+                    "key": entry.key,
+                    "size": entry.size,
+                    "crc64": entry.crc64,
+                    "request_id": entry.request_id,
+                    "downloaded_at": entry.downloaded_at,
+                })
+            })
+            .collect();
+
+        let manifest = json!({
+            "bucket": self.bucket,
+            "created_at": self.created_at,
+            "objects": entries,
+        });
+        serde_json::to_string_pretty(&manifest).expect("清单序列化失败")
+    }
+}
+
+impl Uploader {
+    /// 下载一组对象并连同清单一起导出，用于合规取证等需要留存完整记录的场景
+    ///
+    /// 每个对象下载时都会校验 CRC64，任意对象下载或校验失败都会中止导出。
+    ///
+    /// # 参数
+    ///
+    /// * `keys` - 要导出的对象键，必须能作为相对路径使用（不能包含 `..` 等路径段）
+    /// * `target` - 导出包的写入位置
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回写入的清单
+    pub async fn export_bundle(
+        &self,
+        keys: &[String],
+        target: BundleTarget,
+    ) -> Result<BundleManifest> {
+        for key in keys {
+            check_relative_key(key)?;
+        }
+
+        match target {
+            BundleTarget::Directory(dir) => self.export_to_dir(keys, &dir).await,
+            #[cfg(feature = "tar")]
+            BundleTarget::Tar(path) => {
+                let parent = path
+                    .parent()
+                    .filter(|p| !p.as_os_str().is_empty())
+                    .unwrap_or(Path::new("."));
+                tokio::fs::create_dir_all(parent).await?;
+                // 在目标文件所在目录暂存，打包完成后随临时目录一起删除
+                let staging = tempfile::tempdir_in(parent)?;
+                let manifest = self.export_to_dir(keys, staging.path()).await?;
+
+                let staging_path = staging.path().to_path_buf();
+                tokio::task::spawn_blocking(move || write_tar(&staging_path, &path)).await??;
+                Ok(manifest)
+            }
+        }
+    }
+
+    async fn export_to_dir(&self, keys: &[String], dir: &Path) -> Result<BundleManifest> {
+        let mut entries = Vec::with_capacity(keys.len());
+        tokio::fs::create_dir_all(dir.join(OBJECTS_DIR)).await?;
+
+        for key in keys {
+            let file_path = dir.join(OBJECTS_DIR).join(key);
+            let metadata = self
+                .download_object(key, &file_path)
+                .await
+                .with_context(|| format!("导出对象失败: {}", key))?;
+            let size = tokio::fs::metadata(&file_path).await?.len();
+
+            entries.push(BundleEntry {
+                key: key.clone(),
+                size,
+                crc64: metadata.headers.get(CRC64_HEADER).cloned(),
+                request_id: metadata.request_id,
+                downloaded_at: Utc::now().to_rfc3339(),
+            });
+        }
+
+        let manifest = BundleManifest {
+            bucket: self.config.bucket_name()?,
+            created_at: Utc::now().to_rfc3339(),
+            entries,
+        };
+        tokio::fs::write(dir.join(MANIFEST_FILE), manifest.to_json()).await?;
+        info!("导出 {} 个对象到 {:?}", manifest.entries.len(), dir);
+
+        Ok(manifest)
+    }
+}
+
+/// 把暂存目录中的清单与对象写入 tar 文件
+#[cfg(feature = "tar")]
+fn write_tar(staging: &Path, path: &Path) -> Result<()> {
+    let file = std::fs::File::create(path)?;
+    let mut builder = tar::Builder::new(file);
+    builder.append_path_with_name(staging.join(MANIFEST_FILE), MANIFEST_FILE)?;
+    builder.append_dir_all(OBJECTS_DIR, staging.join(OBJECTS_DIR))?;
+    builder.into_inner()?.sync_all()?;
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::config::Config;
+use crate::options::UploadOptions;
+use crate::types::{Failover, UploadResult};
+use crate::uploader::{UploadSource, Uploader};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::warn;
+
+impl Uploader {
+    /// 设置备用 Bucket：上传到主 Bucket 在重试后仍失败时，改为写入备用 Bucket
+    ///
+    /// 用于对可用性要求高的数据管道，地域故障期间数据不会丢失。只有网络错误与 COS 的 5xx/429 响应
+    /// 会触发切换，权限不足、参数错误等问题在备用 Bucket 上同样会失败，直接返回。
+    /// 写入备用 Bucket 时，结果中的 [`UploadResult::failover`] 记录实际写入的位置与主 Bucket 的错误，
+    /// 对象不在主 Bucket 中，调用方需要在主 Bucket 恢复后自行补传或迁回。
+    ///
+    /// 以整个文件为单位的上传都会切换：[`Uploader::upload_file`]、[`Uploader::upload_file_with_options`]、
+    /// [`Uploader::upload_open_file`]、[`Uploader::start_upload`] 以及 [`TransferManager`](crate::TransferManager) 的上传与上传队列
+    /// （包括通过上传日志入队的文件，写入备用 Bucket 后同样记为完成，[`QueueReport`](crate::QueueReport)
+    /// 的结果中带有切换记录）。由调用方逐个上传分块的 [`Uploader::start_multipart_upload`] 与
+    /// [`Uploader::init_multipart_upload`] 不会切换，上传组的暂存对象也不会写入备用 Bucket。
+    /// 写入备用 Bucket 的上传不会镜像到影子 Bucket。
+    ///
+    /// 备用上传器继承调用时已有的事件广播、重试、限速与进度等设置，
+    /// 因此应在这些 `with_*` 设置之后调用。
+    ///
+    /// # 参数
+    ///
+    /// * `config` - 备用 Bucket 的 COS 配置，通常位于另一个地域
+    pub fn with_failover(mut self, config: Config) -> Self {
+        self.failover = Some(Arc::new(self.derive(config)));
+        self
+    }
+
+    /// 主 Bucket 上传失败后尝试写入备用 Bucket，未设置备用 Bucket 或错误不适合切换时返回原错误
+    pub(crate) async fn failover_upload(
+        &self,
+        source: &UploadSource,
+        object_key: &str,
+        options: &UploadOptions,
+        error: anyhow::Error,
+    ) -> Result<UploadResult> {
+        let Some(failover) = &self.failover else {
+            return Err(error);
+        };
+        if !self.is_retryable(&error) {
+            return Err(error);
+        }
+
+        let primary_error = format!("{:#}", error);
+        warn!(
+            "主 Bucket 上传失败，改为写入备用 Bucket {}: {} ({})",
+            failover.config.bucket, object_key, primary_error
+        );
+        // 备用上传器没有再设置备用 Bucket，递归只有一层；
+        // 覆盖的 Bucket 与地域已由主上传器处理，不能让备用上传器重定向回主 Bucket
+        let options = options.without_target();
+        let mut result = source
+            .upload_to(failover, object_key, &options)
+            .await
+            .with_context(|| {
+                format!(
+                    "主 Bucket 上传失败（{}），备用 Bucket 上传也失败",
+                    primary_error
+                )
+            })?;
+        result.failover = Some(Failover {
+            bucket: failover.config.bucket.clone(),
+            region: failover.config.region.clone(),
+            primary_error,
+        });
+        Ok(result)
+    }
+}
@@ -0,0 +1,285 @@
+use crate::error::{map_already_exists, CosError};
+use crate::options::UploadOptions;
+use crate::request::{object_url_of, read_xml, CosRequest};
+use crate::task::next_transfer_id;
+use crate::types::{crc64_of, request_id_of, UploadResult};
+use crate::uploader::{Uploader, FORBID_OVERWRITE_HEADER};
+use crate::xml::find_tag;
+use anyhow::{anyhow, Context, Result};
+use chrono::Utc;
+use reqwest::Method;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tokio::task::JoinSet;
+use tracing::{info, warn};
+
+/// 上传组暂存对象所在的前缀，暂存对象键为 `{前缀}{group_id}/{对象键}`
+///
+/// 设置了 [`Uploader::with_key_policy`] 时，命名规范需要允许该前缀。
+pub const GROUP_STAGING_PREFIX: &str = ".cos-upload-staging/";
+
+/// 上传组中同时上传的文件数
+const GROUP_CONCURRENCY: usize = 4;
+
+/// 上传组的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupResult {
+    /// 上传组的 ID，也是暂存对象键的一部分
+    pub group_id: String,
+    /// 发布后的对象键与结果，顺序与传入的文件一致
+    pub published: Vec<(String, UploadResult)>,
+}
+
+/// 组成员的暂存对象键
+fn staging_key(group_id: &str, object_key: &str) -> String {
+    format!("{}{}/{}", GROUP_STAGING_PREFIX, group_id, object_key)
+}
+
+impl Uploader {
+    /// 以全有或全无的方式上传一组相关的文件
+    ///
+    /// 参见 [`Uploader::upload_group_with_options`]。
+    pub async fn upload_group<P: AsRef<Path>>(&self, files: &[(P, String)]) -> Result<GroupResult> {
+        self.upload_group_with_options(files, &UploadOptions::default())
+            .await
+    }
+
+    /// 以全有或全无的方式上传一组相关的文件，例如同一版本数据集的多个文件
+    ///
+    /// 先把所有文件并发上传到暂存对象（[`GROUP_STAGING_PREFIX`] 下），全部成功后才依次以服务端复制
+    /// 发布到最终的对象键并删除暂存对象；任何一个文件上传失败时删除已上传的暂存对象，最终的对象键不受影响。
+    ///
+    /// 发布阶段只有服务端复制，耗时很短，但 COS 没有跨对象的事务：发布过程中某个复制失败时，
+    /// 之前已发布的对象会保留，错误信息中列出这些对象键，其余暂存对象会被删除。
+    /// 服务端复制要求单个文件不超过 5 GB。暂存对象只写入主 Bucket：上传组不会切换到备用 Bucket，
+    /// 也不会镜像到影子 Bucket。
+    ///
+    /// # 参数
+    ///
+    /// * `files` - 本地文件路径与最终的对象键
+    /// * `options` - 上传选项，对每个文件生效；禁止覆盖、存储类型与校验值旁路文件在发布时应用于最终的对象
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传组的 ID 与每个对象的发布结果
+    ///
+    /// # 错误
+    ///
+    /// 对象键重复或不合法、任何文件上传失败（已回滚）或发布失败时返回错误。
+    pub async fn upload_group_with_options<P: AsRef<Path>>(
+        &self,
+        files: &[(P, String)],
+        options: &UploadOptions,
+    ) -> Result<GroupResult> {
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.upload_group_with_options(files, options)).await;
+        }
+        let group_id = format!(
+            "{}-{}",
+            Utc::now().format("%Y%m%d%H%M%S"),
+            next_transfer_id()
+        );
+        let mut seen = HashSet::new();
+        for (_, object_key) in files {
+            if !seen.insert(object_key.as_str()) {
+                return Err(anyhow!("上传组中的对象键重复: {}", object_key));
+            }
+            self.config.check_object_key(object_key)?;
+            // 暂存对象键比最终的对象键更长，接近长度上限时要在上传任何文件之前发现
+            self.config
+                .check_object_key(&staging_key(&group_id, object_key))?;
+            if let Some(policy) = &self.key_policy {
+                policy
+                    .check(object_key)
+                    .map_err(|reason| CosError::KeyPolicyViolation {
+                        object_key: object_key.clone(),
+                        reason,
+                    })?;
+            }
+        }
+
+        info!("开始上传组 {}，共 {} 个文件", group_id, files.len());
+
+        // 第一阶段：并发上传到暂存对象
+        let mut staged = Vec::new();
+        let mut failed = Vec::new();
+        let mut tasks: JoinSet<Result<UploadResult>> = JoinSet::new();
+        // 任务异常退出时也能知道它的暂存对象键
+        let mut task_keys = HashMap::new();
+        let mut pending = files.iter().map(|(path, object_key)| {
+            (
+                path.as_ref().to_path_buf(),
+                staging_key(&group_id, object_key),
+            )
+        });
+        loop {
+            while tasks.len() < GROUP_CONCURRENCY {
+                let Some((path, key)) = pending.next() else {
+                    break;
+                };
+                let uploader = self.clone();
+                let options = options.clone();
+                let staging_key = key.clone();
+                let handle = tasks.spawn(async move {
+                    uploader.stage_member(&path, &staging_key, &options).await
+                });
+                task_keys.insert(handle.id(), key);
+            }
+            let Some(joined) = tasks.join_next_with_id().await else {
+                break;
+            };
+            match joined {
+                Ok((id, Ok(_))) => staged.extend(task_keys.remove(&id)),
+                Ok((id, Err(e))) => {
+                    let key = task_keys.remove(&id).unwrap_or_default();
+                    failed.push(format!("{}: {:#}", key, e));
+                }
+                Err(e) => {
+                    // 暂存对象可能已经写入，一并删除
+                    let key = task_keys.remove(&e.id()).unwrap_or_default();
+                    failed.push(format!("{}: 上传任务异常退出: {}", key, e));
+                    staged.push(key);
+                }
+            }
+        }
+
+        if !failed.is_empty() {
+            self.discard_staged(&staged).await;
+            return Err(anyhow!(
+                "上传组 {} 中有 {} 个文件上传失败，已删除暂存对象: {}",
+                group_id,
+                failed.len(),
+                failed.join("; ")
+            ));
+        }
+
+        // 第二阶段：依次发布到最终的对象键
+        let mut published = Vec::new();
+        for (_, object_key) in files {
+            let key = staging_key(&group_id, object_key);
+            let outcome = match self.publish_member(&key, object_key, options).await {
+                Ok(result) => {
+                    if let Err(e) = self.delete_object(&key).await {
+                        warn!("删除已发布的暂存对象失败: {}: {}", key, e);
+                    }
+                    published.push((object_key.clone(), result));
+                    // 旁路文件对应最终的对象，在发布之后写入
+                    if options.checksum_sidecar {
+                        self.upload_sidecar(object_key).await
+                    } else {
+                        Ok(())
+                    }
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = outcome {
+                let remaining: Vec<_> = files[published.len()..]
+                    .iter()
+                    .map(|(_, object_key)| staging_key(&group_id, object_key))
+                    .collect();
+                self.discard_staged(&remaining).await;
+                let published: Vec<_> = published.iter().map(|(key, _)| key.as_str()).collect();
+                return Err(e).with_context(|| {
+                    format!(
+                        "上传组 {} 发布失败，已发布的对象: [{}]",
+                        group_id,
+                        published.join(", ")
+                    )
+                });
+            }
+        }
+
+        info!("上传组 {} 已发布 {} 个对象", group_id, published.len());
+        Ok(GroupResult {
+            group_id,
+            published,
+        })
+    }
+
+    /// 上传一个组成员到暂存对象
+    async fn stage_member(
+        &self,
+        file_path: &Path,
+        staging_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        // 暂存对象不设置禁止覆盖，存储类型与旁路文件在发布时再应用
+        let options = UploadOptions {
+            forbid_overwrite: false,
+            storage_class: None,
+            checksum_sidecar: false,
+            ..options.clone()
+        };
+        // 暂存对象只在主 Bucket 中发布与清理，不镜像到影子 Bucket，也不写入备用 Bucket
+        let uploader = Uploader {
+            shadow: None,
+            failover: None,
+            ..self.clone()
+        };
+        uploader
+            .upload_file_with_options(file_path, staging_key, &options)
+            .await
+    }
+
+    /// 以服务端复制把暂存对象发布到最终的对象键
+    async fn publish_member(
+        &self,
+        staging_key: &str,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .header("x-cos-copy-source", self.copy_source(staging_key));
+        if options.forbid_overwrite {
+            request = request.header(FORBID_OVERWRITE_HEADER, "true");
+        }
+        if let Some(storage_class) = options.storage_class {
+            request = request.header("x-cos-storage-class", storage_class.as_str());
+        }
+
+        let response = self
+            .execute(request)
+            .await
+            .map_err(|e| map_already_exists(e, object_key))?;
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let crc64 = crc64_of(response.headers());
+        let text = read_xml(response).await?;
+        // 复制请求可能在返回 200 的同时在响应体中携带错误
+        if text.contains("<Error>") {
+            return Err(anyhow!("发布对象失败: {}: {}", object_key, text));
+        }
+        self.invalidate_cached(object_key);
+
+        Ok(UploadResult {
+            url,
+            etag: find_tag(&text, "ETag").map(|etag| etag.to_string()),
+            request_id,
+            stats: None,
+            failover: None,
+            crc64,
+        })
+    }
+
+    /// 删除暂存对象，失败时只记录警告
+    async fn discard_staged(&self, keys: &[String]) {
+        for key in keys {
+            if let Err(e) = self.delete_object(key).await {
+                warn!("删除暂存对象失败: {}: {}", key, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staging_key() {
+        assert_eq!(
+            staging_key("20261015-7", "datasets/v3/part-0.parquet"),
+            ".cos-upload-staging/20261015-7/datasets/v3/part-0.parquet"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::checkpoint::MultipartCheckpoint;
+use crate::error::CosError;
+use crate::options::UploadOptions;
+use crate::types::UploadResult;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// 传输的运行状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransferState {
+    /// 正在传输
+    Running,
+    /// 已暂停，不再开始新的分块
+    Paused,
+    /// 已取消
+    Cancelled,
+}
+
+/// 传输的暂停与取消控制
+pub(crate) struct TransferControl {
+    state: watch::Sender<TransferState>,
+}
+
+impl TransferControl {
+    pub(crate) fn new() -> Self {
+        Self {
+            state: watch::Sender::new(TransferState::Running),
+        }
+    }
+
+    pub(crate) fn state(&self) -> TransferState {
+        *self.state.borrow()
+    }
+
+    /// 切换状态，已取消的传输不能再暂停或继续
+    pub(crate) fn set(&self, state: TransferState) {
+        self.state.send_if_modified(|current| {
+            if *current == TransferState::Cancelled || *current == state {
+                return false;
+            }
+            *current = state;
+            true
+        });
+    }
+
+    /// 等待传输继续
+    ///
+    /// # 错误
+    ///
+    /// 传输已取消时返回 [`CosError::Cancelled`]。
+    pub(crate) async fn wait_running(&self, object_key: &str) -> Result<()> {
+        let mut state = self.state.subscribe();
+        loop {
+            match *state.borrow_and_update() {
+                TransferState::Running => return Ok(()),
+                TransferState::Cancelled => {
+                    return Err(CosError::Cancelled {
+                        object_key: object_key.to_string(),
+                    }
+                    .into())
+                }
+                TransferState::Paused => {}
+            }
+            state.changed().await?;
+        }
+    }
+}
+
+/// 后台传输的句柄，由 [`Uploader::start_upload`] 返回
+///
+/// 暂停后不再开始新的分块，已在上传的分块完成后记录到断点中；继续后从断点接着上传。
+/// 暂停期间可以通过 [`TransferHandle::checkpoint`] 取出断点保存到磁盘，
+/// 进程重启后把断点传给 [`Uploader::start_upload`] 即可继续，已完成的分块不会重新上传。
+///
+/// 句柄被丢弃时传输继续在后台运行。
+pub struct TransferHandle {
+    object_key: String,
+    control: Arc<TransferControl>,
+    checkpoint: Arc<Mutex<Option<MultipartCheckpoint>>>,
+    task: JoinHandle<Result<UploadResult>>,
+}
+
+impl TransferHandle {
+    /// 对象键
+    pub fn object_key(&self) -> &str {
+        &self.object_key
+    }
+
+    /// 当前的运行状态
+    pub fn state(&self) -> TransferState {
+        self.control.state()
+    }
+
+    /// 暂停传输
+    ///
+    /// 普通上传（小文件）一旦开始就无法暂停，只能在开始前生效。
+    pub fn pause(&self) {
+        info!("暂停传输: {}", self.object_key);
+        self.control.set(TransferState::Paused);
+    }
+
+    /// 继续已暂停的传输
+    pub fn resume(&self) {
+        info!("继续传输: {}", self.object_key);
+        self.control.set(TransferState::Running);
+    }
+
+    /// 取消传输
+    ///
+    /// 已在上传的分块完成后终止分块上传，[`TransferHandle::join`] 返回 [`CosError::Cancelled`]。
+    /// 需要稍后继续时应使用 [`TransferHandle::pause`]。
+    pub fn cancel(&self) {
+        info!("取消传输: {}", self.object_key);
+        self.control.set(TransferState::Cancelled);
+    }
+
+    /// 最新的分块上传断点，使用普通上传或尚未初始化分块上传时为 `None`
+    pub fn checkpoint(&self) -> Option<MultipartCheckpoint> {
+        self.checkpoint.lock().unwrap().clone()
+    }
+
+    /// 传输是否已经结束（成功、失败或取消）
+    pub fn is_finished(&self) -> bool {
+        self.task.is_finished()
+    }
+
+    /// 等待传输结束
+    ///
+    /// # 错误
+    ///
+    /// 上传失败时返回对应的错误，取消时返回 [`CosError::Cancelled`]。
+    pub async fn join(self) -> Result<UploadResult> {
+        self.task
+            .await
+            .map_err(|e| anyhow!("传输任务异常退出: {}", e))?
+    }
+}
+
+impl Uploader {
+    /// 在后台上传文件，返回可以暂停、继续与取消的句柄
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项
+    /// * `checkpoint` - 之前保存的分块上传断点，为 `None` 时重新开始
+    pub fn start_upload<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        options: UploadOptions,
+        checkpoint: Option<MultipartCheckpoint>,
+    ) -> TransferHandle {
+        let control = Arc::new(TransferControl::new());
+        let latest = Arc::new(Mutex::new(checkpoint.clone()));
+
+        let uploader = self.clone();
+        let file_path = file_path.as_ref().to_path_buf();
+        let key = object_key.to_string();
+        let task_control = control.clone();
+        let task_latest = latest.clone();
+        let task = tokio::spawn(async move {
+            task_control.wait_running(&key).await?;
+            let on_checkpoint = move |checkpoint: &MultipartCheckpoint| {
+                *task_latest.lock().unwrap() = Some(checkpoint.clone());
+            };
+            uploader
+                .upload_file_resumable(
+                    &file_path,
+                    &key,
+                    &options,
+                    checkpoint,
+                    &on_checkpoint,
+                    Some(&task_control),
+                )
+                .await
+        });
+
+        TransferHandle {
+            object_key: object_key.to_string(),
+            control,
+            checkpoint: latest,
+            task,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_transfer_control() {
+        let control = TransferControl::new();
+        control.set(TransferState::Paused);
+        assert_eq!(control.state(), TransferState::Paused);
+        control.set(TransferState::Running);
+        assert!(control.wait_running("a").await.is_ok());
+
+        control.set(TransferState::Cancelled);
+        control.set(TransferState::Running);
+        assert_eq!(control.state(), TransferState::Cancelled);
+        let err = control.wait_running("a").await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CosError>(),
+            Some(CosError::Cancelled { .. })
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+use md5::{Digest, Md5};
+use std::sync::Arc;
+
+/// CRC-64/ECMA-182（反射形式，即 COS `x-cos-hash-crc64ecma` 使用的算法）的多项式
+const CRC64_POLY: u64 = 0xC96C_5795_D787_0F42;
+
+/// slice-by-8 查找表
+static CRC64_TABLES: [[u64; 256]; 8] = build_crc64_tables();
+
+const fn build_crc64_tables() -> [[u64; 256]; 8] {
+    let mut tables = [[0u64; 256]; 8];
+
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ CRC64_POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        tables[0][i] = crc;
+        i += 1;
+    }
+
+    let mut t = 1;
+    while t < 8 {
+        let mut i = 0;
+        while i < 256 {
+            let prev = tables[t - 1][i];
+            tables[t][i] = (prev >> 8) ^ tables[0][(prev & 0xff) as usize];
+            i += 1;
+        }
+        t += 1;
+    }
+
+    tables
+}
+
+/// CRC64 增量计算器
+pub trait Crc64Hasher: Send {
+    /// 追加数据
+    fn update(&mut self, data: &[u8]);
+    /// 当前数据的 CRC64 值
+    fn finish(&self) -> u64;
+}
+
+/// MD5 增量计算器
+pub trait Md5Hasher: Send {
+    /// 追加数据
+    fn update(&mut self, data: &[u8]);
+    /// 计算 MD5 摘要
+    fn finish(self: Box<Self>) -> [u8; 16];
+}
+
+/// 哈希计算后端
+///
+/// 上传时在读取文件数据的同时增量计算校验值，而不是额外遍历一遍文件。
+/// 默认使用纯 Rust 实现；启用 `crc64fast` feature 后默认改用基于 SIMD 指令的 CRC64 实现，
+/// 也可以通过 [`Uploader::with_hash_backend`](crate::Uploader::with_hash_backend) 接入自定义实现。
+pub trait HashBackend: Send + Sync {
+    /// 创建 CRC64 计算器
+    fn crc64(&self) -> Box<dyn Crc64Hasher>;
+    /// 创建 MD5 计算器
+    fn md5(&self) -> Box<dyn Md5Hasher>;
+}
+
+/// 纯 Rust 实现的哈希后端
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SoftwareHashBackend;
+
+impl HashBackend for SoftwareHashBackend {
+    fn crc64(&self) -> Box<dyn Crc64Hasher> {
+        Box::new(SoftwareCrc64::default())
+    }
+
+    fn md5(&self) -> Box<dyn Md5Hasher> {
+        Box::new(Md5Wrapper(Md5::new()))
+    }
+}
+
+/// 使用 `crc64fast` 计算 CRC64 的哈希后端（需启用 `crc64fast` feature）
+#[cfg(feature = "crc64fast")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Crc64FastHashBackend;
+
+#[cfg(feature = "crc64fast")]
+impl HashBackend for Crc64FastHashBackend {
+    fn crc64(&self) -> Box<dyn Crc64Hasher> {
+        Box::new(FastCrc64(crc64fast::Digest::new()))
+    }
+
+    fn md5(&self) -> Box<dyn Md5Hasher> {
+        Box::new(Md5Wrapper(Md5::new()))
+    }
+}
+
+/// 计算数据的 SHA-1，以十六进制字符串表示（即 `x-cos-content-sha1` 的格式）
+#[cfg(feature = "runtime")]
+pub(crate) fn sha1_hex(data: &[u8]) -> String {
+    hex::encode(sha1::Sha1::digest(data))
+}
+
+/// 根据启用的 feature 选择默认的哈希后端
+pub fn default_hash_backend() -> Arc<dyn HashBackend> {
+    #[cfg(feature = "crc64fast")]
+    {
+        Arc::new(Crc64FastHashBackend)
+    }
+
+    #[cfg(not(feature = "crc64fast"))]
+    {
+        Arc::new(SoftwareHashBackend)
+    }
+}
+
+/// 表驱动（slice-by-8）的 CRC64 实现
+struct SoftwareCrc64 {
+    state: u64,
+}
+
+impl Default for SoftwareCrc64 {
+    fn default() -> Self {
+        Self { state: !0 }
+    }
+}
+
+impl Crc64Hasher for SoftwareCrc64 {
+    fn update(&mut self, data: &[u8]) {
+        let mut crc = self.state;
+        let mut chunks = data.chunks_exact(8);
+
+        for chunk in &mut chunks {
+            let value = crc ^ u64::from_le_bytes(chunk.try_into().unwrap());
+            crc = CRC64_TABLES[7][(value & 0xff) as usize]
+                ^ CRC64_TABLES[6][((value >> 8) & 0xff) as usize]
+                ^ CRC64_TABLES[5][((value >> 16) & 0xff) as usize]
+                ^ CRC64_TABLES[4][((value >> 24) & 0xff) as usize]
+                ^ CRC64_TABLES[3][((value >> 32) & 0xff) as usize]
+                ^ CRC64_TABLES[2][((value >> 40) & 0xff) as usize]
+                ^ CRC64_TABLES[1][((value >> 48) & 0xff) as usize]
+                ^ CRC64_TABLES[0][(value >> 56) as usize];
+        }
+
+        for &byte in chunks.remainder() {
+            crc = CRC64_TABLES[0][((crc ^ u64::from(byte)) & 0xff) as usize] ^ (crc >> 8);
+        }
+
+        self.state = crc;
+    }
+
+    fn finish(&self) -> u64 {
+        !self.state
+    }
+}
+
+#[cfg(feature = "crc64fast")]
+struct FastCrc64(crc64fast::Digest);
+
+#[cfg(feature = "crc64fast")]
+impl Crc64Hasher for FastCrc64 {
+    fn update(&mut self, data: &[u8]) {
+        self.0.write(data);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.sum64()
+    }
+}
+
+struct Md5Wrapper(Md5);
+
+impl Md5Hasher for Md5Wrapper {
+    fn update(&mut self, data: &[u8]) {
+        Digest::update(&mut self.0, data);
+    }
+
+    fn finish(self: Box<Self>) -> [u8; 16] {
+        self.0.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_backend(backend: &dyn HashBackend) {
+        // CRC-64/XZ 的标准校验值
+        let mut crc = backend.crc64();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0x995D_C9BB_DF19_39FA);
+
+        let mut md5 = backend.md5();
+        md5.update(b"hello");
+        assert_eq!(
+            hex::encode(md5.finish()),
+            "5d41402abc4b2a76b9719d911017c592"
+        );
+    }
+
+    #[test]
+    fn test_software_backend() {
+        check_backend(&SoftwareHashBackend);
+
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31) as u8).collect();
+        let mut whole = SoftwareHashBackend.crc64();
+        whole.update(&data);
+        let mut split = SoftwareHashBackend.crc64();
+        split.update(&data[..3]);
+        split.update(&data[3..]);
+        assert_eq!(whole.finish(), split.finish());
+    }
+
+    #[cfg(feature = "crc64fast")]
+    #[test]
+    fn test_crc64fast_backend() {
+        check_backend(&Crc64FastHashBackend);
+    }
+}
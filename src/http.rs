@@ -0,0 +1,62 @@
+use reqwest::ClientBuilder;
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    feature = "native-tls",
+    feature = "rustls"
+))]
+compile_error!("`native-tls` 与 `rustls` feature 只能启用其中一个");
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    not(feature = "native-tls"),
+    not(feature = "rustls")
+))]
+compile_error!("需要启用 `native-tls` 或 `rustls` feature 之一来选择 TLS 实现");
+
+/// 默认的 `User-Agent`，即 `cos_upload/{版本号}`
+const USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
+
+/// 请求使用的 `User-Agent`，设置了应用标识时附加在默认值之后
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
+fn user_agent(app_name: Option<&str>) -> String {
+    match app_name {
+        Some(app_name) => format!("{} {}", USER_AGENT, app_name),
+        None => USER_AGENT.to_string(),
+    }
+}
+
+/// 创建按启用的 TLS feature 配置好的 HTTP 客户端构建器
+///
+/// 请求带有 `cos_upload/{版本号} {app_name}` 形式的 `User-Agent`，便于在 COS 访问日志中区分来源。
+/// wasm32 上请求由浏览器的 fetch 发出，不需要选择 TLS 实现，`User-Agent` 也由浏览器决定。
+pub(crate) fn client_builder(app_name: Option<&str>) -> ClientBuilder {
+    let builder = reqwest::Client::builder();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let builder = builder.user_agent(user_agent(app_name));
+    #[cfg(target_arch = "wasm32")]
+    let _ = app_name;
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "native-tls"))]
+    let builder = builder.use_native_tls();
+
+    #[cfg(all(not(target_arch = "wasm32"), feature = "rustls"))]
+    let builder = builder.use_rustls_tls();
+
+    builder
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_agent() {
+        assert_eq!(
+            user_agent(None),
+            format!("cos_upload/{}", env!("CARGO_PKG_VERSION"))
+        );
+        assert!(user_agent(Some("billing-service/2.1")).ends_with(" billing-service/2.1"));
+    }
+}
@@ -0,0 +1,243 @@
+use crate::error::is_not_found;
+use crate::options::UploadOptions;
+use crate::request::{object_url_of, CosRequest};
+use crate::types::{request_id_of, ObjectMetadata, UploadResult};
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tracing::info;
+
+/// 保存幂等令牌的自定义元数据名，即 `x-cos-meta-idempotency-token`
+pub const IDEMPOTENCY_METADATA: &str = "idempotency-token";
+
+/// 幂等令牌对应的上传记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IdempotencyRecord {
+    /// 上传的对象键
+    pub object_key: String,
+    /// 第一次上传的结果
+    pub result: UploadResult,
+}
+
+/// 保存幂等令牌与上传结果的存储
+///
+/// 可以用数据库或 Redis 实现，使多个进程共享同一份记录；未设置存储时只依靠对象上的元数据判断。
+pub trait IdempotencyStore: Send + Sync {
+    /// 查询令牌对应的记录
+    fn get(&self, token: &str) -> Option<IdempotencyRecord>;
+    /// 保存令牌对应的记录
+    fn put(&self, token: &str, record: IdempotencyRecord);
+}
+
+/// 保存在进程内存中的幂等存储
+#[derive(Debug, Default)]
+pub struct MemoryIdempotencyStore {
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+}
+
+impl MemoryIdempotencyStore {
+    /// 创建空的存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl IdempotencyStore for MemoryIdempotencyStore {
+    fn get(&self, token: &str) -> Option<IdempotencyRecord> {
+        self.records.lock().unwrap().get(token).cloned()
+    }
+
+    fn put(&self, token: &str, record: IdempotencyRecord) {
+        self.records
+            .lock()
+            .unwrap()
+            .insert(token.to_string(), record);
+    }
+}
+
+/// 对象上记录的幂等令牌是否与给定的一致
+fn token_matches(metadata: &ObjectMetadata, token: &str) -> bool {
+    metadata
+        .user_metadata
+        .get(IDEMPOTENCY_METADATA)
+        .is_some_and(|value| value == token)
+}
+
+impl Uploader {
+    /// 设置幂等上传使用的存储，参见 [`Uploader::upload_file_idempotent`]
+    pub fn with_idempotency_store(mut self, store: Arc<dyn IdempotencyStore>) -> Self {
+        self.idempotency_store = Some(store);
+        self
+    }
+
+    /// 幂等上传文件
+    ///
+    /// 参见 [`Uploader::upload_file_idempotent_with_options`]。
+    pub async fn upload_file_idempotent<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        idempotency_token: &str,
+    ) -> Result<UploadResult> {
+        self.upload_file_idempotent_with_options(
+            file_path,
+            object_key,
+            idempotency_token,
+            &UploadOptions::default(),
+        )
+        .await
+    }
+
+    /// 幂等上传文件，同一令牌的重复调用返回第一次上传的结果而不再上传
+    ///
+    /// 适用于至少投递一次的任务队列：任务被重复投递时使用相同的令牌（如任务 ID）。依次检查：
+    ///
+    /// 1. [`Uploader::with_idempotency_store`] 设置的存储中是否有该令牌的记录；
+    /// 2. 对象上的 `x-cos-meta-idempotency-token`（[`IDEMPOTENCY_METADATA`]）是否与令牌一致。
+    ///
+    /// 都没有命中时上传文件，令牌随对象写入元数据，并保存到存储中。
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `idempotency_token` - 幂等令牌
+    /// * `options` - 上传选项，令牌会加入其中的自定义元数据
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果；命中时为第一次上传的结果（通过对象元数据命中时 `request_id` 为 HEAD 请求的 ID）
+    ///
+    /// # 错误
+    ///
+    /// 存储中的令牌属于其它对象键、请求失败或上传失败时返回错误。
+    pub async fn upload_file_idempotent_with_options<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        idempotency_token: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.upload_file_idempotent_with_options(
+                file_path,
+                object_key,
+                idempotency_token,
+                options,
+            ))
+            .await;
+        }
+        if let Some(record) = self
+            .idempotency_store
+            .as_ref()
+            .and_then(|store| store.get(idempotency_token))
+        {
+            if record.object_key != object_key {
+                return Err(anyhow!(
+                    "幂等令牌 {} 已用于对象 {}，不能再用于 {}",
+                    idempotency_token,
+                    record.object_key,
+                    object_key
+                ));
+            }
+            info!("幂等令牌已记录，跳过上传: {}", object_key);
+            return Ok(record.result);
+        }
+
+        let result = match self
+            .find_idempotent_upload(object_key, idempotency_token)
+            .await?
+        {
+            Some(result) => {
+                info!("对象已带有相同的幂等令牌，跳过上传: {}", object_key);
+                result
+            }
+            None => {
+                let mut metadata = options.metadata.clone().unwrap_or_default();
+                metadata.insert(
+                    IDEMPOTENCY_METADATA.to_string(),
+                    idempotency_token.to_string(),
+                );
+                let options = options.clone().with_metadata(metadata);
+                self.upload_file_with_options(file_path, object_key, &options)
+                    .await?
+            }
+        };
+
+        if let Some(store) = &self.idempotency_store {
+            store.put(
+                idempotency_token,
+                IdempotencyRecord {
+                    object_key: object_key.to_string(),
+                    result: result.clone(),
+                },
+            );
+        }
+        Ok(result)
+    }
+
+    /// 对象存在且带有相同的幂等令牌时，返回由 HEAD 响应构造的上传结果
+    async fn find_idempotent_upload(
+        &self,
+        object_key: &str,
+        token: &str,
+    ) -> Result<Option<UploadResult>> {
+        let response = match self
+            .execute(CosRequest::new(Method::HEAD, object_key))
+            .await
+        {
+            Ok(response) => response,
+            Err(e) if is_not_found(&e) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+
+        let metadata = ObjectMetadata::from_headers(response.headers());
+        if !token_matches(&metadata, token) {
+            return Ok(None);
+        }
+        Ok(Some(UploadResult {
+            url: object_url_of(&response),
+            etag: metadata.etag,
+            request_id: request_id_of(response.headers()),
+            stats: None,
+            failover: None,
+            crc64: metadata.crc64,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idempotency_token() {
+        let store = MemoryIdempotencyStore::new();
+        assert!(store.get("job-1").is_none());
+        let record = IdempotencyRecord {
+            object_key: "a.txt".to_string(),
+            result: UploadResult {
+                url: "https://b.cos.ap-guangzhou.myqcloud.com/a.txt".to_string(),
+                etag: Some("\"e\"".to_string()),
+                request_id: None,
+                stats: None,
+                failover: None,
+                crc64: None,
+            },
+        };
+        store.put("job-1", record.clone());
+        assert_eq!(store.get("job-1"), Some(record));
+
+        let mut metadata = ObjectMetadata::default();
+        assert!(!token_matches(&metadata, "job-1"));
+        metadata
+            .user_metadata
+            .insert(IDEMPOTENCY_METADATA.to_string(), "job-1".to_string());
+        assert!(token_matches(&metadata, "job-1"));
+        assert!(!token_matches(&metadata, "job-2"));
+    }
+}
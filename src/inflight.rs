@@ -0,0 +1,107 @@
+use crate::error::CosError;
+use anyhow::anyhow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+use tokio::sync::OnceCell;
+
+/// 在并发的调用方之间共享的结果，错误包在 `Arc` 中以便克隆
+type Shared<V> = Result<V, Arc<anyhow::Error>>;
+
+/// 按键合并进行中的请求：同一个键同时只执行一次，其余调用方等待并共享结果
+pub(crate) struct InFlight<K, V> {
+    calls: Mutex<HashMap<K, Arc<OnceCell<Shared<V>>>>>,
+}
+
+impl<K, V> Default for InFlight<K, V> {
+    fn default() -> Self {
+        Self {
+            calls: Mutex::default(),
+        }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> InFlight<K, V> {
+    /// 执行 `f`，同一个键已有进行中的调用时等待它的结果
+    ///
+    /// 执行中的调用方被取消时，由仍在等待的调用方之一重新执行。
+    pub(crate) async fn run<F, Fut>(&self, key: K, f: F) -> anyhow::Result<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<V>>,
+    {
+        let cell = self
+            .calls
+            .lock()
+            .unwrap()
+            .entry(key.clone())
+            .or_default()
+            .clone();
+
+        let result = cell
+            .get_or_init(|| async { f().await.map_err(Arc::new) })
+            .await
+            .clone();
+
+        // 完成后移除，之后的调用重新发起请求
+        let mut calls = self.calls.lock().unwrap();
+        if calls.get(&key).is_some_and(|c| Arc::ptr_eq(c, &cell)) {
+            calls.remove(&key);
+        }
+        drop(calls);
+
+        result.map_err(|e| match e.downcast_ref::<CosError>() {
+            Some(err) => err.clone().into(),
+            None => anyhow!("{:#}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_in_flight_shares_result() {
+        let in_flight = Arc::new(InFlight::<&str, u32>::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let tasks: Vec<_> = (0..10)
+            .map(|_| {
+                let in_flight = in_flight.clone();
+                let calls = calls.clone();
+                tokio::spawn(async move {
+                    in_flight
+                        .run("k", || async {
+                            calls.fetch_add(1, Ordering::SeqCst);
+                            tokio::time::sleep(Duration::from_millis(50)).await;
+                            Ok(7)
+                        })
+                        .await
+                })
+            })
+            .collect();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), 7);
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+        // 完成后再次调用会重新执行，错误中的 CosError 可以取出
+        let err = in_flight
+            .run("k", || async {
+                Err(CosError::Cancelled {
+                    object_key: "k".to_string(),
+                }
+                .into())
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CosError>(),
+            Some(CosError::Cancelled { .. })
+        ));
+    }
+}
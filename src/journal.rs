@@ -0,0 +1,333 @@
+use crate::checkpoint::{str_field, u64_field, MultipartCheckpoint};
+use crate::durable::sync_parent_dir;
+use crate::queue::QueuedUpload;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tracing::{info, warn};
+
+/// 上传日志
+///
+/// 为 [`TransferManager`](crate::TransferManager) 的上传队列提供至少一次的投递保证：
+/// 文件在入队时先写入日志并刷盘，上传并校验通过后再记为完成。进程崩溃或重启后重新打开日志，
+/// 所有未记为完成的文件（连同最新的分块上传断点）都会回到队列中，不会被悄悄丢弃。
+/// 代价是崩溃前已上传、尚未记为完成的文件会再上传一次。
+///
+/// 日志是每行一条 JSON 记录的追加写文件，打开时重放并压缩为只包含未完成条目的新文件。
+/// 同一个日志文件同时只能由一个进程打开。
+pub struct UploadJournal {
+    path: PathBuf,
+    state: Mutex<JournalState>,
+}
+
+struct JournalState {
+    /// 追加写的日志文件，刷盘时在阻塞线程池中共享
+    file: Arc<File>,
+    next_id: u64,
+    /// 打开时尚未完成的条目，交给传输管理器后清空
+    pending: BTreeMap<u64, QueuedUpload>,
+}
+
+/// 日志中的一条记录
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum JournalRecord {
+    /// 文件入队
+    Enqueue { id: u64, upload: QueuedUpload },
+    /// 分块上传的最新断点
+    Checkpoint {
+        id: u64,
+        checkpoint: MultipartCheckpoint,
+    },
+    /// 上传并校验通过
+    Done { id: u64 },
+}
+
+impl JournalRecord {
+    fn to_line(&self) -> String {
+        let value = match self {
+            Self::Enqueue { id, upload } => {
+                json!({"op": "enqueue", "id": id, "upload": upload.to_value()})
+            }
+            Self::Checkpoint { id, checkpoint } => {
+                json!({"op": "checkpoint", "id": id, "checkpoint": checkpoint.to_value()})
+            }
+            Self::Done { id } => json!({"op": "done", "id": id}),
+        };
+        format!("{}\n", value)
+    }
+
+    fn from_line(line: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(line)?;
+        let id = u64_field(&value, "id")?;
+        let field = |name: &str| {
+            value
+                .get(name)
+                .ok_or_else(|| anyhow!("日志记录缺少字段: {}", name))
+        };
+        match str_field(&value, "op")? {
+            "enqueue" => Ok(Self::Enqueue {
+                id,
+                upload: QueuedUpload::from_value(field("upload")?)?,
+            }),
+            "checkpoint" => Ok(Self::Checkpoint {
+                id,
+                checkpoint: MultipartCheckpoint::from_value(field("checkpoint")?)?,
+            }),
+            "done" => Ok(Self::Done { id }),
+            op => Err(anyhow!("未知的日志记录: {}", op)),
+        }
+    }
+}
+
+/// 按顺序重放日志，返回未完成的条目
+///
+/// 最后一行不完整（写入过程中进程退出）时忽略该行，其它行格式错误时返回错误。
+fn replay(text: &str) -> Result<BTreeMap<u64, QueuedUpload>> {
+    let mut pending = BTreeMap::new();
+    let lines: Vec<&str> = text.lines().filter(|line| !line.is_empty()).collect();
+    for (i, line) in lines.iter().enumerate() {
+        let record = match JournalRecord::from_line(line) {
+            Ok(record) => record,
+            Err(e) if i + 1 == lines.len() && !text.ends_with('\n') => {
+                warn!("忽略上传日志中不完整的最后一行: {}", e);
+                break;
+            }
+            Err(e) => return Err(e.context(format!("上传日志第 {} 行格式错误", i + 1))),
+        };
+        match record {
+            JournalRecord::Enqueue { id, upload } => {
+                pending.insert(id, upload);
+            }
+            JournalRecord::Checkpoint { id, checkpoint } => {
+                if let Some(upload) = pending.get_mut(&id) {
+                    upload.checkpoint = Some(checkpoint);
+                }
+            }
+            JournalRecord::Done { id } => {
+                pending.remove(&id);
+            }
+        }
+    }
+    Ok(pending)
+}
+
+impl UploadJournal {
+    /// 打开上传日志，文件不存在时创建
+    ///
+    /// # 参数
+    ///
+    /// * `path` - 日志文件路径
+    ///
+    /// # 错误
+    ///
+    /// 读取、解析或压缩日志失败时返回错误。
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let text = match tokio::fs::read_to_string(&path).await {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(anyhow::Error::new(e).context(format!("读取上传日志失败: {:?}", path)))
+            }
+        };
+        let pending = replay(&text)?;
+
+        // 压缩为只包含未完成条目的新日志，先写入临时文件再重命名
+        let compacted: String = pending
+            .iter()
+            .map(|(id, upload)| {
+                JournalRecord::Enqueue {
+                    id: *id,
+                    upload: upload.clone(),
+                }
+                .to_line()
+            })
+            .collect();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+        let file = tokio::fs::File::create(&temp).await?;
+        let mut file = file.into_std().await;
+        let file = tokio::task::spawn_blocking(move || -> std::io::Result<File> {
+            file.write_all(compacted.as_bytes())?;
+            file.sync_data()?;
+            Ok(file)
+        })
+        .await??;
+        tokio::fs::rename(&temp, &path)
+            .await
+            .with_context(|| format!("压缩上传日志失败: {:?}", path))?;
+        // 重命名记录在目录中，目录也刷盘后压缩后的日志才能在崩溃后保留
+        sync_parent_dir(&path)
+            .await
+            .with_context(|| format!("压缩上传日志失败: {:?}", path))?;
+
+        if !pending.is_empty() {
+            info!("上传日志中有 {} 个未完成的文件: {:?}", pending.len(), path);
+        }
+        Ok(Self {
+            path,
+            state: Mutex::new(JournalState {
+                file: Arc::new(file),
+                next_id: pending.keys().last().map_or(1, |id| id + 1),
+                pending,
+            }),
+        })
+    }
+
+    /// 日志文件路径
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// 打开时尚未完成的文件数
+    pub fn pending_len(&self) -> usize {
+        self.state.lock().unwrap().pending.len()
+    }
+
+    /// 取出打开时尚未完成的条目及其日志 ID
+    pub(crate) fn take_pending(&self) -> BTreeMap<u64, QueuedUpload> {
+        std::mem::take(&mut self.state.lock().unwrap().pending)
+    }
+
+    /// 追加一条记录，不刷盘
+    fn append(state: &JournalState, record: &JournalRecord) -> Result<()> {
+        (&*state.file).write_all(record.to_line().as_bytes())?;
+        Ok(())
+    }
+
+    /// 把日志文件刷盘
+    ///
+    /// 在阻塞线程池中进行，等待磁盘时不占用运行时的工作线程，也不持有日志的锁。
+    async fn sync(file: Arc<File>) -> Result<()> {
+        tokio::task::spawn_blocking(move || file.sync_data()).await??;
+        Ok(())
+    }
+
+    /// 记录入队的文件并刷盘，返回条目的日志 ID
+    ///
+    /// 日志以 JSON 字符串保存文件路径，非 UTF-8 的路径无法原样恢复，直接拒绝，
+    /// 否则重启后的条目会指向不存在的文件而永远无法完成。
+    pub(crate) async fn record_enqueue(&self, upload: &QueuedUpload) -> Result<u64> {
+        if upload.file_path.to_str().is_none() {
+            return Err(anyhow!(
+                "上传日志只能记录 UTF-8 路径: {:?}",
+                upload.file_path
+            ));
+        }
+        let (id, file) = {
+            let mut state = self.state.lock().unwrap();
+            let id = state.next_id;
+            let record = JournalRecord::Enqueue {
+                id,
+                upload: upload.clone(),
+            };
+            Self::append(&state, &record)
+                .with_context(|| format!("写入上传日志失败: {:?}", self.path))?;
+            state.next_id += 1;
+            (id, state.file.clone())
+        };
+        Self::sync(file)
+            .await
+            .with_context(|| format!("写入上传日志失败: {:?}", self.path))?;
+        Ok(id)
+    }
+
+    /// 记录分块上传的最新断点，不刷盘：断点丢失时只是重新上传整个文件
+    pub(crate) fn record_checkpoint(&self, id: u64, checkpoint: &MultipartCheckpoint) {
+        let record = JournalRecord::Checkpoint {
+            id,
+            checkpoint: checkpoint.clone(),
+        };
+        if let Err(e) = Self::append(&self.state.lock().unwrap(), &record) {
+            warn!("写入上传日志失败: {:?}: {}", self.path, e);
+        }
+    }
+
+    /// 记录上传并校验通过的条目并刷盘
+    pub(crate) async fn record_done(&self, id: u64) -> Result<()> {
+        let file = {
+            let state = self.state.lock().unwrap();
+            Self::append(&state, &JournalRecord::Done { id })
+                .with_context(|| format!("写入上传日志失败: {:?}", self.path))?;
+            state.file.clone()
+        };
+        Self::sync(file)
+            .await
+            .with_context(|| format!("写入上传日志失败: {:?}", self.path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upload(object_key: &str) -> QueuedUpload {
+        QueuedUpload {
+            file_path: PathBuf::from("/data").join(object_key),
+            object_key: object_key.to_string(),
+            metadata: None,
+            checkpoint: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replay_and_compact() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("uploads.journal");
+
+        let journal = UploadJournal::open(&path).await.unwrap();
+        let a = journal.record_enqueue(&upload("a.jpg")).await.unwrap();
+        let b = journal.record_enqueue(&upload("b.jpg")).await.unwrap();
+        let checkpoint = MultipartCheckpoint {
+            file_path: PathBuf::from("/data/b.jpg"),
+            object_key: "b.jpg".to_string(),
+            upload_id: "upload-1".to_string(),
+            file_size: 11 * 1024 * 1024,
+            file_mtime: 1700000000,
+            part_size: 5 * 1024 * 1024,
+            part_sizes: Vec::new(),
+            completed_parts: vec![(1, "\"e1\"".to_string())],
+        };
+        journal.record_checkpoint(b, &checkpoint);
+        journal.record_done(a).await.unwrap();
+        drop(journal);
+
+        // 模拟写入到一半时进程退出
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(br#"{"op": "done", "#).unwrap();
+        drop(file);
+
+        let journal = UploadJournal::open(&path).await.unwrap();
+        let pending = journal.take_pending();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[&b].object_key, "b.jpg");
+        assert_eq!(pending[&b].checkpoint, Some(checkpoint));
+        assert!(journal.record_enqueue(&upload("c.jpg")).await.unwrap() > b);
+
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), 2);
+        assert!(replay("not json\n{}\n").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_reject_non_utf8_path() {
+        use std::os::unix::ffi::OsStrExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let journal = UploadJournal::open(dir.path().join("uploads.journal"))
+            .await
+            .unwrap();
+        let mut entry = upload("a.jpg");
+        entry.file_path = PathBuf::from(std::ffi::OsStr::from_bytes(b"/data/\xff.jpg"));
+        assert!(journal.record_enqueue(&entry).await.is_err());
+        assert_eq!(journal.record_enqueue(&upload("b.jpg")).await.unwrap(), 1);
+    }
+}
@@ -0,0 +1,200 @@
+use crate::types::UploadResult;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Context, Result};
+use bytes::Bytes;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// JSON 对象的 Content-Type
+const JSON_CONTENT_TYPE: &str = "application/json";
+/// gzip 压缩对象的 Content-Encoding
+#[cfg(feature = "gzip")]
+const GZIP_ENCODING: &str = "gzip";
+
+/// 从 COS 读取的 JSON 文档
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonDocument<T> {
+    /// 反序列化后的值
+    pub value: T,
+    /// 对象的 ETag，可传给 [`Uploader::put_json_if_match`] 进行条件写入
+    pub etag: Option<String>,
+}
+
+impl Uploader {
+    /// 把值序列化为 JSON 后写入对象
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `value` - 要写入的值
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果，其中的 ETag 可用于之后的条件写入
+    ///
+    /// # 错误
+    ///
+    /// 序列化失败或请求失败时返回错误。
+    pub async fn put_json<T: Serialize + ?Sized>(
+        &self,
+        object_key: &str,
+        value: &T,
+    ) -> Result<UploadResult> {
+        let data = serde_json::to_vec(value)
+            .with_context(|| format!("序列化 JSON 失败: {}", object_key))?;
+        self.put_bytes(object_key, data.into(), JSON_CONTENT_TYPE, &[])
+            .await
+    }
+
+    /// 把值序列化为 JSON 并以 gzip 压缩后写入对象
+    ///
+    /// 对象带有 `Content-Encoding: gzip`，[`Uploader::get_json`] 读取时自动解压；
+    /// 浏览器等 HTTP 客户端直接下载时也会按该头部透明解压。适合体积较大、重复内容多的状态文档。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `value` - 要写入的值
+    ///
+    /// # 错误
+    ///
+    /// 序列化、压缩失败或请求失败时返回错误。
+    #[cfg(feature = "gzip")]
+    pub async fn put_json_gzip<T: Serialize + ?Sized>(
+        &self,
+        object_key: &str,
+        value: &T,
+    ) -> Result<UploadResult> {
+        use std::io::Write;
+
+        let data = serde_json::to_vec(value)
+            .with_context(|| format!("序列化 JSON 失败: {}", object_key))?;
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&data)?;
+        let compressed = encoder.finish()?;
+        self.put_bytes(
+            object_key,
+            compressed.into(),
+            JSON_CONTENT_TYPE,
+            &[("Content-Encoding", GZIP_ENCODING)],
+        )
+        .await
+    }
+
+    /// 仅当对象的 ETag 与期望一致时写入 JSON，参见 [`Uploader::put_if_match`]
+    ///
+    /// 典型用法是先用 [`Uploader::get_json`] 读取文档与 ETag，修改后以该 ETag 写回；
+    /// 返回 [`CosError::PreconditionFailed`](crate::CosError::PreconditionFailed) 时重新读取后重试。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `expected_etag` - 期望的当前 ETag，为 `None` 时要求对象不存在
+    /// * `value` - 要写入的值
+    pub async fn put_json_if_match<T: Serialize + ?Sized>(
+        &self,
+        object_key: &str,
+        expected_etag: Option<&str>,
+        value: &T,
+    ) -> Result<UploadResult> {
+        let data = serde_json::to_vec(value)
+            .with_context(|| format!("序列化 JSON 失败: {}", object_key))?;
+        self.put_bytes_if_match(
+            object_key,
+            expected_etag,
+            data.into(),
+            JSON_CONTENT_TYPE,
+            &[],
+        )
+        .await
+    }
+
+    /// 读取对象并按 JSON 反序列化
+    ///
+    /// 总是向 COS 请求最新的内容，不使用 [`Uploader::with_object_cache`] 的缓存，
+    /// 以保证返回的 ETag 可以用于条件写入。对象带有 `Content-Encoding: gzip` 时先解压，
+    /// 这需要启用 `gzip` feature。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回反序列化后的值与对象的 ETag
+    ///
+    /// # 错误
+    ///
+    /// 对象不存在（[`CosError::Service`](crate::CosError::Service)，状态码 404）、
+    /// 请求失败、内容编码不受支持或内容不是合法的 JSON 时返回错误。
+    pub async fn get_json<T: DeserializeOwned>(&self, object_key: &str) -> Result<JsonDocument<T>> {
+        let fetched = self
+            .fetch_object(object_key, None, None, None)
+            .await?
+            .ok_or_else(|| anyhow!("未带条件的请求不应返回 304: {}", object_key))?;
+        let data = decode_content(
+            object_key,
+            fetched.data,
+            fetched.content_encoding.as_deref(),
+        )?;
+        let value = serde_json::from_slice(&data)
+            .with_context(|| format!("解析 JSON 失败: {}", object_key))?;
+        Ok(JsonDocument {
+            value,
+            etag: fetched.etag,
+        })
+    }
+}
+
+/// 按对象的 `Content-Encoding` 解码内容
+fn decode_content(object_key: &str, data: Bytes, encoding: Option<&str>) -> Result<Bytes> {
+    match encoding.map(str::trim) {
+        None | Some("") | Some("identity") => Ok(data),
+        #[cfg(feature = "gzip")]
+        Some(encoding) if encoding.eq_ignore_ascii_case(GZIP_ENCODING) => {
+            use std::io::Read;
+
+            let mut decoded = Vec::new();
+            flate2::read::GzDecoder::new(data.as_ref())
+                .read_to_end(&mut decoded)
+                .with_context(|| format!("解压 JSON 失败: {}", object_key))?;
+            Ok(decoded.into())
+        }
+        Some(encoding) => Err(anyhow!(
+            "不支持的内容编码 {}（gzip 需要启用 gzip feature）: {}",
+            encoding,
+            object_key
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_content() {
+        let data = Bytes::from_static(b"{}");
+        assert_eq!(decode_content("a.json", data.clone(), None).unwrap(), data);
+        assert_eq!(
+            decode_content("a.json", data.clone(), Some("identity")).unwrap(),
+            data
+        );
+        assert!(decode_content("a.json", data, Some("br")).is_err());
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decode_gzip_content() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"{\"a\":1}").unwrap();
+        let compressed = Bytes::from(encoder.finish().unwrap());
+        assert_eq!(
+            decode_content("a.json", compressed.clone(), Some("gzip")).unwrap(),
+            Bytes::from_static(b"{\"a\":1}")
+        );
+        assert!(decode_content("a.json", compressed.slice(..4), Some("gzip")).is_err());
+    }
+}
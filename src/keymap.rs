@@ -0,0 +1,98 @@
+//! 本地路径到对象键的映射
+//!
+//! 目录上传、同步与目录监听共用这里的规则：把相对路径统一为以 `/` 分隔的对象键，
+//! 在 Windows 上额外处理反斜杠分隔符、`\\?\` 长路径前缀与盘符。
+
+use anyhow::{anyhow, Result};
+use std::path::Path;
+
+/// Windows 文件名中不允许出现的字符
+const WINDOWS_RESERVED: &[char] = &['<', '>', ':', '"', '|', '?', '*'];
+
+/// 把目录下文件的相对路径转换为以 `/` 分隔的对象键
+///
+/// # 错误
+///
+/// 路径不在目录之下、不是合法的 UTF-8 或包含非法字符时返回错误。
+pub(crate) fn relative_key(dir: &Path, path: &Path) -> Result<String> {
+    let relative = path.strip_prefix(dir)?;
+    let relative = relative
+        .to_str()
+        .ok_or_else(|| anyhow!("路径不是合法的 UTF-8: {:?}", relative))?;
+    path_to_key(relative, cfg!(windows))
+}
+
+/// 把相对路径字符串转换为对象键
+///
+/// `windows` 为 `true` 时按 Windows 规则处理：`\` 视为分隔符，去掉 `\\?\` 长路径前缀与盘符，
+/// 并拒绝 Windows 保留字符（例如 NTFS 备用数据流中的 `:`）。
+/// 空路径段与 `.` 会被忽略，`..` 与控制字符会被拒绝。
+pub(crate) fn path_to_key(path: &str, windows: bool) -> Result<String> {
+    let mut rest = path;
+    let normalized;
+    if windows {
+        if rest.starts_with(r"\\?\UNC\") || (rest.starts_with(r"\\") && !rest.starts_with(r"\\?\"))
+        {
+            return Err(anyhow!("不支持把 UNC 路径映射为对象键: {}", path));
+        }
+        rest = rest.strip_prefix(r"\\?\").unwrap_or(rest);
+        let bytes = rest.as_bytes();
+        if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+            rest = &rest[2..];
+        }
+        normalized = rest.replace('\\', "/");
+        rest = &normalized;
+    }
+
+    let mut segments = Vec::new();
+    for segment in rest.split('/') {
+        match segment {
+            "" | "." => continue,
+            ".." => return Err(anyhow!("路径不能包含 `..`: {}", path)),
+            _ => {}
+        }
+        if segment.chars().any(char::is_control) {
+            return Err(anyhow!("路径包含控制字符: {:?}", path));
+        }
+        if windows && segment.contains(WINDOWS_RESERVED) {
+            return Err(anyhow!("路径包含 Windows 保留字符: {}", path));
+        }
+        segments.push(segment);
+    }
+
+    if segments.is_empty() {
+        return Err(anyhow!("路径无法映射为对象键: {:?}", path));
+    }
+    Ok(segments.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_windows_paths() {
+        assert_eq!(
+            path_to_key(r"sub\dir\a.txt", true).unwrap(),
+            "sub/dir/a.txt"
+        );
+        assert_eq!(path_to_key(r"C:\data\a.txt", true).unwrap(), "data/a.txt");
+        assert_eq!(
+            path_to_key(r"\\?\D:\very\long\path.bin", true).unwrap(),
+            "very/long/path.bin"
+        );
+        assert_eq!(path_to_key(r".\mixed/sep\\a", true).unwrap(), "mixed/sep/a");
+        assert!(path_to_key(r"a\..\b", true).is_err());
+        assert!(path_to_key(r"a.txt:stream", true).is_err());
+        assert!(path_to_key(r"\\server\share\a", true).is_err());
+        assert!(path_to_key(r"C:\", true).is_err());
+    }
+
+    #[test]
+    fn test_unix_paths() {
+        // 在 Unix 上反斜杠与冒号是合法的文件名字符
+        assert_eq!(path_to_key(r"a\b:c", false).unwrap(), r"a\b:c");
+        assert_eq!(path_to_key("dir//a.txt", false).unwrap(), "dir/a.txt");
+        assert!(path_to_key("a/\u{7}b", false).is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use crate::error::CosError;
+use crate::request::CosRequest;
+use crate::uploader::Uploader;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::sync::Arc;
+use tracing::warn;
+
+/// 对象键的命名规范，在每个创建对象的请求发出前检查，参见 [`Uploader::with_key_policy`]
+///
+/// 嵌入本库的平台团队可以据此统一约束所有业务代码写入的对象键。[`NamingPolicy`] 提供了
+/// 常用的规则组合，也可以自行实现。
+pub trait KeyPolicy: Send + Sync {
+    /// 检查对象键
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - 要写入的对象键，不含 [`Config::key_prefix`](crate::Config#structfield.key_prefix)
+    ///
+    /// # 返回值
+    ///
+    /// 符合规范时返回 `Ok(())`，否则返回不符合的原因
+    fn check(&self, object_key: &str) -> std::result::Result<(), String>;
+}
+
+/// 常用的对象键命名规范：最大深度、禁止的前缀与正则表达式白名单
+#[derive(Debug, Clone, Default)]
+pub struct NamingPolicy {
+    /// 对象键最多包含的路径段数（`a/b/c.txt` 为 3 段），为 `None` 时不限制
+    pub max_depth: Option<usize>,
+    /// 禁止写入的前缀，例如 `internal/`
+    pub forbidden_prefixes: Vec<String>,
+    /// 对象键必须完整匹配其中之一的正则表达式，为空时不限制
+    pub allowed_patterns: Vec<Regex>,
+}
+
+impl NamingPolicy {
+    /// 创建不做任何限制的命名规范
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置对象键最多包含的路径段数
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// 添加禁止写入的前缀
+    pub fn with_forbidden_prefix(mut self, prefix: String) -> Self {
+        self.forbidden_prefixes.push(prefix);
+        self
+    }
+
+    /// 添加对象键白名单的正则表达式，对象键需要完整匹配（无需自行添加 `^` 与 `$`）
+    ///
+    /// # 错误
+    ///
+    /// 正则表达式语法错误时返回错误。
+    pub fn with_allowed_pattern(mut self, pattern: &str) -> Result<Self> {
+        let regex = Regex::new(&format!("^(?:{})$", pattern))
+            .with_context(|| format!("对象键白名单的正则表达式有误: {}", pattern))?;
+        self.allowed_patterns.push(regex);
+        Ok(self)
+    }
+}
+
+impl KeyPolicy for NamingPolicy {
+    fn check(&self, object_key: &str) -> std::result::Result<(), String> {
+        if let Some(prefix) = self
+            .forbidden_prefixes
+            .iter()
+            .find(|prefix| object_key.starts_with(prefix.as_str()))
+        {
+            return Err(format!("不允许写入前缀 {}", prefix));
+        }
+        if let Some(max_depth) = self.max_depth {
+            let depth = object_key.trim_end_matches('/').split('/').count();
+            if depth > max_depth {
+                return Err(format!("路径深度 {} 超过上限 {}", depth, max_depth));
+            }
+        }
+        if !self.allowed_patterns.is_empty()
+            && !self
+                .allowed_patterns
+                .iter()
+                .any(|regex| regex.is_match(object_key))
+        {
+            return Err("不匹配任何允许的命名规则".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Uploader {
+    /// 设置对象键的命名规范
+    ///
+    /// 普通上传、服务端复制（检查目标对象键）、初始化分块上传与追加上传的请求在发出前检查，
+    /// 不符合时返回 [`CosError::KeyPolicyViolation`]，目录上传、同步、归档解包等批量操作中的每个对象同样受约束。
+    /// 删除与读取不受限制，已有的不规范对象仍可清理。
+    pub fn with_key_policy(mut self, policy: Arc<dyn KeyPolicy>) -> Self {
+        self.key_policy = Some(policy);
+        self
+    }
+
+    /// 按命名规范检查请求
+    pub(crate) fn check_key_policy(&self, request: &CosRequest) -> Result<(), CosError> {
+        let Some(policy) = &self.key_policy else {
+            return Ok(());
+        };
+        if !request.creates_object() {
+            return Ok(());
+        }
+        policy.check(&request.object_key).map_err(|reason| {
+            warn!("对象键不符合命名规范: {} ({})", request.object_key, reason);
+            CosError::KeyPolicyViolation {
+                object_key: request.object_key.clone(),
+                reason,
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_naming_policy() {
+        let policy = NamingPolicy::new()
+            .with_max_depth(3)
+            .with_forbidden_prefix("internal/".to_string())
+            .with_allowed_pattern(r"[a-z0-9\-/]+\.(png|jpg)")
+            .unwrap();
+        assert!(policy.check("avatars/u1/a.png").is_ok());
+        assert!(policy.check("internal/a.png").is_err());
+        assert!(policy.check("a/b/c/d.png").is_err());
+        assert!(policy.check("avatars/A.png").is_err());
+        assert!(policy.check("avatars/a.png.exe").is_err());
+        assert!(NamingPolicy::new().with_allowed_pattern("(").is_err());
+    }
+}
@@ -20,15 +20,116 @@
 //! ## 功能亮点
 //! - 支持普通上传和分块上传，根据文件大小自动选择
 //! - 支持自定义对象元数据（例如用户 ID、用户名、上传时间等）
+//! - 通过 [`UploadOptions`] 指定对象的存储类型（[`StorageClass`]），或为每个分块发送 `x-cos-content-sha1` 由 COS 校验
+//! - 通过 [`UploadOptions`] 设置 `Content-Language` 与静态网站重定向地址（`x-cos-website-redirect-location`），并从 [`ObjectMetadata`] 中读回
+//! - 幂等上传（[`Uploader::upload_file_idempotent`]）：至少投递一次的任务队列重复投递时，按幂等令牌返回第一次上传的结果而不重复上传
+//! - 按 ETag 条件写入（[`Uploader::put_if_match`]），多个服务更新同一状态对象时可以发现丢失的更新
+//! - 启用 `serde` feature 后，可用 `put_json` / `get_json` / `put_json_if_match` 直接读写 JSON 状态文档，读取时一并返回 ETag；启用 `gzip` feature 后可用 `put_json_gzip` 压缩写入，读取时自动解压
+//! - 可以禁止覆盖同名对象，对象键已存在时返回 [`CosError::AlreadyExists`]，避免并发写入互相覆盖
+//! - 对象键为空、等于 `/` 或超过长度限制时在发出请求前返回 [`CosError::InvalidObjectKey`]
+//! - 列举、分块上传与配置查询等控制面的 XML 响应体有大小上限（16 MB），错误响应体只读取前 64 KB，异常的端点或代理不会造成无限制的内存占用
+//! - 完成分块上传前检查分块编号是否重复、缺失或乱序，发现问题时返回列出具体编号的 [`CosError::InvalidPartList`]
+//! - 按 [`ContentTypePolicy`] 允许或禁止上传的内容类型（例如禁止 `text/html` 防止存储型 XSS），发出请求前返回 [`CosError::ContentTypeDenied`]
+//! - 按 [`KeyPolicy`] 统一约束写入的对象键（[`NamingPolicy`] 支持正则白名单、最大深度与禁止的前缀），不符合时返回 [`CosError::KeyPolicyViolation`]
+//! - 上传完成后可按 [`UploadOptions::verify_after_upload`] 重新 HEAD 或抽样读取对象进行读后校验，不一致时返回 [`CosError::VerificationFailed`]
+//! - 可以在对象旁写入记录 CRC64 的校验值旁路文件（`{object_key}.crc64`），并通过 [`Uploader::verify_with_sidecar`] 校验
+//! - 通过 [`UploadOptions::expires_in`] 上传临时对象：设置 `Expires` 缓存头部与过期标签，
+//!   并可确保 Bucket 中存在按标签删除对象的生命周期规则
+//! - 上传结果附带耗时统计（[`TransferStats`]）：总耗时、吞吐量、分块耗时的最小值/中位数/最大值与重试次数
+//! - 上传时增量计算 CRC64 并与 COS 返回的校验值比对，哈希后端可替换（`crc64fast` feature 提供 SIMD 加速）
+//! - COS 返回的 CRC64 以 `u64` 形式出现在 [`ObjectMetadata::crc64`] 与 [`UploadResult::crc64`] 中，
+//!   其它来源的头部可用 [`parse_crc64`] 按无符号十进制解析
+//! - 响应中的时间统一解析为 `chrono::DateTime<Utc>`：[`ObjectMetadata`] 的最后修改时间、`Expires` 与归档恢复的过期时间，
+//!   以及列举结果中的 `last_modified` / `initiated`；其它来源的字符串可用 [`parse_http_date`]、[`parse_iso8601`] 解析
+//! - 边下载边校验 CRC64 的对象下载，以及带清单（对象键、大小、CRC64、请求 ID、时间）的合规导出包（可选 `tar` feature 打包）
+//! - 启用 `tar` feature 后，`download_as_tar` 把一组对象或整个前缀边下载边打包为 tar 写入任意 `AsyncWrite`，不在本地暂存；启用 `zip` feature 后 `download_as_zip` 输出 zip 归档
+//! - 启用 `unpack` feature 后，`upload_archive_contents` 边解压边把 tar.gz / zip 归档中的文件逐个上传为对象，支持按模式筛选条目
+//! - 目录与 COS 前缀之间的双向同步，通过 `x-cos-meta-mtime` 保留修改时间，未变化的文件不会重复传输（`sync` feature，默认启用）
+//! - 分块并发上传，失败时终止分块上传；每个分块任务都带有传输 ID 与分块编号的 tracing span
+//! - 每个 HTTP 请求都在 `cos_request` span 中执行，字段遵循 OpenTelemetry 语义约定（`rpc.system`、`net.peer.name`、
+//!   `http.status_code`、`cos.bucket`、`cos.key` 等），经 `tracing-opentelemetry` 导出后可直接在 Jaeger、Tempo 中检索
+//! - 下载到本地时先写入临时文件，校验并刷新到磁盘后原子重命名；可保留 `.part` 文件以便续传（[`DownloadOptions`]）
+//! - 读取对象内容到内存（[`Uploader::get_object_bytes`]），可选按字节数限制大小的 LRU 缓存，过期后用 ETag 向 COS 确认；
+//!   并发读取同一对象时合并为一次请求
+//! - 公开分块上传的底层接口，可以用 [`Uploader::upload_part_copy`] 按字节范围从已有对象复制分块，自行拼装对象
+//! - 由调用方逐块写入数据的分块上传句柄（[`Uploader::start_multipart_upload`] 返回 [`MultipartUpload`]），
+//!   Web 框架可以在浏览器分片到达时逐个上传，无需先拼成完整的文件
+//! - 可选的自适应分块大小（[`UploadOptions::adaptive_part_size`]），按观测到的吞吐量在 1 MB 到 64 MB 之间调整
+//! - 以全有或全无的方式上传一组相关文件（[`Uploader::upload_group`]）：全部上传到暂存对象后才发布到最终的对象键，任一失败时回滚
+//! - 递归上传目录，整个批量共享重试预算，失败率过高时熔断并返回部分结果；
+//!   符号链接、空目录、隐藏文件与遍历后消失的文件由 [`DirUploadPolicy`] 控制
+//! - 目录的增量上传：传入上一次的 [`UploadManifest`]，只上传大小或 CRC64 变化的文件并返回新的清单，无需列举远端对象
+//! - 批量上传中的重复内容合并：开启 `BatchOptions::dedupe_content` 后大小与 CRC64 相同的文件只上传一次，其余对象键通过服务端复制创建
+//! - 可选的慢请求检测（[`Uploader::with_slow_request_threshold`]），耗时超过阈值的请求以结构化字段输出警告
+//! - [`ObjectStore`] trait 抽象了 put / get / delete / list / presign，业务代码依赖该 trait，测试时换成内存中的 [`MemoryObjectStore`]
+//! - 启用 `object_store` feature 后，`CosObjectStore` 实现 `object_store::ObjectStore`，可直接接入 DataFusion、Parquet 等 Arrow 生态的工具
+//! - 可选的传输事件广播（分块开始、完成、重试与上传完成），便于持久化审计记录
+//! - 分块上传的进度事件带有平滑吞吐量与预计剩余时间，可按时间或字节数设置发送间隔（[`Uploader::with_progress_interval`]），避免高速网络下刷屏
+//! - [`Uploader`] 与 [`TransferManager`] 内部通过 `Arc` 共享状态，克隆开销很小，可直接克隆到各个任务中使用
+//! - 通过 [`Uploader::start_upload`] 在后台上传，返回的 [`TransferHandle`] 可以暂停、继续与取消，暂停时的断点可以保存下来在进程重启后继续
+//! - 断点可以保存在本地目录中（[`FileCheckpointStore`]），按最长保留时间与最大数量（[`CheckpointRetention`]）清理被放弃的断点
+//! - [`TransferManager`] 在进程内协调同一对象键的并发上传（排队、合并或拒绝）
+//! - [`TransferManager`] 的上传队列可以连同分块上传断点保存为 [`TransferSnapshot`]，进程重启后恢复并从断点继续
+//! - 上传已经打开的文件句柄（[`Uploader::upload_open_file`]，接受 `tokio::fs::File` 或 `std::fs::File`），
+//!   适用于 `O_TMPFILE` 匿名文件或沙箱中无法通过路径访问的文件
+//! - 持久化的上传队列（[`UploadJournal`]）：文件入队前先写入日志并刷盘，上传并校验通过后才记为完成，崩溃或重启后不会丢失待上传的文件
+//! - [`TransferManager`] 可以设置传输计划（[`TimeWindow`] 或自定义回调），只在允许的时段传输，其余时段自动暂停
+//! - 支持多租户场景下限定对象键前缀的 [`ScopedUploader`]
+//! - 启用 `tower` feature 后，[`Uploader`] 实现 `tower::Service<CosRequest>`，可以组合 tower 生态的超时、限流、重试等中间件
+//! - 启用 `testing` feature 后，`testing::MockCos` 在进程内启动校验签名的模拟 COS 服务器，集成测试无需真实的密钥与网络
+//! - 底层请求可以转换为签名后的 `http::Request`（[`Uploader::to_http_request`]），交给 hyper、tower 等其它执行器发送
+//! - 可以缩小参与签名的头部范围（[`SignedHeaders`]），避免改写请求头的代理使签名失效
+//! - 配置级别的对象键前缀（[`Config::with_key_prefix`]，如 `env/staging/`），上传、下载、列举与删除都自动加上，隔离不同环境
+//! - 可选的对象键确定性加密（`Config::with_key_encryption`，`crypto` feature，默认启用），共享 Bucket 中的对象名不泄露用户标识，列举与同步仍然可用
+//! - 同步、对象键加密等可选子系统由独立的 feature 控制，`default-features = false` 时只启用 `runtime` 即可构建核心上传路径
+//! - 支持监听目录并持续上传新增或修改的文件（需启用 `notify` feature）
+//! - 请求带有包含库版本与可选应用标识（[`Config::with_app_name`]）的 `User-Agent`
+//! - Bucket 名称可以只写短名称，通过 [`Config::with_app_id`] 自动补全 APPID 后缀（[`Config::bucket_name`]）
+//! - 可以直接读取腾讯云命令行工具（tccli）的凭证配置（`Config::from_tccli`），支持临时密钥
+//! - 只根据 Bucket 名称查询其所在的地域（[`discover_bucket_region`]），不需要密钥
+//! - 列举对象、对象版本与进行中的分块上传，分页状态封装为不透明的 [`Cursor`]（启用 `serde` feature 后可序列化）
+//! - 把前缀下的对象清单流式导出为 NDJSON 或 CSV（[`Uploader::export_listing`]），可选通过 HEAD 补充元数据
+//! - 按元数据查找前缀下的对象（[`Uploader::find_objects`]），并发 HEAD 读取元数据后按条件筛选，例如查找某个用户的全部对象
+//! - 统计对象键前缀下的对象数量、总大小、最大与最早/最晚修改的对象及各存储类型的分布（[`Uploader::prefix_stats`]）
+//! - 启用 `serde` feature 后，[`Config`]（序列化时隐去密钥）、上传结果、对象元数据、列举结果与 [`CosError`] 均可序列化
+//! - Bucket 默认加密配置的查询、设置与删除
+//! - Bucket 自定义域名的查询、设置与删除（[`Uploader::put_bucket_domain`]），把 CNAME 绑定到 Bucket 的流程自动化
+//! - 开启或暂停 Bucket 全球加速，并通过 [`EndpointKind::Accelerate`] 使用加速域名
+//! - 自定义端点与兼容模式（[`CompatibilityProfile::Generic`]），可对接开发环境中路径风格、不返回 CRC64 的 COS 协议兼容网关
+//! - Bucket 名称含有 `.` 时自动改用路径风格的请求，避免虚拟主机风格的域名无法匹配通配证书；
+//!   也可通过 [`AddressingStyle`] 强制指定，签名中的路径始终与请求一致
+//! - 支持内网域名（[`EndpointKind::Internal`]），在腾讯云内网上传时避免外网流量费用，无法连接时自动回退到地域域名
+//! - 探测候选域名的往返时延并切换到最快的一个（[`Uploader::select_fastest_endpoint`]），可在后台定期刷新
+//! - 排查签名问题时可开启 [`Config::debug_signature`]，`SignatureDoesNotMatch` 错误会附上 COS 期望的与本地计算的待签字符串逐行对比
+//! - 影子模式（[`Uploader::with_shadow`]）：每个上传在后台镜像到另一个 Bucket，镜像失败或内容不一致（优先比较 CRC64）时通过事件报告，便于迁移前验证
+//! - 单次上传覆盖 Bucket 与地域（[`UploadOptions::with_bucket`] / [`UploadOptions::with_region`]），同一上传器使用相同密钥写入多个 Bucket
+//! - 备用 Bucket（[`Uploader::with_failover`]）：主 Bucket 重试后仍失败时改为写入另一个 Bucket 或地域，并在结果中记录，地域故障期间不丢数据
+//! - 通过 [`RetryClassifier`] 自定义哪些错误值得重试（例如网关返回 HTML 页面的 502），沿用内置的退避与重试预算
+//! - 按每秒请求数限速（[`Uploader::with_request_rate_limit`]），上传大量小文件时遵守网关的请求配额，所有并发任务共享同一配额
+//! - 分块上传完成后再写入只有上传完才知道的元数据（[`Uploader::finalize_with_metadata`]），内部以替换元数据的自身复制实现
+//! - 占位对象（[`Uploader::create_placeholder`]）：先写入只带元数据的零字节对象登记上传意图，再由 [`Uploader::replace_placeholder`] 确认状态后替换为真正的内容
+//! - 按前缀批量删除对象（[`Uploader::delete_prefix`]），数量超过安全上限时需要 `force` 或给出期望数量确认，防止误删
+//! - 通过 `native-tls`（默认）或 `rustls` feature 选择 TLS 实现，两者必须且只能启用一个
+//! - 预签名 URL 可以同时签入 `Content-Type`、`x-cos-meta-*` 等头部，强制持有者按服务端指定的元数据上传
+//! - 为浏览器一次生成多个文件的直传凭据（[`Presigner::prepare_client_uploads`]，含 URL、必须携带的头部与过期时间），上传后由服务端 HEAD 确认到达
+//! - 为时钟不准的客户端生成起始时间向前回拨的预签名 URL（[`Presigner::presign_url_with_window`]），
+//!   返回结果与直传凭据中附带服务端时间，客户端可据此校正本地时钟的偏差
+//! - 浏览器持续数小时的大文件分块直传（[`Uploader::start_client_multipart_upload`]）：服务端分批签发分块的预签名 URL，
+//!   在即将过期时重新签发并通过回调推送给客户端，最后由服务端合并分块，密钥不离开服务端
+//! - 为 `?restore`、`?acl`、`?tagging` 等子资源生成预签名 URL，把单个运维操作交给脚本执行而无需分发密钥
+//! - 生成预签名 URL；关闭默认的 `runtime` feature、只启用 `presign` 时可编译到 `wasm32-unknown-unknown`，
+//!   通过 [`Presigner`] 在浏览器或边缘函数中签名并上传
+//! - 只启用 `presign` 与一个 TLS 实现时构建最小的客户端：[`Presigner`] 提供单个对象的 PUT / GET / HEAD / DELETE，
+//!   不引入 tokio 运行时、XML 解析、分块上传、目录上传与同步等子系统
 //!
 //! ## 示例
 //!
 //! 以下是一个基本的使用示例，展示了如何上传文件，并附带自定义的元数据。
 //!
-//! ```rust
+//! ```rust,no_run
 //! use anyhow::Result;
 //! use chrono::Utc;
-//! use cos_upload::{Config, Uploader};
+//! use cos_upload::prelude::*;
 //! use std::collections::HashMap;
 //!
 //! #[tokio::main]
@@ -61,7 +162,10 @@
 //!     let object_key = "uploads/user_123/sample_file"; // 按用户组织路径
 //!
 //!     match uploader.upload_file(file_path, object_key, Some(metadata)).await {
-//!         Ok(url) => println!("文件上传成功。URL: {}", url),
+//!         Ok(result) => println!(
+//!             "文件上传成功。URL: {}，请求 ID: {:?}",
+//!             result.url, result.request_id
+//!         ),
 //!         Err(e) => eprintln!("文件上传失败: {}", e),
 //!     }
 //!
@@ -74,17 +178,254 @@
 //! - 使用 `metadata` 字典来存储和传递自定义的元数据信息，这些信息将附加到上传的对象中，便于后续查询。
 //! - 文件路径和对象键（`object_key`）可以根据业务需求自定义，例如按用户 ID 组织的路径结构，以更好地管理上传的资源。
 
+#[cfg(feature = "tar")]
+mod archive;
+#[cfg(feature = "object_store")]
+mod arrow_store;
+#[cfg(feature = "runtime")]
+mod batch;
+#[cfg(feature = "runtime")]
+mod bucket;
+#[cfg(feature = "runtime")]
+mod cache;
+#[cfg(feature = "runtime")]
+mod checkpoint;
+#[cfg(feature = "crypto")]
+mod cipher;
+#[cfg(all(feature = "runtime", feature = "presign"))]
+mod client_multipart;
+#[cfg(feature = "runtime")]
+mod conditional;
 mod config;
+mod datetime;
+#[cfg(feature = "runtime")]
+mod deferred;
+#[cfg(feature = "runtime")]
+mod discovery;
+#[cfg(feature = "runtime")]
+mod download;
+#[cfg(feature = "runtime")]
+mod durable;
+mod error;
+mod events;
+#[cfg(feature = "runtime")]
+mod export;
+#[cfg(feature = "runtime")]
+mod failover;
+#[cfg(feature = "runtime")]
+mod group;
+#[cfg(feature = "runtime")]
+mod handle;
+mod hash;
+#[cfg(any(feature = "runtime", feature = "presign"))]
+mod http;
+#[cfg(feature = "runtime")]
+mod idempotency;
+#[cfg(feature = "runtime")]
+mod inflight;
+#[cfg(feature = "runtime")]
+mod inventory;
+#[cfg(feature = "runtime")]
+mod journal;
+#[cfg(all(feature = "runtime", feature = "serde"))]
+mod json;
+#[cfg(feature = "runtime")]
+mod keymap;
+#[cfg(feature = "runtime")]
+mod keypolicy;
+#[cfg(feature = "runtime")]
+mod lifecycle;
+#[cfg(feature = "runtime")]
+mod list;
+#[cfg(feature = "runtime")]
+mod manifest;
+#[cfg(feature = "runtime")]
+mod mimepolicy;
+#[cfg(feature = "runtime")]
+mod multipart;
+#[cfg(feature = "runtime")]
+mod open_file;
+#[cfg(feature = "runtime")]
+mod options;
+#[cfg(feature = "runtime")]
+mod placeholder;
+#[cfg(feature = "presign")]
+mod presign;
+#[cfg(feature = "runtime")]
+mod probe;
+#[cfg(feature = "runtime")]
+mod progress;
+#[cfg(feature = "runtime")]
+mod purge;
+#[cfg(feature = "runtime")]
+mod queue;
+#[cfg(feature = "runtime")]
+mod ratelimit;
+#[cfg(feature = "runtime")]
+mod request;
+#[cfg(feature = "runtime")]
+mod retry;
+#[cfg(feature = "runtime")]
+mod schedule;
+#[cfg(feature = "runtime")]
+mod scoped;
+#[cfg(feature = "runtime")]
+mod search;
+#[cfg(feature = "tower")]
+mod service;
+#[cfg(feature = "runtime")]
+mod shadow;
+#[cfg(feature = "runtime")]
+mod sidecar;
+#[cfg(any(feature = "runtime", feature = "presign"))]
 mod signature;
+#[cfg(feature = "runtime")]
+mod slowlog;
+#[cfg(feature = "runtime")]
+mod stats;
+#[cfg(feature = "runtime")]
+mod store;
+#[cfg(feature = "sync")]
+mod sync;
+#[cfg(feature = "runtime")]
+mod target;
+#[cfg(feature = "runtime")]
+mod task;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "runtime")]
+mod transfer;
+#[cfg(feature = "runtime")]
+mod tuning;
+mod types;
+#[cfg(feature = "unpack")]
+mod unpack;
+#[cfg(feature = "runtime")]
 mod uploader;
+#[cfg(feature = "runtime")]
+mod verify;
+#[cfg(feature = "notify")]
+mod watcher;
+#[cfg(any(feature = "runtime", feature = "presign"))]
+mod xml;
 
-pub use config::Config;
-pub use uploader::Uploader;
+#[cfg(feature = "tar")]
+pub use archive::ArchiveSelection;
+#[cfg(feature = "object_store")]
+pub use arrow_store::CosObjectStore;
+#[cfg(feature = "runtime")]
+pub use batch::{
+    BatchOptions, BatchReport, DirUploadPolicy, RetryBudget, SymlinkPolicy, SYMLINK_TARGET_METADATA,
+};
+#[cfg(feature = "runtime")]
+pub use bucket::{BucketEncryption, DomainOrigin, DomainReplace, DomainRule, SseAlgorithm};
+#[cfg(feature = "runtime")]
+pub use checkpoint::{CheckpointRetention, FileCheckpointStore, MultipartCheckpoint};
+#[cfg(feature = "crypto")]
+pub use cipher::KeyEncryption;
+#[cfg(all(feature = "runtime", feature = "presign"))]
+pub use client_multipart::{
+    ClientMultipartOptions, ClientMultipartUpload, ClientPartTicket, PartRefreshCallback,
+};
+pub use config::{
+    AddressingStyle, CompatibilityProfile, Config, EndpointKind, HeaderCanonicalization,
+    SignedHeaders, REDACTED,
+};
+pub use datetime::{parse_http_date, parse_iso8601, parse_timestamp};
+#[cfg(feature = "runtime")]
+pub use discovery::discover_bucket_region;
+#[cfg(feature = "runtime")]
+pub use download::{DownloadOptions, PARTIAL_SUFFIX};
+pub use error::{CosError, SignatureMismatch};
+pub use events::TransferEvent;
+#[cfg(feature = "runtime")]
+pub use export::{BundleEntry, BundleManifest, BundleTarget};
+#[cfg(feature = "runtime")]
+pub use group::{GroupResult, GROUP_STAGING_PREFIX};
+#[cfg(feature = "runtime")]
+pub use handle::{TransferHandle, TransferState};
+#[cfg(feature = "crc64fast")]
+pub use hash::Crc64FastHashBackend;
+pub use hash::{default_hash_backend, Crc64Hasher, HashBackend, Md5Hasher, SoftwareHashBackend};
+#[cfg(feature = "runtime")]
+pub use idempotency::{
+    IdempotencyRecord, IdempotencyStore, MemoryIdempotencyStore, IDEMPOTENCY_METADATA,
+};
+#[cfg(feature = "runtime")]
+pub use inventory::ListingFormat;
+#[cfg(feature = "runtime")]
+pub use journal::UploadJournal;
+#[cfg(all(feature = "runtime", feature = "serde"))]
+pub use json::JsonDocument;
+#[cfg(feature = "runtime")]
+pub use keypolicy::{KeyPolicy, NamingPolicy};
+#[cfg(feature = "runtime")]
+pub use list::{
+    Cursor, ListOptions, ListPage, MultipartUploadSummary, ObjectSummary, ObjectVersion,
+};
+#[cfg(feature = "runtime")]
+pub use manifest::{ManifestEntry, UploadManifest};
+#[cfg(feature = "runtime")]
+pub use mimepolicy::ContentTypePolicy;
+#[cfg(feature = "runtime")]
+pub use multipart::MultipartUpload;
+#[cfg(feature = "runtime")]
+pub use options::{StorageClass, UploadOptions, UploadVerification, EXPIRY_TAG_KEY};
+#[cfg(feature = "runtime")]
+pub use placeholder::{PLACEHOLDER_PENDING, PLACEHOLDER_STATE_METADATA};
+#[cfg(feature = "presign")]
+pub use presign::{ClientUploadSpec, ClientUploadTicket, PresignWindow, PresignedUrl, Presigner};
+#[cfg(feature = "runtime")]
+pub use probe::EndpointProbe;
+#[cfg(feature = "runtime")]
+pub use progress::ProgressInterval;
+#[cfg(feature = "runtime")]
+pub use purge::{DeletePrefixOptions, DeletePrefixReport, DEFAULT_DELETE_SAFETY_LIMIT};
+#[cfg(feature = "runtime")]
+pub use queue::{QueueReport, QueuedUpload, TransferSnapshot};
+#[cfg(feature = "runtime")]
+pub use request::CosRequest;
+#[cfg(feature = "runtime")]
+pub use retry::RetryClassifier;
+#[cfg(feature = "runtime")]
+pub use schedule::{TimeWindow, TransferSchedule};
+#[cfg(feature = "runtime")]
+pub use scoped::ScopedUploader;
+#[cfg(feature = "runtime")]
+pub use sidecar::SIDECAR_SUFFIX;
+#[cfg(feature = "runtime")]
+pub use stats::{PrefixStats, StorageClassStats};
+#[cfg(feature = "runtime")]
+pub use store::{MemoryObjectStore, ObjectStore};
+#[cfg(feature = "sync")]
+pub use sync::{SyncReport, MTIME_METADATA};
+#[cfg(feature = "runtime")]
+pub use transfer::{DuplicatePolicy, DuplicateUploadError, TransferManager};
+pub use types::{parse_crc64, DeleteResult, Failover, ObjectMetadata, TransferStats, UploadResult};
+#[cfg(feature = "unpack")]
+pub use unpack::{ArchiveUploadOptions, ArchiveUploadReport};
+#[cfg(feature = "runtime")]
+pub use uploader::{Metadata, Uploader};
+#[cfg(feature = "notify")]
+pub use watcher::{
+    ConflictAction, ConflictPolicy, KeyMapper, OverwritePolicy, RenameWithTimestampPolicy,
+    SkipExistingPolicy, WatchOptions,
+};
 
-#[cfg(test)]
+/// 常用类型的集中导出
+///
+/// ```rust
+/// use cos_upload::prelude::*;
+/// ```
+pub mod prelude {
+    pub use crate::{Config, CosError, EndpointKind, ObjectMetadata, UploadResult};
+    #[cfg(feature = "runtime")]
+    pub use crate::{Metadata, StorageClass, UploadOptions, Uploader};
+}
+
+#[cfg(all(test, feature = "runtime"))]
 mod tests {
     use super::*;
-    use std::env;
 
     #[tokio::test]
     async fn test_upload_and_delete() {
@@ -132,4 +473,53 @@ mod tests {
         // 清理临时文件
         temp_dir.close().expect("Failed to delete temp dir");
     }
+
+    #[tokio::test]
+    async fn test_upload_and_delete_empty_file() {
+        dotenv::dotenv().ok();
+
+        let config = Config::from_env().expect("Failed to load config from env");
+        let uploader = Uploader::new(config);
+
+        let temp_dir = tempfile::tempdir().expect("Failed to create temp dir");
+        let file_path = temp_dir.path().join("empty.txt");
+        std::fs::write(&file_path, "").expect("Failed to write test file");
+        let object_key = "test/empty.txt";
+
+        // 普通上传与断点续传两条路径都应能上传空文件
+        uploader
+            .upload_file(&file_path, object_key, None)
+            .await
+            .expect("Upload empty file failed");
+        uploader
+            .upload_file_resumable(
+                &file_path,
+                object_key,
+                &UploadOptions::default(),
+                None,
+                &|_| {},
+                None,
+            )
+            .await
+            .expect("Resumable upload of empty file failed");
+
+        let metadata = uploader
+            .get_object_metadata(object_key)
+            .await
+            .expect("Get metadata failed");
+        assert_eq!(metadata.content_length, Some(0));
+
+        let download_path = temp_dir.path().join("download/empty.txt");
+        uploader
+            .download_object(object_key, &download_path)
+            .await
+            .expect("Download empty object failed");
+        assert_eq!(std::fs::metadata(&download_path).unwrap().len(), 0);
+
+        uploader
+            .delete_object(object_key)
+            .await
+            .expect("Delete failed");
+        temp_dir.close().expect("Failed to delete temp dir");
+    }
 }
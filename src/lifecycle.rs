@@ -0,0 +1,122 @@
+use crate::bucket::content_md5;
+use crate::error::CosError;
+use crate::options::EXPIRY_TAG_KEY;
+use crate::request::{read_xml, CosRequest};
+use crate::types::request_id_of;
+use crate::uploader::Uploader;
+use crate::xml::{find_all_tags, find_tag};
+use anyhow::Result;
+use reqwest::Method;
+use tracing::{debug, info};
+
+/// 按保留天数删除对象的生命周期规则 ID
+fn expiry_rule_id(days: u64) -> String {
+    format!("cos-upload-expiry-{}d", days)
+}
+
+/// 删除带有 `{EXPIRY_TAG_KEY}={days}` 标签的对象的生命周期规则
+fn expiry_rule_xml(days: u64) -> String {
+    format!(
+        "<Rule><ID>{}</ID><Filter><Tag><Key>{}</Key><Value>{}</Value></Tag></Filter>\
+         <Status>Enabled</Status><Expiration><Days>{}</Days></Expiration></Rule>",
+        expiry_rule_id(days),
+        EXPIRY_TAG_KEY,
+        days,
+        days
+    )
+}
+
+/// 在现有生命周期配置中追加规则，已存在同 ID 的规则时返回 `None`
+fn with_expiry_rule(existing: &str, days: u64) -> Option<String> {
+    let rules = find_all_tags(existing, "Rule");
+    let id = expiry_rule_id(days);
+    if rules
+        .iter()
+        .any(|rule| find_tag(rule, "ID") == Some(id.as_str()))
+    {
+        return None;
+    }
+
+    // 设置生命周期会覆盖整个配置，需要保留已有的规则
+    let mut body = String::from("<LifecycleConfiguration>");
+    for rule in rules {
+        body.push_str("<Rule>");
+        body.push_str(rule);
+        body.push_str("</Rule>");
+    }
+    body.push_str(&expiry_rule_xml(days));
+    body.push_str("</LifecycleConfiguration>");
+    Some(body)
+}
+
+impl Uploader {
+    /// 确保 Bucket 中存在删除过期对象的生命周期规则
+    ///
+    /// 规则删除带有 [`EXPIRY_TAG_KEY`](crate::EXPIRY_TAG_KEY) 标签且值为 `days` 的对象，
+    /// 与 [`UploadOptions::expires_in`](crate::UploadOptions::expires_in) 配合使用。
+    /// 已有的其它规则会被保留；同一上传器（及其克隆）对同一天数只会检查一次。
+    ///
+    /// # 参数
+    ///
+    /// * `days` - 对象上传后保留的天数
+    ///
+    /// # 错误
+    ///
+    /// 没有读写 Bucket 生命周期配置的权限或请求失败时返回错误。
+    pub async fn ensure_expiry_rule(&self, days: u64) -> Result<()> {
+        if self.expiry_rules.lock().unwrap().contains(&days) {
+            return Ok(());
+        }
+
+        let request = CosRequest::bucket(Method::GET).param("lifecycle", "");
+        let existing = match self.execute(request).await {
+            Ok(response) => read_xml(response).await?,
+            Err(e)
+                if e.downcast_ref::<CosError>().and_then(CosError::code)
+                    == Some("NoSuchLifecycleConfiguration") =>
+            {
+                String::new()
+            }
+            Err(e) => return Err(e),
+        };
+
+        match with_expiry_rule(&existing, days) {
+            Some(body) => {
+                let request = CosRequest::bucket(Method::PUT)
+                    .param("lifecycle", "")
+                    .header("Content-Type", "application/xml")
+                    .header("Content-MD5", content_md5(body.as_bytes()))
+                    .body(body);
+                let response = self.execute(request).await?;
+                info!(
+                    "已添加过期生命周期规则: {} (request_id: {:?})",
+                    expiry_rule_id(days),
+                    request_id_of(response.headers())
+                );
+            }
+            None => debug!("过期生命周期规则已存在: {}", expiry_rule_id(days)),
+        }
+
+        self.expiry_rules.lock().unwrap().insert(days);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_expiry_rule() {
+        let existing = "<LifecycleConfiguration><Rule><ID>logs</ID><Filter><Prefix>logs/</Prefix>\
+                        </Filter><Status>Enabled</Status><Expiration><Days>30</Days></Expiration>\
+                        </Rule></LifecycleConfiguration>";
+        let body = with_expiry_rule(existing, 7).unwrap();
+        assert!(body.contains("<ID>logs</ID>"));
+        assert!(body.contains("<ID>cos-upload-expiry-7d</ID>"));
+        assert_eq!(find_all_tags(&body, "Rule").len(), 2);
+
+        assert!(with_expiry_rule(&body, 7).is_none());
+        assert!(with_expiry_rule("", 1).is_some());
+    }
+}
@@ -0,0 +1,582 @@
+use crate::datetime::parse_iso8601;
+use crate::request::{read_xml_limited, CosRequest};
+use crate::uploader::Uploader;
+use crate::xml::{find_all_tags, find_tag, unescape};
+use anyhow::{anyhow, Result};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use reqwest::Method;
+use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "crypto")]
+use tracing::debug;
+
+/// 单页最多返回的条目数
+const DEFAULT_MAX_KEYS: u32 = 1000;
+/// 列举响应体的大小上限
+///
+/// COS 单页最多返回 1000 条，对象键最长 850 字节。即使对象键中的每个字符都转义为 `&amp;`，
+/// 加上每条约 500 字节的其它字段，单页也不超过 5 MB。页面大小有上界，整页读入后再由
+/// [`parse_objects`] 等函数解析的内存占用同样有上界，因此不需要增量解析；
+/// 超过该上限的响应只可能来自异常的端点或代理，直接报错。
+pub(crate) const MAX_LIST_XML_BODY: usize = 8 * 1024 * 1024;
+
+/// 不透明的分页游标
+///
+/// 由列举接口返回，原样传回同一个接口即可获取下一页。游标对外表现为 URL 安全的字符串，
+/// 可以直接交给 Web 客户端往返（启用 `serde` feature 后序列化为该字符串）；
+/// 把一个接口的游标传给另一个接口会返回错误。
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "String", into = "String")
+)]
+pub struct Cursor(String);
+
+/// 游标内部记录的分页状态
+#[derive(Debug, PartialEq, Eq)]
+enum CursorState {
+    /// `list_objects` 的 `marker`
+    Objects { marker: String },
+    /// `list_object_versions` 的 `key-marker` 与 `version-id-marker`
+    Versions {
+        key_marker: String,
+        version_id_marker: String,
+    },
+    /// `list_multipart_uploads` 的 `key-marker` 与 `upload-id-marker`
+    Uploads {
+        key_marker: String,
+        upload_id_marker: String,
+    },
+}
+
+impl Cursor {
+    fn encode(state: CursorState) -> Self {
+        let fields = match state {
+            CursorState::Objects { marker } => vec!["o".to_string(), marker],
+            CursorState::Versions {
+                key_marker,
+                version_id_marker,
+            } => vec!["v".to_string(), key_marker, version_id_marker],
+            CursorState::Uploads {
+                key_marker,
+                upload_id_marker,
+            } => vec!["u".to_string(), key_marker, upload_id_marker],
+        };
+        let json = serde_json::to_vec(&fields).expect("游标序列化失败");
+        Cursor(URL_SAFE_NO_PAD.encode(json))
+    }
+
+    fn decode(&self) -> Result<CursorState> {
+        let invalid = || anyhow!("无效的分页游标: {}", self.0);
+        let json = URL_SAFE_NO_PAD.decode(&self.0).map_err(|_| invalid())?;
+        let fields: Vec<String> = serde_json::from_slice(&json).map_err(|_| invalid())?;
+
+        match fields.as_slice() {
+            [kind, marker] if kind == "o" => Ok(CursorState::Objects {
+                marker: marker.clone(),
+            }),
+            [kind, key, version] if kind == "v" => Ok(CursorState::Versions {
+                key_marker: key.clone(),
+                version_id_marker: version.clone(),
+            }),
+            [kind, key, upload] if kind == "u" => Ok(CursorState::Uploads {
+                key_marker: key.clone(),
+                upload_id_marker: upload.clone(),
+            }),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// 游标的字符串形式
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Cursor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromStr for Cursor {
+    type Err = anyhow::Error;
+
+    /// 解析客户端传回的游标，格式不正确时返回错误
+    fn from_str(s: &str) -> Result<Self> {
+        let cursor = Cursor(s.to_string());
+        cursor.decode()?;
+        Ok(cursor)
+    }
+}
+
+impl TryFrom<String> for Cursor {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Cursor> for String {
+    fn from(cursor: Cursor) -> Self {
+        cursor.0
+    }
+}
+
+/// 列举选项
+#[derive(Debug, Clone, Default)]
+pub struct ListOptions {
+    /// 只列出以该前缀开头的对象键
+    pub prefix: String,
+    /// 分组字符，通常为 `/`；设置后前缀之下更深层的对象键会合并到 [`ListPage::common_prefixes`]
+    pub delimiter: Option<String>,
+    /// 单页最多返回的条目数，默认 1000
+    pub max_keys: Option<u32>,
+}
+
+impl ListOptions {
+    /// 创建列举指定前缀的选项
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// 设置分组字符
+    pub fn with_delimiter(mut self, delimiter: &str) -> Self {
+        self.delimiter = Some(delimiter.to_string());
+        self
+    }
+
+    /// 设置单页最多返回的条目数，COS 对超过 1000 的值按 1000 处理
+    pub fn with_max_keys(mut self, max_keys: u32) -> Self {
+        self.max_keys = Some(max_keys);
+        self
+    }
+
+    /// 生成带有分组字符与数量限制的请求，`stored_prefix` 为 COS 中的完整前缀
+    fn request(&self, page_size_param: &str, stored_prefix: String) -> CosRequest {
+        let mut request = CosRequest::bucket(Method::GET)
+            .param("prefix", stored_prefix)
+            .param(
+                page_size_param,
+                self.max_keys.unwrap_or(DEFAULT_MAX_KEYS).to_string(),
+            );
+        if let Some(delimiter) = &self.delimiter {
+            request = request.param("delimiter", delimiter.clone());
+        }
+        request
+    }
+}
+
+/// 一页列举结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListPage<T> {
+    /// 本页的条目
+    pub items: Vec<T>,
+    /// 设置了分组字符时，被合并的公共前缀
+    pub common_prefixes: Vec<String>,
+    /// 下一页的游标，已经是最后一页时为 `None`
+    pub next: Option<Cursor>,
+}
+
+/// 对象摘要
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectSummary {
+    /// 对象键
+    pub key: String,
+    /// 对象大小（字节）
+    pub size: u64,
+    /// 对象的 ETag
+    pub etag: Option<String>,
+    /// 最后修改时间
+    pub last_modified: Option<DateTime<Utc>>,
+    /// 存储类型，例如 `STANDARD`
+    pub storage_class: Option<String>,
+}
+
+/// 对象版本
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ObjectVersion {
+    /// 对象键
+    pub key: String,
+    /// 版本 ID
+    pub version_id: String,
+    /// 是否为最新版本
+    pub is_latest: bool,
+    /// 是否为删除标记
+    pub is_delete_marker: bool,
+    /// 对象大小（字节），删除标记为 0
+    pub size: u64,
+    /// 对象的 ETag，删除标记为 `None`
+    pub etag: Option<String>,
+    /// 最后修改时间
+    pub last_modified: Option<DateTime<Utc>>,
+}
+
+/// 进行中的分块上传
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MultipartUploadSummary {
+    /// 对象键
+    pub key: String,
+    /// 分块上传 ID
+    pub upload_id: String,
+    /// 初始化时间
+    pub initiated: Option<DateTime<Utc>>,
+}
+
+impl Uploader {
+    /// 列举对象
+    ///
+    /// # 参数
+    ///
+    /// * `opts` - 列举选项
+    /// * `cursor` - 上一页返回的游标，列举第一页时为 `None`
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回一页对象摘要与下一页的游标
+    pub async fn list_objects(
+        &self,
+        opts: &ListOptions,
+        cursor: Option<&Cursor>,
+    ) -> Result<ListPage<ObjectSummary>> {
+        let mut request = opts.request("max-keys", self.config.stored_prefix(&opts.prefix));
+        if let Some(cursor) = cursor {
+            match cursor.decode()? {
+                CursorState::Objects { marker } => request = request.param("marker", marker),
+                _ => return Err(anyhow!("游标不属于 list_objects: {}", cursor)),
+            }
+        }
+
+        let text = read_xml_limited(self.execute(request).await?, MAX_LIST_XML_BODY).await?;
+        let page = parse_objects(&text, self.config.key_prefix());
+        Ok(self.decrypt_page(page, &opts.prefix, |object| &mut object.key))
+    }
+
+    /// 列举对象的所有版本（包括删除标记），需要 Bucket 开启版本控制
+    ///
+    /// # 参数
+    ///
+    /// * `opts` - 列举选项
+    /// * `cursor` - 上一页返回的游标，列举第一页时为 `None`
+    pub async fn list_object_versions(
+        &self,
+        opts: &ListOptions,
+        cursor: Option<&Cursor>,
+    ) -> Result<ListPage<ObjectVersion>> {
+        let mut request = opts
+            .request("max-keys", self.config.stored_prefix(&opts.prefix))
+            .param("versions", "");
+        if let Some(cursor) = cursor {
+            match cursor.decode()? {
+                CursorState::Versions {
+                    key_marker,
+                    version_id_marker,
+                } => {
+                    request = request
+                        .param("key-marker", key_marker)
+                        .param("version-id-marker", version_id_marker)
+                }
+                _ => return Err(anyhow!("游标不属于 list_object_versions: {}", cursor)),
+            }
+        }
+
+        let text = read_xml_limited(self.execute(request).await?, MAX_LIST_XML_BODY).await?;
+        let page = parse_versions(&text, self.config.key_prefix());
+        Ok(self.decrypt_page(page, &opts.prefix, |version| &mut version.key))
+    }
+
+    /// 列举进行中（尚未完成或中止）的分块上传
+    ///
+    /// # 参数
+    ///
+    /// * `opts` - 列举选项
+    /// * `cursor` - 上一页返回的游标，列举第一页时为 `None`
+    pub async fn list_multipart_uploads(
+        &self,
+        opts: &ListOptions,
+        cursor: Option<&Cursor>,
+    ) -> Result<ListPage<MultipartUploadSummary>> {
+        let mut request = opts
+            .request("max-uploads", self.config.stored_prefix(&opts.prefix))
+            .param("uploads", "");
+        if let Some(cursor) = cursor {
+            match cursor.decode()? {
+                CursorState::Uploads {
+                    key_marker,
+                    upload_id_marker,
+                } => {
+                    request = request
+                        .param("key-marker", key_marker)
+                        .param("upload-id-marker", upload_id_marker)
+                }
+                _ => return Err(anyhow!("游标不属于 list_multipart_uploads: {}", cursor)),
+            }
+        }
+
+        let text = read_xml_limited(self.execute(request).await?, MAX_LIST_XML_BODY).await?;
+        let page = parse_uploads(&text, self.config.key_prefix());
+        Ok(self.decrypt_page(page, &opts.prefix, |upload| &mut upload.key))
+    }
+
+    /// 设置了对象键加密时解密本页的对象键与公共前缀，去掉无法解密（不是由同一密钥写入）
+    /// 或不在前缀之下的条目；游标在解密前已经生成，仍指向 COS 中的对象键
+    #[cfg(feature = "crypto")]
+    fn decrypt_page<T>(
+        &self,
+        mut page: ListPage<T>,
+        prefix: &str,
+        key_of: fn(&mut T) -> &mut String,
+    ) -> ListPage<T> {
+        let Some(encryption) = &self.config.key_encryption else {
+            return page;
+        };
+        page.items.retain_mut(|item| {
+            let key = key_of(item);
+            match encryption.decrypt_key(key) {
+                Some(plain) if plain.starts_with(prefix) => {
+                    *key = plain;
+                    true
+                }
+                Some(_) => false,
+                None => {
+                    debug!("跳过无法解密的对象键: {}", key);
+                    false
+                }
+            }
+        });
+        page.common_prefixes = page
+            .common_prefixes
+            .iter()
+            .filter_map(|common_prefix| encryption.decrypt_key(common_prefix))
+            .filter(|common_prefix| common_prefix.starts_with(prefix))
+            .collect();
+        page
+    }
+
+    /// 未启用 `crypto` feature 时对象键不会加密，原样返回
+    #[cfg(not(feature = "crypto"))]
+    fn decrypt_page<T>(
+        &self,
+        page: ListPage<T>,
+        _prefix: &str,
+        _key_of: fn(&mut T) -> &mut String,
+    ) -> ListPage<T> {
+        page
+    }
+}
+
+fn tag_text(block: &str, tag: &str) -> Option<String> {
+    find_tag(block, tag).map(unescape)
+}
+
+fn is_truncated(text: &str) -> bool {
+    find_tag(text, "IsTruncated") == Some("true")
+}
+
+fn common_prefixes(text: &str, key_prefix: &str) -> Vec<String> {
+    find_all_tags(text, "CommonPrefixes")
+        .into_iter()
+        .filter_map(|block| tag_text(block, "Prefix"))
+        .map(|prefix| strip_key_prefix(prefix, key_prefix))
+        .collect()
+}
+
+/// 去掉 COS 返回的对象键中配置的对象键前缀
+fn strip_key_prefix(key: String, key_prefix: &str) -> String {
+    match key.strip_prefix(key_prefix) {
+        Some(stripped) if !key_prefix.is_empty() => stripped.to_string(),
+        _ => key,
+    }
+}
+
+fn parse_objects(text: &str, key_prefix: &str) -> ListPage<ObjectSummary> {
+    let items: Vec<_> = find_all_tags(text, "Contents")
+        .into_iter()
+        .map(|block| ObjectSummary {
+            key: strip_key_prefix(tag_text(block, "Key").unwrap_or_default(), key_prefix),
+            size: find_tag(block, "Size")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            etag: tag_text(block, "ETag"),
+            last_modified: find_tag(block, "LastModified").and_then(parse_iso8601),
+            storage_class: tag_text(block, "StorageClass"),
+        })
+        .collect();
+    let prefixes = common_prefixes(text, key_prefix);
+
+    // 没有返回 NextMarker 时，以本页最后一个对象键或公共前缀作为下一页的起点，
+    // 起点使用 COS 中完整的对象键
+    let next = is_truncated(text).then(|| {
+        let marker = tag_text(text, "NextMarker")
+            .or_else(|| {
+                let last_key = items.last().map(|item| item.key.clone());
+                let last_prefix = prefixes.last().cloned();
+                last_key
+                    .max(last_prefix)
+                    .map(|last| format!("{}{}", key_prefix, last))
+            })
+            .unwrap_or_default();
+        Cursor::encode(CursorState::Objects { marker })
+    });
+
+    ListPage {
+        items,
+        common_prefixes: prefixes,
+        next,
+    }
+}
+
+fn parse_versions(text: &str, key_prefix: &str) -> ListPage<ObjectVersion> {
+    let versions = find_all_tags(text, "Version")
+        .into_iter()
+        .map(|block| (block, false));
+    let markers = find_all_tags(text, "DeleteMarker")
+        .into_iter()
+        .map(|block| (block, true));
+
+    // 版本与删除标记在响应中交错出现，按其在文档中的位置还原顺序
+    let mut blocks: Vec<_> = versions.chain(markers).collect();
+    blocks.sort_by_key(|(block, _)| block.as_ptr() as usize);
+
+    let items = blocks
+        .into_iter()
+        .map(|(block, is_delete_marker)| ObjectVersion {
+            key: strip_key_prefix(tag_text(block, "Key").unwrap_or_default(), key_prefix),
+            version_id: tag_text(block, "VersionId").unwrap_or_default(),
+            is_latest: find_tag(block, "IsLatest") == Some("true"),
+            is_delete_marker,
+            size: find_tag(block, "Size")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(0),
+            etag: tag_text(block, "ETag"),
+            last_modified: find_tag(block, "LastModified").and_then(parse_iso8601),
+        })
+        .collect();
+
+    let next = is_truncated(text).then(|| {
+        Cursor::encode(CursorState::Versions {
+            key_marker: tag_text(text, "NextKeyMarker").unwrap_or_default(),
+            version_id_marker: tag_text(text, "NextVersionIdMarker").unwrap_or_default(),
+        })
+    });
+
+    ListPage {
+        items,
+        common_prefixes: common_prefixes(text, key_prefix),
+        next,
+    }
+}
+
+fn parse_uploads(text: &str, key_prefix: &str) -> ListPage<MultipartUploadSummary> {
+    let items = find_all_tags(text, "Upload")
+        .into_iter()
+        .map(|block| MultipartUploadSummary {
+            key: strip_key_prefix(tag_text(block, "Key").unwrap_or_default(), key_prefix),
+            upload_id: tag_text(block, "UploadId").unwrap_or_default(),
+            initiated: find_tag(block, "Initiated").and_then(parse_iso8601),
+        })
+        .collect();
+
+    let next = is_truncated(text).then(|| {
+        Cursor::encode(CursorState::Uploads {
+            key_marker: tag_text(text, "NextKeyMarker").unwrap_or_default(),
+            upload_id_marker: tag_text(text, "NextUploadIdMarker").unwrap_or_default(),
+        })
+    });
+
+    ListPage {
+        items,
+        common_prefixes: common_prefixes(text, key_prefix),
+        next,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor::encode(CursorState::Versions {
+            key_marker: "a/b&c".to_string(),
+            version_id_marker: "v1".to_string(),
+        });
+        let parsed: Cursor = cursor.to_string().parse().unwrap();
+        assert_eq!(parsed, cursor);
+        assert!(matches!(parsed.decode(), Ok(CursorState::Versions { .. })));
+        assert!("not-a-cursor".parse::<Cursor>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_cursor_serde() {
+        let cursor = Cursor::encode(CursorState::Objects {
+            marker: "a".to_string(),
+        });
+        let json = serde_json::to_string(&cursor).unwrap();
+        assert_eq!(json, format!("\"{}\"", cursor));
+        assert_eq!(serde_json::from_str::<Cursor>(&json).unwrap(), cursor);
+        assert!(serde_json::from_str::<Cursor>("\"bogus\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_objects() {
+        let text = "<ListBucketResult><Prefix>a/</Prefix><IsTruncated>true</IsTruncated>\
+            <Contents><Key>a/1&amp;2.txt</Key><Size>3</Size><ETag>&quot;e1&quot;</ETag></Contents>\
+            <Contents><Key>a/2.txt</Key><Size>5</Size>\
+            <LastModified>2015-10-21T07:28:00.000Z</LastModified></Contents>\
+            <CommonPrefixes><Prefix>a/sub/</Prefix></CommonPrefixes></ListBucketResult>";
+        let page = parse_objects(text, "");
+
+        assert_eq!(page.items.len(), 2);
+        assert_eq!(page.items[0].key, "a/1&2.txt");
+        assert_eq!(page.items[0].etag.as_deref(), Some("\"e1\""));
+        assert_eq!(page.items[0].last_modified, None);
+        assert_eq!(
+            page.items[1].last_modified,
+            DateTime::from_timestamp(1445412480, 0)
+        );
+        assert_eq!(page.common_prefixes, vec!["a/sub/".to_string()]);
+        assert_eq!(
+            page.next.unwrap().decode().unwrap(),
+            CursorState::Objects {
+                marker: "a/sub/".to_string()
+            }
+        );
+
+        // 配置了对象键前缀时返回逻辑上的对象键，游标仍使用完整的对象键
+        let page = parse_objects(text, "a/");
+        assert_eq!(page.items[1].key, "2.txt");
+        assert_eq!(page.common_prefixes, vec!["sub/".to_string()]);
+        assert_eq!(
+            page.next.unwrap().decode().unwrap(),
+            CursorState::Objects {
+                marker: "a/sub/".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_versions_keeps_order() {
+        let text = "<ListVersionsResult><IsTruncated>false</IsTruncated>\
+            <DeleteMarker><Key>k</Key><VersionId>3</VersionId><IsLatest>true</IsLatest></DeleteMarker>\
+            <Version><Key>k</Key><VersionId>2</VersionId><IsLatest>false</IsLatest><Size>1</Size></Version>\
+            </ListVersionsResult>";
+        let page = parse_versions(text, "");
+
+        assert!(page.next.is_none());
+        assert!(page.items[0].is_delete_marker && page.items[0].is_latest);
+        assert_eq!(page.items[1].version_id, "2");
+    }
+}
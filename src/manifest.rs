@@ -0,0 +1,112 @@
+use anyhow::{anyhow, Result};
+use std::collections::BTreeMap;
+
+/// 上传清单中单个对象的记录
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManifestEntry {
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 文件内容的 CRC64（CRC-64/ECMA-182）
+    pub crc64: u64,
+}
+
+/// 目录上传的清单，记录每个对象键对应文件的大小与 CRC64
+///
+/// 把上一次 [`Uploader::upload_dir`](crate::Uploader::upload_dir) 返回的清单通过
+/// [`BatchOptions::previous_manifest`](crate::BatchOptions::previous_manifest) 传入，
+/// 只有大小或 CRC64 变化的文件会被重新上传，无需列举远端的对象。
+/// 清单可以用 [`UploadManifest::to_text`] 保存为文本，启用 `serde` feature 后也可以直接序列化。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UploadManifest {
+    /// 对象键到文件记录的映射
+    pub entries: BTreeMap<String, ManifestEntry>,
+}
+
+impl UploadManifest {
+    /// 创建空的清单，作为第一次上传的“上一次清单”时会上传所有文件
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 对象键对应的记录
+    pub fn get(&self, object_key: &str) -> Option<&ManifestEntry> {
+        self.entries.get(object_key)
+    }
+
+    /// 文件是否与清单中的记录一致
+    pub(crate) fn is_unchanged(&self, object_key: &str, entry: &ManifestEntry) -> bool {
+        self.get(object_key) == Some(entry)
+    }
+
+    /// 转换为文本，每行为 `{crc64}\t{size}\t{object_key}`，按对象键排序
+    pub fn to_text(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(key, entry)| format!("{}\t{}\t{}\n", entry.crc64, entry.size, key))
+            .collect()
+    }
+
+    /// 解析 [`UploadManifest::to_text`] 生成的文本，忽略空行
+    ///
+    /// # 错误
+    ///
+    /// 某一行的格式不正确时返回包含行号的错误。
+    pub fn from_text(text: &str) -> Result<Self> {
+        let mut entries = BTreeMap::new();
+        for (index, line) in text.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || anyhow!("上传清单第 {} 行格式不正确: {}", index + 1, line);
+            let mut fields = line.splitn(3, '\t');
+            let crc64 = fields
+                .next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(invalid)?;
+            let size = fields
+                .next()
+                .and_then(|v| v.parse().ok())
+                .ok_or_else(invalid)?;
+            let key = fields
+                .next()
+                .filter(|key| !key.is_empty())
+                .ok_or_else(invalid)?;
+            entries.insert(key.to_string(), ManifestEntry { size, crc64 });
+        }
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_text_round_trip() {
+        let mut manifest = UploadManifest::new();
+        manifest.entries.insert(
+            "site/a b.html".to_string(),
+            ManifestEntry {
+                size: 12,
+                crc64: u64::MAX,
+            },
+        );
+        manifest.entries.insert(
+            "site/b.css".to_string(),
+            ManifestEntry { size: 0, crc64: 0 },
+        );
+
+        let text = manifest.to_text();
+        assert_eq!(
+            text,
+            "18446744073709551615\t12\tsite/a b.html\n0\t0\tsite/b.css\n"
+        );
+        assert_eq!(UploadManifest::from_text(&text).unwrap(), manifest);
+        assert!(manifest.is_unchanged("site/b.css", &ManifestEntry { size: 0, crc64: 0 }));
+        assert!(!manifest.is_unchanged("site/b.css", &ManifestEntry { size: 1, crc64: 0 }));
+
+        assert!(UploadManifest::from_text("1\t2\n").is_err());
+    }
+}
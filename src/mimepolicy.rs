@@ -0,0 +1,142 @@
+use crate::error::CosError;
+use crate::request::CosRequest;
+use crate::uploader::Uploader;
+use std::sync::Arc;
+use tracing::warn;
+
+/// 上传内容类型的允许/禁止策略，参见 [`Uploader::with_content_type_policy`]
+///
+/// 规则为完整的类型（如 `text/html`）或以 `/*` 结尾的大类（如 `image/*`），不区分大小写，
+/// 比较时忽略 `; charset=utf-8` 等参数。禁止列表优先；允许列表非空时，只有匹配其中某条规则的类型才能上传。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ContentTypePolicy {
+    /// 允许上传的类型，为空时不限制
+    pub allowed: Vec<String>,
+    /// 禁止上传的类型
+    pub denied: Vec<String>,
+}
+
+/// 内容类型是否匹配规则
+fn type_matches(rule: &str, content_type: &str) -> bool {
+    let rule = rule.trim().to_ascii_lowercase();
+    match rule.strip_suffix("/*") {
+        Some(major) => content_type.split('/').next().is_some_and(|m| m == major),
+        None => rule == content_type,
+    }
+}
+
+impl ContentTypePolicy {
+    /// 创建不做任何限制的策略
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加允许上传的类型
+    pub fn with_allowed(mut self, content_type: String) -> Self {
+        self.allowed.push(content_type);
+        self
+    }
+
+    /// 添加禁止上传的类型
+    pub fn with_denied(mut self, content_type: String) -> Self {
+        self.denied.push(content_type);
+        self
+    }
+
+    /// 判断内容类型是否允许上传
+    pub fn permits(&self, content_type: &str) -> bool {
+        let essence = content_type
+            .split(';')
+            .next()
+            .unwrap_or("")
+            .trim()
+            .to_ascii_lowercase();
+        if self.denied.iter().any(|rule| type_matches(rule, &essence)) {
+            return false;
+        }
+        self.allowed.is_empty() || self.allowed.iter().any(|rule| type_matches(rule, &essence))
+    }
+}
+
+/// 上传对象内容的请求（普通上传、初始化分块上传与追加上传）最终的内容类型
+///
+/// 没有 `Content-Type` 头部时按对象键的扩展名推断，与 COS 的行为一致。其它请求返回 `None`。
+fn upload_content_type(request: &CosRequest) -> Option<String> {
+    if !request.creates_object() || request.headers.contains_key("x-cos-copy-source") {
+        return None;
+    }
+    let declared = request
+        .headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+        .map(|(_, value)| value.clone());
+    Some(declared.unwrap_or_else(|| {
+        mime_guess::from_path(&request.object_key)
+            .first_or_octet_stream()
+            .to_string()
+    }))
+}
+
+impl Uploader {
+    /// 设置上传内容类型的允许/禁止策略
+    ///
+    /// 每个普通上传与初始化分块上传的请求在发出前按 `Content-Type`（没有时按扩展名推断）检查，
+    /// 不允许的类型返回 [`CosError::ContentTypeDenied`]，不会发送任何数据。例如面向用户上传的公有读 Bucket
+    /// 可以禁止 `text/html` 与 `image/svg+xml`，避免存储型 XSS。服务端复制与预签名 URL 不受该策略约束。
+    pub fn with_content_type_policy(mut self, policy: ContentTypePolicy) -> Self {
+        self.content_type_policy = Some(Arc::new(policy));
+        self
+    }
+
+    /// 按内容类型策略检查请求
+    pub(crate) fn check_content_type(&self, request: &CosRequest) -> Result<(), CosError> {
+        let Some(policy) = &self.content_type_policy else {
+            return Ok(());
+        };
+        match upload_content_type(request) {
+            Some(content_type) if !policy.permits(&content_type) => {
+                warn!(
+                    "内容类型不允许上传: {} ({})",
+                    request.object_key, content_type
+                );
+                Err(CosError::ContentTypeDenied {
+                    object_key: request.object_key.clone(),
+                    content_type,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::Method;
+
+    #[test]
+    fn test_content_type_policy() {
+        let policy = ContentTypePolicy::new()
+            .with_denied("text/html".to_string())
+            .with_denied("image/svg+xml".to_string());
+        assert!(!policy.permits("text/html; charset=utf-8"));
+        assert!(!policy.permits("Image/SVG+XML"));
+        assert!(policy.permits("image/png"));
+
+        let images = ContentTypePolicy::new()
+            .with_allowed("image/*".to_string())
+            .with_denied("image/svg+xml".to_string());
+        assert!(images.permits("image/jpeg"));
+        assert!(!images.permits("image/svg+xml"));
+        assert!(!images.permits("application/pdf"));
+
+        let put = CosRequest::new(Method::PUT, "a/index.html");
+        assert_eq!(upload_content_type(&put).as_deref(), Some("text/html"));
+        let put = put.header("Content-Type", "image/png");
+        assert_eq!(upload_content_type(&put).as_deref(), Some("image/png"));
+        let part = CosRequest::new(Method::PUT, "a/index.html").param("partNumber", "1");
+        assert_eq!(upload_content_type(&part), None);
+        let init = CosRequest::new(Method::POST, "a.svg").param("uploads", "");
+        assert_eq!(upload_content_type(&init).as_deref(), Some("image/svg+xml"));
+    }
+}
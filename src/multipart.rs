@@ -0,0 +1,221 @@
+use crate::events::TransferEvent;
+use crate::hash::Crc64Hasher;
+use crate::options::UploadOptions;
+use crate::task::next_transfer_id;
+use crate::types::{TransferStats, UploadResult};
+use crate::uploader::{Uploader, MAX_PARTS, MIN_PART_SIZE};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use std::fmt;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+/// 由调用方逐块写入数据的分块上传
+///
+/// 通过 [`Uploader::start_multipart_upload`] 创建。适用于 Web 框架接收浏览器分片上传的场景：
+/// 每收到一个分片就调用 [`MultipartUpload::write_part`] 上传，全部到达后调用
+/// [`MultipartUpload::complete`]，无需先把整个文件落盘。
+///
+/// 分块按写入顺序编号，失败时按上传器的重试策略重试；完成时按写入的数据校验对象的 CRC64。
+/// 除最后一个分块外，每个分块不能小于 1 MB。放弃上传时应调用 [`MultipartUpload::abort`]
+/// 释放已上传的分块，否则分块会一直占用存储空间。
+pub struct MultipartUpload {
+    uploader: Uploader,
+    object_key: String,
+    upload_id: String,
+    forbid_overwrite: bool,
+    transfer_id: u64,
+    parts: Vec<(u32, String)>,
+    /// 最后一个分块的大小，用于检查非末尾分块的最小大小
+    last_part_size: Option<u64>,
+    crc64: Box<dyn Crc64Hasher>,
+    bytes: u64,
+    latencies: Vec<Duration>,
+    retries: u32,
+    started: Instant,
+}
+
+impl fmt::Debug for MultipartUpload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartUpload")
+            .field("object_key", &self.object_key)
+            .field("upload_id", &self.upload_id)
+            .field("parts", &self.parts.len())
+            .field("bytes", &self.bytes)
+            .finish()
+    }
+}
+
+impl MultipartUpload {
+    /// 对象键
+    pub fn object_key(&self) -> &str {
+        &self.object_key
+    }
+
+    /// 分块上传 ID，可用于在进程外终止或查询这次上传
+    pub fn upload_id(&self) -> &str {
+        &self.upload_id
+    }
+
+    /// 已上传的分块数
+    pub fn parts_written(&self) -> u32 {
+        self.parts.len() as u32
+    }
+
+    /// 已上传的字节数
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes
+    }
+
+    /// 上传下一个分块
+    ///
+    /// # 参数
+    ///
+    /// * `data` - 分块的数据，除最后一个分块外不能小于 1 MB
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回该分块的编号（从 1 开始）
+    ///
+    /// # 错误
+    ///
+    /// 上一个分块小于 1 MB（即已经写入过末尾分块）、分块数超过 10000
+    /// 或重试后仍上传失败时返回错误，此时可以继续调用 [`MultipartUpload::abort`]。
+    pub async fn write_part(&mut self, data: impl Into<Bytes>) -> Result<u32> {
+        let data = data.into();
+        if let Some(size) = self.last_part_size.filter(|size| *size < MIN_PART_SIZE) {
+            return Err(anyhow!(
+                "只有最后一个分块可以小于 1 MB，第 {} 个分块只有 {} 字节: {}",
+                self.parts.len(),
+                size,
+                self.object_key
+            ));
+        }
+        if self.parts.len() >= MAX_PARTS {
+            return Err(anyhow!("分块数超过上限 {}: {}", MAX_PARTS, self.object_key));
+        }
+
+        let part_number = self.parts.len() as u32 + 1;
+        let size = data.len() as u64;
+        let started = Instant::now();
+        let (etag, retries) = self
+            .uploader
+            .upload_part_with_retry(
+                self.transfer_id,
+                &self.object_key,
+                &self.upload_id,
+                part_number,
+                data.clone(),
+                None,
+            )
+            .await?;
+
+        self.crc64.update(&data);
+        self.parts.push((part_number, etag));
+        self.last_part_size = Some(size);
+        self.bytes += size;
+        self.latencies.push(started.elapsed());
+        self.retries += retries;
+        Ok(part_number)
+    }
+
+    /// 按写入顺序合并所有分块，完成上传
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回对象的上传结果，其中包含本次上传的耗时统计
+    ///
+    /// # 错误
+    ///
+    /// 没有写入任何分块、完成请求失败或对象的 CRC64 与写入的数据不一致时返回错误。
+    /// 完成请求失败时分块仍然保留在 COS 中，需要调用方通过 [`Uploader::abort_multipart_upload`] 释放。
+    pub async fn complete(self) -> Result<UploadResult> {
+        let (mut result, crc) = self
+            .uploader
+            .finish_multipart_upload(
+                &self.object_key,
+                &self.upload_id,
+                &self.parts,
+                Some(self.parts.len() as u32),
+                self.forbid_overwrite,
+            )
+            .await?;
+        self.uploader.invalidate_cached(&self.object_key);
+        self.uploader
+            .check_crc64(&self.object_key, self.crc64.finish(), result.crc64)?;
+        result.stats = Some(TransferStats::new(
+            self.started.elapsed(),
+            self.bytes,
+            self.latencies,
+            self.retries,
+        ));
+        info!(
+            "分块上传成功: {} ({} 个分块, request_id: {:?})",
+            result.url,
+            self.parts.len(),
+            result.request_id
+        );
+
+        self.uploader.emit(TransferEvent::UploadCompleted {
+            transfer_id: self.transfer_id,
+            object_key: self.object_key,
+            etag: result.etag.clone(),
+            crc,
+            request_id: result.request_id.clone(),
+        });
+        Ok(result)
+    }
+
+    /// 终止上传，释放已上传的分块
+    pub async fn abort(self) -> Result<()> {
+        self.uploader
+            .abort_multipart_upload(&self.object_key, &self.upload_id)
+            .await
+            .inspect_err(|e| warn!("终止分块上传失败: {}: {}", self.object_key, e))?;
+        info!(
+            "已终止分块上传: {} ({} 个分块)",
+            self.object_key,
+            self.parts.len()
+        );
+        Ok(())
+    }
+}
+
+impl Uploader {
+    /// 开始一个由调用方逐块写入数据的分块上传
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项，其中的元数据、存储类型与禁止覆盖等设置作用于最终的对象
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传句柄，通过 [`MultipartUpload::write_part`] 写入分块
+    pub async fn start_multipart_upload(
+        &self,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<MultipartUpload> {
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.start_multipart_upload(object_key, options)).await;
+        }
+        let upload_id = self.init_multipart_upload(object_key, options).await?;
+        info!("开始分块上传: {} (upload_id: {})", object_key, upload_id);
+
+        Ok(MultipartUpload {
+            uploader: self.clone(),
+            object_key: object_key.to_string(),
+            upload_id,
+            forbid_overwrite: options.forbid_overwrite,
+            transfer_id: next_transfer_id(),
+            parts: Vec::new(),
+            last_part_size: None,
+            crc64: self.hash_backend.crc64(),
+            bytes: 0,
+            latencies: Vec::new(),
+            retries: 0,
+            started: Instant::now(),
+        })
+    }
+}
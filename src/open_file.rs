@@ -0,0 +1,120 @@
+use crate::options::UploadOptions;
+use crate::types::UploadResult;
+use crate::uploader::{UploadSource, Uploader, MAX_PARTS, MULTIPART_THRESHOLD, PART_SIZE};
+use anyhow::{anyhow, Result};
+use std::io::SeekFrom;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tracing::debug;
+
+/// 覆盖 `len` 字节且分块数不超过上限的分块大小
+fn part_size_for(len: u64) -> u64 {
+    PART_SIZE.max(len.div_ceil(MAX_PARTS as u64))
+}
+
+impl Uploader {
+    /// 上传已经打开的文件
+    ///
+    /// 适用于只持有文件句柄、无法通过路径访问文件的场景，例如以 `O_TMPFILE` 创建的匿名文件，
+    /// 或者由沙箱外传入的文件描述符。从文件开头读取 `len` 字节：不超过 5 MB 时以一个 PUT 请求上传，
+    /// 否则按顺序逐块进行分块上传，两种方式都会校验 CRC64。
+    /// `Content-Type` 按对象键的扩展名推断。与按路径上传一样会切换到备用 Bucket、镜像到影子 Bucket，
+    /// 这两种情况下会从文件开头重新读取，期间文件不能被修改。
+    ///
+    /// # 参数
+    ///
+    /// * `file` - 已打开的文件，也可以传入 `std::fs::File`
+    /// * `len` - 要上传的字节数，文件比它短时返回错误
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果
+    ///
+    /// # 错误
+    ///
+    /// 设置了需要重新读取本地文件的读后校验（`verify_after_upload`）、读取文件失败或上传失败时返回错误；
+    /// 读取或上传分块失败时会终止已经开始的分块上传。
+    pub async fn upload_open_file(
+        &self,
+        file: impl Into<File>,
+        len: u64,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        if options.verify_after_upload.is_some() {
+            return Err(anyhow!(
+                "上传已打开的文件不支持读后校验，上传过程中已校验 CRC64: {}",
+                object_key
+            ));
+        }
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.upload_open_file(file, len, object_key, options)).await;
+        }
+
+        // 切换到备用 Bucket 与镜像到影子 Bucket 时通过复制的文件描述符重新读取
+        let file = file.into().into_std().await;
+        let source = UploadSource::Open(file.try_clone()?, len);
+        let mut file = File::from_std(file);
+        let content_type = mime_guess::from_path(object_key)
+            .first_or_octet_stream()
+            .to_string();
+
+        let transfer = async {
+            file.seek(SeekFrom::Start(0)).await?;
+            if len > MULTIPART_THRESHOLD {
+                return self
+                    .upload_open_file_multipart(&mut file, len, object_key, options)
+                    .await;
+            }
+            let mut content = vec![0u8; len as usize];
+            file.read_exact(&mut content).await?;
+            debug!("普通上传已打开的文件: {} ({} 字节)", object_key, len);
+            self.put_content(content, content_type, object_key, options, None)
+                .await
+        };
+        self.run_upload(&source, object_key, options, transfer)
+            .await
+    }
+
+    /// 按顺序逐块读取已打开的文件并进行分块上传，失败时终止分块上传
+    async fn upload_open_file_multipart(
+        &self,
+        file: &mut File,
+        len: u64,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        let part_size = part_size_for(len);
+        let mut upload = self.start_multipart_upload(object_key, options).await?;
+        let mut remaining = len;
+        while remaining > 0 {
+            let size = remaining.min(part_size);
+            let mut data = vec![0u8; size as usize];
+            let written = match file.read_exact(&mut data).await {
+                Ok(_) => upload.write_part(data).await,
+                Err(e) => Err(e.into()),
+            };
+            if let Err(e) = written {
+                // 终止失败时已经记录日志，返回上传的错误
+                let _ = upload.abort().await;
+                return Err(e);
+            }
+            remaining -= size;
+        }
+        upload.complete().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_part_size_for() {
+        assert_eq!(part_size_for(6 * 1024 * 1024), PART_SIZE);
+        let len: u64 = 100 * 1024 * 1024 * 1024;
+        assert!(len.div_ceil(part_size_for(len)) <= MAX_PARTS as u64);
+    }
+}
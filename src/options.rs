@@ -0,0 +1,268 @@
+use crate::datetime::format_http_date;
+use crate::request::CosRequest;
+use crate::uploader::Metadata;
+use chrono::Utc;
+use std::time::Duration;
+
+/// 记录对象保留天数的对象标签键，生命周期规则按该标签删除过期的对象
+pub const EXPIRY_TAG_KEY: &str = "cos-upload-expiry-days";
+
+/// 一天的秒数
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// 对象的存储类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StorageClass {
+    /// 标准存储（`STANDARD`）
+    #[default]
+    Standard,
+    /// 低频存储（`STANDARD_IA`）
+    StandardIa,
+    /// 智能分层存储（`INTELLIGENT_TIERING`）
+    IntelligentTiering,
+    /// 归档存储（`ARCHIVE`）
+    Archive,
+    /// 深度归档存储（`DEEP_ARCHIVE`）
+    DeepArchive,
+    /// 多 AZ 标准存储（`MAZ_STANDARD`）
+    MazStandard,
+    /// 多 AZ 低频存储（`MAZ_STANDARD_IA`）
+    MazStandardIa,
+    /// 多 AZ 智能分层存储（`MAZ_INTELLIGENT_TIERING`）
+    MazIntelligentTiering,
+}
+
+impl StorageClass {
+    /// `x-cos-storage-class` 头部中使用的名称
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StorageClass::Standard => "STANDARD",
+            StorageClass::StandardIa => "STANDARD_IA",
+            StorageClass::IntelligentTiering => "INTELLIGENT_TIERING",
+            StorageClass::Archive => "ARCHIVE",
+            StorageClass::DeepArchive => "DEEP_ARCHIVE",
+            StorageClass::MazStandard => "MAZ_STANDARD",
+            StorageClass::MazStandardIa => "MAZ_STANDARD_IA",
+            StorageClass::MazIntelligentTiering => "MAZ_INTELLIGENT_TIERING",
+        }
+    }
+}
+
+/// 上传完成后的读后校验方式，参见 [`UploadOptions::verify_after_upload`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UploadVerification {
+    /// `HEAD` 对象，比对大小、CRC64（兼容模式支持时）、自定义元数据与 `Content-Language`
+    Head,
+    /// 在 [`UploadVerification::Head`] 的基础上，按范围读取对象开头、中间与末尾各 64 KB，与本地文件逐字节比对
+    SpotCheck,
+}
+
+/// 上传选项
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UploadOptions {
+    /// 自定义元数据，以 `x-cos-meta-*` 头部发送
+    pub metadata: Option<Metadata>,
+    /// 对象的有效期
+    ///
+    /// 设置后上传时会带上 `Expires` 缓存头部，并打上 [`EXPIRY_TAG_KEY`] 标签（值为向上取整的天数）。
+    pub expires_in: Option<Duration>,
+    /// 设置了有效期时，是否确保 Bucket 中存在按该标签删除对象的生命周期规则（默认关闭）
+    ///
+    /// 需要读写 Bucket 生命周期配置的权限，没有权限时只记录警告，不影响上传。
+    pub ensure_lifecycle_rule: bool,
+    /// 对象的存储类型，为 `None` 时使用 Bucket 的默认存储类型
+    pub storage_class: Option<StorageClass>,
+    /// 分块上传时是否为每个分块计算 SHA-1 并以 `x-cos-content-sha1` 发送（默认关闭）
+    ///
+    /// COS 会校验分块内容与该值是否一致，不一致时拒绝该分块，以额外的 CPU 开销换取更强的传输完整性保证。
+    pub part_sha1: bool,
+    /// 是否禁止覆盖同名对象（默认关闭）
+    ///
+    /// 开启后普通上传与完成分块上传时发送 `x-cos-forbid-overwrite: true`，
+    /// 对象键已存在时返回 [`CosError::AlreadyExists`](crate::CosError::AlreadyExists)，
+    /// 避免并发写入同一对象键时互相覆盖。
+    pub forbid_overwrite: bool,
+    /// 上传成功后是否在对象旁写入校验值旁路文件 `{object_key}.crc64`（默认关闭）
+    ///
+    /// 旁路文件记录对象的 CRC64，可以用 [`Uploader::verify_with_sidecar`](crate::Uploader::verify_with_sidecar)
+    /// 或任何能计算 CRC-64/ECMA-182 的工具校验对象，即使以后迁移到其它存储也能沿用。
+    /// 写入前需要额外一次 `HEAD` 请求读取 COS 计算的校验值。
+    pub checksum_sidecar: bool,
+    /// 分块上传时是否按观测到的吞吐量自动调整分块大小（默认关闭，固定为 5 MB）
+    ///
+    /// 从 1 MB 的分块开始，吞吐量高时逐步增大（不超过 64 MB），使每个分块的上传耗时保持在数秒左右：
+    /// 快速链路上减少请求次数，慢速链路上保持较小的分块以降低重试的代价。
+    /// 从按固定大小上传的断点继续时不会生效。
+    pub adaptive_part_size: bool,
+    /// 对象的 `Content-Language`，例如 `zh-CN`
+    pub content_language: Option<String>,
+    /// 对象的重定向地址，以 `x-cos-website-redirect-location` 头部发送
+    ///
+    /// 仅在 Bucket 开启静态网站时生效：通过静态网站域名访问该对象时返回 301 跳转到该地址，
+    /// 可以是同一 Bucket 中的路径（如 `/new/index.html`）或完整的 URL。
+    pub website_redirect_location: Option<String>,
+    /// 上传完成后是否重新读取对象进行校验（默认不校验）
+    ///
+    /// 校验不通过时上传返回 [`CosError::VerificationFailed`](crate::CosError::VerificationFailed)
+    /// 或 [`CosError::ChecksumMismatch`](crate::CosError::ChecksumMismatch)，对象保留在 COS 中由调用方处理。
+    /// 计算 CRC64 需要再读取一遍本地文件。切换到备用后端的上传不做校验。
+    pub verify_after_upload: Option<UploadVerification>,
+    /// 本次上传写入的 Bucket，为 `None` 时使用配置中的 Bucket
+    ///
+    /// 使用相同的密钥、连接池与签名器，按新的 Bucket 重新生成域名与签名，无需为每个 Bucket 创建上传器。
+    /// 覆盖后的上传不会切换到备用 Bucket，也不会镜像到影子 Bucket。
+    /// 对 [`Uploader::upload_file_with_options`](crate::Uploader::upload_file_with_options)、
+    /// [`Uploader::start_multipart_upload`](crate::Uploader::start_multipart_upload)、
+    /// [`Uploader::upload_group_with_options`](crate::Uploader::upload_group_with_options)
+    /// 等以整个上传为单位的方法生效；需要自行传递上传 ID 的
+    /// [`Uploader::init_multipart_upload`](crate::Uploader::init_multipart_upload) 不支持覆盖。
+    pub bucket: Option<String>,
+    /// 本次上传写入的 Bucket 所在的地域，为 `None` 时使用配置中的地域，参见 [`UploadOptions::bucket`]
+    pub region: Option<String>,
+}
+
+impl UploadOptions {
+    /// 创建默认的上传选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置自定义元数据
+    pub fn with_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// 设置对象的有效期
+    pub fn with_expires_in(mut self, expires_in: Duration) -> Self {
+        self.expires_in = Some(expires_in);
+        self
+    }
+
+    /// 设置是否确保存在删除过期对象的生命周期规则
+    pub fn with_lifecycle_rule(mut self, ensure: bool) -> Self {
+        self.ensure_lifecycle_rule = ensure;
+        self
+    }
+
+    /// 设置对象的存储类型
+    pub fn with_storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.storage_class = Some(storage_class);
+        self
+    }
+
+    /// 设置是否为每个分块发送 `x-cos-content-sha1` 校验值
+    pub fn with_part_sha1(mut self, enabled: bool) -> Self {
+        self.part_sha1 = enabled;
+        self
+    }
+
+    /// 设置是否禁止覆盖同名对象
+    pub fn with_forbid_overwrite(mut self, forbid: bool) -> Self {
+        self.forbid_overwrite = forbid;
+        self
+    }
+
+    /// 设置是否写入校验值旁路文件
+    pub fn with_checksum_sidecar(mut self, enabled: bool) -> Self {
+        self.checksum_sidecar = enabled;
+        self
+    }
+
+    /// 设置是否自动调整分块大小
+    pub fn with_adaptive_part_size(mut self, enabled: bool) -> Self {
+        self.adaptive_part_size = enabled;
+        self
+    }
+
+    /// 设置对象的 `Content-Language`
+    pub fn with_content_language(mut self, content_language: String) -> Self {
+        self.content_language = Some(content_language);
+        self
+    }
+
+    /// 设置静态网站访问对象时的重定向地址
+    pub fn with_website_redirect_location(mut self, location: String) -> Self {
+        self.website_redirect_location = Some(location);
+        self
+    }
+
+    /// 设置上传完成后的读后校验方式
+    pub fn with_verify_after_upload(mut self, verification: UploadVerification) -> Self {
+        self.verify_after_upload = Some(verification);
+        self
+    }
+
+    /// 设置本次上传写入的 Bucket
+    pub fn with_bucket(mut self, bucket: String) -> Self {
+        self.bucket = Some(bucket);
+        self
+    }
+
+    /// 设置本次上传写入的 Bucket 所在的地域
+    pub fn with_region(mut self, region: String) -> Self {
+        self.region = Some(region);
+        self
+    }
+
+    /// 有效期对应的天数，不足一天按一天计算
+    pub(crate) fn expiry_days(&self) -> Option<u64> {
+        self.expires_in
+            .map(|d| d.as_secs().div_ceil(SECS_PER_DAY).max(1))
+    }
+
+    /// 把选项对应的头部添加到上传请求（普通上传或初始化分块上传）中
+    ///
+    /// 禁止覆盖的头部需要在普通上传与完成分块上传时发送，由调用方单独添加。
+    pub(crate) fn apply(&self, mut request: CosRequest) -> CosRequest {
+        if let Some(metadata) = &self.metadata {
+            for (key, value) in metadata {
+                request = request.header(&format!("x-cos-meta-{}", key), value.clone());
+            }
+        }
+
+        if let Some(storage_class) = self.storage_class {
+            request = request.header("x-cos-storage-class", storage_class.as_str());
+        }
+        if let Some(content_language) = &self.content_language {
+            request = request.header("Content-Language", content_language.clone());
+        }
+        if let Some(location) = &self.website_redirect_location {
+            request = request.header("x-cos-website-redirect-location", location.clone());
+        }
+
+        if let Some(expires_in) = self.expires_in {
+            let expires = chrono::Duration::from_std(expires_in)
+                .ok()
+                .and_then(|d| Utc::now().checked_add_signed(d));
+            if let Some(expires) = expires {
+                request = request.header("Expires", format_http_date(&expires));
+            }
+        }
+        if let Some(days) = self.expiry_days() {
+            request = request.header("x-cos-tagging", format!("{}={}", EXPIRY_TAG_KEY, days));
+        }
+
+        request
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_days() {
+        let days = |secs| {
+            UploadOptions::new()
+                .with_expires_in(Duration::from_secs(secs))
+                .expiry_days()
+        };
+        assert_eq!(days(60), Some(1));
+        assert_eq!(days(SECS_PER_DAY), Some(1));
+        assert_eq!(days(SECS_PER_DAY + 1), Some(2));
+        assert_eq!(days(0), Some(1));
+        assert_eq!(UploadOptions::new().expiry_days(), None);
+    }
+}
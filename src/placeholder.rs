@@ -0,0 +1,144 @@
+use crate::error::is_not_found;
+use crate::options::UploadOptions;
+use crate::types::{ObjectMetadata, UploadResult};
+use crate::uploader::{Metadata, Uploader};
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use std::path::Path;
+use tracing::info;
+
+/// 标记占位对象的自定义元数据名，即 `x-cos-meta-placeholder-state`
+pub const PLACEHOLDER_STATE_METADATA: &str = "placeholder-state";
+/// 占位对象的状态值，表示真正的内容尚未上传
+pub const PLACEHOLDER_PENDING: &str = "pending";
+
+/// 对象是否为尚未被替换的占位对象
+fn is_placeholder(metadata: &ObjectMetadata) -> bool {
+    metadata.content_length == Some(0)
+        && metadata
+            .user_metadata
+            .get(PLACEHOLDER_STATE_METADATA)
+            .is_some_and(|state| state == PLACEHOLDER_PENDING)
+}
+
+impl Uploader {
+    /// 创建只带元数据的零字节占位对象，用于在真正上传之前登记对象
+    ///
+    /// 占位对象带有 `x-cos-meta-placeholder-state: pending`（[`PLACEHOLDER_STATE_METADATA`]）
+    /// 以及给定的元数据（例如 `expected-size`），其它流程可以据此得知对象即将上传。
+    /// 对象已存在时不会覆盖。之后用 [`Uploader::replace_placeholder`] 上传真正的内容。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `metadata` - 占位对象的自定义元数据，以 `x-cos-meta-*` 头部发送
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回占位对象的上传结果
+    ///
+    /// # 错误
+    ///
+    /// 对象已存在时返回 [`CosError::PreconditionFailed`](crate::CosError::PreconditionFailed)，
+    /// 请求失败时返回对应的错误。
+    pub async fn create_placeholder(
+        &self,
+        object_key: &str,
+        metadata: &Metadata,
+    ) -> Result<UploadResult> {
+        let headers: Vec<_> = metadata
+            .iter()
+            .filter(|(key, _)| key.as_str() != PLACEHOLDER_STATE_METADATA)
+            .map(|(key, value)| (format!("x-cos-meta-{}", key), value.as_str()))
+            .chain(std::iter::once((
+                format!("x-cos-meta-{}", PLACEHOLDER_STATE_METADATA),
+                PLACEHOLDER_PENDING,
+            )))
+            .collect();
+        let headers: Vec<_> = headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), *value))
+            .collect();
+
+        let result = self
+            .put_bytes_if_match(
+                object_key,
+                None,
+                Bytes::new(),
+                "application/octet-stream",
+                &headers,
+            )
+            .await?;
+        info!("创建占位对象: {}", object_key);
+        Ok(result)
+    }
+
+    /// 用本地文件替换占位对象
+    ///
+    /// 上传前先以 `HEAD` 确认对象仍是 [`Uploader::create_placeholder`] 创建的占位对象（零字节且状态为
+    /// `pending`），避免覆盖已经被其它流程替换的内容。上传会覆盖占位对象的全部元数据，
+    /// 需要保留的元数据应放在 `options` 中。确认与上传之间仍可能发生并发写入。
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果
+    ///
+    /// # 错误
+    ///
+    /// 对象不存在或不是待替换的占位对象时返回错误，上传失败时返回对应的错误。
+    pub async fn replace_placeholder<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.replace_placeholder(file_path, object_key, options)).await;
+        }
+        let metadata = match self.get_object_metadata(object_key).await {
+            Ok(metadata) => metadata,
+            Err(e) if is_not_found(&e) => {
+                return Err(anyhow!("占位对象不存在: {}", object_key));
+            }
+            Err(e) => return Err(e),
+        };
+        if !is_placeholder(&metadata) {
+            return Err(anyhow!(
+                "对象不是待替换的占位对象: {} (大小 {:?}，状态 {:?})",
+                object_key,
+                metadata.content_length,
+                metadata.user_metadata.get(PLACEHOLDER_STATE_METADATA)
+            ));
+        }
+
+        self.upload_file_with_options(file_path, object_key, options)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_placeholder() {
+        let mut metadata = ObjectMetadata {
+            content_length: Some(0),
+            ..Default::default()
+        };
+        assert!(!is_placeholder(&metadata));
+        metadata.user_metadata.insert(
+            PLACEHOLDER_STATE_METADATA.to_string(),
+            PLACEHOLDER_PENDING.to_string(),
+        );
+        assert!(is_placeholder(&metadata));
+        metadata.content_length = Some(10);
+        assert!(!is_placeholder(&metadata));
+    }
+}
@@ -0,0 +1,822 @@
+use crate::config::Config;
+use crate::error::CosError;
+use crate::http::client_builder;
+use crate::signature::Signer;
+use crate::types::{crc64_of, request_id_of, DeleteResult, ObjectMetadata, UploadResult};
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::Utc;
+use reqwest::{Client, Method, Response};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tracing::info;
+
+/// 请求签名的有效期（秒）
+const SIGN_EXPIRE: i64 = 3600;
+
+/// 由浏览器等客户端直接上传的一个文件
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientUploadSpec {
+    /// 对象键
+    pub object_key: String,
+    /// 对象的 Content-Type，设置后客户端必须以该值上传
+    pub content_type: Option<String>,
+    /// 自定义元数据，设置后客户端必须以相同的 `x-cos-meta-*` 头部上传
+    pub metadata: Option<HashMap<String, String>>,
+}
+
+impl ClientUploadSpec {
+    /// 创建只指定对象键的上传描述
+    pub fn new(object_key: String) -> Self {
+        Self {
+            object_key,
+            ..Self::default()
+        }
+    }
+
+    /// 设置 Content-Type
+    pub fn with_content_type(mut self, content_type: String) -> Self {
+        self.content_type = Some(content_type);
+        self
+    }
+
+    /// 设置自定义元数据
+    pub fn with_metadata(mut self, metadata: HashMap<String, String>) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+}
+
+/// 交给客户端的上传凭据
+///
+/// 客户端以 `method` 向 `url` 发送文件内容，并带上 `headers` 中的全部头部（取值必须完全一致），
+/// 否则 COS 返回签名不匹配。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClientUploadTicket {
+    /// 对象键
+    pub object_key: String,
+    /// HTTP 方法，固定为 `PUT`
+    pub method: String,
+    /// 预签名 URL
+    pub url: String,
+    /// 上传时必须携带的头部
+    pub headers: BTreeMap<String, String>,
+    /// URL 的过期时间（Unix 秒）
+    pub expires_at: i64,
+    /// URL 开始生效的时间（Unix 秒），向前回拨时早于 `server_time`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub valid_from: i64,
+    /// 生成凭据时服务端的时间（Unix 秒），客户端可以据此估算本地时钟的偏差
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub server_time: i64,
+}
+
+/// 预签名 URL 的有效时间窗口
+///
+/// COS 按签名中的起止时间校验请求，时钟偏慢的客户端（常见于移动设备）拿到刚生成的 URL 时，
+/// 其请求可能早于签名的起始时间而被拒绝。把起始时间向前回拨几分钟即可容忍这种偏差。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PresignWindow {
+    /// 从当前时间起的有效期
+    pub validity: Duration,
+    /// 签名起始时间比当前时间提前的时长（默认为 0）
+    pub backdate: Duration,
+}
+
+impl PresignWindow {
+    /// 创建从当前时间开始、有效期为 `validity` 的时间窗口
+    pub fn new(validity: Duration) -> Self {
+        Self {
+            validity,
+            backdate: Duration::ZERO,
+        }
+    }
+
+    /// 设置签名起始时间向前回拨的时长
+    pub fn with_backdate(mut self, backdate: Duration) -> Self {
+        self.backdate = backdate;
+        self
+    }
+
+    /// 以 `now` 为当前时间的起止时间（Unix 秒）
+    fn range(&self, now: i64) -> (i64, i64) {
+        (
+            now - self.backdate.as_secs() as i64,
+            now + self.validity.as_secs() as i64,
+        )
+    }
+}
+
+/// 预签名 URL 及其有效时间
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PresignedUrl {
+    /// 预签名 URL
+    pub url: String,
+    /// URL 开始生效的时间（Unix 秒）
+    pub valid_from: i64,
+    /// URL 的过期时间（Unix 秒）
+    pub expires_at: i64,
+    /// 生成 URL 时服务端的时间（Unix 秒）
+    ///
+    /// 客户端收到后以 `server_time - 本地时间` 估算时钟偏差，据此判断 URL 是否仍然有效，
+    /// 或校正其它依赖时间的逻辑。
+    pub server_time: i64,
+}
+
+/// 生成一个文件的上传凭据，Content-Type 与自定义元数据都签入 URL
+fn client_upload_ticket(
+    signer: &Signer,
+    config: &Config,
+    spec: &ClientUploadSpec,
+    window: &PresignWindow,
+) -> ClientUploadTicket {
+    let mut headers = BTreeMap::new();
+    if let Some(content_type) = &spec.content_type {
+        headers.insert("Content-Type".to_string(), content_type.clone());
+    }
+    for (key, value) in spec.metadata.iter().flatten() {
+        headers.insert(format!("x-cos-meta-{}", key), value.clone());
+    }
+
+    let signed: Vec<_> = headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()))
+        .collect();
+    let presigned = presign_url(
+        signer,
+        config,
+        "PUT",
+        &spec.object_key,
+        window,
+        &[],
+        &signed,
+    );
+
+    ClientUploadTicket {
+        object_key: spec.object_key.clone(),
+        method: "PUT".to_string(),
+        url: presigned.url,
+        headers,
+        expires_at: presigned.expires_at,
+        valid_from: presigned.valid_from,
+        server_time: presigned.server_time,
+    }
+}
+
+/// 预签名器
+///
+/// 只依赖签名逻辑与 `reqwest`，不依赖 tokio 与本地文件系统，关闭默认的 `runtime` feature 后
+/// 可以编译到 `wasm32-unknown-unknown`，在浏览器或边缘函数中生成预签名 URL 并通过 fetch 上传数据。
+///
+/// 也可作为只需要单个对象读写的最小客户端，提供 [`put_object`](Presigner::put_object)、
+/// [`get_object`](Presigner::get_object)、[`head_object`](Presigner::head_object) 与
+/// [`delete_object`](Presigner::delete_object)。
+pub struct Presigner {
+    client: Client,
+    config: Config,
+    signer: Signer,
+    host: String,
+}
+
+impl Presigner {
+    /// 创建新的预签名器
+    ///
+    /// # 参数
+    ///
+    /// * `config` - COS 配置
+    pub fn new(config: Config) -> Self {
+        config.signed_headers.warn_if_reduced();
+        Self {
+            client: client_builder(config.app_name.as_deref())
+                .build()
+                .expect("创建 HTTP 客户端失败"),
+            signer: Signer::new(&config.secret_id, &config.secret_key)
+                .with_canonicalization(config.header_canonicalization),
+            host: config.host_for(&config.region),
+            config,
+        }
+    }
+
+    /// 生成预签名 URL
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法（如 "GET", "PUT"）
+    /// * `object_key` - COS 中的对象键
+    /// * `expire` - URL 的有效期
+    ///
+    /// # 返回值
+    ///
+    /// 返回带签名查询参数的对象 URL，持有者可在有效期内直接用对应的方法访问该对象
+    pub fn presign_url(&self, method: &str, object_key: &str, expire: Duration) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            &[],
+            &[],
+        )
+        .url
+    }
+
+    /// 按给定的时间窗口生成预签名 URL，并返回有效时间与服务端时间
+    ///
+    /// 时钟偏慢的客户端可以使用起始时间向前回拨的 URL（[`PresignWindow::with_backdate`]），
+    /// 并根据返回的 [`PresignedUrl::server_time`] 校正本地时钟的偏差。
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法（如 "GET", "PUT"）
+    /// * `object_key` - COS 中的对象键
+    /// * `window` - URL 的有效时间窗口
+    ///
+    /// # 返回值
+    ///
+    /// 返回预签名 URL、生效与过期时间以及生成时的服务端时间
+    pub fn presign_url_with_window(
+        &self,
+        method: &str,
+        object_key: &str,
+        window: &PresignWindow,
+    ) -> PresignedUrl {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            window,
+            &[],
+            &[],
+        )
+    }
+
+    /// 生成同时签入指定头部的预签名 URL
+    ///
+    /// 除 Host 外，`headers` 中的头部也参与签名（写入 `q-header-list`）。持有者发起请求时必须
+    /// 携带完全相同的头部与取值，否则 COS 返回签名不匹配，可以用来强制客户端上传时使用服务端
+    /// 指定的 `Content-Type`、`x-cos-meta-*` 元数据或 `Referer`。
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法（如 "GET", "PUT"）
+    /// * `object_key` - COS 中的对象键
+    /// * `expire` - URL 的有效期
+    /// * `headers` - 额外参与签名的头部（名称, 值）
+    ///
+    /// # 返回值
+    ///
+    /// 返回带签名查询参数的对象 URL
+    pub fn presign_url_with_headers(
+        &self,
+        method: &str,
+        object_key: &str,
+        expire: Duration,
+        headers: &[(&str, &str)],
+    ) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            &[],
+            headers,
+        )
+        .url
+    }
+
+    /// 生成访问子资源或带查询参数的预签名 URL
+    ///
+    /// `params` 会写入 URL 并参与签名（写入 `q-url-param-list`），持有者无法修改或增删。
+    /// 可以把单个运维操作交给脚本执行而不分发密钥，例如 `POST ?restore` 恢复归档对象、
+    /// `GET ?acl` 读取 ACL、`PUT ?tagging` 设置标签；对象键为空字符串时为 Bucket 级别的子资源。
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法（如 "GET", "PUT", "POST", "DELETE"）
+    /// * `object_key` - COS 中的对象键
+    /// * `expire` - URL 的有效期
+    /// * `params` - 查询参数（名称, 值），值为空字符串时只输出参数名（如 `restore`）
+    ///
+    /// # 返回值
+    ///
+    /// 返回带签名查询参数的 URL
+    pub fn presign_url_with_params(
+        &self,
+        method: &str,
+        object_key: &str,
+        expire: Duration,
+        params: &[(&str, &str)],
+    ) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            params,
+            &[],
+        )
+        .url
+    }
+
+    /// 为多个文件一次生成客户端直传的凭据
+    ///
+    /// 适合浏览器一次上传多个文件：服务端生成凭据交给浏览器，浏览器直接向 COS 上传，
+    /// 完成后服务端可以用 [`Uploader::confirm_uploads`](crate::Uploader::confirm_uploads) 确认文件已到达。
+    /// 每个文件的 Content-Type 与自定义元数据都签入 URL，客户端无法更改。
+    ///
+    /// # 参数
+    ///
+    /// * `specs` - 要上传的文件
+    /// * `expire` - URL 的有效期
+    ///
+    /// # 返回值
+    ///
+    /// 按 `specs` 的顺序返回每个文件的上传凭据
+    pub fn prepare_client_uploads(
+        &self,
+        specs: &[ClientUploadSpec],
+        expire: Duration,
+    ) -> Vec<ClientUploadTicket> {
+        self.prepare_client_uploads_with_window(specs, &PresignWindow::new(expire))
+    }
+
+    /// 按给定的时间窗口为多个文件生成客户端直传的凭据
+    ///
+    /// 与 [`Presigner::prepare_client_uploads`] 相同，凭据中的 [`ClientUploadTicket::server_time`]
+    /// 可供时钟不准的客户端校正偏差。
+    pub fn prepare_client_uploads_with_window(
+        &self,
+        specs: &[ClientUploadSpec],
+        window: &PresignWindow,
+    ) -> Vec<ClientUploadTicket> {
+        specs
+            .iter()
+            .map(|spec| client_upload_ticket(&self.signer, &self.config, spec, window))
+            .collect()
+    }
+
+    /// 通过 HTTP 请求上传内存中的数据
+    ///
+    /// 在 wasm32 上由浏览器的 fetch 发出请求。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键
+    /// * `body` - 对象内容
+    /// * `content_type` - 对象的 Content-Type，为 `None` 时由 COS 决定
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果
+    pub async fn put_object(
+        &self,
+        object_key: &str,
+        body: impl Into<Bytes>,
+        content_type: Option<&str>,
+    ) -> Result<UploadResult> {
+        let (url, response) = self
+            .send(Method::PUT, object_key, content_type, Some(body.into()))
+            .await?;
+        info!("文件上传成功: {} (bucket: {})", url, self.config.bucket);
+
+        let headers = response.headers();
+        Ok(UploadResult {
+            etag: headers
+                .get("ETag")
+                .and_then(|v| v.to_str().ok())
+                .map(|v| v.to_string()),
+            request_id: request_id_of(headers),
+            url,
+            stats: None,
+            failover: None,
+            crc64: crc64_of(headers),
+        })
+    }
+
+    /// 读取对象的全部内容到内存
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键
+    ///
+    /// # 错误
+    ///
+    /// 对象不存在或请求失败时返回错误。
+    pub async fn get_object(&self, object_key: &str) -> Result<Bytes> {
+        let (_, response) = self.send(Method::GET, object_key, None, None).await?;
+        Ok(response.bytes().await?)
+    }
+
+    /// 读取对象的元数据
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键
+    ///
+    /// # 错误
+    ///
+    /// 对象不存在或请求失败时返回错误。
+    pub async fn head_object(&self, object_key: &str) -> Result<ObjectMetadata> {
+        let (_, response) = self.send(Method::HEAD, object_key, None, None).await?;
+        Ok(ObjectMetadata::from_headers(response.headers()))
+    }
+
+    /// 删除对象，对象不存在时 COS 同样返回成功
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键
+    pub async fn delete_object(&self, object_key: &str) -> Result<DeleteResult> {
+        let (_, response) = self.send(Method::DELETE, object_key, None, None).await?;
+        Ok(DeleteResult {
+            request_id: request_id_of(response.headers()),
+        })
+    }
+
+    /// 签名并发送一个对象请求，返回请求的 URL 与成功的响应
+    async fn send(
+        &self,
+        method: Method,
+        object_key: &str,
+        content_type: Option<&str>,
+        body: Option<Bytes>,
+    ) -> Result<(String, Response)> {
+        let mut headers = HashMap::new();
+        headers.insert("Host".to_string(), self.host.clone());
+        if let Some(body) = &body {
+            headers.insert("Content-Length".to_string(), body.len().to_string());
+        }
+        if let Some(content_type) = content_type {
+            headers.insert("Content-Type".to_string(), content_type.to_string());
+        }
+        if let Some(token) = &self.config.security_token {
+            headers.insert("x-cos-security-token".to_string(), token.clone());
+        }
+
+        let path = self.config.object_path(object_key);
+        let url = format!("{}://{}{}", self.config.scheme(), self.host, path);
+        let authorization = self
+            .signer
+            .sign(
+                method.as_str(),
+                &path,
+                &HashMap::new(),
+                &self.config.signed_headers.select(&headers),
+                SIGN_EXPIRE,
+            )
+            .authorization;
+
+        // Host 与 Content-Length 由 HTTP 客户端（或浏览器）自行设置
+        let mut builder = self
+            .client
+            .request(method, &url)
+            .header("Authorization", authorization);
+        if let Some(content_type) = content_type {
+            builder = builder.header("Content-Type", content_type);
+        }
+        if let Some(token) = &self.config.security_token {
+            builder = builder.header("x-cos-security-token", token);
+        }
+        if let Some(body) = body {
+            builder = builder.body(body);
+        }
+
+        let response = builder.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let headers = response.headers().clone();
+            let text = response.text().await.unwrap_or_default();
+            return Err(CosError::from_response(status, &headers, &text).into());
+        }
+        Ok((url, response))
+    }
+}
+
+#[cfg(feature = "runtime")]
+impl crate::Uploader {
+    /// 生成预签名 URL
+    ///
+    /// 参见 [`Presigner::presign_url`]。URL 始终使用配置的域名，不受端点探测的影响。
+    pub fn presign_url(&self, method: &str, object_key: &str, expire: Duration) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            &[],
+            &[],
+        )
+        .url
+    }
+
+    /// 按给定的时间窗口生成预签名 URL，并返回有效时间与服务端时间
+    ///
+    /// 参见 [`Presigner::presign_url_with_window`]。
+    pub fn presign_url_with_window(
+        &self,
+        method: &str,
+        object_key: &str,
+        window: &PresignWindow,
+    ) -> PresignedUrl {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            window,
+            &[],
+            &[],
+        )
+    }
+
+    /// 生成同时签入指定头部的预签名 URL
+    ///
+    /// 参见 [`Presigner::presign_url_with_headers`]。
+    pub fn presign_url_with_headers(
+        &self,
+        method: &str,
+        object_key: &str,
+        expire: Duration,
+        headers: &[(&str, &str)],
+    ) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            &[],
+            headers,
+        )
+        .url
+    }
+
+    /// 生成访问子资源或带查询参数的预签名 URL
+    ///
+    /// 参见 [`Presigner::presign_url_with_params`]。
+    pub fn presign_url_with_params(
+        &self,
+        method: &str,
+        object_key: &str,
+        expire: Duration,
+        params: &[(&str, &str)],
+    ) -> String {
+        presign_url(
+            &self.signer,
+            &self.config,
+            method,
+            object_key,
+            &PresignWindow::new(expire),
+            params,
+            &[],
+        )
+        .url
+    }
+
+    /// 生成上传指定分块的预签名 URL
+    pub(crate) fn presign_part_url(
+        &self,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        window: &PresignWindow,
+    ) -> PresignedUrl {
+        presign_url(
+            &self.signer,
+            &self.config,
+            "PUT",
+            object_key,
+            window,
+            &[
+                ("partNumber", &part_number.to_string()),
+                ("uploadId", upload_id),
+            ],
+            &[],
+        )
+    }
+
+    /// 为多个文件一次生成客户端直传的凭据
+    ///
+    /// 参见 [`Presigner::prepare_client_uploads`]。
+    pub fn prepare_client_uploads(
+        &self,
+        specs: &[ClientUploadSpec],
+        expire: Duration,
+    ) -> Vec<ClientUploadTicket> {
+        self.prepare_client_uploads_with_window(specs, &PresignWindow::new(expire))
+    }
+
+    /// 按给定的时间窗口为多个文件生成客户端直传的凭据
+    ///
+    /// 参见 [`Presigner::prepare_client_uploads_with_window`]。
+    pub fn prepare_client_uploads_with_window(
+        &self,
+        specs: &[ClientUploadSpec],
+        window: &PresignWindow,
+    ) -> Vec<ClientUploadTicket> {
+        specs
+            .iter()
+            .map(|spec| client_upload_ticket(&self.signer, &self.config, spec, window))
+            .collect()
+    }
+
+    /// 确认客户端按凭据上传的文件已经到达
+    ///
+    /// 对每个凭据的对象键发送 HEAD 请求。
+    ///
+    /// # 参数
+    ///
+    /// * `tickets` - [`Uploader::prepare_client_uploads`](crate::Uploader::prepare_client_uploads) 生成的凭据
+    ///
+    /// # 返回值
+    ///
+    /// 按 `tickets` 的顺序返回对象键与元数据，对象尚未到达时元数据为 `None`
+    ///
+    /// # 错误
+    ///
+    /// HEAD 请求失败（对象不存在除外）时返回错误。
+    pub async fn confirm_uploads(
+        &self,
+        tickets: &[ClientUploadTicket],
+    ) -> Result<Vec<(String, Option<crate::ObjectMetadata>)>> {
+        let mut confirmed = Vec::with_capacity(tickets.len());
+        for ticket in tickets {
+            let metadata = match self.get_object_metadata(&ticket.object_key).await {
+                Ok(metadata) => Some(metadata),
+                Err(e) if crate::error::is_not_found(&e) => None,
+                Err(e) => return Err(e),
+            };
+            confirmed.push((ticket.object_key.clone(), metadata));
+        }
+
+        let arrived = confirmed.iter().filter(|(_, m)| m.is_some()).count();
+        info!("确认客户端上传: {}/{} 个文件已到达", arrived, tickets.len());
+        Ok(confirmed)
+    }
+}
+
+/// 生成预签名 URL，签名中包含 `params`、Host 与 `extra_headers`，起止时间按当前时间与 `window` 计算
+fn presign_url(
+    signer: &Signer,
+    config: &Config,
+    method: &str,
+    object_key: &str,
+    window: &PresignWindow,
+    params: &[(&str, &str)],
+    extra_headers: &[(&str, &str)],
+) -> PresignedUrl {
+    let now = Utc::now().timestamp();
+    let (start_time, end_time) = window.range(now);
+    let host = config.host_for(&config.region);
+    let path = config.object_path(object_key);
+    let mut headers = HashMap::from([("Host".to_string(), host.clone())]);
+    for (name, value) in extra_headers {
+        headers.insert(name.to_string(), value.to_string());
+    }
+
+    let signed_params: HashMap<_, _> = params
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    let authorization = signer.sign_exact(
+        start_time,
+        end_time,
+        method,
+        &path,
+        &signed_params,
+        &headers,
+    );
+
+    let mut url = format!("{}://{}{}?", config.scheme(), host, path);
+    let mut sorted = params.to_vec();
+    sorted.sort();
+    for (name, value) in sorted {
+        url.push_str(&urlencoding::encode(name));
+        if !value.is_empty() {
+            url.push('=');
+            url.push_str(&urlencoding::encode(value));
+        }
+        url.push('&');
+    }
+    url.push_str(&authorization);
+    if let Some(token) = &config.security_token {
+        url.push_str("&x-cos-security-token=");
+        url.push_str(&urlencoding::encode(token));
+    }
+    PresignedUrl {
+        url,
+        valid_from: start_time,
+        expires_at: end_time,
+        server_time: now,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client_upload_ticket() {
+        let signer = Signer::new("id", "key");
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        let spec = ClientUploadSpec::new("u/1/a.png".into())
+            .with_content_type("image/png".into())
+            .with_metadata(HashMap::from([("user-id".to_string(), "1".to_string())]));
+
+        let ticket = client_upload_ticket(
+            &signer,
+            &config,
+            &spec,
+            &PresignWindow::new(Duration::from_secs(600)),
+        );
+        assert_eq!(ticket.method, "PUT");
+        assert_eq!(
+            ticket.headers,
+            BTreeMap::from([
+                ("Content-Type".to_string(), "image/png".to_string()),
+                ("x-cos-meta-user-id".to_string(), "1".to_string()),
+            ])
+        );
+        assert!(ticket
+            .url
+            .contains("q-header-list=content-type;host;x-cos-meta-user-id&"));
+        assert!(ticket.expires_at > Utc::now().timestamp());
+    }
+
+    #[test]
+    fn test_presign_url_signs_extra_headers() {
+        let signer = Signer::new("id", "key");
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        let url = presign_url(
+            &signer,
+            &config,
+            "PUT",
+            "a.txt",
+            &PresignWindow::new(Duration::from_secs(60)),
+            &[],
+            &[
+                ("Content-Type", "text/plain"),
+                ("x-cos-meta-owner", "alice"),
+            ],
+        )
+        .url;
+        assert!(url.contains("q-header-list=content-type;host;x-cos-meta-owner&"));
+
+        let plain = presign_url(
+            &signer,
+            &config,
+            "PUT",
+            "a.txt",
+            &PresignWindow::new(Duration::from_secs(60)),
+            &[],
+            &[],
+        )
+        .url;
+        assert!(plain.contains("q-header-list=host&"));
+        assert!(plain.contains("q-url-param-list=&"));
+    }
+
+    #[test]
+    fn test_presign_url_signs_params() {
+        let signer = Signer::new("id", "key");
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        let url = presign_url(
+            &signer,
+            &config,
+            "POST",
+            "archive/a.txt",
+            &PresignWindow::new(Duration::from_secs(60)),
+            &[("versionId", "v 1"), ("restore", "")],
+            &[],
+        )
+        .url;
+        assert!(url.starts_with(
+            "https://b.cos.ap-guangzhou.myqcloud.com/archive/a.txt?restore&versionId=v%201&q-sign-algorithm=sha1&"
+        ));
+        assert!(url.contains("q-url-param-list=restore;versionid&"));
+    }
+
+    #[test]
+    fn test_presign_window() {
+        let signer = Signer::new("id", "key");
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        let window =
+            PresignWindow::new(Duration::from_secs(600)).with_backdate(Duration::from_secs(300));
+        let presigned = presign_url(&signer, &config, "GET", "a.txt", &window, &[], &[]);
+        assert_eq!(presigned.valid_from, presigned.server_time - 300);
+        assert_eq!(presigned.expires_at, presigned.server_time + 600);
+        assert!(presigned.url.contains(&format!(
+            "q-sign-time={};{}&",
+            presigned.valid_from, presigned.expires_at
+        )));
+    }
+}
@@ -0,0 +1,210 @@
+use crate::config::{Config, EndpointKind};
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// 每个候选域名的探测次数，取最小值以排除建立连接与 TLS 握手的影响
+const PROBE_SAMPLES: u32 = 3;
+/// 单次探测的超时时间
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 上传器当前使用的域名
+pub(crate) struct SelectedEndpoint {
+    /// 域名类型
+    pub(crate) kind: EndpointKind,
+    /// 配置地域下 Bucket 的访问域名
+    pub(crate) host: Arc<str>,
+}
+
+impl SelectedEndpoint {
+    pub(crate) fn new(config: &Config, kind: EndpointKind) -> Self {
+        Self {
+            kind,
+            host: config.host_of(kind, &config.region).into(),
+        }
+    }
+}
+
+/// 单个候选域名的探测结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointProbe {
+    /// 域名类型
+    pub kind: EndpointKind,
+    /// 探测的域名
+    pub host: String,
+    /// 多次探测中最短的往返时延，不可达时为 `None`
+    pub rtt: Option<Duration>,
+    /// 不可达时的错误信息
+    pub error: Option<String>,
+}
+
+/// 时延最低的可达候选，时延相同时取靠前的
+fn fastest(probes: &[EndpointProbe]) -> Option<&EndpointProbe> {
+    probes
+        .iter()
+        .filter_map(|probe| probe.rtt.map(|rtt| (rtt, probe)))
+        .min_by_key(|(rtt, _)| *rtt)
+        .map(|(_, probe)| probe)
+}
+
+impl Uploader {
+    /// 当前使用的域名类型
+    ///
+    /// 未进行端点探测时即为配置中的 [`Config::endpoint`](crate::Config::endpoint)。
+    pub fn current_endpoint(&self) -> EndpointKind {
+        self.endpoint.read().unwrap().kind
+    }
+
+    /// 探测候选域名的往返时延
+    ///
+    /// 对每个候选发送若干次不带签名的 `HEAD` 请求，收到任意 HTTP 响应（包括 403）都视为可达。
+    ///
+    /// # 参数
+    ///
+    /// * `candidates` - 候选的域名类型
+    ///
+    /// # 返回值
+    ///
+    /// 与候选顺序一致的探测结果
+    pub async fn probe_endpoints(&self, candidates: &[EndpointKind]) -> Vec<EndpointProbe> {
+        let mut probes = Vec::with_capacity(candidates.len());
+        for &kind in candidates {
+            probes.push(self.probe_endpoint(kind).await);
+        }
+        probes
+    }
+
+    async fn probe_endpoint(&self, kind: EndpointKind) -> EndpointProbe {
+        let host = self.config.host_of(kind, &self.config.region);
+        let url = format!("{}://{}/", self.config.scheme(), host);
+        let mut rtt: Option<Duration> = None;
+        let mut error = None;
+
+        for _ in 0..PROBE_SAMPLES {
+            let start = Instant::now();
+            match self.client.head(&url).timeout(PROBE_TIMEOUT).send().await {
+                Ok(_) => {
+                    let elapsed = start.elapsed();
+                    rtt = Some(rtt.map_or(elapsed, |rtt| rtt.min(elapsed)));
+                }
+                Err(e) => {
+                    error = Some(e.to_string());
+                    break;
+                }
+            }
+        }
+
+        // 任意一次失败都视为不可达，避免选中不稳定的域名
+        if error.is_some() {
+            rtt = None;
+        }
+        debug!("端点探测: {} 时延 {:?} 错误 {:?}", host, rtt, error);
+        EndpointProbe {
+            kind,
+            host,
+            rtt,
+            error,
+        }
+    }
+
+    /// 探测候选域名并切换到时延最低的一个
+    ///
+    /// 切换对共享同一状态的所有克隆立即生效；预签名 URL 不受影响，始终使用配置的域名。
+    ///
+    /// # 参数
+    ///
+    /// * `candidates` - 候选的域名类型
+    ///
+    /// # 返回值
+    ///
+    /// 选中的候选的探测结果
+    ///
+    /// # 错误
+    ///
+    /// 所有候选都不可达时返回错误，此时保持当前域名不变。
+    pub async fn select_fastest_endpoint(
+        &self,
+        candidates: &[EndpointKind],
+    ) -> Result<EndpointProbe> {
+        let probes = self.probe_endpoints(candidates).await;
+        let Some(best) = fastest(&probes).cloned() else {
+            warn!("端点探测: 没有可达的候选域名，保持当前域名");
+            return Err(anyhow!("没有可达的候选域名: {:?}", candidates));
+        };
+
+        let previous = {
+            let mut endpoint = self.endpoint.write().unwrap();
+            let previous = endpoint.kind;
+            *endpoint = SelectedEndpoint::new(&self.config, best.kind);
+            previous
+        };
+        if previous != best.kind {
+            info!(
+                "端点探测: 切换到 {:?} ({})，时延 {:?}",
+                best.kind, best.host, best.rtt
+            );
+        } else {
+            debug!("端点探测: 继续使用 {:?}，时延 {:?}", best.kind, best.rtt);
+        }
+        Ok(best)
+    }
+
+    /// 在后台定期重新探测候选域名并切换到最快的一个
+    ///
+    /// 启动后立即探测一次。后台任务会一直运行，不再需要时调用返回句柄的 `abort`。
+    ///
+    /// # 参数
+    ///
+    /// * `candidates` - 候选的域名类型
+    /// * `interval` - 探测间隔
+    pub fn spawn_endpoint_refresh(
+        &self,
+        candidates: Vec<EndpointKind>,
+        interval: Duration,
+    ) -> JoinHandle<()> {
+        let uploader = self.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // 失败时已经记录日志并保持当前域名
+                let _ = uploader.select_fastest_endpoint(&candidates).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(kind: EndpointKind, rtt: Option<u64>) -> EndpointProbe {
+        EndpointProbe {
+            kind,
+            host: String::new(),
+            rtt: rtt.map(Duration::from_millis),
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_fastest() {
+        let probes = [
+            probe(EndpointKind::Regional, Some(20)),
+            probe(EndpointKind::Accelerate, None),
+        ];
+        assert_eq!(fastest(&probes).unwrap().kind, EndpointKind::Regional);
+
+        let probes = [
+            probe(EndpointKind::Regional, Some(20)),
+            probe(EndpointKind::Accelerate, Some(20)),
+        ];
+        assert_eq!(fastest(&probes).unwrap().kind, EndpointKind::Regional);
+
+        assert!(fastest(&[probe(EndpointKind::Regional, None)]).is_none());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::events::TransferEvent;
+use crate::uploader::Uploader;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+
+/// 平滑吞吐量时新采样的权重
+const SMOOTHING: f64 = 0.3;
+
+/// 传输进度事件（[`TransferEvent::Progress`]）的发送间隔
+///
+/// 分块在高速网络上很快完成，每个分块都发送进度会让界面刷新过于频繁；
+/// 间隔内完成的分块合并为一次进度事件。传输完成时总会发送最后一次进度。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProgressInterval {
+    /// 距上次发送至少经过给定的时间
+    Time(Duration),
+    /// 距上次发送至少又上传了给定的字节数
+    Bytes(u64),
+}
+
+impl Default for ProgressInterval {
+    fn default() -> Self {
+        ProgressInterval::Time(Duration::from_secs(1))
+    }
+}
+
+/// 跟踪一次传输的进度，按间隔计算平滑吞吐量与预计剩余时间并发送进度事件
+pub(crate) struct ProgressTracker {
+    events: Option<broadcast::Sender<TransferEvent>>,
+    transfer_id: u64,
+    object_key: String,
+    interval: ProgressInterval,
+    total: u64,
+    done: u64,
+    /// 上次发送进度时已完成的字节数，不含从断点跳过的部分
+    reported: u64,
+    last_report: Instant,
+    /// 指数加权平均的吞吐量（字节/秒）
+    rate: Option<f64>,
+}
+
+impl ProgressTracker {
+    fn new(
+        events: Option<broadcast::Sender<TransferEvent>>,
+        transfer_id: u64,
+        object_key: &str,
+        interval: ProgressInterval,
+        total: u64,
+    ) -> Self {
+        Self {
+            events,
+            transfer_id,
+            object_key: object_key.to_string(),
+            interval,
+            total,
+            done: 0,
+            reported: 0,
+            last_report: Instant::now(),
+            rate: None,
+        }
+    }
+
+    /// 记录断点中已完成、本次不再上传的字节，不计入吞吐量
+    pub(crate) fn skip(&mut self, bytes: u64) {
+        self.done += bytes;
+        self.reported += bytes;
+    }
+
+    /// 记录新上传完成的字节，到达发送间隔时发送进度事件
+    pub(crate) fn record(&mut self, bytes: u64) {
+        if let Some(event) = self.record_at(bytes, Instant::now()) {
+            if let Some(sender) = &self.events {
+                let _ = sender.send(event);
+            }
+        }
+    }
+
+    fn record_at(&mut self, bytes: u64, now: Instant) -> Option<TransferEvent> {
+        self.done = (self.done + bytes).min(self.total);
+        let window = now.saturating_duration_since(self.last_report);
+        let pending = self.done - self.reported;
+        let due = self.done >= self.total
+            || match self.interval {
+                ProgressInterval::Time(interval) => window >= interval,
+                ProgressInterval::Bytes(interval) => pending >= interval,
+            };
+        if !due {
+            return None;
+        }
+
+        let seconds = window.as_secs_f64();
+        if seconds > 0.0 {
+            let sample = pending as f64 / seconds;
+            self.rate = Some(match self.rate {
+                Some(rate) => SMOOTHING * sample + (1.0 - SMOOTHING) * rate,
+                None => sample,
+            });
+        }
+        self.last_report = now;
+        self.reported = self.done;
+
+        let rate = self.rate.unwrap_or(0.0);
+        let eta =
+            (rate > 0.0).then(|| Duration::from_secs_f64((self.total - self.done) as f64 / rate));
+        Some(TransferEvent::Progress {
+            transfer_id: self.transfer_id,
+            object_key: self.object_key.clone(),
+            bytes_transferred: self.done,
+            total_bytes: self.total,
+            bytes_per_sec: rate as u64,
+            eta,
+        })
+    }
+}
+
+impl Uploader {
+    /// 设置分块上传进度事件的发送间隔，默认每秒最多一次
+    ///
+    /// 进度通过 [`TransferEvent::Progress`] 发送，需要同时通过 [`Uploader::with_event_channel`] 开启事件广播。
+    pub fn with_progress_interval(mut self, interval: ProgressInterval) -> Self {
+        self.progress_interval = interval;
+        self
+    }
+
+    /// 为一次传输创建进度跟踪器
+    pub(crate) fn progress_tracker(
+        &self,
+        transfer_id: u64,
+        object_key: &str,
+        total: u64,
+    ) -> ProgressTracker {
+        ProgressTracker::new(
+            self.events.clone(),
+            transfer_id,
+            object_key,
+            self.progress_interval,
+            total,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_tracker() {
+        let mut tracker =
+            ProgressTracker::new(None, 1, "a.bin", ProgressInterval::Bytes(100), 1000);
+        let start = tracker.last_report;
+        tracker.skip(200);
+
+        // 不足间隔时不发送
+        assert!(tracker
+            .record_at(50, start + Duration::from_secs(1))
+            .is_none());
+        let Some(TransferEvent::Progress {
+            bytes_transferred,
+            bytes_per_sec,
+            eta,
+            ..
+        }) = tracker.record_at(50, start + Duration::from_secs(1))
+        else {
+            panic!("应当发送进度");
+        };
+        // 跳过的字节不计入吞吐量
+        assert_eq!(bytes_transferred, 300);
+        assert_eq!(bytes_per_sec, 100);
+        assert_eq!(eta, Some(Duration::from_secs(7)));
+
+        // 吞吐量按指数加权平均平滑
+        let Some(TransferEvent::Progress { bytes_per_sec, .. }) =
+            tracker.record_at(200, start + Duration::from_secs(2))
+        else {
+            panic!("应当发送进度");
+        };
+        assert_eq!(bytes_per_sec, 130);
+
+        // 完成时总会发送
+        let mut tracker = ProgressTracker::new(
+            None,
+            1,
+            "a.bin",
+            ProgressInterval::Time(Duration::from_secs(60)),
+            10,
+        );
+        let start = tracker.last_report;
+        assert!(matches!(
+            tracker.record_at(10, start + Duration::from_secs(1)),
+            Some(TransferEvent::Progress { eta: Some(eta), .. }) if eta.is_zero()
+        ));
+    }
+}
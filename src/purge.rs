@@ -0,0 +1,247 @@
+use crate::bucket::content_md5;
+use crate::list::ListOptions;
+use crate::request::{read_xml, CosRequest};
+use crate::uploader::Uploader;
+use crate::xml::{escape, find_all_tags, find_tag, unescape};
+use anyhow::{anyhow, Result};
+use reqwest::Method;
+use tracing::{info, warn};
+
+/// 默认的安全上限：超过该数量的删除需要显式确认
+pub const DEFAULT_DELETE_SAFETY_LIMIT: u64 = 1000;
+/// 单次批量删除请求最多包含的对象数
+const DELETE_BATCH_SIZE: usize = 1000;
+
+/// 按前缀删除对象的选项
+#[derive(Debug, Clone)]
+pub struct DeletePrefixOptions {
+    /// 对象数量超过该值时需要通过 `force` 或 `expected_count` 确认，默认为 [`DEFAULT_DELETE_SAFETY_LIMIT`]
+    pub safety_limit: u64,
+    /// 期望删除的对象数量；设置后实际数量必须与之一致，超过安全上限时也视为确认
+    pub expected_count: Option<u64>,
+    /// 超过安全上限时不需要确认，直接删除
+    pub force: bool,
+}
+
+impl Default for DeletePrefixOptions {
+    fn default() -> Self {
+        Self {
+            safety_limit: DEFAULT_DELETE_SAFETY_LIMIT,
+            expected_count: None,
+            force: false,
+        }
+    }
+}
+
+impl DeletePrefixOptions {
+    /// 创建默认的删除选项
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 设置安全上限
+    pub fn with_safety_limit(mut self, limit: u64) -> Self {
+        self.safety_limit = limit;
+        self
+    }
+
+    /// 设置期望删除的对象数量
+    pub fn with_expected_count(mut self, count: u64) -> Self {
+        self.expected_count = Some(count);
+        self
+    }
+
+    /// 设置是否跳过安全上限的确认
+    pub fn with_force(mut self, force: bool) -> Self {
+        self.force = force;
+        self
+    }
+
+    /// 检查即将删除的对象数量是否已得到确认
+    fn confirm(&self, prefix: &str, count: u64) -> Result<()> {
+        if let Some(expected) = self.expected_count {
+            if expected != count {
+                return Err(anyhow!(
+                    "前缀 {} 下有 {} 个对象，与期望删除的 {} 个不一致，未删除任何对象",
+                    prefix,
+                    count,
+                    expected
+                ));
+            }
+            return Ok(());
+        }
+        if count > self.safety_limit && !self.force {
+            return Err(anyhow!(
+                "前缀 {} 下有 {} 个对象，超过安全上限 {}，需要设置 force 或 expected_count 确认，未删除任何对象",
+                prefix,
+                count,
+                self.safety_limit
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// 按前缀删除对象的结果
+#[derive(Debug, Default)]
+pub struct DeletePrefixReport {
+    /// 已删除的对象键
+    pub deleted: Vec<String>,
+    /// 删除失败的对象键及原因
+    pub failed: Vec<(String, String)>,
+}
+
+impl DeletePrefixReport {
+    /// 是否所有对象都已删除
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 生成批量删除的请求体，使用安静模式只返回失败的对象
+fn delete_body(keys: &[String]) -> String {
+    let objects: String = keys
+        .iter()
+        .map(|key| format!("<Object><Key>{}</Key></Object>", escape(key)))
+        .collect();
+    format!("<Delete><Quiet>true</Quiet>{}</Delete>", objects)
+}
+
+/// 解析批量删除响应中失败的对象，对象键去掉配置中的前缀
+fn parse_delete_errors(text: &str, key_prefix: &str) -> Vec<(String, String)> {
+    find_all_tags(text, "Error")
+        .into_iter()
+        .filter_map(|block| {
+            let key = unescape(find_tag(block, "Key")?);
+            let key = key.strip_prefix(key_prefix).unwrap_or(&key).to_string();
+            let reason = format!(
+                "{}: {}",
+                find_tag(block, "Code").unwrap_or_default(),
+                unescape(find_tag(block, "Message").unwrap_or_default())
+            );
+            Some((key, reason))
+        })
+        .collect()
+}
+
+impl Uploader {
+    /// 删除前缀下的所有对象
+    ///
+    /// 先列举出前缀下的全部对象并检查数量：超过 [`DeletePrefixOptions::safety_limit`] 时必须设置
+    /// `force`，或者通过 `expected_count` 给出与实际一致的数量，否则不删除任何对象，
+    /// 避免前缀写错（例如空字符串）时误删整个 Bucket。确认后按每批 1000 个对象批量删除。
+    ///
+    /// 未开启版本控制时删除不可恢复。列举之后新写入的对象不会被删除。
+    ///
+    /// # 参数
+    ///
+    /// * `prefix` - 对象键前缀，为空字符串时删除整个 Bucket 中的对象
+    /// * `opts` - 删除选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回已删除与删除失败的对象
+    ///
+    /// # 错误
+    ///
+    /// 数量未得到确认、列举或批量删除请求失败时返回错误。
+    pub async fn delete_prefix(
+        &self,
+        prefix: &str,
+        opts: &DeletePrefixOptions,
+    ) -> Result<DeletePrefixReport> {
+        let list_opts = ListOptions::new(prefix);
+        let mut cursor = None;
+        let mut keys = Vec::new();
+        loop {
+            let page = self.list_objects(&list_opts, cursor.as_ref()).await?;
+            keys.extend(page.items.into_iter().map(|object| object.key));
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        opts.confirm(prefix, keys.len() as u64)?;
+
+        let mut report = DeletePrefixReport::default();
+        for batch in keys.chunks(DELETE_BATCH_SIZE) {
+            let full_keys: Vec<_> = batch
+                .iter()
+                .map(|key| {
+                    format!(
+                        "{}{}",
+                        self.config.key_prefix(),
+                        self.config.stored_key(key)
+                    )
+                })
+                .collect();
+            let body = delete_body(&full_keys);
+            let request = CosRequest::bucket(Method::POST)
+                .param("delete", "")
+                .header("Content-Type", "application/xml")
+                .header("Content-MD5", content_md5(body.as_bytes()))
+                .body(body);
+            let text = read_xml(self.execute(request).await?).await?;
+
+            #[cfg_attr(not(feature = "crypto"), allow(unused_mut))]
+            let mut failed = parse_delete_errors(&text, self.config.key_prefix());
+            #[cfg(feature = "crypto")]
+            if let Some(encryption) = &self.config.key_encryption {
+                for (key, _) in &mut failed {
+                    if let Some(plain) = encryption.decrypt_key(key) {
+                        *key = plain;
+                    }
+                }
+            }
+            for key in batch {
+                if failed.iter().any(|(failed_key, _)| failed_key == key) {
+                    continue;
+                }
+                self.invalidate_cached(key);
+                report.deleted.push(key.clone());
+            }
+            for (key, reason) in &failed {
+                warn!("删除对象失败: {} ({})", key, reason);
+            }
+            report.failed.extend(failed);
+        }
+
+        info!(
+            "删除前缀 {} 下的对象: 成功 {} 个，失败 {} 个",
+            prefix,
+            report.deleted.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirm() {
+        let opts = DeletePrefixOptions::new().with_safety_limit(10);
+        assert!(opts.confirm("logs/", 10).is_ok());
+        assert!(opts.confirm("logs/", 11).is_err());
+        assert!(opts.clone().with_force(true).confirm("logs/", 11).is_ok());
+        let opts = opts.with_expected_count(11);
+        assert!(opts.confirm("logs/", 11).is_ok());
+        assert!(opts.confirm("logs/", 3).is_err());
+    }
+
+    #[test]
+    fn test_delete_xml() {
+        assert_eq!(
+            delete_body(&["p/a&b".to_string()]),
+            "<Delete><Quiet>true</Quiet><Object><Key>p/a&amp;b</Key></Object></Delete>"
+        );
+        let text = "<DeleteResult><Error><Key>p/a&amp;b</Key><Code>AccessDenied</Code>\
+                    <Message>Access Denied</Message></Error></DeleteResult>";
+        assert_eq!(
+            parse_delete_errors(text, "p/"),
+            vec![("a&b".to_string(), "AccessDenied: Access Denied".to_string())]
+        );
+    }
+}
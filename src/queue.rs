@@ -0,0 +1,418 @@
+use crate::checkpoint::{array_field, path_to_json, str_field, MultipartCheckpoint};
+use crate::handle::TransferControl;
+use crate::journal::UploadJournal;
+use crate::options::{UploadOptions, UploadVerification};
+use crate::transfer::{DuplicatePolicy, TransferManager};
+use crate::types::UploadResult;
+use crate::uploader::Metadata;
+use anyhow::{anyhow, Context, Result};
+use serde_json::{json, Value};
+use std::collections::{HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+/// 快照格式的版本号
+const SNAPSHOT_VERSION: u64 = 1;
+
+/// 上传队列中的一个条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedUpload {
+    /// 本地文件路径
+    pub file_path: PathBuf,
+    /// 对象键
+    pub object_key: String,
+    /// 自定义元数据
+    pub metadata: Option<Metadata>,
+    /// 进行中的分块上传的断点，尚未开始或使用普通上传时为 `None`
+    pub checkpoint: Option<MultipartCheckpoint>,
+}
+
+impl QueuedUpload {
+    pub(crate) fn to_value(&self) -> Value {
+        json!({
+            "file_path": path_to_json(&self.file_path),
+            "object_key": self.object_key,
+            "metadata": self.metadata,
+            "checkpoint": self.checkpoint.as_ref().map(MultipartCheckpoint::to_value),
+        })
+    }
+
+    pub(crate) fn from_value(value: &Value) -> Result<Self> {
+        let metadata = match value.get("metadata") {
+            None | Some(Value::Null) => None,
+            Some(Value::Object(map)) => Some(
+                map.iter()
+                    .map(|(k, v)| {
+                        v.as_str()
+                            .map(|v| (k.clone(), v.to_string()))
+                            .ok_or_else(|| anyhow!("元数据的值必须是字符串: {}", k))
+                    })
+                    .collect::<Result<_>>()?,
+            ),
+            Some(_) => return Err(anyhow!("元数据必须是对象")),
+        };
+        let checkpoint = match value.get("checkpoint") {
+            None | Some(Value::Null) => None,
+            Some(checkpoint) => Some(MultipartCheckpoint::from_value(checkpoint)?),
+        };
+
+        Ok(Self {
+            file_path: PathBuf::from(str_field(value, "file_path")?),
+            object_key: str_field(value, "object_key")?.to_string(),
+            metadata,
+            checkpoint,
+        })
+    }
+}
+
+/// 传输管理器上传队列的快照
+///
+/// 包含尚未完成的全部条目：排队中的文件，以及正在进行或失败的分块上传的断点。
+/// 保存到磁盘后，可以在进程重启后通过 [`TransferManager::restore`] 恢复并从断点继续。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TransferSnapshot {
+    /// 队列中的条目，按入队顺序
+    pub uploads: Vec<QueuedUpload>,
+}
+
+impl TransferSnapshot {
+    /// 快照的 JSON 表示
+    pub fn to_json(&self) -> String {
+        let uploads: Vec<_> = self.uploads.iter().map(QueuedUpload::to_value).collect();
+        let snapshot = json!({
+            "version": SNAPSHOT_VERSION,
+            "uploads": uploads,
+        });
+        serde_json::to_string_pretty(&snapshot).expect("快照序列化失败")
+    }
+
+    /// 从 JSON 解析快照
+    ///
+    /// # 错误
+    ///
+    /// JSON 格式错误、缺少字段或版本不受支持时返回错误。
+    pub fn from_json(text: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(text).context("解析传输快照失败")?;
+        let version = value.get("version").and_then(Value::as_u64);
+        if version != Some(SNAPSHOT_VERSION) {
+            return Err(anyhow!("不支持的传输快照版本: {:?}", version));
+        }
+
+        let uploads = array_field(&value, "uploads")?
+            .iter()
+            .map(QueuedUpload::from_value)
+            .collect::<Result<_>>()?;
+        Ok(Self { uploads })
+    }
+
+    /// 把快照写入文件
+    ///
+    /// 先写入同目录下的临时文件再重命名，写入过程中进程退出也不会留下不完整的快照。
+    pub async fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let mut temp = path.as_os_str().to_owned();
+        temp.push(".tmp");
+
+        tokio::fs::write(&temp, self.to_json()).await?;
+        tokio::fs::rename(&temp, path).await?;
+        Ok(())
+    }
+
+    /// 从文件读取快照
+    pub async fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = tokio::fs::read_to_string(path)
+            .await
+            .with_context(|| format!("读取传输快照失败: {:?}", path))?;
+        Self::from_json(&text)
+    }
+}
+
+/// 执行上传队列的报告
+#[derive(Debug, Default)]
+pub struct QueueReport {
+    /// 上传成功的对象键及结果
+    pub completed: Vec<(String, UploadResult)>,
+    /// 上传失败的对象键及失败原因，失败的条目保留在队列中
+    pub failed: Vec<(String, String)>,
+}
+
+impl QueueReport {
+    /// 是否没有失败的条目
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// 队列中的条目
+struct QueueEntry {
+    id: u64,
+    upload: QueuedUpload,
+    /// 上传日志中的 ID，不是通过日志入队时为 `None`
+    journal_id: Option<u64>,
+    /// 是否正在执行
+    active: bool,
+}
+
+/// 传输管理器的上传队列
+#[derive(Default)]
+pub(crate) struct UploadQueue {
+    entries: VecDeque<QueueEntry>,
+    next_id: u64,
+}
+
+impl UploadQueue {
+    pub(crate) fn push(&mut self, upload: QueuedUpload, journal_id: Option<u64>) {
+        self.next_id += 1;
+        self.entries.push_back(QueueEntry {
+            id: self.next_id,
+            upload,
+            journal_id,
+            active: false,
+        });
+    }
+
+    /// 取出下一个未在执行且不在 `skip` 中的条目，并标记为执行中
+    fn start_next(&mut self, skip: &HashSet<u64>) -> Option<(u64, Option<u64>, QueuedUpload)> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| !entry.active && !skip.contains(&entry.id))?;
+        entry.active = true;
+        Some((entry.id, entry.journal_id, entry.upload.clone()))
+    }
+
+    fn entry_mut(&mut self, id: u64) -> Option<&mut QueueEntry> {
+        self.entries.iter_mut().find(|entry| entry.id == id)
+    }
+}
+
+impl TransferManager {
+    /// 把文件加入上传队列，由 [`TransferManager::run_queue`] 执行
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键
+    /// * `metadata` - 自定义元数据
+    pub fn enqueue<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        metadata: Option<Metadata>,
+    ) {
+        self.queue.lock().unwrap().push(
+            QueuedUpload {
+                file_path: file_path.as_ref().to_path_buf(),
+                object_key: object_key.to_string(),
+                metadata,
+                checkpoint: None,
+            },
+            None,
+        );
+    }
+
+    /// 把文件写入上传日志后再加入上传队列
+    ///
+    /// 设置了上传日志（[`TransferManager::with_journal`]）时，返回成功即表示条目已经刷盘，
+    /// 进程崩溃或重启后重新打开日志仍能恢复；未设置上传日志时与 [`TransferManager::enqueue`] 相同。
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键
+    /// * `metadata` - 自定义元数据
+    ///
+    /// # 错误
+    ///
+    /// 写入上传日志失败，或设置了上传日志而文件路径不是 UTF-8 时返回错误，此时文件没有入队。
+    pub async fn enqueue_durable<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        metadata: Option<Metadata>,
+    ) -> Result<()> {
+        let upload = QueuedUpload {
+            file_path: file_path.as_ref().to_path_buf(),
+            object_key: object_key.to_string(),
+            metadata,
+            checkpoint: None,
+        };
+        let journal_id = match &self.journal {
+            Some(journal) => Some(journal.record_enqueue(&upload).await?),
+            None => None,
+        };
+        self.queue.lock().unwrap().push(upload, journal_id);
+        Ok(())
+    }
+
+    /// 设置上传日志，并把日志中尚未完成的文件加入上传队列
+    ///
+    /// 设置后通过 [`TransferManager::enqueue_durable`] 入队的文件先写入日志，
+    /// [`TransferManager::run_queue`] 上传后以 HEAD 校验对象（[`UploadVerification::Head`]），
+    /// 校验通过才在日志中记为完成。参见 [`UploadJournal`]。
+    pub fn with_journal(mut self, journal: UploadJournal) -> Self {
+        {
+            let mut queue = self.queue.lock().unwrap();
+            for (id, upload) in journal.take_pending() {
+                queue.push(upload, Some(id));
+            }
+        }
+        self.journal = Some(Arc::new(journal));
+        self
+    }
+
+    /// 上传队列当前状态的快照，包括正在执行的分块上传的最新断点
+    pub fn snapshot(&self) -> TransferSnapshot {
+        let queue = self.queue.lock().unwrap();
+        TransferSnapshot {
+            uploads: queue
+                .entries
+                .iter()
+                .map(|entry| entry.upload.clone())
+                .collect(),
+        }
+    }
+
+    /// 把快照中的条目加入上传队列
+    ///
+    /// 带有断点的条目在执行时会沿用原来的分块上传，只上传尚未完成的分块；
+    /// 如果本地文件在此期间被修改，则放弃旧的分块上传重新开始。
+    pub fn restore(&self, snapshot: TransferSnapshot) {
+        let mut queue = self.queue.lock().unwrap();
+        for upload in snapshot.uploads {
+            queue.push(upload, None);
+        }
+    }
+
+    /// 依次执行上传队列中的条目
+    ///
+    /// 成功的条目从队列中移除；失败的条目连同最新的断点保留在队列中，下一次执行或恢复快照后继续。
+    /// 与 [`TransferManager::upload_file`] 一样按 [`DuplicatePolicy`] 与同一对象键的其它上传协调，
+    /// 被拒绝的条目同样保留在队列中；通过日志入队的条目不共享其它上传的结果，[`DuplicatePolicy::Coalesce`] 时改为排队。
+    /// 执行期间可以随时调用 [`TransferManager::snapshot`] 保存进度。
+    /// 设置了传输计划（[`TransferManager::with_schedule`]）时，不允许传输的时段内暂停。
+    ///
+    /// # 返回值
+    ///
+    /// 本次执行的报告，单个条目的失败记录在报告中
+    pub async fn run_queue(&self) -> QueueReport {
+        let mut report = QueueReport::default();
+        let mut attempted = HashSet::new();
+
+        loop {
+            let Some((id, journal_id, upload)) = self.queue.lock().unwrap().start_next(&attempted)
+            else {
+                break;
+            };
+            attempted.insert(id);
+
+            self.wait_for_schedule().await;
+            let control = Arc::new(TransferControl::new());
+            let guard = self.spawn_schedule_guard(control.clone());
+
+            let queue = self.queue.clone();
+            let journal = self.journal.clone();
+            let on_checkpoint = move |checkpoint: &MultipartCheckpoint| {
+                if let Some(entry) = queue.lock().unwrap().entry_mut(id) {
+                    entry.upload.checkpoint = Some(checkpoint.clone());
+                }
+                if let (Some(journal), Some(journal_id)) = (&journal, journal_id) {
+                    journal.record_checkpoint(journal_id, checkpoint);
+                }
+            };
+            // 通过日志入队的文件必须由自己上传并校验通过才能记为完成，不共享其它上传的结果
+            let policy = match self.duplicate_policy {
+                DuplicatePolicy::Coalesce if journal_id.is_some() => DuplicatePolicy::Queue,
+                policy => policy,
+            };
+            let options = UploadOptions {
+                metadata: upload.metadata,
+                verify_after_upload: journal_id.map(|_| UploadVerification::Head),
+                ..UploadOptions::default()
+            };
+            let result = self
+                .coordinate(&upload.object_key, policy, || {
+                    self.uploader.upload_file_resumable(
+                        &upload.file_path,
+                        &upload.object_key,
+                        &options,
+                        upload.checkpoint,
+                        &on_checkpoint,
+                        Some(&control),
+                    )
+                })
+                .await;
+            if let Some(guard) = guard {
+                guard.abort();
+            }
+
+            if let (Ok(_), Some(journal), Some(journal_id)) = (&result, &self.journal, journal_id) {
+                // 记录失败时下次打开日志会重新上传该文件，不影响本次结果
+                if let Err(e) = journal.record_done(journal_id).await {
+                    warn!("{:#}", e);
+                }
+            }
+            let mut queue = self.queue.lock().unwrap();
+            match result {
+                Ok(result) => {
+                    queue.entries.retain(|entry| entry.id != id);
+                    report.completed.push((upload.object_key, result));
+                }
+                Err(e) => {
+                    error!("队列上传失败: {}: {:#}", upload.object_key, e);
+                    if let Some(entry) = queue.entry_mut(id) {
+                        entry.active = false;
+                    }
+                    report.failed.push((upload.object_key, format!("{:#}", e)));
+                }
+            }
+        }
+
+        info!(
+            "上传队列执行结束: 成功 {}，失败 {}",
+            report.completed.len(),
+            report.failed.len()
+        );
+        report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_round_trip() {
+        let snapshot = TransferSnapshot {
+            uploads: vec![
+                QueuedUpload {
+                    file_path: PathBuf::from("/data/a.txt"),
+                    object_key: "a.txt".to_string(),
+                    metadata: Some(Metadata::from([("user".to_string(), "1".to_string())])),
+                    checkpoint: None,
+                },
+                QueuedUpload {
+                    file_path: PathBuf::from("/data/b.bin"),
+                    object_key: "b.bin".to_string(),
+                    metadata: None,
+                    checkpoint: Some(MultipartCheckpoint {
+                        file_path: PathBuf::from("/data/b.bin"),
+                        object_key: "b.bin".to_string(),
+                        upload_id: "upload-1".to_string(),
+                        file_size: 11 * 1024 * 1024,
+                        file_mtime: 1700000000,
+                        part_size: 5 * 1024 * 1024,
+                        part_sizes: Vec::new(),
+                        completed_parts: vec![(1, "\"e1\"".to_string())],
+                    }),
+                },
+            ],
+        };
+
+        let parsed = TransferSnapshot::from_json(&snapshot.to_json()).unwrap();
+        assert_eq!(parsed, snapshot);
+        assert!(TransferSnapshot::from_json(r#"{"version": 99, "uploads": []}"#).is_err());
+    }
+}
@@ -0,0 +1,78 @@
+use crate::uploader::Uploader;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// 按固定间隔放行请求的限速器
+///
+/// 记录下一个可用的发送时刻，每个请求占用一个时刻并把它向后推一个间隔，
+/// 请求之间均匀分布，不会在空闲之后集中爆发。
+#[derive(Debug)]
+pub(crate) struct RequestRateLimiter {
+    /// 相邻两个请求之间的最小间隔
+    interval: Duration,
+    /// 下一个请求可以发送的时刻
+    next: Mutex<Instant>,
+}
+
+impl RequestRateLimiter {
+    fn new(requests_per_second: u32) -> Self {
+        Self {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// 占用一个发送时刻，返回请求可以发送的时刻
+    fn reserve(&self, now: Instant) -> Instant {
+        let mut next = self.next.lock().unwrap();
+        let slot = (*next).max(now);
+        *next = slot + self.interval;
+        slot
+    }
+
+    /// 等待到可以发送下一个请求
+    pub(crate) async fn acquire(&self) {
+        let now = Instant::now();
+        let slot = self.reserve(now);
+        if slot > now {
+            tokio::time::sleep(slot - now).await;
+        }
+    }
+}
+
+impl Uploader {
+    /// 限制每秒发送的请求数，用于遵守内部网关的请求配额
+    ///
+    /// 上传数万个小文件时瓶颈往往是请求数而不是带宽。限速器在该上传器及其所有克隆之间共享，
+    /// 目录上传、上传队列等并发任务的请求都按固定间隔依次发出；分块重试、地域重定向等
+    /// 每一次实际发出的 HTTP 请求都计入配额。备用 Bucket 与影子 Bucket 的上传器不受限制。
+    ///
+    /// # 参数
+    ///
+    /// * `requests_per_second` - 每秒最多发送的请求数，为 0 时按 1 处理
+    pub fn with_request_rate_limit(mut self, requests_per_second: u32) -> Self {
+        self.request_limiter = Some(Arc::new(RequestRateLimiter::new(requests_per_second)));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_rate_limiter() {
+        let limiter = RequestRateLimiter::new(10);
+        let interval = Duration::from_millis(100);
+        let now = Instant::now();
+        let first = limiter.reserve(now);
+        assert!(first <= now);
+        assert_eq!(limiter.reserve(now), first.max(now) + interval);
+        assert_eq!(limiter.reserve(now), first.max(now) + interval * 2);
+
+        // 空闲一段时间后不会积攒配额
+        let later = now + Duration::from_secs(5);
+        assert_eq!(limiter.reserve(later), later);
+        assert_eq!(limiter.reserve(later), later + interval);
+    }
+}
@@ -0,0 +1,603 @@
+use crate::config::EndpointKind;
+use crate::error::{CosError, SignatureMismatch};
+use crate::probe::SelectedEndpoint;
+use crate::signature::Signature;
+use crate::types::request_id_of;
+use crate::uploader::Uploader;
+use crate::xml::{find_tag, unescape};
+use anyhow::Result;
+use bytes::Bytes;
+use reqwest::{Method, Response};
+use std::collections::HashMap;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::{info_span, warn, Instrument, Span};
+use urlencoding::encode as url_encode;
+
+/// 请求签名的有效期（秒）
+const SIGN_EXPIRE: i64 = 3600;
+/// 控制面 XML 响应体的大小上限
+///
+/// 批量删除等最多 1000 条的结果在对象键接近 850 字节上限时约 2 MB，留有充足的余量。
+/// 列举使用更低的上限 [`MAX_LIST_XML_BODY`](crate::list::MAX_LIST_XML_BODY)。
+pub(crate) const MAX_XML_BODY: usize = 16 * 1024 * 1024;
+/// 错误响应体的读取上限，超出的部分直接丢弃
+const MAX_ERROR_BODY: usize = 64 * 1024;
+
+/// 一次待签名的 COS 请求
+///
+/// 可以通过 [`Uploader::to_http_request`] 转换为签名后的 `http::Request<Bytes>`，交给 hyper、
+/// tower 等其它执行器发送；也可以通过 [`CosRequest::from_http`] 从 `http::Request` 构建。
+#[derive(Debug, Clone)]
+pub struct CosRequest {
+    /// HTTP 方法
+    pub(crate) method: Method,
+    /// 对象键，不含开头的 `/`；Bucket 级别的请求为空字符串
+    pub(crate) object_key: String,
+    /// 查询参数，值为空字符串时只输出参数名（如 `?uploads`）
+    pub(crate) params: HashMap<String, String>,
+    /// 参与签名的请求头（`Host` 与 `Content-Length` 会自动添加）
+    pub(crate) headers: HashMap<String, String>,
+    /// 请求体
+    pub(crate) body: Option<Bytes>,
+    /// 是否为 Bucket 级别的请求，对象级别的请求会在发送前检查对象键
+    bucket_level: bool,
+}
+
+impl CosRequest {
+    /// 创建对象级别的请求
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法
+    /// * `object_key` - 对象键，不含开头的 `/`，也不含 [`Config::key_prefix`](crate::Config#structfield.key_prefix)
+    pub fn new(method: Method, object_key: &str) -> Self {
+        Self {
+            method,
+            object_key: object_key.to_string(),
+            params: HashMap::new(),
+            headers: HashMap::new(),
+            body: None,
+            bucket_level: false,
+        }
+    }
+
+    /// 创建 Bucket 级别的请求（对象键为空字符串）
+    pub fn bucket(method: Method) -> Self {
+        Self {
+            bucket_level: true,
+            ..Self::new(method, "")
+        }
+    }
+
+    /// 添加查询参数，值为空字符串时只输出参数名（如 `?uploads`）
+    pub fn param(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.params.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// 添加参与签名的请求头
+    pub fn header(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.headers.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// 设置请求体
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        self.body = Some(body.into());
+        self
+    }
+
+    /// 是否为创建对象的请求：普通上传、服务端复制、初始化分块上传与追加上传
+    #[cfg(feature = "runtime")]
+    pub(crate) fn creates_object(&self) -> bool {
+        if self.bucket_level {
+            return false;
+        }
+        match self.method {
+            Method::PUT => self.params.is_empty(),
+            Method::POST => {
+                self.params.contains_key("uploads") || self.params.contains_key("append")
+            }
+            _ => false,
+        }
+    }
+
+    /// 从 `http::Request` 构建请求
+    ///
+    /// 路径（去掉开头的 `/` 并解码）作为对象键，路径为 `/` 时为 Bucket 级别的请求；
+    /// URI 中的域名会被忽略，`Host`、`Authorization`、`Content-Length` 与临时密钥头部在签名时重新生成。
+    ///
+    /// # 错误
+    ///
+    /// 路径、查询参数或请求头的值不是合法的 UTF-8 时返回错误。
+    pub fn from_http(request: http::Request<Bytes>) -> Result<Self> {
+        let (parts, body) = request.into_parts();
+        // 零字节的 PUT / POST 同样要保留请求体，签名时才会带上 `Content-Length: 0`
+        let keep_body = !body.is_empty()
+            || parts.headers.contains_key(http::header::CONTENT_LENGTH)
+            || matches!(parts.method, Method::PUT | Method::POST);
+        let object_key = urlencoding::decode(parts.uri.path().trim_start_matches('/'))?;
+        let mut cos_request = if object_key.is_empty() {
+            Self::bucket(parts.method)
+        } else {
+            Self::new(parts.method, &object_key)
+        };
+
+        for pair in parts.uri.query().unwrap_or("").split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            cos_request =
+                cos_request.param(&urlencoding::decode(key)?, urlencoding::decode(value)?);
+        }
+
+        for (name, value) in &parts.headers {
+            if is_generated_header(name.as_str()) {
+                continue;
+            }
+            cos_request = cos_request.header(name.as_str(), value.to_str()?);
+        }
+
+        if keep_body {
+            cos_request = cos_request.body(body);
+        }
+        Ok(cos_request)
+    }
+
+    /// 生成 URL 中的查询字符串（含开头的 `?`）
+    fn query(&self) -> String {
+        if self.params.is_empty() {
+            return String::new();
+        }
+
+        let mut params: Vec<_> = self.params.iter().collect();
+        params.sort_by(|a, b| a.0.cmp(b.0));
+
+        let query = params
+            .iter()
+            .map(|(k, v)| {
+                if v.is_empty() {
+                    url_encode(k).into_owned()
+                } else {
+                    format!("{}={}", url_encode(k), url_encode(v))
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("&");
+
+        format!("?{}", query)
+    }
+}
+
+/// 是否为签名时重新生成的请求头
+fn is_generated_header(name: &str) -> bool {
+    [
+        "host",
+        "authorization",
+        "content-length",
+        "x-cos-security-token",
+    ]
+    .iter()
+    .any(|generated| name.eq_ignore_ascii_case(generated))
+}
+
+/// 读取响应头的文本值
+pub(crate) fn header_of(response: &Response, name: &str) -> Option<String> {
+    response
+        .headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// 读取响应体，超过 `limit` 字节时停止读取
+///
+/// # 返回值
+///
+/// 返回读取到的内容（不超过 `limit` 字节）与响应体是否超过上限
+async fn read_limited(response: &mut Response, limit: usize) -> Result<(Vec<u8>, bool)> {
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > limit {
+            body.extend_from_slice(&chunk[..limit - body.len()]);
+            return Ok((body, true));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok((body, false))
+}
+
+/// 读取控制面请求（列举、初始化与完成分块上传、配置查询等）的 XML 响应体
+///
+/// 边读取边累计长度，异常的端点或代理返回超大的响应时立即停止读取，不会无限制地占用内存。
+///
+/// # 错误
+///
+/// 响应体超过 [`MAX_XML_BODY`] 时返回错误。
+pub(crate) async fn read_xml(response: Response) -> Result<String> {
+    read_xml_limited(response, MAX_XML_BODY).await
+}
+
+/// 读取 XML 响应体，超过 `limit` 字节时停止读取并返回错误
+pub(crate) async fn read_xml_limited(mut response: Response, limit: usize) -> Result<String> {
+    let declared_too_large = response
+        .content_length()
+        .is_some_and(|len| len > limit as u64);
+    let (body, truncated) = if declared_too_large {
+        (Vec::new(), true)
+    } else {
+        read_limited(&mut response, limit).await?
+    };
+    if truncated {
+        return Err(anyhow::anyhow!(
+            "响应体超过 {} 字节的上限，已停止读取: {}",
+            limit,
+            object_url_of(&response)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// 是否为建立连接阶段的网络错误
+fn is_connect_error(error: &anyhow::Error) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_connect())
+}
+
+/// 从 `SignatureDoesNotMatch` 的错误响应中取出 COS 期望的待签字符串，与本地签名放在一起
+fn signature_mismatch(body: &str, signature: &Signature) -> SignatureMismatch {
+    let expected = |tag| find_tag(body, tag).map(|v| redact_token(&unescape(v)));
+    SignatureMismatch {
+        expected_format_string: expected("FormatString"),
+        expected_string_to_sign: expected("StringToSign"),
+        format_string: redact_token(&signature.format_string),
+        string_to_sign: signature.string_to_sign.clone(),
+    }
+}
+
+/// 隐去规范请求中 `x-cos-security-token` 的值
+fn redact_token(text: &str) -> String {
+    const KEY: &str = "x-cos-security-token=";
+    let Some(start) = text.find(KEY).map(|i| i + KEY.len()) else {
+        return text.to_string();
+    };
+    let end = text[start..]
+        .find(['&', '\n'])
+        .map_or(text.len(), |i| start + i);
+    format!("{}***{}", &text[..start], &text[end..])
+}
+
+/// 把 `http::Request` 转换为 reqwest 的请求
+///
+/// 只有带 `Content-Length` 头部的请求才设置请求体，避免 GET、HEAD 等请求带上空的请求体。
+fn to_reqwest(request: http::Request<Bytes>) -> Result<reqwest::Request> {
+    let (parts, body) = request.into_parts();
+    let mut reqwest_request =
+        reqwest::Request::new(parts.method, reqwest::Url::parse(&parts.uri.to_string())?);
+    if parts.headers.contains_key(http::header::CONTENT_LENGTH) {
+        *reqwest_request.body_mut() = Some(body.into());
+    }
+    *reqwest_request.headers_mut() = parts.headers;
+    Ok(reqwest_request)
+}
+
+/// 去掉 URL 中的查询参数，得到对象的访问地址
+pub(crate) fn object_url_of(response: &Response) -> String {
+    let mut url = response.url().clone();
+    url.set_query(None);
+    url.to_string()
+}
+
+impl Uploader {
+    /// 指定地域下 Bucket 的访问域名
+    ///
+    /// 使用端点探测选出的域名类型。
+    pub(crate) fn host(&self, region: &str) -> String {
+        let endpoint = self.endpoint.read().unwrap();
+        if region == self.config.region {
+            endpoint.host.to_string()
+        } else {
+            self.config.host_of(endpoint.kind, region)
+        }
+    }
+
+    /// 签名并发送请求
+    ///
+    /// 只有成功的响应会以 `Ok` 返回，失败时返回包含 [`CosError`] 的错误。
+    /// 设置了慢请求阈值（[`Uploader::with_slow_request_threshold`]）时，耗时超过阈值的请求会输出警告。
+    /// 若 Bucket 不在配置的地域且开启了 `follow_region_redirects`，会向正确的地域重试一次；
+    /// 使用内网域名而无法建立连接时，回退到地域域名重试一次。
+    /// 对象级别的请求在发送前检查对象键，不合法时返回 [`CosError::InvalidObjectKey`]。
+    ///
+    /// 每个请求都在 `cos_request` span 中执行，字段遵循 OpenTelemetry 的语义约定
+    /// （`rpc.system`、`net.peer.name`、`http.status_code` 等，另有 `cos.bucket`、`cos.key`），
+    /// 经 `tracing-opentelemetry` 导出到 Jaeger、Tempo 等系统时无需额外配置即可按这些属性检索。
+    pub(crate) async fn execute(&self, request: CosRequest) -> Result<Response> {
+        let bucket = self.config.bucket_name()?;
+        if !request.bucket_level {
+            self.config.check_object_key(&request.object_key)?;
+        }
+        self.check_key_policy(&request)?;
+        self.check_content_type(&request)?;
+
+        let span = info_span!(
+            "cos_request",
+            otel.name = %format_args!("COS {}", request.method),
+            otel.kind = "client",
+            otel.status_code = Empty,
+            rpc.system = "cos",
+            http.method = %request.method,
+            http.status_code = Empty,
+            net.peer.name = Empty,
+            cos.bucket = %bucket,
+            cos.region = Empty,
+            cos.key = %request.object_key,
+            cos.request_id = Empty,
+        );
+        let started = Instant::now();
+        let outcome = self
+            .execute_checked(&request)
+            .instrument(span.clone())
+            .await;
+        if outcome.is_err() {
+            span.record("otel.status_code", "ERROR");
+        }
+        self.report_slow_request(&request, started.elapsed(), &outcome);
+        outcome
+    }
+
+    /// 发送已检查过的请求，按需向正确的地域或地域域名重试
+    async fn execute_checked(&self, request: &CosRequest) -> Result<Response> {
+        let mut region = self.config.region.clone();
+        let mut redirected = false;
+        let mut fell_back = false;
+
+        loop {
+            let used_internal = self.current_endpoint() == EndpointKind::Internal;
+            let outcome = match self.send_once(request, &region).await {
+                Err(e) if used_internal && !fell_back && is_connect_error(&e) => {
+                    self.fall_back_from_internal(&e);
+                    fell_back = true;
+                    continue;
+                }
+                outcome => outcome?,
+            };
+
+            match outcome {
+                Ok(response) => return Ok(response),
+                Err(CosError::WrongRegion { expected, .. })
+                    if self.config.follow_region_redirects && !redirected =>
+                {
+                    warn!(
+                        "Bucket {} 实际位于地域 {}，而不是配置的 {}，正在重试",
+                        self.config.bucket, expected, region
+                    );
+                    region = expected;
+                    redirected = true;
+                }
+                Err(err) => return Err(err.into()),
+            }
+        }
+    }
+
+    /// 内网域名无法建立连接时切换到地域域名，之后的请求都使用地域域名
+    ///
+    /// 只在连接阶段失败时调用，此时请求尚未发出，重试不会重复执行请求。
+    fn fall_back_from_internal(&self, error: &anyhow::Error) {
+        let mut endpoint = self.endpoint.write().unwrap();
+        // 并发的请求可能已经完成了切换
+        if endpoint.kind == EndpointKind::Internal {
+            warn!(
+                "无法连接内网域名 {}，回退到地域域名: {}",
+                endpoint.host, error
+            );
+            *endpoint = SelectedEndpoint::new(&self.config, EndpointKind::Regional);
+        }
+    }
+
+    /// 把请求转换为签名后的 `http::Request`
+    ///
+    /// 使用配置的地域与端点探测选出的域名，对象键加上 [`Config::key_prefix`](crate::Config#structfield.key_prefix)。
+    /// 签名的有效期为一小时，得到的请求可以交给任意 HTTP 执行器发送，响应可以用
+    /// [`CosError::from_http_response`] 解析错误。
+    ///
+    /// # 错误
+    ///
+    /// 对象级别的请求对象键不合法，或请求头不能作为 HTTP 头部时返回错误。
+    pub fn to_http_request(&self, request: &CosRequest) -> Result<http::Request<Bytes>> {
+        self.config.bucket_name()?;
+        if !request.bucket_level {
+            self.config.check_object_key(&request.object_key)?;
+        }
+        let (http_request, _) = self.build_http_request(request, &self.config.region)?;
+        Ok(http_request)
+    }
+
+    /// 构建发往指定地域的签名请求，同时返回签名的中间结果用于排查签名问题
+    fn build_http_request(
+        &self,
+        request: &CosRequest,
+        region: &str,
+    ) -> Result<(http::Request<Bytes>, Signature)> {
+        let host = self.host(region);
+        let path = self.config.object_path(&request.object_key);
+        // 经由 URL 解析对路径中的非 ASCII 字符与空格进行百分号编码
+        let url = reqwest::Url::parse(&format!(
+            "{}://{}{}{}",
+            self.config.scheme(),
+            host,
+            path,
+            request.query()
+        ))?;
+
+        let mut headers = request.headers.clone();
+        headers.insert("Host".to_string(), host.clone());
+        if let Some(body) = &request.body {
+            headers.insert("Content-Length".to_string(), body.len().to_string());
+        }
+        if let Some(token) = &self.config.security_token {
+            headers.insert("x-cos-security-token".to_string(), token.clone());
+        }
+
+        let signature = self.signer.sign(
+            request.method.as_str(),
+            &path,
+            &request.params,
+            &self.config.signed_headers.select(&headers),
+            SIGN_EXPIRE,
+        );
+
+        let mut builder = http::Request::builder()
+            .method(request.method.clone())
+            .uri(url.as_str())
+            .header("Authorization", &signature.authorization);
+        for (key, value) in headers {
+            builder = builder.header(key, value);
+        }
+        let http_request = builder.body(request.body.clone().unwrap_or_default())?;
+        Ok((http_request, signature))
+    }
+
+    /// 向指定地域发送一次请求，服务端错误以内层 `Err` 返回
+    async fn send_once(
+        &self,
+        request: &CosRequest,
+        region: &str,
+    ) -> Result<std::result::Result<Response, CosError>> {
+        let (http_request, signature) = self.build_http_request(request, region)?;
+        let span = Span::current();
+        span.record("cos.region", region);
+        if let Some(host) = http_request.uri().host() {
+            span.record("net.peer.name", host);
+        }
+        if let Some(limiter) = &self.request_limiter {
+            limiter.acquire().await;
+        }
+        let response = self.client.execute(to_reqwest(http_request)?).await?;
+        span.record("http.status_code", response.status().as_u16());
+        if let Some(request_id) = request_id_of(response.headers()) {
+            span.record("cos.request_id", request_id);
+        }
+
+        if response.status().is_success() {
+            return Ok(Ok(response));
+        }
+
+        let status = response.status();
+        let headers = response.headers().clone();
+        let mut response = response;
+        let body = match read_limited(&mut response, MAX_ERROR_BODY).await {
+            Ok((body, _)) => String::from_utf8_lossy(&body).into_owned(),
+            Err(_) => String::new(),
+        };
+        let err = CosError::from_response(status, &headers, &body);
+
+        if self.config.debug_signature && err.code() == Some("SignatureDoesNotMatch") {
+            let mismatch = signature_mismatch(&body, &signature);
+            return Err(anyhow::Error::new(err).context(mismatch));
+        }
+        Ok(Err(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_http_request_round_trip() {
+        let uploader = Uploader::new(crate::Config::new(
+            "id".into(),
+            "key".into(),
+            "ap-guangzhou".into(),
+            "b-1250000000".into(),
+        ));
+        let request = CosRequest::new(Method::PUT, "dir/a b.txt")
+            .param("tagging", "")
+            .header("Content-Type", "text/plain")
+            .body("abc");
+
+        let http_request = uploader.to_http_request(&request).unwrap();
+        assert_eq!(
+            http_request.uri(),
+            "https://b-1250000000.cos.ap-guangzhou.myqcloud.com/dir/a%20b.txt?tagging"
+        );
+        assert_eq!(http_request.headers()["content-length"], "3");
+        assert!(http_request.headers()["authorization"]
+            .to_str()
+            .unwrap()
+            .contains("q-url-param-list=tagging"));
+
+        let parsed = CosRequest::from_http(http_request).unwrap();
+        assert_eq!(parsed.object_key, "dir/a b.txt");
+        assert_eq!(parsed.params, request.params);
+        assert_eq!(
+            parsed.headers,
+            HashMap::from([("content-type".to_string(), "text/plain".to_string())])
+        );
+        assert_eq!(parsed.body, request.body);
+
+        assert!(uploader
+            .to_http_request(&CosRequest::new(Method::GET, ""))
+            .is_err());
+
+        // 零字节的 PUT（如目录占位对象）转换后仍带有 `Content-Length: 0`
+        let empty = CosRequest::new(Method::PUT, "dir/").body(Bytes::new());
+        let http_request = uploader.to_http_request(&empty).unwrap();
+        assert_eq!(http_request.headers()["content-length"], "0");
+        let parsed = CosRequest::from_http(http_request).unwrap();
+        assert_eq!(parsed.body, Some(Bytes::new()));
+        let http_request = uploader.to_http_request(&parsed).unwrap();
+        assert_eq!(http_request.headers()["content-length"], "0");
+
+        let get = http::Request::get("/a.txt").body(Bytes::new()).unwrap();
+        assert_eq!(CosRequest::from_http(get).unwrap().body, None);
+    }
+
+    #[tokio::test]
+    async fn test_read_xml_limit() {
+        let response = Response::from(http::Response::new("<Result/>"));
+        assert_eq!(read_xml(response).await.unwrap(), "<Result/>");
+
+        let response = Response::from(http::Response::new(vec![b'a'; MAX_XML_BODY + 1]));
+        assert!(read_xml(response).await.is_err());
+
+        let response = Response::from(http::Response::new(vec![b'a'; 1025]));
+        assert!(read_xml_limited(response, 1024).await.is_err());
+
+        let mut response = Response::from(http::Response::new(vec![b'a'; MAX_ERROR_BODY * 2]));
+        let (body, truncated) = read_limited(&mut response, MAX_ERROR_BODY).await.unwrap();
+        assert!(truncated);
+        assert_eq!(body.len(), MAX_ERROR_BODY);
+    }
+
+    #[test]
+    fn test_signature_mismatch() {
+        let signature = Signature {
+            authorization: String::new(),
+            format_string: "put\n/a\n\nhost=b&x-cos-security-token=secret\n".to_string(),
+            string_to_sign: "sha1\n1;2\nabc\n".to_string(),
+        };
+        let body = "<Error><Code>SignatureDoesNotMatch</Code>\
+                    <FormatString>put\n/a\n\nhost=c&amp;x-cos-security-token=secret\n</FormatString>\
+                    <StringToSign>sha1\n1;2\nabc\n</StringToSign></Error>";
+
+        let mismatch = signature_mismatch(body, &signature);
+        assert_eq!(
+            mismatch.format_string,
+            "put\n/a\n\nhost=b&x-cos-security-token=***\n"
+        );
+        assert_eq!(
+            mismatch.expected_format_string.as_deref(),
+            Some("put\n/a\n\nhost=c&x-cos-security-token=***\n")
+        );
+
+        let text = mismatch.to_string();
+        assert!(text.contains("  = \"put\""));
+        assert!(text.contains("  ! COS : \"host=c&x-cos-security-token=***\""));
+        assert!(!text.contains("secret"));
+    }
+}
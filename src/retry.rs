@@ -0,0 +1,82 @@
+use crate::error::is_retryable;
+use crate::uploader::Uploader;
+use std::sync::Arc;
+
+/// 自定义哪些错误值得重试
+///
+/// 默认只重试网络错误与 COS 的 5xx/429 响应。经过网关或代理访问 COS 时，可以据此把
+/// 特定的响应（例如网关返回 HTML 页面的 502）或本地错误也视为可重试，或者反过来禁止重试某些错误。
+/// 重试次数、退避间隔与重试预算不变。
+///
+/// 闭包 `Fn(&anyhow::Error, bool) -> bool` 也实现了该 trait。
+pub trait RetryClassifier: Send + Sync {
+    /// 判断错误是否值得重试
+    ///
+    /// # 参数
+    ///
+    /// * `error` - 请求或上传失败的错误，可以 `downcast_ref::<CosError>()` 查看状态码与错误码
+    /// * `default` - 默认的判断结果
+    ///
+    /// # 返回值
+    ///
+    /// 返回 `true` 时重试（或切换到备用 Bucket），返回 `default` 则保持默认行为
+    fn should_retry(&self, error: &anyhow::Error, default: bool) -> bool;
+}
+
+impl<F> RetryClassifier for F
+where
+    F: Fn(&anyhow::Error, bool) -> bool + Send + Sync,
+{
+    fn should_retry(&self, error: &anyhow::Error, default: bool) -> bool {
+        self(error, default)
+    }
+}
+
+impl Uploader {
+    /// 设置自定义的重试判断，参见 [`RetryClassifier`]
+    ///
+    /// 对普通上传、分块上传、批量上传中的单个文件以及是否切换到备用 Bucket 的判断都生效。
+    pub fn with_retry_classifier(mut self, classifier: Arc<dyn RetryClassifier>) -> Self {
+        self.retry_classifier = Some(classifier);
+        self
+    }
+
+    /// 判断错误是否值得重试，设置了 [`RetryClassifier`] 时由其决定
+    pub(crate) fn is_retryable(&self, error: &anyhow::Error) -> bool {
+        let default = is_retryable(error);
+        match &self.retry_classifier {
+            Some(classifier) => classifier.should_retry(error, default),
+            None => default,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CosError;
+
+    #[test]
+    fn test_retry_classifier() {
+        let classifier = |error: &anyhow::Error, default: bool| match error.downcast_ref() {
+            Some(CosError::Service { status: 404, .. }) => true,
+            Some(CosError::Service { status: 503, .. }) => false,
+            _ => default,
+        };
+        let service = |status| -> anyhow::Error {
+            CosError::Service {
+                status,
+                code: "Unknown".to_string(),
+                message: String::new(),
+                request_id: None,
+            }
+            .into()
+        };
+        let e = service(404);
+        assert!(classifier.should_retry(&e, is_retryable(&e)));
+        let e = service(503);
+        assert!(!classifier.should_retry(&e, is_retryable(&e)));
+        let e = service(500);
+        assert!(classifier.should_retry(&e, is_retryable(&e)));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::handle::{TransferControl, TransferState};
+use crate::transfer::TransferManager;
+use chrono::{Local, NaiveTime};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::info;
+
+/// 检查传输计划的间隔
+const SCHEDULE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// 传输计划，决定当前是否允许传输
+///
+/// 已为 `Fn() -> bool` 闭包实现，可以直接用回调判断，例如在按流量计费的网络下返回 `false`。
+pub trait TransferSchedule: Send + Sync {
+    /// 当前是否允许传输
+    fn is_allowed(&self) -> bool;
+}
+
+impl<F> TransferSchedule for F
+where
+    F: Fn() -> bool + Send + Sync,
+{
+    fn is_allowed(&self) -> bool {
+        self()
+    }
+}
+
+/// 每天的本地时间窗口，例如只在 00:00–06:00 传输
+///
+/// 窗口包含开始时间、不包含结束时间；开始时间晚于结束时间时跨越午夜，例如 22:00–06:00；
+/// 两者相同时全天允许。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimeWindow {
+    /// 开始时间（本地时间）
+    pub start: NaiveTime,
+    /// 结束时间（本地时间）
+    pub end: NaiveTime,
+}
+
+impl TimeWindow {
+    /// 创建时间窗口
+    pub fn new(start: NaiveTime, end: NaiveTime) -> Self {
+        Self { start, end }
+    }
+
+    /// 给定时间是否在窗口内
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start == self.end || (self.start <= time && time < self.end)
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+impl TransferSchedule for TimeWindow {
+    fn is_allowed(&self) -> bool {
+        self.contains(Local::now().time())
+    }
+}
+
+impl TransferManager {
+    /// 设置传输计划
+    ///
+    /// 执行上传队列时，不允许传输的时段内暂停：不再开始新的分块，已在上传的分块完成后记录到断点中，
+    /// 恢复允许后从断点继续。[`TransferManager::upload_file`] 会在允许传输时才开始，开始后不再暂停。
+    /// 计划每 30 秒检查一次。
+    pub fn with_schedule(mut self, schedule: Arc<dyn TransferSchedule>) -> Self {
+        self.schedule = Some(schedule);
+        self
+    }
+
+    /// 等待传输计划允许传输，没有设置计划时立即返回
+    pub(crate) async fn wait_for_schedule(&self) {
+        let Some(schedule) = &self.schedule else {
+            return;
+        };
+        if !schedule.is_allowed() {
+            info!("当前不在允许传输的时段，等待中");
+            while !schedule.is_allowed() {
+                tokio::time::sleep(SCHEDULE_POLL_INTERVAL).await;
+            }
+            info!("进入允许传输的时段，开始传输");
+        }
+    }
+
+    /// 在后台按传输计划暂停或继续传输，没有设置计划时返回 `None`
+    ///
+    /// 传输结束后需要调用返回句柄的 `abort`。
+    pub(crate) fn spawn_schedule_guard(
+        &self,
+        control: Arc<TransferControl>,
+    ) -> Option<JoinHandle<()>> {
+        let schedule = self.schedule.clone()?;
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(SCHEDULE_POLL_INTERVAL);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                let state = if schedule.is_allowed() {
+                    TransferState::Running
+                } else {
+                    TransferState::Paused
+                };
+                if control.state() != state {
+                    info!("按传输计划切换状态: {:?}", state);
+                    control.set(state);
+                }
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn time(hour: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_time_window() {
+        let night = TimeWindow::new(time(0), time(6));
+        assert!(night.contains(time(0)));
+        assert!(night.contains(time(5)));
+        assert!(!night.contains(time(6)));
+        assert!(!night.contains(time(12)));
+
+        let overnight = TimeWindow::new(time(22), time(6));
+        assert!(overnight.contains(time(23)));
+        assert!(overnight.contains(time(3)));
+        assert!(!overnight.contains(time(12)));
+
+        assert!(TimeWindow::new(time(8), time(8)).contains(time(20)));
+    }
+}
@@ -0,0 +1,165 @@
+use crate::types::{DeleteResult, ObjectMetadata, UploadResult};
+use crate::uploader::{Metadata, Uploader};
+use anyhow::Result;
+use std::path::Path;
+use std::sync::Arc;
+
+/// 限定在某个租户前缀下的上传器
+///
+/// 所有对象键都会被拼接到 `tenants/{id}/` 之下，试图通过 `..`、`.` 或绝对路径
+/// 逃逸出该前缀的对象键会被拒绝。适合在多租户服务中把受限的句柄交给各租户的代码路径。
+#[derive(Clone)]
+pub struct ScopedUploader {
+    inner: Arc<Uploader>,
+    tenant_id: String,
+    prefix: String,
+}
+
+impl ScopedUploader {
+    /// 创建限定在指定租户下的上传器
+    ///
+    /// # 参数
+    ///
+    /// * `uploader` - 共享的上传器实例
+    /// * `tenant_id` - 租户 ID，不能为空，也不能包含 `/`、`\` 或等于 `.`、`..`
+    ///
+    /// # 错误
+    ///
+    /// 租户 ID 不合法时返回错误。
+    pub fn new(uploader: Arc<Uploader>, tenant_id: &str) -> Result<Self> {
+        if tenant_id.is_empty()
+            || tenant_id == "."
+            || tenant_id == ".."
+            || tenant_id.contains(['/', '\\'])
+        {
+            return Err(anyhow::anyhow!("非法的租户 ID: {:?}", tenant_id));
+        }
+
+        Ok(Self {
+            inner: uploader,
+            tenant_id: tenant_id.to_string(),
+            prefix: format!("tenants/{}/", tenant_id),
+        })
+    }
+
+    /// 租户 ID
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    /// 该租户的对象键前缀，形如 `tenants/{id}/`
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// 将租户内的相对对象键转换为完整的对象键
+    ///
+    /// # 错误
+    ///
+    /// 对象键为空、以 `/` 开头、包含 `\`、空路径段或 `.`/`..` 路径段时返回错误。
+    pub fn scoped_key(&self, object_key: &str) -> Result<String> {
+        check_relative_key(object_key)?;
+
+        Ok(format!("{}{}", self.prefix, object_key))
+    }
+
+    /// 上传文件到租户前缀下
+    ///
+    /// 参见 [`Uploader::upload_file`]。
+    pub async fn upload_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        metadata: Option<Metadata>,
+    ) -> Result<UploadResult> {
+        let object_key = self.scoped_key(object_key)?;
+        self.inner
+            .upload_file(file_path, &object_key, metadata)
+            .await
+    }
+
+    /// 获取租户前缀下对象的元数据
+    ///
+    /// 参见 [`Uploader::get_object_metadata`]。
+    pub async fn get_object_metadata(&self, object_key: &str) -> Result<ObjectMetadata> {
+        let object_key = self.scoped_key(object_key)?;
+        self.inner.get_object_metadata(&object_key).await
+    }
+
+    /// 删除租户前缀下的对象
+    ///
+    /// 参见 [`Uploader::delete_object`]。
+    pub async fn delete_object(&self, object_key: &str) -> Result<DeleteResult> {
+        let object_key = self.scoped_key(object_key)?;
+        self.inner.delete_object(&object_key).await
+    }
+}
+
+/// 检查对象键能否安全地作为相对路径使用
+///
+/// # 错误
+///
+/// 对象键为空、以 `/` 开头、包含 `\`、空路径段或 `.`/`..` 路径段时返回错误。
+pub(crate) fn check_relative_key(object_key: &str) -> Result<()> {
+    if object_key.is_empty() {
+        return Err(anyhow::anyhow!("对象键不能为空"));
+    }
+    if object_key.starts_with('/') {
+        return Err(anyhow::anyhow!("不允许使用绝对对象键: {}", object_key));
+    }
+    if object_key.contains('\\') {
+        return Err(anyhow::anyhow!("对象键不能包含反斜杠: {}", object_key));
+    }
+    if object_key
+        .split('/')
+        .any(|segment| segment.is_empty() || segment == "." || segment == "..")
+    {
+        return Err(anyhow::anyhow!("对象键包含非法的路径段: {}", object_key));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Config;
+
+    fn scoped(tenant_id: &str) -> Result<ScopedUploader> {
+        let config = Config::new(
+            "id".to_string(),
+            "key".to_string(),
+            "ap-guangzhou".to_string(),
+            "bucket-1250000000".to_string(),
+        );
+        ScopedUploader::new(Arc::new(Uploader::new(config)), tenant_id)
+    }
+
+    #[test]
+    fn test_scoped_key_jail() {
+        let uploader = scoped("42").unwrap();
+        assert_eq!(
+            uploader.scoped_key("a/b.txt").unwrap(),
+            "tenants/42/a/b.txt"
+        );
+
+        for key in [
+            "",
+            "/etc/passwd",
+            "../43/x",
+            "a/../../x",
+            "a//b",
+            "./a",
+            "a\\b",
+        ] {
+            assert!(uploader.scoped_key(key).is_err(), "应拒绝对象键: {:?}", key);
+        }
+    }
+
+    #[test]
+    fn test_invalid_tenant_id() {
+        for id in ["", ".", "..", "a/b", "a\\b"] {
+            assert!(scoped(id).is_err(), "应拒绝租户 ID: {:?}", id);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+use crate::error::is_not_found;
+use crate::list::ListOptions;
+use crate::types::ObjectMetadata;
+use crate::uploader::Uploader;
+use anyhow::{Context, Result};
+use tokio::task::JoinSet;
+use tracing::info;
+
+/// 查找对象时同时进行的 HEAD 请求数
+const FIND_CONCURRENCY: usize = 16;
+
+impl Uploader {
+    /// 按元数据查找前缀下的对象
+    ///
+    /// 逐页列举对象，并发地（最多 16 个）通过 HEAD 读取每个对象的元数据，保留满足 `predicate` 的对象。
+    /// 内存中只保存一页的列举结果与匹配的对象，适合按 `x-cos-meta-user-id` 等自定义元数据查找要清理的对象。
+    ///
+    /// # 参数
+    ///
+    /// * `prefix` - 对象键前缀，为空字符串时查找整个 Bucket
+    /// * `predicate` - 判断对象是否匹配，参数为 HEAD 得到的元数据
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回匹配的对象键与元数据，按对象键排序
+    ///
+    /// # 错误
+    ///
+    /// 列举或 HEAD 请求失败时返回错误；列举后被删除的对象会被跳过。
+    pub async fn find_objects<F>(
+        &self,
+        prefix: &str,
+        predicate: F,
+    ) -> Result<Vec<(String, ObjectMetadata)>>
+    where
+        F: Fn(&ObjectMetadata) -> bool,
+    {
+        let opts = ListOptions::new(prefix);
+        let mut cursor = None;
+        let mut scanned = 0u64;
+        let mut matches = Vec::new();
+
+        loop {
+            let page = self.list_objects(&opts, cursor.as_ref()).await?;
+            scanned += page.items.len() as u64;
+
+            let mut tasks: JoinSet<(String, Result<ObjectMetadata>)> = JoinSet::new();
+            let mut keys = page.items.into_iter().map(|object| object.key);
+            loop {
+                while tasks.len() < FIND_CONCURRENCY {
+                    let Some(key) = keys.next() else { break };
+                    let uploader = self.clone();
+                    tasks.spawn(async move {
+                        let metadata = uploader.get_object_metadata(&key).await;
+                        (key, metadata)
+                    });
+                }
+                let Some(joined) = tasks.join_next().await else {
+                    break;
+                };
+                match joined? {
+                    (key, Ok(metadata)) => {
+                        if predicate(&metadata) {
+                            matches.push((key, metadata));
+                        }
+                    }
+                    (_, Err(e)) if is_not_found(&e) => {}
+                    (key, Err(e)) => {
+                        return Err(e).with_context(|| format!("读取对象元数据失败: {}", key))
+                    }
+                }
+            }
+
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        matches.sort_by(|a, b| a.0.cmp(&b.0));
+        info!(
+            "在 {} 下检查了 {} 个对象，{} 个匹配",
+            prefix,
+            scanned,
+            matches.len()
+        );
+        Ok(matches)
+    }
+}
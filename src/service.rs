@@ -0,0 +1,39 @@
+use crate::request::CosRequest;
+use crate::uploader::Uploader;
+use anyhow::Result;
+use bytes::Bytes;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tower_service::Service;
+
+/// 把签名后的 COS 调用作为 tower 服务
+///
+/// 每次调用签名并发送一个 [`CosRequest`]，与上传器内部的请求一样处理地域重定向与内网域名回退，
+/// 失败的响应以包含 [`CosError`](crate::CosError) 的错误返回。超时、限流、重试、过载保护等策略
+/// 可以直接用 tower 生态的中间件组合，例如 `ServiceBuilder::new().timeout(..).service(uploader.clone())`。
+///
+/// 响应体会被完整读入内存，适合元数据请求与中小对象。
+impl Service<CosRequest> for Uploader {
+    type Response = http::Response<Bytes>;
+    type Error = anyhow::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: CosRequest) -> Self::Future {
+        let uploader = self.clone();
+        Box::pin(async move {
+            let response = uploader.execute(request).await?;
+            let mut builder = http::Response::builder()
+                .status(response.status())
+                .version(response.version());
+            if let Some(headers) = builder.headers_mut() {
+                headers.extend(response.headers().clone());
+            }
+            Ok(builder.body(response.bytes().await?)?)
+        })
+    }
+}
@@ -0,0 +1,127 @@
+use crate::config::Config;
+use crate::events::TransferEvent;
+use crate::options::UploadOptions;
+use crate::types::UploadResult;
+use crate::uploader::{UploadSource, Uploader};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+impl Uploader {
+    /// 开启影子模式：每个成功的上传都会在后台再上传一份到另一个配置的 Bucket
+    ///
+    /// 用于迁移前验证新的 Bucket（或兼容 COS 协议的其它服务）。镜像上传不影响主上传的结果，
+    /// 镜像失败或两边的内容不一致时发送 [`TransferEvent::ShadowMismatch`] 事件，
+    /// 需要同时通过 [`Uploader::with_event_channel`] 开启事件广播才能收到。
+    /// 两边都返回 CRC64 时按 CRC64 比较，否则比较 ETag（分块上传的 ETag 与分块大小有关，
+    /// 两边的分块设置不同时 ETag 也会不同）。
+    ///
+    /// 影子上传器继承调用时已有的事件广播、重试、限速与进度等设置，
+    /// 因此应在这些 `with_*` 设置之后调用。
+    ///
+    /// 镜像上传在主上传完成后才开始读取本地文件，期间文件不能被删除或修改。
+    /// 只有 [`Uploader::upload_file`]、[`Uploader::upload_file_with_options`]、[`Uploader::upload_open_file`]
+    /// 以及上传队列会被镜像。
+    ///
+    /// # 参数
+    ///
+    /// * `config` - 镜像目标的 COS 配置
+    pub fn with_shadow(mut self, config: Config) -> Self {
+        self.shadow = Some(Arc::new(self.derive(config)));
+        self
+    }
+
+    /// 在后台把刚上传成功的文件镜像到影子 Bucket，未开启影子模式时什么也不做
+    pub(crate) fn mirror_upload(
+        &self,
+        source: &UploadSource,
+        object_key: &str,
+        options: &UploadOptions,
+        primary: &UploadResult,
+    ) {
+        let Some(shadow) = self.shadow.clone() else {
+            return;
+        };
+        let source = match source.try_clone() {
+            Ok(source) => source,
+            Err(e) => {
+                warn!("无法为影子上传复制已打开的文件: {}: {}", object_key, e);
+                self.emit(TransferEvent::ShadowMismatch {
+                    object_key: object_key.to_string(),
+                    primary_etag: primary.etag.clone(),
+                    shadow_etag: None,
+                    error: Some(e.to_string()),
+                });
+                return;
+            }
+        };
+
+        let uploader = self.clone();
+        let primary_etag = primary.etag.clone();
+        let primary_crc64 = primary.crc64;
+        let object_key = object_key.to_string();
+        // 覆盖的 Bucket 与地域已由主上传器处理，不能让影子上传器重定向回主 Bucket
+        let options = options.without_target();
+        tokio::spawn(async move {
+            let (shadow_etag, shadow_crc64, error) =
+                match source.upload_to(&shadow, &object_key, &options).await {
+                    Ok(result) => (result.etag, result.crc64, None),
+                    Err(e) => (None, None, Some(format!("{:#}", e))),
+                };
+
+            if error.is_none()
+                && same_content(
+                    (primary_crc64, primary_etag.as_deref()),
+                    (shadow_crc64, shadow_etag.as_deref()),
+                )
+            {
+                debug!("影子上传一致: {}", object_key);
+                return;
+            }
+            warn!(
+                "影子上传不一致: {} 主 ETag {:?} CRC64 {:?} 镜像 ETag {:?} CRC64 {:?} 错误 {:?}",
+                object_key, primary_etag, primary_crc64, shadow_etag, shadow_crc64, error
+            );
+            uploader.emit(TransferEvent::ShadowMismatch {
+                object_key,
+                primary_etag,
+                shadow_etag,
+                error,
+            });
+        });
+    }
+}
+
+/// 判断主上传与镜像上传的内容是否一致：两边都有 CRC64 时比较 CRC64，否则比较 ETag
+fn same_content(primary: (Option<u64>, Option<&str>), shadow: (Option<u64>, Option<&str>)) -> bool {
+    match (primary.0, shadow.0) {
+        (Some(primary), Some(shadow)) => primary == shadow,
+        _ => primary.1 == shadow.1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_content() {
+        // 分块设置不同导致 ETag 不同，CRC64 一致即视为一致
+        assert!(same_content(
+            (Some(1), Some("\"a-2\"")),
+            (Some(1), Some("\"b-3\""))
+        ));
+        assert!(!same_content(
+            (Some(1), Some("\"a\"")),
+            (Some(2), Some("\"a\""))
+        ));
+        // 任一边缺少 CRC64 时退回比较 ETag
+        assert!(same_content(
+            (Some(1), Some("\"a\"")),
+            (None, Some("\"a\""))
+        ));
+        assert!(!same_content(
+            (None, Some("\"a\"")),
+            (Some(1), Some("\"b\""))
+        ));
+    }
+}
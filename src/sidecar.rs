@@ -0,0 +1,109 @@
+use crate::error::CosError;
+use crate::request::CosRequest;
+use crate::types::request_id_of;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Context, Result};
+use reqwest::Method;
+use tracing::info;
+
+/// 校验值旁路文件的对象键后缀
+///
+/// 旁路文件的内容为十进制的 CRC-64/ECMA-182 校验值（与 `x-cos-hash-crc64ecma` 相同）加换行，
+/// 任何能计算该算法的工具都可以据此校验对象，不依赖 COS 的元数据。
+pub const SIDECAR_SUFFIX: &str = ".crc64";
+
+/// 对象的校验值旁路文件的对象键
+fn sidecar_key(object_key: &str) -> String {
+    format!("{}{}", object_key, SIDECAR_SUFFIX)
+}
+
+/// 解析旁路文件的内容
+fn parse_sidecar(text: &str) -> Option<u64> {
+    text.split_whitespace().next()?.parse().ok()
+}
+
+impl Uploader {
+    /// 为已上传的对象写入校验值旁路文件
+    ///
+    /// 校验值取自 COS 返回的 `x-cos-hash-crc64ecma`，上传时已与本地计算的值比对过。
+    pub(crate) async fn upload_sidecar(&self, object_key: &str) -> Result<()> {
+        let metadata = self.get_object_metadata(object_key).await?;
+        let crc = metadata
+            .crc64
+            .ok_or_else(|| anyhow!("COS 未返回对象的 CRC64，无法写入旁路文件: {}", object_key))?;
+
+        let key = sidecar_key(object_key);
+        let request = CosRequest::new(Method::PUT, &key)
+            .header("Content-Type", "text/plain")
+            .body(format!("{}\n", crc));
+        let response = self
+            .execute(request)
+            .await
+            .with_context(|| format!("写入校验值旁路文件失败: {}", key))?;
+        info!(
+            "已写入校验值旁路文件: {} (request_id: {:?})",
+            key,
+            request_id_of(response.headers())
+        );
+        Ok(())
+    }
+
+    /// 按校验值旁路文件校验对象
+    ///
+    /// 下载对象并在本地计算 CRC64，与 `{object_key}.crc64` 中记录的值比对。
+    /// 旁路文件由开启了 [`UploadOptions::checksum_sidecar`](crate::UploadOptions::checksum_sidecar) 的上传写入。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    ///
+    /// # 错误
+    ///
+    /// 旁路文件不存在或格式错误、请求失败，或校验值不一致
+    /// （[`CosError::ChecksumMismatch`]）时返回错误。
+    pub async fn verify_with_sidecar(&self, object_key: &str) -> Result<()> {
+        let key = sidecar_key(object_key);
+        let text = self
+            .execute(CosRequest::new(Method::GET, &key))
+            .await
+            .with_context(|| format!("读取校验值旁路文件失败: {}", key))?
+            .text()
+            .await?;
+        let expected =
+            parse_sidecar(&text).ok_or_else(|| anyhow!("校验值旁路文件格式错误: {}", key))?;
+
+        let mut response = self
+            .execute(CosRequest::new(Method::GET, object_key))
+            .await?;
+        let mut crc64 = self.hash_backend.crc64();
+        while let Some(chunk) = response.chunk().await? {
+            crc64.update(&chunk);
+        }
+        let local = crc64.finish();
+
+        if local != expected {
+            return Err(CosError::ChecksumMismatch {
+                object_key: object_key.to_string(),
+                local,
+                remote: expected,
+            }
+            .into());
+        }
+        info!("按旁路文件校验通过: {}", object_key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_sidecar() {
+        assert_eq!(parse_sidecar("123456789\n"), Some(123456789));
+        assert_eq!(parse_sidecar("  42  data.bin\n"), Some(42));
+        assert_eq!(parse_sidecar(""), None);
+        assert_eq!(parse_sidecar("abc"), None);
+        assert_eq!(sidecar_key("a/b.bin"), "a/b.bin.crc64");
+    }
+}
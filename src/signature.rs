@@ -1,98 +1,240 @@
+use crate::config::HeaderCanonicalization;
 use chrono::Utc;
 use hmac::{Hmac, Mac};
 use sha1::{Digest, Sha1};
 use std::collections::HashMap;
+use std::fmt::Write;
+use std::sync::Mutex;
 use urlencoding::encode as url_encode;
 
-/// 生成腾讯云 COS 的授权签名
-///
-/// # 参数
-///
-/// * `secret_id` - 腾讯云 SecretId
-/// * `secret_key` - 腾讯云 SecretKey
-/// * `method` - HTTP 方法（如 "get", "put", "post" 等）
-/// * `path` - 对象的路径
-/// * `params` - 查询参数
-/// * `headers` - HTTP 头部
-/// * `expire` - 签名的有效期（以秒为单位）
-///
-/// # 返回值
+/// 签名起始时间对齐的粒度（秒），同一时间窗口内的请求共用一个 SignKey
+const SIGN_WINDOW: i64 = 300;
+
+/// 一次签名的结果
+#[cfg_attr(not(feature = "runtime"), allow(dead_code))]
+pub(crate) struct Signature {
+    /// `Authorization` 头部的值
+    pub(crate) authorization: String,
+    /// 参与签名的规范请求（HttpString / FormatString）
+    pub(crate) format_string: String,
+    /// 待签字符串（StringToSign）
+    pub(crate) string_to_sign: String,
+}
+
+/// 按时间窗口缓存 SignKey 的签名器
 ///
-/// 返回生成的授权签名字符串
-pub(crate) fn generate_authorization(
-    secret_id: &str,
-    secret_key: &str,
-    method: &str,
-    path: &str,
-    params: &HashMap<String, String>,
-    headers: &HashMap<String, String>,
-    expire: i64,
-) -> String {
-    let now = Utc::now();
-    let start_time = now.timestamp();
-    let end_time = start_time + expire;
-    let key_time = format!("{};{}", start_time, end_time);
+/// SignKey 只依赖于 SecretKey 与 `q-key-time`，把起始时间对齐到 [`SIGN_WINDOW`] 后，
+/// 同一窗口内的所有请求可以复用同一个 SignKey，省去每个请求一次 HMAC 计算。
+pub(crate) struct Signer {
+    secret_id: String,
+    secret_key: String,
+    canonicalization: HeaderCanonicalization,
+    /// 缓存的 (`q-key-time`, SignKey)
+    cache: Mutex<Option<(String, String)>>,
+}
 
-    let (url_param_list, http_parameters) = format_params(params);
-    let (header_list, http_headers) = format_headers(headers);
+impl Signer {
+    pub(crate) fn new(secret_id: &str, secret_key: &str) -> Self {
+        Self {
+            secret_id: secret_id.to_string(),
+            secret_key: secret_key.to_string(),
+            canonicalization: HeaderCanonicalization::default(),
+            cache: Mutex::new(None),
+        }
+    }
 
-    let sign_key = hmac_sha1(secret_key, &key_time);
+    /// 设置规范化请求头的方式
+    pub(crate) fn with_canonicalization(
+        mut self,
+        canonicalization: HeaderCanonicalization,
+    ) -> Self {
+        self.canonicalization = canonicalization;
+        self
+    }
 
-    let http_string = format!(
-        "{}\n{}\n{}\n{}\n",
-        method.to_lowercase(),
-        path,
-        http_parameters,
-        http_headers
-    );
+    /// 生成腾讯云 COS 的授权签名
+    ///
+    /// # 参数
+    ///
+    /// * `method` - HTTP 方法（如 "get", "put", "post" 等）
+    /// * `path` - 对象的路径
+    /// * `params` - 查询参数
+    /// * `headers` - HTTP 头部
+    /// * `expire` - 签名的有效期（以秒为单位），从所在时间窗口的起点开始计算
+    ///
+    /// # 返回值
+    ///
+    /// 返回生成的授权签名，以及用于排查签名问题的规范请求与待签字符串
+    pub(crate) fn sign(
+        &self,
+        method: &str,
+        path: &str,
+        params: &HashMap<String, String>,
+        headers: &HashMap<String, String>,
+        expire: i64,
+    ) -> Signature {
+        self.sign_at(
+            Utc::now().timestamp(),
+            method,
+            path,
+            params,
+            headers,
+            expire,
+        )
+    }
 
-    let sha1_http_string = sha1_digest(&http_string);
-    let string_to_sign = format!("sha1\n{}\n{}\n", key_time, sha1_http_string);
+    fn sign_at(
+        &self,
+        now: i64,
+        method: &str,
+        path: &str,
+        params: &HashMap<String, String>,
+        headers: &HashMap<String, String>,
+        expire: i64,
+    ) -> Signature {
+        let start_time = now - now.rem_euclid(SIGN_WINDOW);
+        let end_time = start_time + expire;
+        let (key_time, sign_key) = self.sign_key(start_time, end_time);
 
-    let signature = hmac_sha1(&sign_key, &string_to_sign);
+        self.assemble(&key_time, &sign_key, method, path, params, headers)
+    }
 
-    format!(
-        "q-sign-algorithm=sha1&q-ak={}&q-sign-time={}&q-key-time={}&q-header-list={}&q-url-param-list={}&q-signature={}",
-        secret_id, key_time, key_time, header_list, url_param_list, signature
-    )
-}
+    /// 使用精确的起止时间生成签名，不经过 SignKey 缓存
+    ///
+    /// 用于预签名 URL，有效期从当前时间而不是时间窗口的起点开始计算。
+    #[cfg(feature = "presign")]
+    pub(crate) fn sign_exact(
+        &self,
+        start_time: i64,
+        end_time: i64,
+        method: &str,
+        path: &str,
+        params: &HashMap<String, String>,
+        headers: &HashMap<String, String>,
+    ) -> String {
+        let key_time = format!("{};{}", start_time, end_time);
+        let sign_key = hmac_sha1(&self.secret_key, &key_time);
 
-fn format_params(params: &HashMap<String, String>) -> (String, String) {
-    let mut sorted_params: Vec<_> = params.iter().collect();
-    sorted_params.sort_by(|a, b| a.0.cmp(b.0));
+        self.assemble(&key_time, &sign_key, method, path, params, headers)
+            .authorization
+    }
 
-    let url_param_list = sorted_params
-        .iter()
-        .map(|(k, _)| k.to_lowercase())
-        .collect::<Vec<_>>()
-        .join(";");
+    fn assemble(
+        &self,
+        key_time: &str,
+        sign_key: &str,
+        method: &str,
+        path: &str,
+        params: &HashMap<String, String>,
+        headers: &HashMap<String, String>,
+    ) -> Signature {
+        let mut param_list = String::new();
+        let mut header_list = String::new();
+        let mut http_string = String::with_capacity(256);
 
-    let http_parameters = sorted_params
-        .iter()
-        .map(|(k, v)| format!("{}={}", url_encode(&k.to_lowercase()), url_encode(v)))
-        .collect::<Vec<_>>()
-        .join("&");
+        http_string.push_str(&method.to_lowercase());
+        http_string.push('\n');
+        http_string.push_str(path);
+        http_string.push('\n');
+        write_canonical(
+            &mut http_string,
+            &mut param_list,
+            params,
+            HeaderCanonicalization::default(),
+        );
+        http_string.push('\n');
+        write_canonical(
+            &mut http_string,
+            &mut header_list,
+            headers,
+            self.canonicalization,
+        );
+        http_string.push('\n');
 
-    (url_param_list, http_parameters)
-}
+        let string_to_sign = format!("sha1\n{}\n{}\n", key_time, sha1_digest(&http_string));
+        let signature = hmac_sha1(sign_key, &string_to_sign);
 
-fn format_headers(headers: &HashMap<String, String>) -> (String, String) {
-    let mut sorted_headers: Vec<_> = headers.iter().collect();
-    sorted_headers.sort_by(|a, b| a.0.cmp(b.0));
+        let authorization = format!(
+            "q-sign-algorithm=sha1&q-ak={}&q-sign-time={}&q-key-time={}&q-header-list={}&q-url-param-list={}&q-signature={}",
+            self.secret_id, key_time, key_time, header_list, param_list, signature
+        );
 
-    let header_list = sorted_headers
-        .iter()
-        .map(|(k, _)| k.to_lowercase())
-        .collect::<Vec<_>>()
-        .join(";");
+        Signature {
+            authorization,
+            format_string: http_string,
+            string_to_sign,
+        }
+    }
+
+    /// 取出时间窗口对应的 (`q-key-time`, SignKey)，窗口变化时重新计算
+    fn sign_key(&self, start_time: i64, end_time: i64) -> (String, String) {
+        let key_time = format!("{};{}", start_time, end_time);
+        let mut cache = self.cache.lock().unwrap();
+
+        match &*cache {
+            Some((cached_time, sign_key)) if *cached_time == key_time => {
+                (key_time, sign_key.clone())
+            }
+            _ => {
+                let sign_key = hmac_sha1(&self.secret_key, &key_time);
+                *cache = Some((key_time.clone(), sign_key.clone()));
+                (key_time, sign_key)
+            }
+        }
+    }
+}
 
-    let http_headers = sorted_headers
+/// 按给定的 `q-key-time`、参与签名的查询参数与请求头重新计算 `q-signature`，用于模拟服务器校验签名
+#[cfg(feature = "testing")]
+pub(crate) fn expected_signature(
+    secret_key: &str,
+    key_time: &str,
+    method: &str,
+    path: &str,
+    params: &HashMap<String, String>,
+    headers: &HashMap<String, String>,
+) -> String {
+    let signer = Signer::new("", secret_key);
+    let sign_key = hmac_sha1(secret_key, key_time);
+    let signature = signer.assemble(key_time, &sign_key, method, path, params, headers);
+    hmac_sha1(&sign_key, &signature.string_to_sign)
+}
+
+/// 把键值对按键排序后写入 `out`（`k1=v1&k2=v2`），同时把键列表写入 `list`（`k1;k2`）
+///
+/// 查询参数总是使用默认的规范化方式：键转为小写，值原样编码。
+fn write_canonical(
+    out: &mut String,
+    list: &mut String,
+    pairs: &HashMap<String, String>,
+    canonicalization: HeaderCanonicalization,
+) {
+    let mut sorted: Vec<_> = pairs
         .iter()
-        .map(|(k, v)| format!("{}={}", url_encode(&k.to_lowercase()), url_encode(v)))
-        .collect::<Vec<_>>()
-        .join("&");
+        .map(|(k, v)| {
+            let key = if canonicalization.lowercase_names {
+                k.to_lowercase()
+            } else {
+                k.clone()
+            };
+            let value = if canonicalization.trim_values {
+                v.trim()
+            } else {
+                v.as_str()
+            };
+            (key, value)
+        })
+        .collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
 
-    (header_list, http_headers)
+    for (i, (key, value)) in sorted.iter().enumerate() {
+        if i > 0 {
+            out.push('&');
+            list.push(';');
+        }
+        list.push_str(key);
+        let _ = write!(out, "{}={}", url_encode(key), url_encode(value));
+    }
 }
 
 fn hmac_sha1(key: &str, message: &str) -> String {
@@ -107,3 +249,136 @@ fn sha1_digest(message: &str) -> String {
     hasher.update(message.as_bytes());
     hex::encode(hasher.finalize())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_key_cached_per_window() {
+        let signer = Signer::new("id", "key");
+        let params = HashMap::new();
+        let headers = HashMap::from([("Host".to_string(), "example.com".to_string())]);
+
+        let first = signer
+            .sign_at(1_000_000, "PUT", "/a", &params, &headers, 3600)
+            .authorization;
+        let same_window = signer
+            .sign_at(1_000_100, "PUT", "/a", &params, &headers, 3600)
+            .authorization;
+        assert_eq!(first, same_window);
+        assert!(first.contains("q-key-time=999900;1003500&"));
+        assert!(first.contains("q-header-list=host&"));
+
+        let next_window = signer
+            .sign_at(1_000_200, "PUT", "/a", &params, &headers, 3600)
+            .authorization;
+        assert_ne!(first, next_window);
+        assert!(next_window.contains("q-key-time=1000200;1003800&"));
+    }
+
+    /// 把字面量转换为签名使用的键值对
+    fn pairs(items: &[(&str, &str)]) -> HashMap<String, String> {
+        items
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    /// 官方文档“请求签名”中的示例，密钥与时间均来自文档
+    #[test]
+    fn test_documented_examples() {
+        const SECRET_KEY: &str = "BQYIM75p8x0iWVFSIgqEKwFprpRSVHlz";
+        let signer = Signer::new("AKIDQjz3ltompVjBni5LitkWHFlFpwkn9U5q", SECRET_KEY);
+        let host = "examplebucket-1250000000.cos.ap-beijing.myqcloud.com";
+
+        // 上传对象
+        let key_time = "1557989151;1557996351";
+        let sign_key = hmac_sha1(SECRET_KEY, key_time);
+        assert_eq!(sign_key, "eb2519b498b02ac213cb1f3d1a3d27a3b3c9bc5f");
+        let headers = pairs(&[
+            ("Host", host),
+            ("Date", "Thu, 16 May 2019 06:45:51 GMT"),
+            ("Content-Type", "text/plain"),
+            ("Content-Length", "13"),
+            ("Content-MD5", "mQ/fVh815F3k6TAUm8m0eg=="),
+            ("x-cos-acl", "private"),
+            ("x-cos-grant-read", "uin=\"100000000011\""),
+        ]);
+        let signature = signer.assemble(
+            key_time,
+            &sign_key,
+            "PUT",
+            "/exampleobject(腾讯云)",
+            &HashMap::new(),
+            &headers,
+        );
+        assert_eq!(
+            signature.format_string,
+            "put\n/exampleobject(腾讯云)\n\ncontent-length=13&content-md5=mQ%2FfVh815F3k6TAUm8m0eg%3D%3D\
+             &content-type=text%2Fplain&date=Thu%2C%2016%20May%202019%2006%3A45%3A51%20GMT\
+             &host=examplebucket-1250000000.cos.ap-beijing.myqcloud.com&x-cos-acl=private\
+             &x-cos-grant-read=uin%3D%22100000000011%22\n"
+        );
+        assert_eq!(
+            signature.string_to_sign,
+            "sha1\n1557989151;1557996351\n8b2751e77f43a0995d6e9eb9477f4b685cca4172\n"
+        );
+        assert_eq!(
+            signature.authorization,
+            "q-sign-algorithm=sha1&q-ak=AKIDQjz3ltompVjBni5LitkWHFlFpwkn9U5q\
+             &q-sign-time=1557989151;1557996351&q-key-time=1557989151;1557996351\
+             &q-header-list=content-length;content-md5;content-type;date;host;x-cos-acl;x-cos-grant-read\
+             &q-url-param-list=&q-signature=3b8851a11a569213c17ba8fa7dcf2abec6935172"
+        );
+
+        // 带查询参数的下载对象
+        let key_time = "1557989753;1557996953";
+        let sign_key = hmac_sha1(SECRET_KEY, key_time);
+        let params = pairs(&[
+            ("response-content-type", "application/octet-stream"),
+            ("response-cache-control", "max-age=600"),
+        ]);
+        let headers = pairs(&[("Host", host), ("Date", "Thu, 16 May 2019 06:55:53 GMT")]);
+        let signature = signer.assemble(
+            key_time,
+            &sign_key,
+            "GET",
+            "/exampleobject(腾讯云)",
+            &params,
+            &headers,
+        );
+        assert_eq!(
+            signature.string_to_sign,
+            "sha1\n1557989753;1557996953\n54ecfe22f59d3514fdc764b87a32d8133ea611e6\n"
+        );
+        assert!(signature.authorization.ends_with(
+            "&q-header-list=date;host&q-url-param-list=response-cache-control;response-content-type\
+             &q-signature=01681b8c9d798a678e43b685a9f1bba0f6c0e012"
+        ));
+    }
+
+    #[test]
+    fn test_header_canonicalization() {
+        let headers = pairs(&[("Host", "example.com"), ("X-Cos-Meta-A", " v ")]);
+        let sign = |canonicalization| {
+            Signer::new("id", "key")
+                .with_canonicalization(canonicalization)
+                .assemble("1;2", "k", "GET", "/a", &HashMap::new(), &headers)
+                .format_string
+        };
+
+        assert_eq!(
+            sign(HeaderCanonicalization::default()),
+            "get\n/a\n\nhost=example.com&x-cos-meta-a=%20v%20\n"
+        );
+        assert_eq!(
+            sign(HeaderCanonicalization::new().with_trim_values(true)),
+            "get\n/a\n\nhost=example.com&x-cos-meta-a=v\n"
+        );
+        assert_eq!(
+            sign(HeaderCanonicalization::new().with_lowercase_names(false)),
+            "get\n/a\n\nHost=example.com&X-Cos-Meta-A=%20v%20\n"
+        );
+    }
+}
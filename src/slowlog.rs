@@ -0,0 +1,140 @@
+use crate::error::CosError;
+use crate::request::CosRequest;
+use crate::types::request_id_of;
+use crate::uploader::Uploader;
+use anyhow::Result;
+use reqwest::header::CONTENT_LENGTH;
+use reqwest::{Method, Response};
+use std::time::Duration;
+use tracing::warn;
+
+/// 请求对应的 COS 接口名称
+///
+/// Bucket 配置类的请求（如 `?lifecycle`）记为方法加子资源，例如 `PUT ?lifecycle`。
+fn operation_name(request: &CosRequest) -> String {
+    let has = |param: &str| request.params.contains_key(param);
+    let copy = request.headers.contains_key("x-cos-copy-source");
+    let bucket_level = request.object_key.is_empty();
+    let name = match request.method {
+        Method::PUT if has("partNumber") && copy => "UploadPartCopy",
+        Method::PUT if has("partNumber") => "UploadPart",
+        Method::PUT if copy => "CopyObject",
+        Method::PUT if !bucket_level => "PutObject",
+        Method::POST if has("uploads") => "InitiateMultipartUpload",
+        Method::POST if has("uploadId") => "CompleteMultipartUpload",
+        Method::POST if has("delete") => "DeleteMultipleObjects",
+        Method::DELETE if has("uploadId") => "AbortMultipartUpload",
+        Method::DELETE if !bucket_level => "DeleteObject",
+        Method::GET if has("versions") => "ListObjectVersions",
+        Method::GET if has("uploads") => "ListMultipartUploads",
+        Method::GET if bucket_level && has("prefix") => "ListObjects",
+        Method::GET if !bucket_level => "GetObject",
+        Method::HEAD if bucket_level => "HeadBucket",
+        Method::HEAD => "HeadObject",
+        _ => {
+            let mut params: Vec<_> = request.params.keys().map(String::as_str).collect();
+            params.sort_unstable();
+            return match params.first() {
+                Some(param) => format!("{} ?{}", request.method, param),
+                None => request.method.to_string(),
+            };
+        }
+    };
+    name.to_string()
+}
+
+impl Uploader {
+    /// 设置慢请求的阈值，单个 COS 请求（包括地域重定向与内网回退的重试）耗时超过该值时输出警告
+    ///
+    /// 警告以结构化字段记录接口名称（`operation`，如 `UploadPart`）、对象键、请求或响应体的字节数、
+    /// 耗时（毫秒）与 `x-cos-request-id`，可以直接在日志系统中按字段筛选，定位拖慢批量上传的具体分块。
+    /// 默认不检测。
+    pub fn with_slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// 请求耗时超过阈值时输出结构化的警告
+    pub(crate) fn report_slow_request(
+        &self,
+        request: &CosRequest,
+        elapsed: Duration,
+        outcome: &Result<Response>,
+    ) {
+        let Some(threshold) = self.slow_request_threshold else {
+            return;
+        };
+        if elapsed < threshold {
+            return;
+        }
+
+        let (status, request_id, response_bytes) = match outcome {
+            Ok(response) => (
+                response.status().as_u16(),
+                request_id_of(response.headers()),
+                response
+                    .headers()
+                    .get(CONTENT_LENGTH)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(|v| v.parse::<u64>().ok()),
+            ),
+            Err(e) => match e.downcast_ref::<CosError>() {
+                Some(CosError::Service {
+                    status, request_id, ..
+                }) => (*status, request_id.clone(), None),
+                Some(CosError::WrongRegion { request_id, .. }) => (301, request_id.clone(), None),
+                _ => (0, None, None),
+            },
+        };
+        // 上传类请求记录请求体大小，下载类请求记录响应体大小
+        let bytes = match &request.body {
+            Some(body) if !body.is_empty() => body.len() as u64,
+            _ => response_bytes.unwrap_or(0),
+        };
+
+        warn!(
+            operation = %operation_name(request),
+            object_key = %request.object_key,
+            part_number = request.params.get("partNumber").map(String::as_str),
+            bytes,
+            duration_ms = elapsed.as_millis() as u64,
+            threshold_ms = threshold.as_millis() as u64,
+            status,
+            request_id = request_id.as_deref(),
+            "COS 请求耗时过长"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_operation_name() {
+        let part = CosRequest::new(Method::PUT, "a.bin")
+            .param("partNumber", "3")
+            .param("uploadId", "u");
+        assert_eq!(operation_name(&part), "UploadPart");
+        assert_eq!(
+            operation_name(&part.header("x-cos-copy-source", "b/a.bin")),
+            "UploadPartCopy"
+        );
+        assert_eq!(
+            operation_name(&CosRequest::new(Method::POST, "a.bin").param("uploads", "")),
+            "InitiateMultipartUpload"
+        );
+        assert_eq!(
+            operation_name(&CosRequest::bucket(Method::GET).param("prefix", "")),
+            "ListObjects"
+        );
+        assert_eq!(
+            operation_name(&CosRequest::new(Method::HEAD, "a.bin")),
+            "HeadObject"
+        );
+        assert_eq!(
+            operation_name(&CosRequest::bucket(Method::PUT).param("lifecycle", "")),
+            "PUT ?lifecycle"
+        );
+    }
+}
@@ -0,0 +1,151 @@
+use crate::list::{ListOptions, ObjectSummary};
+use crate::uploader::Uploader;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use tracing::info;
+
+/// 未返回存储类型的对象按标准存储统计
+const DEFAULT_STORAGE_CLASS: &str = "STANDARD";
+
+/// 某一存储类型下的对象统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StorageClassStats {
+    /// 对象数量
+    pub object_count: u64,
+    /// 对象总大小（字节）
+    pub total_bytes: u64,
+}
+
+/// 对象键前缀下的对象统计
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PrefixStats {
+    /// 对象数量
+    pub object_count: u64,
+    /// 对象总大小（字节）
+    pub total_bytes: u64,
+    /// 最大的对象，大小相同时取先列出的
+    pub largest: Option<ObjectSummary>,
+    /// 最后修改时间最早的对象
+    pub oldest: Option<ObjectSummary>,
+    /// 最后修改时间最晚的对象
+    pub newest: Option<ObjectSummary>,
+    /// 按存储类型（例如 `STANDARD`、`ARCHIVE`）分组的统计
+    pub by_storage_class: BTreeMap<String, StorageClassStats>,
+}
+
+impl PrefixStats {
+    /// 把一个对象计入统计
+    pub(crate) fn record(&mut self, object: &ObjectSummary) {
+        self.object_count += 1;
+        self.total_bytes += object.size;
+
+        let class = object
+            .storage_class
+            .as_deref()
+            .unwrap_or(DEFAULT_STORAGE_CLASS);
+        let class_stats = self.by_storage_class.entry(class.to_string()).or_default();
+        class_stats.object_count += 1;
+        class_stats.total_bytes += object.size;
+
+        if self.largest.as_ref().is_none_or(|o| object.size > o.size) {
+            self.largest = Some(object.clone());
+        }
+
+        if let Some(modified) = object.last_modified {
+            let older = |o: &ObjectSummary| o.last_modified.is_none_or(|m| modified < m);
+            let newer = |o: &ObjectSummary| o.last_modified.is_none_or(|m| modified > m);
+            if self.oldest.as_ref().is_none_or(older) {
+                self.oldest = Some(object.clone());
+            }
+            if self.newest.as_ref().is_none_or(newer) {
+                self.newest = Some(object.clone());
+            }
+        }
+    }
+}
+
+impl Uploader {
+    /// 统计对象键前缀下的对象数量、总大小、最大对象、最早与最晚修改的对象以及各存储类型的分布
+    ///
+    /// 需要列举前缀下的全部对象，对象很多时会发出较多请求。
+    ///
+    /// # 参数
+    ///
+    /// * `prefix` - 对象键前缀，为空时统计整个 Bucket
+    ///
+    /// # 错误
+    ///
+    /// 任意一页列举失败时返回错误。
+    pub async fn prefix_stats(&self, prefix: &str) -> Result<PrefixStats> {
+        let mut stats = PrefixStats::default();
+        let opts = ListOptions::new(prefix);
+        let mut cursor = None;
+
+        loop {
+            let page = self.list_objects(&opts, cursor.as_ref()).await?;
+            for object in &page.items {
+                stats.record(object);
+            }
+
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        info!(
+            "前缀统计: {} 对象 {} 个，共 {} 字节",
+            prefix, stats.object_count, stats.total_bytes
+        );
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(key: &str, size: u64, modified: &str, class: Option<&str>) -> ObjectSummary {
+        ObjectSummary {
+            key: key.to_string(),
+            size,
+            etag: None,
+            last_modified: crate::datetime::parse_iso8601(modified),
+            storage_class: class.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut stats = PrefixStats::default();
+        stats.record(&object("a", 10, "2024-03-01T00:00:00.000Z", None));
+        stats.record(&object(
+            "b",
+            30,
+            "2024-01-01T00:00:00.000Z",
+            Some("ARCHIVE"),
+        ));
+        stats.record(&object(
+            "c",
+            30,
+            "2024-05-01T00:00:00.000Z",
+            Some("STANDARD"),
+        ));
+
+        assert_eq!(stats.object_count, 3);
+        assert_eq!(stats.total_bytes, 70);
+        assert_eq!(stats.largest.unwrap().key, "b");
+        assert_eq!(stats.oldest.unwrap().key, "b");
+        assert_eq!(stats.newest.unwrap().key, "c");
+        assert_eq!(
+            stats.by_storage_class["STANDARD"],
+            StorageClassStats {
+                object_count: 2,
+                total_bytes: 40
+            }
+        );
+        assert_eq!(stats.by_storage_class["ARCHIVE"].object_count, 1);
+    }
+}
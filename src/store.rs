@@ -0,0 +1,212 @@
+use crate::error::CosError;
+use crate::list::{ListOptions, ObjectSummary};
+use crate::types::{DeleteResult, UploadResult};
+use crate::uploader::Uploader;
+use anyhow::Result;
+use bytes::Bytes;
+use md5::{Digest, Md5};
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 对象存储的最小接口
+///
+/// 业务代码依赖该 trait 而不是具体的 [`Uploader`]，测试时可以换成 [`MemoryObjectStore`]，
+/// 以后也可以换成其它后端，而不必修改调用处。
+///
+/// 方法返回 `Send` 的 future，可以在 `tokio::spawn` 的任务中调用；需要动态分发时由调用方按需包装。
+pub trait ObjectStore: Send + Sync {
+    /// 写入对象，Content-Type 按对象键的扩展名推断
+    fn put(
+        &self,
+        object_key: &str,
+        data: Bytes,
+    ) -> impl Future<Output = Result<UploadResult>> + Send;
+
+    /// 读取对象的全部内容
+    ///
+    /// 对象不存在时返回状态码为 404 的 [`CosError::Service`]。
+    fn get(&self, object_key: &str) -> impl Future<Output = Result<Bytes>> + Send;
+
+    /// 删除对象，对象不存在时同样返回成功
+    fn delete(&self, object_key: &str) -> impl Future<Output = Result<DeleteResult>> + Send;
+
+    /// 列举前缀下的全部对象，按对象键排序
+    fn list(&self, prefix: &str) -> impl Future<Output = Result<Vec<ObjectSummary>>> + Send;
+
+    /// 生成预签名 URL
+    ///
+    /// `method` 为 HTTP 方法（如 `GET`、`PUT`），`expire` 为有效期。
+    fn presign(&self, method: &str, object_key: &str, expire: Duration) -> Result<String>;
+}
+
+impl ObjectStore for Uploader {
+    async fn put(&self, object_key: &str, data: Bytes) -> Result<UploadResult> {
+        let content_type = mime_guess::from_path(object_key)
+            .first_or_octet_stream()
+            .to_string();
+        self.put_bytes(object_key, data, &content_type, &[]).await
+    }
+
+    async fn get(&self, object_key: &str) -> Result<Bytes> {
+        self.get_object_bytes(object_key, None).await
+    }
+
+    async fn delete(&self, object_key: &str) -> Result<DeleteResult> {
+        self.delete_object(object_key).await
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectSummary>> {
+        let opts = ListOptions::new(prefix);
+        let mut cursor = None;
+        let mut objects = Vec::new();
+        loop {
+            let page = self.list_objects(&opts, cursor.as_ref()).await?;
+            objects.extend(page.items);
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => return Ok(objects),
+            }
+        }
+    }
+
+    #[cfg(feature = "presign")]
+    fn presign(&self, method: &str, object_key: &str, expire: Duration) -> Result<String> {
+        Ok(self.presign_url(method, object_key, expire))
+    }
+
+    #[cfg(not(feature = "presign"))]
+    fn presign(&self, _method: &str, _object_key: &str, _expire: Duration) -> Result<String> {
+        Err(anyhow::anyhow!("生成预签名 URL 需要启用 presign feature"))
+    }
+}
+
+/// 保存在进程内存中的对象存储，用于测试依赖 [`ObjectStore`] 的代码
+///
+/// ETag 为内容的 MD5，与 COS 普通上传的 ETag 格式一致；预签名 URL 形如
+/// `memory://{method}/{object_key}?expire={秒}`，只用于断言，不能实际访问。
+#[derive(Debug, Default)]
+pub struct MemoryObjectStore {
+    objects: Mutex<BTreeMap<String, (Bytes, String)>>,
+}
+
+impl MemoryObjectStore {
+    /// 创建空的存储
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 存储中的对象数量
+    pub fn len(&self) -> usize {
+        self.objects.lock().unwrap().len()
+    }
+
+    /// 存储是否为空
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl ObjectStore for MemoryObjectStore {
+    async fn put(&self, object_key: &str, data: Bytes) -> Result<UploadResult> {
+        let etag = format!("\"{}\"", hex::encode(Md5::digest(&data)));
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(object_key.to_string(), (data, etag.clone()));
+        Ok(UploadResult {
+            url: format!("memory:///{}", object_key),
+            etag: Some(etag),
+            request_id: None,
+            stats: None,
+            failover: None,
+            crc64: None,
+        })
+    }
+
+    async fn get(&self, object_key: &str) -> Result<Bytes> {
+        match self.objects.lock().unwrap().get(object_key) {
+            Some((data, _)) => Ok(data.clone()),
+            None => Err(CosError::Service {
+                status: 404,
+                code: "NoSuchKey".to_string(),
+                message: format!("对象不存在: {}", object_key),
+                request_id: None,
+            }
+            .into()),
+        }
+    }
+
+    async fn delete(&self, object_key: &str) -> Result<DeleteResult> {
+        self.objects.lock().unwrap().remove(object_key);
+        Ok(DeleteResult { request_id: None })
+    }
+
+    async fn list(&self, prefix: &str) -> Result<Vec<ObjectSummary>> {
+        let objects = self.objects.lock().unwrap();
+        Ok(objects
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, (data, etag))| ObjectSummary {
+                key: key.clone(),
+                size: data.len() as u64,
+                etag: Some(etag.clone()),
+                last_modified: None,
+                storage_class: Some("STANDARD".to_string()),
+            })
+            .collect())
+    }
+
+    fn presign(&self, method: &str, object_key: &str, expire: Duration) -> Result<String> {
+        Ok(format!(
+            "memory://{}/{}?expire={}",
+            method.to_uppercase(),
+            object_key,
+            expire.as_secs()
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::is_not_found;
+
+    /// 只依赖 trait 的业务代码
+    async fn archive<S: ObjectStore>(store: &S, key: &str) -> Result<()> {
+        let data = store.get(key).await?;
+        store.put(&format!("archive/{}", key), data).await?;
+        store.delete(key).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_object_store() {
+        let store = MemoryObjectStore::new();
+        let result = store.put("a.txt", Bytes::from_static(b"hi")).await.unwrap();
+        assert_eq!(
+            result.etag.as_deref(),
+            Some("\"49f68a5c8493ec2c0bf489821c21fc3b\"")
+        );
+        store.put("b.txt", Bytes::new()).await.unwrap();
+
+        archive(&store, "a.txt").await.unwrap();
+        assert!(is_not_found(&store.get("a.txt").await.unwrap_err()));
+        let keys: Vec<_> = store
+            .list("")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|object| object.key)
+            .collect();
+        assert_eq!(keys, ["archive/a.txt", "b.txt"]);
+        assert_eq!(store.list("archive/").await.unwrap()[0].size, 2);
+        assert_eq!(
+            store
+                .presign("get", "b.txt", Duration::from_secs(60))
+                .unwrap(),
+            "memory://GET/b.txt?expire=60"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::batch::collect_files;
+use crate::error::is_not_found;
+use crate::keymap::relative_key;
+use crate::list::{ListOptions, ObjectSummary};
+use crate::scoped::check_relative_key;
+use crate::types::ObjectMetadata;
+use crate::uploader::{Metadata, Uploader};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, info};
+
+/// 保存源文件修改时间（Unix 秒）的自定义元数据名，即 `x-cos-meta-mtime`
+pub const MTIME_METADATA: &str = "mtime";
+
+/// 同步报告
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// 已传输的对象键
+    pub transferred: Vec<String>,
+    /// 两端一致而跳过的对象键
+    pub skipped: Vec<String>,
+    /// 传输失败的对象键及失败原因
+    pub failed: Vec<(String, String)>,
+}
+
+impl SyncReport {
+    /// 是否没有失败的条目
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    fn record(&mut self, key: String, result: Result<bool>) {
+        match result {
+            Ok(true) => self.transferred.push(key),
+            Ok(false) => self.skipped.push(key),
+            Err(e) => {
+                error!("同步失败: {}: {}", key, e);
+                self.failed.push((key, format!("{:#}", e)));
+            }
+        }
+    }
+}
+
+impl Uploader {
+    /// 把本地目录同步到 COS
+    ///
+    /// 上传时把源文件的修改时间保存到 `x-cos-meta-mtime`。远端对象大小相同且记录的修改时间与本地一致时跳过；
+    /// 没有该元数据的旧对象，以其 `Last-Modified` 不早于本地修改时间作为一致的依据。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 本地目录
+    /// * `prefix` - 对象键前缀，例如 `"backup/"`
+    ///
+    /// # 返回值
+    ///
+    /// 成功遍历目录后返回同步报告，单个文件的失败记录在报告中
+    pub async fn sync_up<P: AsRef<Path>>(&self, dir: P, prefix: &str) -> Result<SyncReport> {
+        let dir = dir.as_ref();
+        let mut report = SyncReport::default();
+
+        for path in collect_files(dir).await? {
+            let key = format!("{}{}", prefix, relative_key(dir, &path)?);
+            let result = self.sync_file_up(&path, &key).await;
+            report.record(key, result);
+        }
+
+        info!(
+            "同步到 COS 结束: 传输 {}，跳过 {}，失败 {}",
+            report.transferred.len(),
+            report.skipped.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// 把 COS 前缀下的对象同步到本地目录
+    ///
+    /// 下载后把本地文件的修改时间设置为 `x-cos-meta-mtime`（没有时使用 `Last-Modified`），
+    /// 下一轮同步时大小与修改时间都一致的文件会被跳过。
+    ///
+    /// # 参数
+    ///
+    /// * `prefix` - 对象键前缀
+    /// * `dir` - 本地目录，对象键去掉前缀后作为相对路径
+    ///
+    /// # 返回值
+    ///
+    /// 成功列举对象后返回同步报告，单个对象的失败记录在报告中
+    pub async fn sync_down<P: AsRef<Path>>(&self, prefix: &str, dir: P) -> Result<SyncReport> {
+        let dir = dir.as_ref();
+        let mut report = SyncReport::default();
+        let opts = ListOptions::new(prefix);
+        let mut cursor = None;
+
+        loop {
+            let page = self.list_objects(&opts, cursor.as_ref()).await?;
+            for object in page.items {
+                // 目录占位对象没有对应的本地文件
+                if object.key.ends_with('/') {
+                    continue;
+                }
+                let result = self.sync_object_down(&object, prefix, dir).await;
+                report.record(object.key, result);
+            }
+
+            match page.next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        info!(
+            "同步到本地结束: 传输 {}，跳过 {}，失败 {}",
+            report.transferred.len(),
+            report.skipped.len(),
+            report.failed.len()
+        );
+        Ok(report)
+    }
+
+    /// 同步单个本地文件，返回是否进行了上传
+    async fn sync_file_up(&self, path: &Path, key: &str) -> Result<bool> {
+        let local = tokio::fs::metadata(path).await?;
+        let local_mtime = unix_secs(local.modified()?);
+
+        let remote = match self.get_object_metadata(key).await {
+            Ok(remote) => Some(remote),
+            Err(e) if is_not_found(&e) => None,
+            Err(e) => return Err(e),
+        };
+
+        if let Some(remote) = remote {
+            let same_size = remote.content_length == Some(local.len());
+            let in_sync = match remote_mtime(&remote) {
+                Some(remote_mtime) if remote.user_metadata.contains_key(MTIME_METADATA) => {
+                    remote_mtime == local_mtime
+                }
+                Some(remote_mtime) => remote_mtime >= local_mtime,
+                None => false,
+            };
+            if same_size && in_sync {
+                debug!("跳过未修改的文件: {:?}", path);
+                return Ok(false);
+            }
+        }
+
+        let metadata = Metadata::from([(MTIME_METADATA.to_string(), local_mtime.to_string())]);
+        self.upload_file(path, key, Some(metadata)).await?;
+        Ok(true)
+    }
+
+    /// 同步单个对象，返回是否进行了下载
+    async fn sync_object_down(
+        &self,
+        object: &ObjectSummary,
+        prefix: &str,
+        dir: &Path,
+    ) -> Result<bool> {
+        let relative = object.key.strip_prefix(prefix).unwrap_or(&object.key);
+        check_relative_key(relative)?;
+        let path: PathBuf = dir.join(relative.split('/').collect::<PathBuf>());
+
+        if let Ok(local) = tokio::fs::metadata(&path).await {
+            if local.len() == object.size {
+                let remote = self.get_object_metadata(&object.key).await?;
+                if remote_mtime(&remote) == Some(unix_secs(local.modified()?)) {
+                    debug!("跳过未修改的对象: {}", object.key);
+                    return Ok(false);
+                }
+            }
+        }
+
+        let remote = self.download_object(&object.key, &path).await?;
+        if let Some(mtime) = remote_mtime(&remote) {
+            let path = path.clone();
+            tokio::task::spawn_blocking(move || {
+                std::fs::File::options()
+                    .write(true)
+                    .open(path)?
+                    .set_modified(UNIX_EPOCH + Duration::from_secs(mtime))
+            })
+            .await??;
+        }
+        Ok(true)
+    }
+}
+
+/// 对象的修改时间（Unix 秒）：优先使用 `x-cos-meta-mtime`，其次是 `Last-Modified`
+fn remote_mtime(metadata: &ObjectMetadata) -> Option<u64> {
+    if let Some(mtime) = metadata
+        .user_metadata
+        .get(MTIME_METADATA)
+        .and_then(|v| v.parse().ok())
+    {
+        return Some(mtime);
+    }
+
+    metadata
+        .last_modified
+        .and_then(|t| u64::try_from(t.timestamp()).ok())
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remote_mtime() {
+        let mut metadata = ObjectMetadata {
+            last_modified: chrono::DateTime::from_timestamp(1445412480, 0),
+            ..Default::default()
+        };
+        assert_eq!(remote_mtime(&metadata), Some(1445412480));
+
+        metadata
+            .user_metadata
+            .insert(MTIME_METADATA.to_string(), "1700000000".to_string());
+        assert_eq!(remote_mtime(&metadata), Some(1700000000));
+    }
+}
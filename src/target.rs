@@ -0,0 +1,141 @@
+use crate::config::Config;
+use crate::options::UploadOptions;
+use crate::probe::SelectedEndpoint;
+use crate::signature::Signer;
+use crate::uploader::Uploader;
+use std::sync::{Arc, RwLock};
+use tracing::debug;
+
+impl Uploader {
+    /// 按上传选项中覆盖的 Bucket 与地域得到实际执行上传的上传器
+    ///
+    /// 新的上传器与原上传器共享连接池、签名器与限速器，按新的 Bucket 与地域重新生成域名；
+    /// 不继承对象缓存、影子 Bucket 与备用 Bucket，它们都只对应配置中的 Bucket。
+    ///
+    /// # 返回值
+    ///
+    /// 选项没有覆盖 Bucket 与地域，或覆盖的值与配置相同时返回 `None`
+    pub(crate) fn retarget(&self, options: &UploadOptions) -> Option<Uploader> {
+        let bucket = options
+            .bucket
+            .as_ref()
+            .filter(|bucket| **bucket != self.config.bucket);
+        let region = options
+            .region
+            .as_ref()
+            .filter(|region| **region != self.config.region);
+        if bucket.is_none() && region.is_none() {
+            return None;
+        }
+
+        let config = Config {
+            bucket: bucket.unwrap_or(&self.config.bucket).clone(),
+            region: region.unwrap_or(&self.config.region).clone(),
+            ..(*self.config).clone()
+        };
+        debug!("本次上传写入 {} ({})", config.bucket, config.region);
+        Some(Uploader {
+            endpoint: Arc::new(RwLock::new(SelectedEndpoint::new(&config, config.endpoint))),
+            config: Arc::new(config),
+            expiry_rules: Arc::default(),
+            shadow: None,
+            failover: None,
+            object_cache: None,
+            in_flight_reads: Arc::default(),
+            ..self.clone()
+        })
+    }
+
+    /// 按另一份配置得到写入其它 Bucket 的上传器，用于备用 Bucket 与影子 Bucket
+    ///
+    /// 与 [`Uploader::retarget`] 一样继承连接池、事件广播、重试、限速、哈希后端与进度设置，
+    /// 按新的配置重新生成签名器与域名；不继承对象缓存、影子 Bucket 与备用 Bucket。
+    pub(crate) fn derive(&self, config: Config) -> Uploader {
+        config.signed_headers.warn_if_reduced();
+        Uploader {
+            signer: Arc::new(
+                Signer::new(&config.secret_id, &config.secret_key)
+                    .with_canonicalization(config.header_canonicalization),
+            ),
+            endpoint: Arc::new(RwLock::new(SelectedEndpoint::new(&config, config.endpoint))),
+            config: Arc::new(config),
+            expiry_rules: Arc::default(),
+            shadow: None,
+            failover: None,
+            object_cache: None,
+            in_flight_reads: Arc::default(),
+            ..self.clone()
+        }
+    }
+}
+
+impl UploadOptions {
+    /// 去掉覆盖的 Bucket 与地域，用于备用 Bucket 与影子 Bucket 上的上传
+    ///
+    /// 主上传器已经按覆盖的值完成了重定向（或覆盖的值就是配置中的 Bucket），
+    /// 原样传给备用或影子上传器会使它们重定向回主 Bucket。
+    pub(crate) fn without_target(&self) -> UploadOptions {
+        UploadOptions {
+            bucket: None,
+            region: None,
+            ..self.clone()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::request::CosRequest;
+    use reqwest::Method;
+
+    #[test]
+    fn test_retarget() {
+        let uploader = Uploader::new(Config::new(
+            "id".into(),
+            "key".into(),
+            "ap-guangzhou".into(),
+            "a-1250000000".into(),
+        ));
+        assert!(uploader.retarget(&UploadOptions::new()).is_none());
+        assert!(uploader
+            .retarget(&UploadOptions::new().with_region("ap-guangzhou".into()))
+            .is_none());
+
+        let target = uploader
+            .retarget(
+                &UploadOptions::new()
+                    .with_bucket("b-1250000000".into())
+                    .with_region("ap-shanghai".into()),
+            )
+            .unwrap();
+        let request = target
+            .to_http_request(&CosRequest::new(Method::PUT, "x.txt"))
+            .unwrap();
+        assert_eq!(
+            request.uri(),
+            "https://b-1250000000.cos.ap-shanghai.myqcloud.com/x.txt"
+        );
+        assert_eq!(
+            request.headers()["host"],
+            "b-1250000000.cos.ap-shanghai.myqcloud.com"
+        );
+        assert!(uploader
+            .to_http_request(&CosRequest::new(Method::PUT, "x.txt"))
+            .unwrap()
+            .uri()
+            .to_string()
+            .starts_with("https://a-1250000000.cos.ap-guangzhou"));
+
+        // 覆盖的值就是主 Bucket 时，备用上传器不能再重定向回去
+        let options = UploadOptions::new().with_bucket("a-1250000000".into());
+        let failover = Uploader::new(Config::new(
+            "id".into(),
+            "key".into(),
+            "ap-shanghai".into(),
+            "c-1250000000".into(),
+        ));
+        assert!(failover.retarget(&options).is_some());
+        assert!(failover.retarget(&options.without_target()).is_none());
+    }
+}
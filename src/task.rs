@@ -0,0 +1,35 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::task::JoinSet;
+
+static NEXT_TRANSFER_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 生成进程内唯一的传输 ID，用于在日志和 tokio-console 中关联同一传输的任务
+pub(crate) fn next_transfer_id() -> u64 {
+    NEXT_TRANSFER_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// 在 `JoinSet` 中启动一个带名称的任务
+///
+/// 只有在以 `--cfg tokio_unstable` 编译并启用 `tokio-console` feature 时才会设置任务名称，
+/// 否则退化为普通的 `JoinSet::spawn`。
+pub(crate) fn spawn_named<T, F>(tasks: &mut JoinSet<T>, name: &str, future: F)
+where
+    F: Future<Output = T> + Send + 'static,
+    T: Send + 'static,
+{
+    #[cfg(all(tokio_unstable, feature = "tokio-console"))]
+    {
+        tasks
+            .build_task()
+            .name(name)
+            .spawn(future)
+            .expect("启动任务失败");
+    }
+
+    #[cfg(not(all(tokio_unstable, feature = "tokio-console")))]
+    {
+        let _ = name;
+        tasks.spawn(future);
+    }
+}
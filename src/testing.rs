@@ -0,0 +1,886 @@
+//! 用于集成测试的进程内模拟 COS 服务器
+//!
+//! [`MockCos`] 在本地端口上实现了本库用到的 COS 协议子集：对象的 PUT / GET / HEAD / DELETE、
+//! 服务端复制（含元数据与标签的替换指令）、分块上传（含分块复制）、按前缀与分隔符列举以及批量删除，并按请求签名校验密钥。
+//! 上传器通过自定义端点连接到它，测试不需要真实的密钥，也不会访问网络。
+//!
+//! ```rust,no_run
+//! use cos_upload::testing::MockCos;
+//!
+//! # async fn example() -> anyhow::Result<()> {
+//! let mock = MockCos::start().await?;
+//! let uploader = mock.uploader();
+//! uploader.upload_file("Cargo.toml", "a/Cargo.toml", None).await?;
+//! assert!(mock.object("a/Cargo.toml").is_some());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::config::Config;
+use crate::datetime::{format_http_date, format_iso8601};
+use crate::hash::{HashBackend, SoftwareHashBackend};
+use crate::signature::expected_signature;
+use crate::uploader::Uploader;
+use crate::xml::{escape, find_all_tags, find_tag, unescape};
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{HeaderMap, HeaderName, HeaderValue};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use md5::{Digest, Md5};
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::ops::Bound;
+use std::sync::{Arc, Mutex};
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::debug;
+
+/// 模拟服务器接受的 SecretId
+pub const MOCK_SECRET_ID: &str = "mock-secret-id";
+/// 模拟服务器接受的 SecretKey
+pub const MOCK_SECRET_KEY: &str = "mock-secret-key";
+/// [`MockCos::config`] 使用的 Bucket 名称
+pub const MOCK_BUCKET: &str = "mock-1250000000";
+/// [`MockCos::config`] 使用的地域
+pub const MOCK_REGION: &str = "ap-guangzhou";
+
+/// 上传时保存、读取时原样返回的请求头（另外还有全部 `x-cos-meta-*`）
+const STORED_HEADERS: [&str; 8] = [
+    "content-type",
+    "content-encoding",
+    "content-language",
+    "content-disposition",
+    "cache-control",
+    "expires",
+    "x-cos-storage-class",
+    "x-cos-website-redirect-location",
+];
+
+/// 保存的对象
+#[derive(Debug, Clone)]
+struct MockObject {
+    data: Bytes,
+    etag: String,
+    crc64: u64,
+    headers: Vec<(String, String)>,
+    /// `x-cos-tagging` 的原始值
+    tagging: Option<String>,
+    last_modified: DateTime<Utc>,
+}
+
+/// 进行中的分块上传
+#[derive(Debug, Default)]
+struct MockUpload {
+    object_key: String,
+    headers: Vec<(String, String)>,
+    tagging: Option<String>,
+    parts: BTreeMap<u32, (String, Bytes)>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    objects: BTreeMap<String, MockObject>,
+    uploads: HashMap<String, MockUpload>,
+    next_id: u64,
+    /// 接下来要以给定状态码拒绝的请求数
+    failures: Option<(usize, u16)>,
+    /// 接下来要以给定状态码拒绝的分块上传请求数
+    part_failures: Option<(usize, u16)>,
+    requests: usize,
+}
+
+/// 进程内的模拟 COS 服务器，参见[模块文档](self)
+///
+/// 被丢弃时停止服务。
+pub struct MockCos {
+    endpoint: String,
+    state: Arc<Mutex<MockState>>,
+    server: JoinHandle<()>,
+}
+
+impl MockCos {
+    /// 在 `127.0.0.1` 的随机端口上启动模拟服务器
+    ///
+    /// # 错误
+    ///
+    /// 无法监听本地端口时返回错误。
+    pub async fn start() -> Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let endpoint = format!("http://{}", listener.local_addr()?);
+        let state = Arc::new(Mutex::new(MockState::default()));
+
+        let shared = state.clone();
+        let server = tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let state = shared.clone();
+                tokio::spawn(async move {
+                    let service = service_fn(move |request| {
+                        let state = state.clone();
+                        async move { Ok::<_, Infallible>(handle(&state, request).await) }
+                    });
+                    if let Err(e) = http1::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await
+                    {
+                        debug!("模拟 COS 连接出错: {}", e);
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            endpoint,
+            state,
+            server,
+        })
+    }
+
+    /// 服务器地址，例如 `http://127.0.0.1:40123`
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
+    }
+
+    /// 连接到模拟服务器的配置，可以在此基础上继续设置对象键前缀等选项
+    pub fn config(&self) -> Config {
+        Config::new(
+            MOCK_SECRET_ID.to_string(),
+            MOCK_SECRET_KEY.to_string(),
+            MOCK_REGION.to_string(),
+            MOCK_BUCKET.to_string(),
+        )
+        .with_custom_endpoint(self.endpoint.clone())
+    }
+
+    /// 使用 [`MockCos::config`] 创建的上传器
+    pub fn uploader(&self) -> Uploader {
+        Uploader::new(self.config())
+    }
+
+    /// 读取保存的对象内容
+    ///
+    /// # 参数
+    ///
+    /// * `stored_key` - 对象在服务器中的完整键，包含对象键前缀，开启了对象键加密时为密文
+    pub fn object(&self, stored_key: &str) -> Option<Bytes> {
+        let state = self.state.lock().unwrap();
+        state
+            .objects
+            .get(stored_key)
+            .map(|object| object.data.clone())
+    }
+
+    /// 对象的标签（上传时 `x-cos-tagging` 的原始值），对象不存在或没有标签时返回 `None`
+    pub fn object_tagging(&self, stored_key: &str) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state.objects.get(stored_key)?.tagging.clone()
+    }
+
+    /// 保存的全部对象键，按字典序排列
+    pub fn object_keys(&self) -> Vec<String> {
+        self.state.lock().unwrap().objects.keys().cloned().collect()
+    }
+
+    /// 尚未完成或终止的分块上传数
+    pub fn pending_uploads(&self) -> usize {
+        self.state.lock().unwrap().uploads.len()
+    }
+
+    /// 收到的请求总数（包括签名错误与注入失败的请求）
+    pub fn request_count(&self) -> usize {
+        self.state.lock().unwrap().requests
+    }
+
+    /// 以给定的状态码（如 503）拒绝接下来的 `count` 个请求，用于测试重试
+    pub fn fail_next(&self, count: usize, status: u16) {
+        self.state.lock().unwrap().failures = Some((count, status));
+    }
+
+    /// 以给定的状态码拒绝接下来的 `count` 个分块上传请求，其他请求不受影响，
+    /// 用于测试分块失败后的处理
+    pub fn fail_next_parts(&self, count: usize, status: u16) {
+        self.state.lock().unwrap().part_failures = Some((count, status));
+    }
+}
+
+impl Drop for MockCos {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+type MockResponse = Response<Full<Bytes>>;
+
+/// 读取完请求体的请求
+struct MockRequest {
+    method: Method,
+    key: String,
+    params: HashMap<String, String>,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl MockRequest {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|v| v.to_str().ok())
+    }
+
+    fn has(&self, param: &str) -> bool {
+        self.params.contains_key(param)
+    }
+
+    /// 需要随对象保存的请求头
+    fn tagging(&self) -> Option<String> {
+        self.header("x-cos-tagging").map(str::to_string)
+    }
+
+    fn stored_headers(&self) -> Vec<(String, String)> {
+        self.headers
+            .iter()
+            .filter(|(name, _)| {
+                let name = name.as_str();
+                name.starts_with("x-cos-meta-") || STORED_HEADERS.contains(&name)
+            })
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect()
+    }
+}
+
+fn decode(text: &str) -> String {
+    urlencoding::decode(text)
+        .map(|v| v.into_owned())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    query
+        .unwrap_or("")
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) => (decode(key), decode(value)),
+            None => (decode(pair), String::new()),
+        })
+        .collect()
+}
+
+fn respond(status: StatusCode, headers: &[(&str, String)], body: impl Into<Bytes>) -> MockResponse {
+    let mut response = Response::new(Full::new(body.into()));
+    *response.status_mut() = status;
+    for (name, value) in headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            response.headers_mut().append(name, value);
+        }
+    }
+    response
+}
+
+fn xml(body: String) -> MockResponse {
+    respond(
+        StatusCode::OK,
+        &[("content-type", "application/xml".to_string())],
+        body,
+    )
+}
+
+fn error(status: u16, code: &str, message: &str) -> MockResponse {
+    let status = StatusCode::from_u16(status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+    let body = format!(
+        "<?xml version='1.0' encoding='utf-8' ?><Error><Code>{}</Code><Message>{}</Message></Error>",
+        code,
+        escape(message)
+    );
+    respond(
+        status,
+        &[("content-type", "application/xml".to_string())],
+        body,
+    )
+}
+
+fn etag_of(data: &[u8]) -> String {
+    format!("\"{}\"", hex::encode(Md5::digest(data)))
+}
+
+fn crc64_of(data: &[u8]) -> u64 {
+    let mut crc64 = SoftwareHashBackend.crc64();
+    crc64.update(data);
+    crc64.finish()
+}
+
+fn new_object(data: Bytes, headers: Vec<(String, String)>) -> MockObject {
+    MockObject {
+        etag: etag_of(&data),
+        crc64: crc64_of(&data),
+        data,
+        headers,
+        tagging: None,
+        last_modified: Utc::now(),
+    }
+}
+
+/// 解析 `bytes=a-b` / `bytes=a-` 形式的范围，返回左闭右开区间
+fn parse_range(value: &str, size: u64) -> Option<(u64, u64)> {
+    let (start, end) = value.strip_prefix("bytes=")?.split_once('-')?;
+    let start: u64 = start.parse().ok()?;
+    let end = match end {
+        "" => size,
+        end => end.parse::<u64>().ok()?.saturating_add(1).min(size),
+    };
+    (start < end).then_some((start, end))
+}
+
+/// 校验请求签名，不通过时返回错误响应
+fn check_signature(request: &MockRequest, path: &str) -> Option<MockResponse> {
+    // 预签名 URL 把签名放在 q-* 查询参数中
+    let authorization = match request.header("authorization") {
+        Some(authorization) => authorization.to_string(),
+        None if request.has("q-signature") => request
+            .params
+            .iter()
+            .filter(|(key, _)| key.starts_with("q-"))
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect::<Vec<_>>()
+            .join("&"),
+        None => return Some(error(403, "AccessDenied", "缺少 Authorization 头部")),
+    };
+    let fields: HashMap<&str, &str> = authorization
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .collect();
+    let field = |name: &str| fields.get(name).copied().unwrap_or("");
+
+    if field("q-ak") != MOCK_SECRET_ID {
+        return Some(error(403, "InvalidAccessKeyId", "SecretId 不存在"));
+    }
+    let key_time = field("q-key-time");
+    let expired = key_time
+        .split_once(';')
+        .and_then(|(start, end)| Some((start.parse::<i64>().ok()?, end.parse::<i64>().ok()?)))
+        .is_none_or(|(start, end)| {
+            let now = Utc::now().timestamp();
+            now < start - 60 || now > end
+        });
+    if expired {
+        return Some(error(403, "AccessDenied", "签名已过期或尚未生效"));
+    }
+
+    let names = |list: &str| -> Vec<String> {
+        list.split(';')
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let params: HashMap<String, String> = names(field("q-url-param-list"))
+        .into_iter()
+        .map(|name| {
+            let value = request
+                .params
+                .iter()
+                .find(|(key, _)| key.eq_ignore_ascii_case(&name))
+                .map(|(_, value)| value.clone())
+                .unwrap_or_default();
+            (name, value)
+        })
+        .collect();
+    let headers: HashMap<String, String> = names(field("q-header-list"))
+        .into_iter()
+        .map(|name| {
+            let value = request.header(&name).unwrap_or("").to_string();
+            (name, value)
+        })
+        .collect();
+
+    let expected = expected_signature(
+        MOCK_SECRET_KEY,
+        key_time,
+        request.method.as_str(),
+        path,
+        &params,
+        &headers,
+    );
+    (field("q-signature") != expected)
+        .then(|| error(403, "SignatureDoesNotMatch", "签名与服务器计算的不一致"))
+}
+
+async fn handle(state: &Mutex<MockState>, request: Request<Incoming>) -> MockResponse {
+    let (parts, body) = request.into_parts();
+    let body = match body.collect().await {
+        Ok(collected) => collected.to_bytes(),
+        Err(e) => return error(400, "IncompleteBody", &e.to_string()),
+    };
+    let path = decode(parts.uri.path());
+    let request = MockRequest {
+        method: parts.method,
+        key: path.trim_start_matches('/').to_string(),
+        params: parse_query(parts.uri.query()),
+        headers: parts.headers,
+        body,
+    };
+
+    let mut state = state.lock().unwrap();
+    state.requests += 1;
+    state.next_id += 1;
+    let request_id = format!("mock-{:08}", state.next_id);
+
+    let mut response = match check_signature(&request, &path) {
+        Some(denied) => denied,
+        None => match state.failures.take() {
+            Some((count, status)) if count > 0 => {
+                if count > 1 {
+                    state.failures = Some((count - 1, status));
+                }
+                error(status, "InternalError", "模拟服务器注入的失败")
+            }
+            _ if request.key.is_empty() => handle_bucket(&mut state, &request),
+            _ => handle_object(&mut state, &request),
+        },
+    };
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert("x-cos-request-id", value);
+    }
+    response
+}
+
+fn handle_bucket(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    match request.method {
+        Method::HEAD => respond(StatusCode::OK, &[], Bytes::new()),
+        Method::GET if !request.has("versions") && !request.has("uploads") => {
+            list_objects(state, request)
+        }
+        Method::POST if request.has("delete") => {
+            let body = String::from_utf8_lossy(&request.body);
+            let mut deleted = String::new();
+            for object in find_all_tags(&body, "Object") {
+                let Some(key) = find_tag(object, "Key").map(unescape) else {
+                    continue;
+                };
+                state.objects.remove(&key);
+                deleted.push_str(&format!("<Deleted><Key>{}</Key></Deleted>", escape(&key)));
+            }
+            xml(format!("<DeleteResult>{}</DeleteResult>", deleted))
+        }
+        _ => error(501, "NotImplemented", "模拟服务器不支持该 Bucket 操作"),
+    }
+}
+
+fn list_objects(state: &MockState, request: &MockRequest) -> MockResponse {
+    let param = |name: &str| request.params.get(name).cloned().unwrap_or_default();
+    let prefix = param("prefix");
+    let delimiter = param("delimiter");
+    let marker = param("marker");
+    let max_keys: usize = request
+        .params
+        .get("max-keys")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(1000);
+
+    let mut contents = String::new();
+    let mut prefixes: Vec<String> = Vec::new();
+    let mut count = 0;
+    let mut last = None;
+    let mut truncated = false;
+    let after_marker = (Bound::Excluded(marker.clone()), Bound::Unbounded);
+    for (key, object) in state.objects.range(after_marker) {
+        if !key.starts_with(&prefix) {
+            continue;
+        }
+        let common = (!delimiter.is_empty())
+            .then(|| {
+                key[prefix.len()..]
+                    .find(&delimiter)
+                    .map(|i| key[..prefix.len() + i + delimiter.len()].to_string())
+            })
+            .flatten();
+        if let Some(common) = &common {
+            if prefixes.last() == Some(common) || common.as_str() <= marker.as_str() {
+                continue;
+            }
+        }
+        if count == max_keys {
+            truncated = true;
+            break;
+        }
+        count += 1;
+        match common {
+            Some(common) => {
+                last = Some(common.clone());
+                prefixes.push(common);
+            }
+            None => {
+                last = Some(key.clone());
+                contents.push_str(&format!(
+                    "<Contents><Key>{}</Key><LastModified>{}</LastModified><ETag>{}</ETag><Size>{}</Size><StorageClass>{}</StorageClass></Contents>",
+                    escape(key),
+                    format_iso8601(&object.last_modified),
+                    escape(&object.etag),
+                    object.data.len(),
+                    object
+                        .headers
+                        .iter()
+                        .find(|(name, _)| name == "x-cos-storage-class")
+                        .map_or("STANDARD", |(_, value)| value.as_str())
+                ));
+            }
+        }
+    }
+
+    let common_prefixes: String = prefixes
+        .iter()
+        .map(|p| {
+            format!(
+                "<CommonPrefixes><Prefix>{}</Prefix></CommonPrefixes>",
+                escape(p)
+            )
+        })
+        .collect();
+    let next_marker = match (truncated, last) {
+        (true, Some(last)) => format!("<NextMarker>{}</NextMarker>", escape(&last)),
+        _ => String::new(),
+    };
+    xml(format!(
+        "<ListBucketResult><Name>{}</Name><Prefix>{}</Prefix><Marker>{}</Marker><MaxKeys>{}</MaxKeys><IsTruncated>{}</IsTruncated>{}{}{}</ListBucketResult>",
+        MOCK_BUCKET,
+        escape(&prefix),
+        escape(&marker),
+        max_keys,
+        truncated,
+        next_marker,
+        contents,
+        common_prefixes
+    ))
+}
+
+/// 读取 `x-cos-copy-source` 指向的对象（`{host}/{path}`），返回对象键
+fn copy_source_key(request: &MockRequest) -> Option<String> {
+    let source = request.header("x-cos-copy-source")?;
+    let path = source.split_once('/').map_or("", |(_, path)| path);
+    Some(decode(path))
+}
+
+/// 按写入条件检查，不满足时返回错误响应
+fn check_write_conditions(
+    state: &MockState,
+    request: &MockRequest,
+    key: &str,
+) -> Option<MockResponse> {
+    let current = state.objects.get(key);
+    if request.header("x-cos-forbid-overwrite") == Some("true") && current.is_some() {
+        return Some(error(409, "FileAlreadyExists", "对象已存在"));
+    }
+    if let Some(expected) = request.header("if-match") {
+        if current.is_none_or(|object| object.etag != expected) {
+            return Some(error(412, "PreconditionFailed", "ETag 不匹配"));
+        }
+    }
+    if request.header("if-none-match") == Some("*") && current.is_some() {
+        return Some(error(412, "PreconditionFailed", "对象已存在"));
+    }
+    None
+}
+
+fn object_headers(object: &MockObject) -> Vec<(&str, String)> {
+    let mut headers = vec![
+        ("etag", object.etag.clone()),
+        ("x-cos-hash-crc64ecma", object.crc64.to_string()),
+        ("last-modified", format_http_date(&object.last_modified)),
+    ];
+    if let Some(tagging) = &object.tagging {
+        headers.push((
+            "x-cos-tagging-count",
+            tagging.split('&').count().to_string(),
+        ));
+    }
+    if !object
+        .headers
+        .iter()
+        .any(|(name, _)| name == "content-type")
+    {
+        headers.push(("content-type", "application/octet-stream".to_string()));
+    }
+    headers.extend(
+        object
+            .headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.clone())),
+    );
+    headers
+}
+
+fn handle_object(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let key = request.key.clone();
+    match request.method {
+        Method::PUT if request.has("partNumber") => match state.part_failures.take() {
+            Some((count, status)) if count > 0 => {
+                if count > 1 {
+                    state.part_failures = Some((count - 1, status));
+                }
+                error(status, "InternalError", "模拟服务器注入的失败")
+            }
+            _ => upload_part(state, request),
+        },
+        Method::PUT if request.has("tagging") => respond(StatusCode::OK, &[], Bytes::new()),
+        Method::PUT if request.params.is_empty() => {
+            if let Some(denied) = check_write_conditions(state, request, &key) {
+                return denied;
+            }
+            match copy_source_key(request) {
+                Some(source) => {
+                    let Some(source) = state.objects.get(&source).cloned() else {
+                        return error(404, "NoSuchKey", "复制源对象不存在");
+                    };
+                    let headers = if request.header("x-cos-metadata-directive") == Some("Replaced")
+                    {
+                        request.stored_headers()
+                    } else {
+                        source.headers.clone()
+                    };
+                    // 与元数据不同，标签由单独的 `x-cos-tagging-directive` 决定是否替换
+                    let mut object = new_object(source.data, headers);
+                    object.tagging =
+                        if request.header("x-cos-tagging-directive") == Some("Replaced") {
+                            request.tagging()
+                        } else {
+                            source.tagging
+                        };
+                    let body = format!(
+                        "<CopyObjectResult><ETag>{}</ETag><LastModified>{}</LastModified></CopyObjectResult>",
+                        escape(&object.etag),
+                        format_iso8601(&object.last_modified)
+                    );
+                    state.objects.insert(key, object);
+                    xml(body)
+                }
+                None => {
+                    let mut object = new_object(request.body.clone(), request.stored_headers());
+                    object.tagging = request.tagging();
+                    let headers = [
+                        ("etag", object.etag.clone()),
+                        ("x-cos-hash-crc64ecma", object.crc64.to_string()),
+                    ];
+                    state.objects.insert(key, object);
+                    respond(StatusCode::OK, &headers, Bytes::new())
+                }
+            }
+        }
+        Method::GET | Method::HEAD if request.params.keys().all(|p| p == "versionId") => {
+            let Some(object) = state.objects.get(&key) else {
+                return match request.method {
+                    Method::HEAD => respond(StatusCode::NOT_FOUND, &[], Bytes::new()),
+                    _ => error(404, "NoSuchKey", "对象不存在"),
+                };
+            };
+            if request.header("if-none-match") == Some(object.etag.as_str()) {
+                return respond(
+                    StatusCode::NOT_MODIFIED,
+                    &[("etag", object.etag.clone())],
+                    Bytes::new(),
+                );
+            }
+            let size = object.data.len() as u64;
+            let mut headers = object_headers(object);
+            // `If-Range` 与当前 ETag 不一致时忽略范围，返回整个对象
+            let range = request.header("range").filter(|_| {
+                request
+                    .header("if-range")
+                    .is_none_or(|tag| tag == object.etag)
+            });
+            let (status, data) = match range {
+                Some(range) => match parse_range(range, size) {
+                    Some((start, end)) => {
+                        headers.push((
+                            "content-range",
+                            format!("bytes {}-{}/{}", start, end - 1, size),
+                        ));
+                        (
+                            StatusCode::PARTIAL_CONTENT,
+                            object.data.slice(start as usize..end as usize),
+                        )
+                    }
+                    None => return error(416, "InvalidRange", "范围无效"),
+                },
+                None => (StatusCode::OK, object.data.clone()),
+            };
+            if request.method == Method::HEAD {
+                headers.push(("content-length", data.len().to_string()));
+                return respond(status, &headers, Bytes::new());
+            }
+            respond(status, &headers, data)
+        }
+        Method::DELETE if request.has("uploadId") => {
+            match state.uploads.remove(&request.params["uploadId"]) {
+                Some(_) => respond(StatusCode::NO_CONTENT, &[], Bytes::new()),
+                None => error(404, "NoSuchUpload", "分块上传不存在"),
+            }
+        }
+        Method::DELETE if request.params.is_empty() => {
+            state.objects.remove(&key);
+            respond(StatusCode::NO_CONTENT, &[], Bytes::new())
+        }
+        Method::POST if request.has("uploads") => {
+            state.next_id += 1;
+            let upload_id = format!("mock-upload-{}", state.next_id);
+            state.uploads.insert(
+                upload_id.clone(),
+                MockUpload {
+                    object_key: key.clone(),
+                    headers: request.stored_headers(),
+                    tagging: request.tagging(),
+                    parts: BTreeMap::new(),
+                },
+            );
+            xml(format!(
+                "<InitiateMultipartUploadResult><Bucket>{}</Bucket><Key>{}</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                MOCK_BUCKET,
+                escape(&key),
+                upload_id
+            ))
+        }
+        Method::POST if request.has("uploadId") => complete_upload(state, request),
+        _ => error(501, "NotImplemented", "模拟服务器不支持该对象操作"),
+    }
+}
+
+fn upload_part(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let Some(part_number) = request.params["partNumber"].parse::<u32>().ok() else {
+        return error(400, "InvalidArgument", "分块编号无效");
+    };
+    let upload_id = request.params.get("uploadId").cloned().unwrap_or_default();
+    if !state.uploads.contains_key(&upload_id) {
+        return error(404, "NoSuchUpload", "分块上传不存在");
+    }
+
+    let (data, copy) = match copy_source_key(request) {
+        Some(source) => {
+            let Some(source) = state.objects.get(&source) else {
+                return error(404, "NoSuchKey", "复制源对象不存在");
+            };
+            let size = source.data.len() as u64;
+            let data = match request.header("x-cos-copy-source-range") {
+                Some(range) => match parse_range(range, size) {
+                    Some((start, end)) => source.data.slice(start as usize..end as usize),
+                    None => return error(400, "InvalidArgument", "复制范围无效"),
+                },
+                None => source.data.clone(),
+            };
+            (data, true)
+        }
+        None => (request.body.clone(), false),
+    };
+
+    let etag = etag_of(&data);
+    let crc64 = crc64_of(&data);
+    if let Some(upload) = state.uploads.get_mut(&upload_id) {
+        upload.parts.insert(part_number, (etag.clone(), data));
+    }
+    if copy {
+        return xml(format!(
+            "<CopyPartResult><ETag>{}</ETag><LastModified>{}</LastModified></CopyPartResult>",
+            escape(&etag),
+            Utc::now().format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        ));
+    }
+    respond(
+        StatusCode::OK,
+        &[("etag", etag), ("x-cos-hash-crc64ecma", crc64.to_string())],
+        Bytes::new(),
+    )
+}
+
+fn complete_upload(state: &mut MockState, request: &MockRequest) -> MockResponse {
+    let upload_id = request.params["uploadId"].clone();
+    let Some(upload) = state.uploads.get(&upload_id) else {
+        return error(404, "NoSuchUpload", "分块上传不存在");
+    };
+    if upload.object_key != request.key {
+        return error(400, "InvalidArgument", "对象键与分块上传不一致");
+    }
+
+    let body = String::from_utf8_lossy(&request.body);
+    let mut data = Vec::new();
+    let mut digests = Vec::new();
+    let mut previous = 0;
+    let listed = find_all_tags(&body, "Part");
+    if listed.is_empty() {
+        return error(400, "MalformedXML", "分块列表为空");
+    }
+    for part in &listed {
+        let number = find_tag(part, "PartNumber").and_then(|v| v.parse::<u32>().ok());
+        let etag = find_tag(part, "ETag").map(unescape);
+        let (Some(number), Some(etag)) = (number, etag) else {
+            return error(400, "MalformedXML", "分块列表格式错误");
+        };
+        if number <= previous {
+            return error(400, "InvalidPartOrder", "分块编号未按升序排列");
+        }
+        previous = number;
+        match upload.parts.get(&number) {
+            Some((stored, bytes)) if *stored == etag => {
+                data.extend_from_slice(bytes);
+                digests
+                    .extend_from_slice(&hex::decode(stored.trim_matches('"')).unwrap_or_default());
+            }
+            _ => {
+                return error(
+                    400,
+                    "InvalidPart",
+                    &format!("分块 {} 不存在或 ETag 不一致", number),
+                )
+            }
+        }
+    }
+
+    let key = request.key.clone();
+    if let Some(denied) = check_write_conditions(state, request, &key) {
+        return denied;
+    }
+    let Some(upload) = state.uploads.remove(&upload_id) else {
+        return error(404, "NoSuchUpload", "分块上传不存在");
+    };
+    let mut object = new_object(Bytes::from(data), upload.headers);
+    object.tagging = upload.tagging;
+    object.etag = format!(
+        "\"{}-{}\"",
+        hex::encode(Md5::digest(&digests)),
+        listed.len()
+    );
+    let body = format!(
+        "<CompleteMultipartUploadResult><Location>{}/{}</Location><Bucket>{}</Bucket><Key>{}</Key><ETag>{}</ETag></CompleteMultipartUploadResult>",
+        MOCK_BUCKET,
+        escape(&key),
+        MOCK_BUCKET,
+        escape(&key),
+        escape(&object.etag)
+    );
+    let crc64 = object.crc64;
+    state.objects.insert(key, object);
+    respond(
+        StatusCode::OK,
+        &[
+            ("content-type", "application/xml".to_string()),
+            ("x-cos-hash-crc64ecma", crc64.to_string()),
+        ],
+        body,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("bytes=0-9", 100), Some((0, 10)));
+        assert_eq!(parse_range("bytes=90-", 100), Some((90, 100)));
+        assert_eq!(parse_range("bytes=90-200", 100), Some((90, 100)));
+        assert_eq!(parse_range("bytes=100-", 100), None);
+        assert_eq!(parse_range("items=0-1", 100), None);
+    }
+}
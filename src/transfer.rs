@@ -0,0 +1,293 @@
+use crate::journal::UploadJournal;
+use crate::queue::UploadQueue;
+use crate::schedule::TransferSchedule;
+use crate::types::UploadResult;
+use crate::uploader::{Metadata, Uploader};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::watch;
+use tracing::debug;
+
+/// 同一对象键存在进行中的上传时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicatePolicy {
+    /// 排队等待前一个上传结束后再上传
+    #[default]
+    Queue,
+    /// 不再上传，直接等待并共享进行中上传的结果（后来者的文件路径与元数据会被忽略）
+    Coalesce,
+    /// 立即返回 [`DuplicateUploadError`]
+    Reject,
+}
+
+/// 同一对象键已有上传正在进行时返回的错误（[`DuplicatePolicy::Reject`]）
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DuplicateUploadError {
+    /// 冲突的对象键
+    pub object_key: String,
+}
+
+impl fmt::Display for DuplicateUploadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "对象键已有上传正在进行: {}", self.object_key)
+    }
+}
+
+impl std::error::Error for DuplicateUploadError {}
+
+/// 进行中上传的结果，错误以文本形式共享给等待者
+type Outcome = Option<std::result::Result<UploadResult, String>>;
+type KeyLocks = Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>;
+type InFlight = Arc<Mutex<HashMap<String, watch::Receiver<Outcome>>>>;
+
+/// 传输管理器
+///
+/// 在进程内按对象键协调并发上传，避免两个 `upload_file` 调用对同一对象键交错地进行分块上传而浪费流量。
+///
+/// 与 [`Uploader`] 一样克隆的开销很小，克隆出的实例共享同一张对象键表，彼此之间同样会协调。
+#[derive(Clone)]
+pub struct TransferManager {
+    pub(crate) uploader: Arc<Uploader>,
+    pub(crate) duplicate_policy: DuplicatePolicy,
+    key_locks: KeyLocks,
+    in_flight: InFlight,
+    /// 通过 [`TransferManager::enqueue`] 排队的上传，包括正在执行的条目
+    pub(crate) queue: Arc<Mutex<UploadQueue>>,
+    /// 传输计划，为 `None` 时随时允许传输
+    pub(crate) schedule: Option<Arc<dyn TransferSchedule>>,
+    /// 上传日志，为 `None` 时上传队列只保存在内存中
+    pub(crate) journal: Option<Arc<UploadJournal>>,
+}
+
+impl TransferManager {
+    /// 创建传输管理器，默认对同一对象键的上传排队执行
+    ///
+    /// # 参数
+    ///
+    /// * `uploader` - 共享的上传器实例
+    pub fn new(uploader: Arc<Uploader>) -> Self {
+        Self {
+            uploader,
+            duplicate_policy: DuplicatePolicy::default(),
+            key_locks: Arc::default(),
+            in_flight: Arc::default(),
+            queue: Arc::default(),
+            schedule: None,
+            journal: None,
+        }
+    }
+
+    /// 设置同一对象键并发上传时的处理方式
+    pub fn with_duplicate_policy(mut self, policy: DuplicatePolicy) -> Self {
+        self.duplicate_policy = policy;
+        self
+    }
+
+    /// 上传文件到 COS，并按 [`DuplicatePolicy`] 协调同一对象键的并发上传
+    ///
+    /// 参见 [`Uploader::upload_file`]。
+    pub async fn upload_file<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        metadata: Option<Metadata>,
+    ) -> Result<UploadResult> {
+        self.wait_for_schedule().await;
+        self.coordinate(object_key, self.duplicate_policy, || {
+            self.uploader.upload_file(file_path, object_key, metadata)
+        })
+        .await
+    }
+
+    /// 按给定的策略与同一对象键的其它上传协调后执行 `upload`
+    ///
+    /// [`TransferManager::upload_file`] 与上传队列共用同一张对象键表，彼此之间同样会协调。
+    pub(crate) async fn coordinate<F, Fut>(
+        &self,
+        object_key: &str,
+        policy: DuplicatePolicy,
+        upload: F,
+    ) -> Result<UploadResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<UploadResult>>,
+    {
+        match policy {
+            DuplicatePolicy::Queue => self.upload_queued(object_key, upload).await,
+            DuplicatePolicy::Coalesce | DuplicatePolicy::Reject => {
+                self.upload_exclusive(object_key, policy, upload).await
+            }
+        }
+    }
+
+    /// 持有对象键的异步锁进行上传，同一对象键的上传依次执行
+    async fn upload_queued<F, Fut>(&self, object_key: &str, upload: F) -> Result<UploadResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<UploadResult>>,
+    {
+        let lock = self
+            .key_locks
+            .lock()
+            .unwrap()
+            .entry(object_key.to_string())
+            .or_default()
+            .clone();
+
+        let result = {
+            let _guard = lock.lock().await;
+            upload().await
+        };
+
+        // 没有其它调用者持有该锁时清理，避免锁表无限增长
+        let mut locks = self.key_locks.lock().unwrap();
+        if Arc::strong_count(&lock) == 2 {
+            locks.remove(object_key);
+        }
+
+        result
+    }
+
+    /// 同一对象键只允许一个上传在进行，后来者等待其结果或被拒绝
+    async fn upload_exclusive<F, Fut>(
+        &self,
+        object_key: &str,
+        policy: DuplicatePolicy,
+        upload: F,
+    ) -> Result<UploadResult>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<UploadResult>>,
+    {
+        let sender = {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            match in_flight.get(object_key) {
+                Some(receiver) => Err(receiver.clone()),
+                None => {
+                    let (sender, receiver) = watch::channel(None);
+                    in_flight.insert(object_key.to_string(), receiver);
+                    Ok(sender)
+                }
+            }
+        };
+
+        let sender = match sender {
+            Ok(sender) => sender,
+            Err(_) if policy == DuplicatePolicy::Reject => {
+                return Err(DuplicateUploadError {
+                    object_key: object_key.to_string(),
+                }
+                .into());
+            }
+            Err(mut receiver) => {
+                debug!("等待进行中的上传: {}", object_key);
+                let outcome = receiver
+                    .wait_for(|outcome| outcome.is_some())
+                    .await
+                    .map_err(|_| anyhow::anyhow!("进行中的上传已被取消: {}", object_key))?
+                    .clone();
+                return match outcome {
+                    Some(Ok(result)) => Ok(result),
+                    Some(Err(message)) => Err(anyhow::anyhow!(
+                        "进行中的上传失败: {}: {}",
+                        object_key,
+                        message
+                    )),
+                    None => Err(anyhow::anyhow!("进行中的上传没有结果: {}", object_key)),
+                };
+            }
+        };
+
+        // 即使上传的 future 被丢弃，也要从进行中的表里移除
+        let _cleanup = InFlightGuard {
+            in_flight: &self.in_flight,
+            object_key,
+        };
+
+        let result = upload().await;
+
+        let _ = sender.send(Some(match &result {
+            Ok(result) => Ok(result.clone()),
+            Err(e) => Err(format!("{:#}", e)),
+        }));
+
+        result
+    }
+}
+
+/// 在离开作用域时把对象键从进行中的表里移除
+struct InFlightGuard<'a> {
+    in_flight: &'a InFlight,
+    object_key: &'a str,
+}
+
+impl Drop for InFlightGuard<'_> {
+    fn drop(&mut self) {
+        self.in_flight.lock().unwrap().remove(self.object_key);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use std::time::Duration;
+
+    fn manager() -> TransferManager {
+        let config = Config::new("id".into(), "key".into(), "ap-guangzhou".into(), "b".into());
+        TransferManager::new(Arc::new(Uploader::new(config)))
+    }
+
+    #[tokio::test]
+    async fn test_key_lock_cleanup() {
+        let manager = manager();
+        let order = Mutex::new(Vec::new());
+        let upload = |name: &'static str, delay: u64| {
+            let order = &order;
+            let manager = &manager;
+            move || async move {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                // 持有锁期间对象键一直留在锁表中
+                assert!(manager.key_locks.lock().unwrap().contains_key("k"));
+                order.lock().unwrap().push(name);
+                Err(anyhow::anyhow!("上传失败: {}", name))
+            }
+        };
+
+        let (first, second) = tokio::join!(
+            manager.coordinate("k", DuplicatePolicy::Queue, upload("first", 20)),
+            manager.coordinate("k", DuplicatePolicy::Queue, upload("second", 0)),
+        );
+        assert!(first.is_err() && second.is_err());
+        assert_eq!(*order.lock().unwrap(), ["first", "second"]);
+        assert!(manager.key_locks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_in_flight_cleanup_on_cancel() {
+        let manager = manager();
+        let pending = std::future::pending::<Result<UploadResult>>;
+
+        // 进行中的上传被丢弃时，等待者收到错误，对象键从进行中的表里移除
+        let (running, waiting, rejected) = tokio::join!(
+            tokio::time::timeout(
+                Duration::from_millis(20),
+                manager.coordinate("k", DuplicatePolicy::Coalesce, pending),
+            ),
+            manager.coordinate("k", DuplicatePolicy::Coalesce, pending),
+            manager.coordinate("k", DuplicatePolicy::Reject, pending),
+        );
+        assert!(running.is_err());
+        assert!(waiting.unwrap_err().to_string().contains("已被取消"));
+        assert!(rejected
+            .unwrap_err()
+            .downcast_ref::<DuplicateUploadError>()
+            .is_some());
+        assert!(manager.in_flight.lock().unwrap().is_empty());
+    }
+}
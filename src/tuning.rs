@@ -0,0 +1,106 @@
+//! 分块大小的自适应调整
+//!
+//! 从较小的分块开始，让第一个分块尽快完成；之后按已完成分块的吞吐量调整分块大小，
+//! 使每个分块的上传耗时接近 [`TARGET_PART_DURATION`]：快速链路上减少请求次数，
+//! 慢速链路上保持较小的分块，重试时需要重传的数据也更少。
+
+use std::time::Duration;
+
+/// 自适应模式下第一个分块的大小
+const INITIAL_PART_SIZE: u64 = 1024 * 1024; // 1 MB
+/// 自适应模式下的最小分块大小（COS 要求除最后一个分块外不小于 1 MB）
+const MIN_ADAPTIVE_PART_SIZE: u64 = 1024 * 1024; // 1 MB
+/// 自适应模式下的最大分块大小，并发上传的分块都驻留在内存中，需要限制单个分块的大小
+const MAX_ADAPTIVE_PART_SIZE: u64 = 64 * 1024 * 1024; // 64 MB
+/// 期望的单个分块上传耗时
+const TARGET_PART_DURATION: Duration = Duration::from_secs(4);
+/// 吞吐量指数移动平均中新样本的权重
+const THROUGHPUT_WEIGHT: f64 = 0.5;
+/// 单次调整的最大倍数，避免偶然的快速分块让分块大小剧烈变化
+const MAX_GROWTH: u64 = 4;
+
+/// 按观测到的吞吐量决定下一个分块的大小
+#[derive(Debug)]
+pub(crate) struct PartSizeTuner {
+    /// 单个分块上传的吞吐量（字节/秒）的指数移动平均
+    throughput: Option<f64>,
+    /// 最近一次给出的分块大小
+    current: u64,
+    /// 分块数量上限
+    max_parts: u64,
+}
+
+impl PartSizeTuner {
+    pub(crate) fn new(max_parts: usize) -> Self {
+        Self {
+            throughput: None,
+            current: INITIAL_PART_SIZE,
+            max_parts: max_parts as u64,
+        }
+    }
+
+    /// 记录一个已完成分块的大小与耗时
+    pub(crate) fn record(&mut self, bytes: u64, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64().max(0.001);
+        let sample = bytes as f64 / secs;
+        self.throughput = Some(match self.throughput {
+            Some(average) => average * (1.0 - THROUGHPUT_WEIGHT) + sample * THROUGHPUT_WEIGHT,
+            None => sample,
+        });
+    }
+
+    /// 下一个分块的大小
+    ///
+    /// # 参数
+    ///
+    /// * `remaining` - 尚未分块的字节数
+    /// * `parts_used` - 已经分配的分块数量
+    pub(crate) fn next_size(&mut self, remaining: u64, parts_used: u64) -> u64 {
+        let mut size = match self.throughput {
+            Some(throughput) => {
+                let target = (throughput * TARGET_PART_DURATION.as_secs_f64()) as u64;
+                target.clamp(self.current / MAX_GROWTH, self.current * MAX_GROWTH)
+            }
+            None => self.current,
+        };
+        size = size.clamp(MIN_ADAPTIVE_PART_SIZE, MAX_ADAPTIVE_PART_SIZE);
+
+        // 保证剩余的数据能在分块数量上限内分完
+        let parts_left = self.max_parts.saturating_sub(parts_used).max(1);
+        size = size.max(remaining.div_ceil(parts_left));
+
+        self.current = size;
+        size
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MB: u64 = 1024 * 1024;
+
+    #[test]
+    fn test_part_size_tuner() {
+        let mut tuner = PartSizeTuner::new(10000);
+        assert_eq!(tuner.next_size(100 * MB, 0), INITIAL_PART_SIZE);
+
+        // 快速链路上逐步增大，但单次不超过 4 倍且不超过上限
+        tuner.record(MB, Duration::from_millis(10));
+        assert_eq!(tuner.next_size(100 * MB, 1), 4 * MB);
+        for _ in 0..10 {
+            tuner.record(64 * MB, Duration::from_millis(100));
+            tuner.next_size(10_000 * MB, 2);
+        }
+        assert_eq!(tuner.next_size(10_000 * MB, 12), MAX_ADAPTIVE_PART_SIZE);
+
+        // 慢速链路上保持最小分块
+        let mut slow = PartSizeTuner::new(10000);
+        slow.next_size(100 * MB, 0);
+        slow.record(MB, Duration::from_secs(20));
+        assert_eq!(slow.next_size(100 * MB, 1), MIN_ADAPTIVE_PART_SIZE);
+
+        // 分块数量即将用完时增大分块
+        assert_eq!(slow.next_size(100 * MB, 9999), 100 * MB);
+    }
+}
@@ -0,0 +1,292 @@
+#[cfg(any(feature = "runtime", feature = "presign"))]
+use crate::datetime::parse_http_date;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::Duration;
+
+/// COS 返回的请求 ID 头部
+#[cfg(any(feature = "runtime", feature = "presign"))]
+pub(crate) const REQUEST_ID_HEADER: &str = "x-cos-request-id";
+/// COS 返回的 CRC64 校验值头部
+#[cfg(any(feature = "runtime", feature = "presign"))]
+pub(crate) const CRC64_HEADER: &str = "x-cos-hash-crc64ecma";
+
+/// 从响应头中取出 COS 请求 ID
+#[cfg(any(feature = "runtime", feature = "presign"))]
+pub(crate) fn request_id_of(headers: &reqwest::header::HeaderMap) -> Option<String> {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
+
+/// 解析 `x-cos-hash-crc64ecma` 头部的值
+///
+/// COS 以无符号十进制字符串返回 CRC64-ECMA，常常超过 `i64` 的范围，不能按有符号整数解析；
+/// 经过部分代理时值两侧可能带有空白或引号，一并去掉。
+///
+/// # 返回值
+///
+/// 不是合法的无符号十进制数时返回 `None`
+pub fn parse_crc64(value: &str) -> Option<u64> {
+    value.trim().trim_matches('"').parse().ok()
+}
+
+/// 从响应头中取出并解析对象的 CRC64
+#[cfg(any(feature = "runtime", feature = "presign"))]
+pub(crate) fn crc64_of(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    headers
+        .get(CRC64_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_crc64)
+}
+
+/// 上传结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UploadResult {
+    /// 上传后的文件 URL
+    pub url: String,
+    /// 对象的 ETag
+    pub etag: Option<String>,
+    /// 最后一次请求（PUT 或完成分块上传）的 `x-cos-request-id`，
+    /// 可与业务流水号一同记录，便于向腾讯云支持排查问题
+    pub request_id: Option<String>,
+    /// 上传的耗时统计，只有通过 `upload_file` 系列方法实际上传了文件时才有
+    pub stats: Option<TransferStats>,
+    /// 主 Bucket 上传失败、改为写入备用 Bucket 时的记录，正常写入主 Bucket 时为 `None`
+    pub failover: Option<Failover>,
+    /// COS 返回的对象 CRC64（`x-cos-hash-crc64ecma`），COS 未返回时为 `None`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub crc64: Option<u64>,
+}
+
+impl fmt::Display for UploadResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+/// 上传改为写入备用 Bucket 的记录，参见 `Uploader::with_failover`
+///
+/// 对象此时不在主 Bucket 中，调用方应记录下来，在主 Bucket 恢复后补传或迁回。
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Failover {
+    /// 实际写入的备用 Bucket
+    pub bucket: String,
+    /// 备用 Bucket 所在的地域
+    pub region: String,
+    /// 主 Bucket 上传失败的原因
+    pub primary_error: String,
+}
+
+/// 一次上传的耗时统计
+///
+/// 可以直接记录到日志或监控中，在上传性能下降时告警，无需在外部自行计时。
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TransferStats {
+    /// 上传的总耗时（不含上传后写入校验值旁路文件等附加操作）
+    pub duration: Duration,
+    /// 本次实际上传的字节数，从断点继续时不含此前已完成的分块
+    pub bytes: u64,
+    /// 本次上传的分块数，普通上传为 0
+    pub parts: u32,
+    /// 单个分块耗时（含重试）的最小值，普通上传为 `None`
+    pub min_part_latency: Option<Duration>,
+    /// 单个分块耗时（含重试）的中位数，普通上传为 `None`
+    pub median_part_latency: Option<Duration>,
+    /// 单个分块耗时（含重试）的最大值，普通上传为 `None`
+    pub max_part_latency: Option<Duration>,
+    /// 分块重试的总次数
+    pub retries: u32,
+}
+
+impl TransferStats {
+    /// 根据总耗时与各分块的耗时生成统计
+    #[cfg(feature = "runtime")]
+    pub(crate) fn new(
+        duration: Duration,
+        bytes: u64,
+        mut part_latencies: Vec<Duration>,
+        retries: u32,
+    ) -> Self {
+        part_latencies.sort();
+        let len = part_latencies.len();
+        let median_part_latency = match len {
+            0 => None,
+            _ if len % 2 == 1 => Some(part_latencies[len / 2]),
+            _ => Some((part_latencies[len / 2 - 1] + part_latencies[len / 2]) / 2),
+        };
+
+        Self {
+            duration,
+            bytes,
+            parts: len as u32,
+            min_part_latency: part_latencies.first().copied(),
+            median_part_latency,
+            max_part_latency: part_latencies.last().copied(),
+            retries,
+        }
+    }
+
+    /// 平均吞吐量（字节/秒），耗时为 0 时返回 0
+    pub fn bytes_per_sec(&self) -> f64 {
+        let secs = self.duration.as_secs_f64();
+        if secs > 0.0 {
+            self.bytes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 删除结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DeleteResult {
+    /// 删除请求的 `x-cos-request-id`
+    pub request_id: Option<String>,
+}
+
+/// 对象元数据
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct ObjectMetadata {
+    /// 对象大小（字节）
+    pub content_length: Option<u64>,
+    /// 对象的 Content-Type
+    pub content_type: Option<String>,
+    /// 对象的 Content-Language
+    pub content_language: Option<String>,
+    /// 静态网站访问对象时的重定向地址（`x-cos-website-redirect-location`）
+    pub website_redirect_location: Option<String>,
+    /// 对象的 ETag
+    pub etag: Option<String>,
+    /// 对象的最后修改时间（`Last-Modified`）
+    pub last_modified: Option<DateTime<Utc>>,
+    /// 对象的缓存过期时间（`Expires`），未设置或不是合法的 HTTP 日期时为 `None`
+    pub expires: Option<DateTime<Utc>>,
+    /// 归档对象恢复出的临时副本的过期时间（`x-cos-restore` 中的 `expiry-date`），
+    /// 未恢复或仍在恢复中时为 `None`
+    pub restore_expiry: Option<DateTime<Utc>>,
+    /// 对象的 CRC64（`x-cos-hash-crc64ecma`），COS 未返回时为 `None`
+    pub crc64: Option<u64>,
+    /// 自定义元数据，键已去掉 `x-cos-meta-` 前缀
+    pub user_metadata: HashMap<String, String>,
+    /// HEAD 请求的 `x-cos-request-id`
+    pub request_id: Option<String>,
+    /// 全部原始响应头，键为小写
+    pub headers: HashMap<String, String>,
+}
+
+/// 取出 `x-cos-restore` 头部中的 `expiry-date`，例如
+/// `ongoing-request="false", expiry-date="Wed, 21 Oct 2015 07:28:00 GMT"`
+#[cfg(any(feature = "runtime", feature = "presign"))]
+fn parse_restore_expiry(value: &str) -> Option<DateTime<Utc>> {
+    let start = value.find("expiry-date=\"")? + "expiry-date=\"".len();
+    let end = value[start..].find('"')? + start;
+    parse_http_date(&value[start..end])
+}
+
+#[cfg(any(feature = "runtime", feature = "presign"))]
+impl ObjectMetadata {
+    /// 从 HEAD 响应头构建对象元数据
+    pub(crate) fn from_headers(headers: &reqwest::header::HeaderMap) -> Self {
+        let headers: HashMap<String, String> = headers
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
+            .collect();
+
+        let user_metadata = headers
+            .iter()
+            .filter_map(|(k, v)| {
+                k.strip_prefix("x-cos-meta-")
+                    .map(|name| (name.to_string(), v.clone()))
+            })
+            .collect();
+        let date_header = |name: &str| headers.get(name).and_then(|v| parse_http_date(v));
+
+        Self {
+            content_length: headers.get("content-length").and_then(|v| v.parse().ok()),
+            content_type: headers.get("content-type").cloned(),
+            content_language: headers.get("content-language").cloned(),
+            website_redirect_location: headers.get("x-cos-website-redirect-location").cloned(),
+            etag: headers.get("etag").cloned(),
+            last_modified: date_header("last-modified"),
+            expires: date_header("expires"),
+            restore_expiry: headers
+                .get("x-cos-restore")
+                .and_then(|v| parse_restore_expiry(v)),
+            crc64: headers.get(CRC64_HEADER).and_then(|v| parse_crc64(v)),
+            user_metadata,
+            request_id: headers.get(REQUEST_ID_HEADER).cloned(),
+            headers,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "runtime"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_stats() {
+        let ms = Duration::from_millis;
+        let stats = TransferStats::new(ms(2000), 4000, vec![ms(300), ms(100), ms(200), ms(900)], 1);
+        assert_eq!(stats.parts, 4);
+        assert_eq!(stats.min_part_latency, Some(ms(100)));
+        assert_eq!(stats.median_part_latency, Some(ms(250)));
+        assert_eq!(stats.max_part_latency, Some(ms(900)));
+        assert_eq!(stats.bytes_per_sec(), 2000.0);
+
+        let stats = TransferStats::new(ms(0), 10, Vec::new(), 0);
+        assert_eq!(stats.median_part_latency, None);
+        assert_eq!(stats.bytes_per_sec(), 0.0);
+    }
+
+    #[test]
+    fn test_parse_crc64() {
+        assert_eq!(
+            parse_crc64("16749565679157681890"),
+            Some(16749565679157681890)
+        );
+        assert_eq!(parse_crc64(" \"42\" "), Some(42));
+        assert_eq!(parse_crc64("-1"), None);
+        assert_eq!(parse_crc64(""), None);
+
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(CRC64_HEADER, "18446744073709551615".parse().unwrap());
+        assert_eq!(ObjectMetadata::from_headers(&headers).crc64, Some(u64::MAX));
+    }
+
+    #[test]
+    fn test_metadata_dates() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            "last-modified",
+            "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap(),
+        );
+        headers.insert("expires", "0".parse().unwrap());
+        headers.insert(
+            "x-cos-restore",
+            "ongoing-request=\"false\", expiry-date=\"Thu, 22 Oct 2015 00:00:00 GMT\""
+                .parse()
+                .unwrap(),
+        );
+        let metadata = ObjectMetadata::from_headers(&headers);
+        assert_eq!(
+            metadata.last_modified,
+            DateTime::from_timestamp(1445412480, 0)
+        );
+        assert_eq!(metadata.expires, None);
+        assert_eq!(
+            metadata.restore_expiry,
+            DateTime::from_timestamp(1445472000, 0)
+        );
+        assert_eq!(parse_restore_expiry("ongoing-request=\"true\""), None);
+    }
+}
@@ -0,0 +1,456 @@
+use crate::keymap::path_to_key;
+use crate::options::UploadOptions;
+use crate::types::UploadResult;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::path::{Path, PathBuf};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// 单个条目不超过该大小时以一次 `PUT` 上传，否则按该大小分块上传
+const CHUNK_SIZE: u64 = 8 * 1024 * 1024; // 8 MB
+/// 分块上传最多的分块数
+const MAX_PARTS: u64 = 10000;
+/// 解压线程最多领先上传的数据块数
+const CHANNEL_CAPACITY: usize = 2;
+
+/// 归档格式，按文件扩展名判断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ArchiveFormat {
+    Tar,
+    TarGz,
+    Zip,
+}
+
+impl ArchiveFormat {
+    fn from_path(path: &Path) -> Result<Self> {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+            Ok(ArchiveFormat::TarGz)
+        } else if name.ends_with(".tar") {
+            Ok(ArchiveFormat::Tar)
+        } else if name.ends_with(".zip") {
+            Ok(ArchiveFormat::Zip)
+        } else {
+            Err(anyhow!(
+                "无法识别的归档格式（支持 .tar、.tar.gz、.tgz 与 .zip）: {:?}",
+                path
+            ))
+        }
+    }
+}
+
+/// 上传归档内容的选项
+#[derive(Debug, Clone, Default)]
+pub struct ArchiveUploadOptions {
+    /// 只上传路径匹配其中任一模式的条目，为空时上传全部条目
+    ///
+    /// 模式与条目在归档中的路径（以 `/` 分隔）整体匹配：`*` 匹配路径段内的任意字符，
+    /// `**` 可以跨越 `/`（`**/` 也可以匹配零个路径段），`?` 匹配路径段内的单个字符，
+    /// 例如 `*.html`、`assets/**`、`**/*.json`。
+    pub include: Vec<String>,
+    /// 应用到每个对象的上传选项（元数据、存储类型等）
+    pub upload: UploadOptions,
+}
+
+impl ArchiveUploadOptions {
+    /// 创建默认选项（上传全部条目）
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 添加一个包含模式，参见 [`ArchiveUploadOptions::include`]
+    pub fn with_include(mut self, pattern: String) -> Self {
+        self.include.push(pattern);
+        self
+    }
+
+    /// 设置应用到每个对象的上传选项
+    pub fn with_upload_options(mut self, upload: UploadOptions) -> Self {
+        self.upload = upload;
+        self
+    }
+
+    /// 条目路径是否需要上传
+    fn includes(&self, path: &str) -> bool {
+        self.include.is_empty() || self.include.iter().any(|pattern| glob_match(pattern, path))
+    }
+}
+
+/// 上传归档内容的报告
+#[derive(Debug, Default)]
+pub struct ArchiveUploadReport {
+    /// 上传成功的对象键及其结果
+    pub uploaded: Vec<(String, UploadResult)>,
+    /// 上传失败的条目（对象键，无法映射为对象键时为条目路径）及失败原因
+    pub failed: Vec<(String, String)>,
+    /// 不匹配包含模式而跳过的条目路径
+    pub skipped: Vec<String>,
+}
+
+impl ArchiveUploadReport {
+    /// 是否所有选中的条目都上传成功
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// `*` 匹配路径段内的任意字符，`**` 可以跨越 `/`，`?` 匹配路径段内的单个字符
+fn glob_match(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[char], path: &[char]) -> bool {
+        match pattern {
+            [] => path.is_empty(),
+            ['*', '*', rest @ ..] => {
+                // `**/` 可以匹配零个路径段
+                if let ['/', after @ ..] = rest {
+                    if matches(after, path) {
+                        return true;
+                    }
+                }
+                (0..=path.len()).any(|i| matches(rest, &path[i..]))
+            }
+            ['*', rest @ ..] => (0..=path.len())
+                .take_while(|&i| i == 0 || path[i - 1] != '/')
+                .any(|i| matches(rest, &path[i..])),
+            ['?', rest @ ..] => {
+                matches!(path.first(), Some(c) if *c != '/') && matches(rest, &path[1..])
+            }
+            [c, rest @ ..] => path.first() == Some(c) && matches(rest, &path[1..]),
+        }
+    }
+    let pattern: Vec<char> = pattern.chars().collect();
+    let path: Vec<char> = path.chars().collect();
+    matches(&pattern, &path)
+}
+
+/// 解压线程发给上传任务的消息
+enum ArchiveItem {
+    /// 一个选中条目的开始，随后是它的数据块与 [`ArchiveItem::End`]
+    Entry {
+        path: String,
+        size: u64,
+    },
+    Data(Bytes),
+    End,
+    /// 不匹配包含模式的条目
+    Skipped(String),
+}
+
+/// 条目的分块大小，保证分块数不超过上限
+fn chunk_size(size: u64) -> u64 {
+    CHUNK_SIZE.max(size.div_ceil(MAX_PARTS))
+}
+
+/// 在阻塞线程中顺序读取归档，把选中条目的数据按块发送给上传任务
+///
+/// 上传任务提前结束（接收端被丢弃）时停止读取。
+fn read_archive(
+    path: &Path,
+    format: ArchiveFormat,
+    options: &ArchiveUploadOptions,
+    tx: &mpsc::Sender<ArchiveItem>,
+) -> Result<()> {
+    let file = BufReader::new(File::open(path)?);
+    match format {
+        ArchiveFormat::Tar | ArchiveFormat::TarGz => {
+            let reader: Box<dyn Read> = match format {
+                ArchiveFormat::TarGz => Box::new(GzDecoder::new(file)),
+                _ => Box::new(file),
+            };
+            let mut archive = tar::Archive::new(reader);
+            for entry in archive.entries()? {
+                let mut entry = entry?;
+                if !entry.header().entry_type().is_file() {
+                    continue;
+                }
+                let name = entry.path()?.to_string_lossy().into_owned();
+                let size = entry.size();
+                if !send_entry(tx, options, name, size, &mut entry)? {
+                    return Ok(());
+                }
+            }
+        }
+        ArchiveFormat::Zip => {
+            let mut archive = zip::ZipArchive::new(file)?;
+            for i in 0..archive.len() {
+                let mut entry = archive.by_index(i)?;
+                if !entry.is_file() {
+                    continue;
+                }
+                let name = entry.name()?.into_owned();
+                let size = entry.size();
+                if !send_entry(tx, options, name, size, &mut entry)? {
+                    return Ok(());
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 发送一个条目，返回 `false` 表示上传任务已经结束
+fn send_entry(
+    tx: &mpsc::Sender<ArchiveItem>,
+    options: &ArchiveUploadOptions,
+    path: String,
+    size: u64,
+    reader: &mut impl Read,
+) -> Result<bool> {
+    if !options.includes(path.trim_start_matches("./")) {
+        return Ok(tx.blocking_send(ArchiveItem::Skipped(path)).is_ok());
+    }
+    if tx.blocking_send(ArchiveItem::Entry { path, size }).is_err() {
+        return Ok(false);
+    }
+    let chunk_size = chunk_size(size);
+    loop {
+        let mut chunk = Vec::new();
+        reader.by_ref().take(chunk_size).read_to_end(&mut chunk)?;
+        if chunk.is_empty() {
+            break;
+        }
+        if tx.blocking_send(ArchiveItem::Data(chunk.into())).is_err() {
+            return Ok(false);
+        }
+    }
+    Ok(tx.blocking_send(ArchiveItem::End).is_ok())
+}
+
+/// 正在上传的条目
+struct EntryUpload {
+    object_key: String,
+    /// 第一个数据块，条目只有一块时以一次 `PUT` 上传
+    first: Option<Bytes>,
+    upload_id: Option<String>,
+    parts: Vec<(u32, String)>,
+}
+
+impl Uploader {
+    /// 把归档中的文件逐个上传为独立的对象
+    ///
+    /// 参见 [`Uploader::upload_archive_contents_with_options`]。
+    pub async fn upload_archive_contents<P: AsRef<Path>>(
+        &self,
+        archive_path: P,
+        prefix: &str,
+    ) -> Result<ArchiveUploadReport> {
+        self.upload_archive_contents_with_options(
+            archive_path,
+            prefix,
+            &ArchiveUploadOptions::default(),
+        )
+        .await
+    }
+
+    /// 边解压边把归档中的文件逐个上传为独立的对象（需要启用 `unpack` feature）
+    ///
+    /// 适合把 CI 产物包展开为可以直接浏览的对象而无需先解压到本地：归档在阻塞线程中顺序读取，
+    /// 每个条目按 8 MB 的块交给上传任务，不超过一块的条目以一次 `PUT` 上传，更大的条目分块上传，
+    /// 内存中最多保留几个数据块。目录、符号链接等非普通文件的条目被忽略。
+    ///
+    /// 支持 `.tar`、`.tar.gz`（`.tgz`）与 `.zip`，按文件扩展名判断格式。
+    ///
+    /// # 参数
+    ///
+    /// * `archive_path` - 归档文件路径
+    /// * `prefix` - 对象键前缀，对象键为 `prefix` + 条目在归档中的路径
+    /// * `options` - 包含模式与上传选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功读取整个归档后返回上传报告，单个条目的上传失败记录在报告中而不会作为错误返回
+    ///
+    /// # 错误
+    ///
+    /// 无法识别归档格式、打开或解析归档失败时返回错误。
+    pub async fn upload_archive_contents_with_options<P: AsRef<Path>>(
+        &self,
+        archive_path: P,
+        prefix: &str,
+        options: &ArchiveUploadOptions,
+    ) -> Result<ArchiveUploadReport> {
+        if let Some(target) = self.retarget(&options.upload) {
+            return Box::pin(target.upload_archive_contents_with_options(
+                archive_path,
+                prefix,
+                options,
+            ))
+            .await;
+        }
+        let archive_path: PathBuf = archive_path.as_ref().to_path_buf();
+        let format = ArchiveFormat::from_path(&archive_path)?;
+
+        let (tx, mut rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let reader = {
+            let archive_path = archive_path.clone();
+            let options = options.clone();
+            tokio::task::spawn_blocking(move || read_archive(&archive_path, format, &options, &tx))
+        };
+
+        let mut report = ArchiveUploadReport::default();
+        // 当前条目，映射对象键失败或上传出错后为 `None`，其余数据块被丢弃
+        let mut current: Option<EntryUpload> = None;
+        while let Some(item) = rx.recv().await {
+            match item {
+                ArchiveItem::Skipped(path) => report.skipped.push(path),
+                ArchiveItem::Entry { path, size } => match path_to_key(&path, false) {
+                    Ok(key) => {
+                        current = Some(EntryUpload {
+                            object_key: format!("{}{}", prefix, key),
+                            first: None,
+                            upload_id: None,
+                            parts: Vec::new(),
+                        });
+                        info!("上传归档条目: {} ({} 字节)", path, size);
+                    }
+                    Err(e) => {
+                        warn!("跳过无法映射为对象键的归档条目: {}", path);
+                        report.failed.push((path, format!("{:#}", e)));
+                    }
+                },
+                ArchiveItem::Data(data) => {
+                    let Some(entry) = current.as_mut() else {
+                        continue;
+                    };
+                    if let Err(e) = self.push_entry_data(entry, data, &options.upload).await {
+                        let entry = current.take().expect("当前条目存在");
+                        self.fail_entry(entry, e, &mut report).await;
+                    }
+                }
+                ArchiveItem::End => {
+                    let Some(entry) = current.take() else {
+                        continue;
+                    };
+                    match self.finish_entry(&entry, &options.upload).await {
+                        Ok(result) => report.uploaded.push((entry.object_key, result)),
+                        Err(e) => self.fail_entry(entry, e, &mut report).await,
+                    }
+                }
+            }
+        }
+        reader.await??;
+
+        info!(
+            "归档 {:?} 上传完成: 成功 {}，失败 {}，跳过 {}",
+            archive_path,
+            report.uploaded.len(),
+            report.failed.len(),
+            report.skipped.len()
+        );
+        Ok(report)
+    }
+
+    /// 处理条目的一个数据块：第一块先保留，出现第二块时改为分块上传
+    async fn push_entry_data(
+        &self,
+        entry: &mut EntryUpload,
+        data: Bytes,
+        options: &UploadOptions,
+    ) -> Result<()> {
+        if entry.upload_id.is_none() {
+            let Some(first) = entry.first.take() else {
+                entry.first = Some(data);
+                return Ok(());
+            };
+            let upload_id = self
+                .init_multipart_upload(&entry.object_key, options)
+                .await?;
+            entry.upload_id = Some(upload_id);
+            self.push_part(entry, first).await?;
+        }
+        self.push_part(entry, data).await
+    }
+
+    async fn push_part(&self, entry: &mut EntryUpload, data: Bytes) -> Result<()> {
+        let upload_id = entry.upload_id.as_deref().expect("分块上传已初始化");
+        let part_number = entry.parts.len() as u32 + 1;
+        let etag = self
+            .upload_part_bytes(&entry.object_key, upload_id, part_number, data)
+            .await?;
+        entry.parts.push((part_number, etag));
+        Ok(())
+    }
+
+    async fn finish_entry(
+        &self,
+        entry: &EntryUpload,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        match &entry.upload_id {
+            Some(upload_id) => {
+                self.complete_multipart_upload(&entry.object_key, upload_id, &entry.parts)
+                    .await
+            }
+            None => {
+                let content_type = mime_guess::from_path(&entry.object_key)
+                    .first_or_octet_stream()
+                    .to_string();
+                self.put_bytes_with_options(
+                    &entry.object_key,
+                    entry.first.clone().unwrap_or_default(),
+                    &content_type,
+                    options,
+                )
+                .await
+            }
+        }
+    }
+
+    /// 记录失败的条目，已经开始的分块上传被终止
+    async fn fail_entry(
+        &self,
+        entry: EntryUpload,
+        error: anyhow::Error,
+        report: &mut ArchiveUploadReport,
+    ) {
+        warn!("上传归档条目失败: {}: {:#}", entry.object_key, error);
+        if let Some(upload_id) = &entry.upload_id {
+            if let Err(e) = self
+                .abort_multipart_upload(&entry.object_key, upload_id)
+                .await
+            {
+                warn!("终止分块上传失败: {}: {:#}", entry.object_key, e);
+            }
+        }
+        report
+            .failed
+            .push((entry.object_key, format!("{:#}", error)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("*.html", "index.html"));
+        assert!(!glob_match("*.html", "docs/index.html"));
+        assert!(glob_match("**/*.html", "index.html"));
+        assert!(glob_match("**/*.html", "docs/api/index.html"));
+        assert!(glob_match("assets/**", "assets/css/a.css"));
+        assert!(!glob_match("assets/**", "static/a.css"));
+        assert!(glob_match("report-?.txt", "report-1.txt"));
+        assert!(!glob_match("report-?.txt", "report-10.txt"));
+
+        let options = ArchiveUploadOptions::new();
+        assert!(options.includes("anything"));
+        let options = options.with_include("*.txt".to_string());
+        assert!(options.includes("a.txt") && !options.includes("a.bin"));
+
+        assert_eq!(
+            ArchiveFormat::from_path(Path::new("dist.TGZ")).unwrap(),
+            ArchiveFormat::TarGz
+        );
+        assert!(ArchiveFormat::from_path(Path::new("dist.rar")).is_err());
+        assert_eq!(chunk_size(1), CHUNK_SIZE);
+        assert_eq!(chunk_size(MAX_PARTS * CHUNK_SIZE * 2), CHUNK_SIZE * 2);
+    }
+}
@@ -1,23 +1,159 @@
-use crate::config::Config;
-use crate::signature::generate_authorization;
+use crate::batch::RetryBudget;
+use crate::cache::{CacheKey, ObjectCache};
+use crate::checkpoint::{file_mtime, MultipartCheckpoint};
+use crate::config::{Config, SignedHeaders};
+use crate::error::{is_not_found, is_stream_error, map_already_exists, CosError};
+use crate::events::TransferEvent;
+use crate::handle::{TransferControl, TransferState};
+use crate::hash::{default_hash_backend, sha1_hex, HashBackend};
+use crate::http::client_builder;
+use crate::idempotency::IdempotencyStore;
+use crate::inflight::InFlight;
+use crate::keypolicy::KeyPolicy;
+use crate::mimepolicy::ContentTypePolicy;
+use crate::options::UploadOptions;
+use crate::probe::SelectedEndpoint;
+use crate::progress::{ProgressInterval, ProgressTracker};
+use crate::ratelimit::RequestRateLimiter;
+use crate::request::{header_of, object_url_of, read_xml, CosRequest};
+use crate::retry::RetryClassifier;
+use crate::signature::Signer;
+use crate::task::{next_transfer_id, spawn_named};
+use crate::tuning::PartSizeTuner;
+use crate::types::{
+    parse_crc64, request_id_of, DeleteResult, ObjectMetadata, TransferStats, UploadResult,
+    CRC64_HEADER,
+};
+use crate::xml::find_tag;
 use anyhow::Result;
-use reqwest::Client;
-use std::collections::HashMap;
-use std::path::Path;
+use bytes::Bytes;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Method};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 use tokio::fs::File;
 use tokio::io::{AsyncReadExt, AsyncSeekExt};
-use tracing::{debug, error, info};
+use tokio::sync::{broadcast, Semaphore};
+use tokio::task::JoinSet;
+use tracing::{debug, error, info, info_span, warn, Instrument};
+use urlencoding::encode as url_encode;
 
 /// 分块上传的阈值，超过此大小的文件将使用分块上传
-const MULTIPART_THRESHOLD: u64 = 5 * 1024 * 1024; // 5 MB
+pub(crate) const MULTIPART_THRESHOLD: u64 = 5 * 1024 * 1024; // 5 MB
 /// 每个分块的大小
-const PART_SIZE: u64 = 5 * 1024 * 1024; // 5 MB
+pub(crate) const PART_SIZE: u64 = 5 * 1024 * 1024; // 5 MB
+/// 同时上传的分块数量
+///
+/// 单个分块的上传时间主要花在往返与 TCP 慢启动上，多个分块同时上传才能占满带宽；
+/// 每个进行中的分块都会在内存中保留一份数据，按默认分块大小最多占用约 20 MB。
+const PART_CONCURRENCY: usize = 4;
+/// 分块的最小大小（最后一个分块除外）
+pub(crate) const MIN_PART_SIZE: u64 = 1024 * 1024; // 1 MB
+/// 服务端复制单个分块的最大大小
+const MAX_COPY_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024; // 5 GB
+/// 单个分块上传允许的最大分块数
+pub(crate) const MAX_PARTS: usize = 10000;
+/// 单个分块的最大尝试次数
+const PART_MAX_ATTEMPTS: u32 = 3;
+/// 普通上传的最大尝试次数
+const SIMPLE_PUT_MAX_ATTEMPTS: u32 = 3;
+/// 普通上传在发送请求体时中断达到该次数后改用分块上传
+const STREAM_FAILURES_BEFORE_MULTIPART: u32 = 2;
+/// 禁止覆盖同名对象的请求头部
+pub(crate) const FORBID_OVERWRITE_HEADER: &str = "x-cos-forbid-overwrite";
+/// 分块重试的初始退避时间，之后每次翻倍
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
 
+/// 整文件上传的数据来源，切换到备用 Bucket 或镜像到影子 Bucket 时从这里重新读取
+pub(crate) enum UploadSource {
+    /// 本地文件路径
+    Path(PathBuf),
+    /// 已打开的文件与要上传的字节数
+    Open(std::fs::File, u64),
+}
+
+impl UploadSource {
+    /// 复制一份来源，已打开的文件复制文件描述符
+    pub(crate) fn try_clone(&self) -> Result<Self> {
+        Ok(match self {
+            Self::Path(path) => Self::Path(path.clone()),
+            Self::Open(file, len) => Self::Open(file.try_clone()?, *len),
+        })
+    }
+
+    /// 从来源重新上传到另一个上传器
+    pub(crate) async fn upload_to(
+        &self,
+        uploader: &Uploader,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        match self {
+            Self::Path(path) => {
+                Box::pin(uploader.upload_file_with_options(path, object_key, options)).await
+            }
+            Self::Open(file, len) => {
+                let file = File::from_std(file.try_clone()?);
+                Box::pin(uploader.upload_open_file(file, *len, object_key, options)).await
+            }
+        }
+    }
+}
+
+/// COS 上传器
+///
+/// 克隆的开销很小：HTTP 连接池、配置、签名器与哈希后端都在 `Arc` 中共享，
+/// 克隆出的实例与原实例使用同一连接池和 SignKey 缓存。
+/// `Uploader` 是 `Send + Sync` 的，可以直接克隆后移动到 `tokio::spawn` 的任务中，无需再包一层 `Arc`。
+#[derive(Clone)]
 pub struct Uploader {
-    client: Client,
-    config: Config,
+    pub(crate) client: Client,
+    pub(crate) config: Arc<Config>,
+    pub(crate) events: Option<broadcast::Sender<TransferEvent>>,
+    /// 批量操作共享的重试预算，为 `None` 时分块重试不受限制
+    pub(crate) retry_budget: Option<Arc<RetryBudget>>,
+    /// 计算上传数据校验值的后端
+    pub(crate) hash_backend: Arc<dyn HashBackend>,
+    /// 按时间窗口缓存 SignKey 的签名器
+    pub(crate) signer: Arc<Signer>,
+    /// 当前使用的域名类型与配置地域下的访问域名，创建时预先生成，可由端点探测切换
+    pub(crate) endpoint: Arc<RwLock<SelectedEndpoint>>,
+    /// 已确认存在的过期生命周期规则（按保留天数）
+    pub(crate) expiry_rules: Arc<Mutex<HashSet<u64>>>,
+    /// 影子模式下额外接收每个上传的上传器
+    pub(crate) shadow: Option<Arc<Uploader>>,
+    /// 主 Bucket 持续失败时改为写入的备用上传器
+    pub(crate) failover: Option<Arc<Uploader>>,
+    /// 下载对象内容的内存缓存
+    pub(crate) object_cache: Option<Arc<ObjectCache>>,
+    /// 幂等上传使用的存储
+    pub(crate) idempotency_store: Option<Arc<dyn IdempotencyStore>>,
+    /// 进行中的对象读取，并发读取同一对象时合并为一次请求
+    pub(crate) in_flight_reads: Arc<InFlight<CacheKey, Bytes>>,
+    /// 分块上传进度事件的发送间隔
+    pub(crate) progress_interval: ProgressInterval,
+    /// 慢请求的阈值，超过时输出警告
+    pub(crate) slow_request_threshold: Option<Duration>,
+    /// 自定义的重试判断，为 `None` 时使用默认判断
+    pub(crate) retry_classifier: Option<Arc<dyn RetryClassifier>>,
+    /// 每秒请求数的限速器，所有克隆共享
+    pub(crate) request_limiter: Option<Arc<RequestRateLimiter>>,
+    /// 上传内容类型的允许/禁止策略
+    pub(crate) content_type_policy: Option<Arc<ContentTypePolicy>>,
+    /// 对象键的命名规范
+    pub(crate) key_policy: Option<Arc<dyn KeyPolicy>>,
 }
 
+// 保证 `Uploader` 始终可以廉价克隆并在任务间共享
+const _: fn() = || {
+    fn assert_shareable<T: Clone + Send + Sync>() {}
+    assert_shareable::<Uploader>();
+};
+
 pub type Metadata = HashMap<String, String>;
 
 impl Uploader {
@@ -27,9 +163,77 @@ impl Uploader {
     ///
     /// * `config` - COS 配置
     pub fn new(config: Config) -> Self {
+        // 不自动跟随重定向：重定向到其它地域的域名会导致签名中的 Host 失效，
+        // 地域不匹配由 `execute` 统一识别并处理
+        let client = client_builder(config.app_name.as_deref())
+            .redirect(Policy::none())
+            .build()
+            .expect("创建 HTTP 客户端失败");
+        config.signed_headers.warn_if_reduced();
+
         Self {
-            client: Client::new(),
-            config,
+            client,
+            signer: Arc::new(
+                Signer::new(&config.secret_id, &config.secret_key)
+                    .with_canonicalization(config.header_canonicalization),
+            ),
+            endpoint: Arc::new(RwLock::new(SelectedEndpoint::new(&config, config.endpoint))),
+            expiry_rules: Arc::default(),
+            shadow: None,
+            failover: None,
+            object_cache: None,
+            idempotency_store: None,
+            in_flight_reads: Arc::default(),
+            progress_interval: ProgressInterval::default(),
+            slow_request_threshold: None,
+            retry_classifier: None,
+            request_limiter: None,
+            content_type_policy: None,
+            key_policy: None,
+            config: Arc::new(config),
+            events: None,
+            retry_budget: None,
+            hash_backend: default_hash_backend(),
+        }
+    }
+
+    /// 设置参与请求签名的头部范围，参见 [`SignedHeaders`](crate::SignedHeaders)
+    ///
+    /// 上传器克隆的开销很小，只有部分请求需要经过改写头部的代理时，可以在克隆上设置：
+    /// `uploader.clone().with_signed_headers(SignedHeaders::Minimal)`。
+    pub fn with_signed_headers(mut self, signed_headers: SignedHeaders) -> Self {
+        signed_headers.warn_if_reduced();
+        Arc::make_mut(&mut self.config).signed_headers = signed_headers;
+        self
+    }
+
+    /// 设置计算上传数据校验值的哈希后端
+    pub fn with_hash_backend(mut self, backend: Arc<dyn HashBackend>) -> Self {
+        self.hash_backend = backend;
+        self
+    }
+
+    /// 开启传输事件广播
+    ///
+    /// # 参数
+    ///
+    /// * `capacity` - 广播通道的容量，订阅者处理过慢时会丢失最早的事件
+    pub fn with_event_channel(mut self, capacity: usize) -> Self {
+        self.events = Some(broadcast::channel(capacity).0);
+        self
+    }
+
+    /// 订阅传输事件
+    ///
+    /// 未通过 [`Uploader::with_event_channel`] 开启事件广播时返回 `None`。
+    pub fn subscribe_events(&self) -> Option<broadcast::Receiver<TransferEvent>> {
+        self.events.as_ref().map(|sender| sender.subscribe())
+    }
+
+    /// 发送传输事件，没有订阅者时直接丢弃
+    pub(crate) fn emit(&self, event: TransferEvent) {
+        if let Some(sender) = &self.events {
+            let _ = sender.send(event);
         }
     }
 
@@ -44,224 +248,692 @@ impl Uploader {
     ///
     /// # 返回值
     ///
-    /// 成功时返回上传后的文件 URL
+    /// 成功时返回上传结果，包含文件 URL、ETag 与最后一次请求的 `x-cos-request-id`
     pub async fn upload_file<P: AsRef<Path>>(
         &self,
         file_path: P,
         object_key: &str,
         metadata: Option<Metadata>,
-    ) -> Result<String> {
+    ) -> Result<UploadResult> {
+        let options = UploadOptions {
+            metadata,
+            ..UploadOptions::default()
+        };
+        self.upload_file_with_options(file_path, object_key, &options)
+            .await
+    }
+
+    /// 按上传选项上传文件到 COS
+    ///
+    /// 与 [`Uploader::upload_file`] 相同，但可以设置有效期等选项。
+    ///
+    /// # 参数
+    ///
+    /// * `file_path` - 要上传的文件路径
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回上传结果，包含文件 URL、ETag 与最后一次请求的 `x-cos-request-id`
+    pub async fn upload_file_with_options<P: AsRef<Path>>(
+        &self,
+        file_path: P,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
         let file_path = file_path.as_ref();
+        match self.retarget(options) {
+            Some(target) => {
+                target
+                    .upload_to_bucket(file_path, object_key, options)
+                    .await
+            }
+            None => self.upload_to_bucket(file_path, object_key, options).await,
+        }
+    }
+
+    /// 按上传选项上传文件到当前配置的 Bucket，失败时按需切换到备用 Bucket
+    async fn upload_to_bucket(
+        &self,
+        file_path: &Path,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
         let file_size = tokio::fs::metadata(file_path).await?.len();
 
-        if file_size > MULTIPART_THRESHOLD {
-            self.multipart_upload(file_path, object_key, metadata).await
-        } else {
-            self.simple_upload(file_path, object_key, metadata).await
+        let transfer = async {
+            if file_size > MULTIPART_THRESHOLD {
+                self.multipart_upload(file_path, object_key, options).await
+            } else {
+                self.simple_upload(file_path, object_key, options).await
+            }
+        };
+        let source = UploadSource::Path(file_path.to_path_buf());
+        self.run_upload(&source, object_key, options, transfer)
+            .await
+    }
+
+    /// 上传文件，使用分块上传时从给定的断点继续，并通过 `on_checkpoint` 报告最新的断点
+    ///
+    /// 给定 `control` 时，每开始一个分块前检查是否被暂停或取消。
+    pub(crate) async fn upload_file_resumable(
+        &self,
+        file_path: &Path,
+        object_key: &str,
+        options: &UploadOptions,
+        checkpoint: Option<MultipartCheckpoint>,
+        on_checkpoint: &(dyn Fn(&MultipartCheckpoint) + Send + Sync),
+        control: Option<&TransferControl>,
+    ) -> Result<UploadResult> {
+        if let Some(target) = self.retarget(options) {
+            return Box::pin(target.upload_file_resumable(
+                file_path,
+                object_key,
+                options,
+                checkpoint,
+                on_checkpoint,
+                control,
+            ))
+            .await;
         }
+        let file_size = tokio::fs::metadata(file_path).await?.len();
+
+        let transfer = async {
+            if file_size > MULTIPART_THRESHOLD {
+                return self
+                    .multipart_upload_resumable(
+                        file_path,
+                        object_key,
+                        options,
+                        checkpoint,
+                        on_checkpoint,
+                        control,
+                    )
+                    .await;
+            }
+            // 文件缩小到不再需要分块上传（包括变为空文件）时，释放断点中的分块上传
+            if let Some(stale) = checkpoint {
+                warn!("文件已变化，放弃旧的断点: {}", stale.upload_id);
+                if let Err(e) = self
+                    .abort_multipart_upload(&stale.object_key, &stale.upload_id)
+                    .await
+                {
+                    warn!("终止旧的分块上传失败: {}", e);
+                }
+            }
+            self.simple_upload(file_path, object_key, options).await
+        };
+        let source = UploadSource::Path(file_path.to_path_buf());
+        self.run_upload(&source, object_key, options, transfer)
+            .await
+    }
+
+    /// 执行一次整文件上传，并完成各个上传入口共用的前后步骤
+    ///
+    /// 文件路径与已打开的文件两种来源共用，新的上传选项只需要在这里处理一次。
+    ///
+    /// 上传前按需确保过期生命周期规则；`transfer` 失败时按需切换到备用 Bucket；
+    /// 成功后清除对象缓存、进行读后校验、写入校验值旁路文件并镜像到影子 Bucket。
+    pub(crate) async fn run_upload(
+        &self,
+        source: &UploadSource,
+        object_key: &str,
+        options: &UploadOptions,
+        transfer: impl Future<Output = Result<UploadResult>>,
+    ) -> Result<UploadResult> {
+        if options.ensure_lifecycle_rule {
+            if let Some(days) = options.expiry_days() {
+                // 生命周期规则只是兜底清理，缺少权限时不影响上传
+                if let Err(e) = self.ensure_expiry_rule(days).await {
+                    warn!("确保过期生命周期规则失败: {}", e);
+                }
+            }
+        }
+
+        let result = match transfer.await {
+            Ok(result) => result,
+            Err(e) => return self.failover_upload(source, object_key, options, e).await,
+        };
+        self.invalidate_cached(object_key);
+        // 已打开的文件在入口处拒绝了读后校验
+        if let UploadSource::Path(file_path) = source {
+            self.verify_upload(file_path, object_key, options).await?;
+        }
+        if options.checksum_sidecar {
+            self.upload_sidecar(object_key).await?;
+        }
+        self.mirror_upload(source, object_key, options, &result);
+        Ok(result)
     }
 
     /// 普通上传
+    ///
+    /// 文件读入内存后作为可重放的请求体发送，网络错误与 5xx/429 响应最多尝试 [`SIMPLE_PUT_MAX_ATTEMPTS`] 次；
+    /// 发送请求体时连接中断（如连接被重置）达到 [`STREAM_FAILURES_BEFORE_MULTIPART`] 次后，
+    /// 改用自适应大小的分块上传，每个分块单独重试。
     async fn simple_upload<P: AsRef<Path>>(
         &self,
         file_path: P,
         object_key: &str,
-        metadata: Option<Metadata>,
-    ) -> Result<String> {
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
         let file_path = file_path.as_ref();
         debug!("普通上传文件: {:?}", file_path);
 
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}",
-            self.config.bucket, self.config.region, object_key
-        );
-
         let content_type = mime_guess::from_path(file_path)
             .first_or_octet_stream()
             .to_string();
 
         let file_content = tokio::fs::read(file_path).await?;
+        self.put_content(
+            file_content,
+            content_type,
+            object_key,
+            options,
+            Some(file_path),
+        )
+        .await
+    }
 
-        let mut headers = HashMap::new();
-        headers.insert("Content-Type".to_string(), content_type.clone());
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
-        );
-        headers.insert("Content-Length".to_string(), file_content.len().to_string());
+    /// 以一个 PUT 请求上传内存中的内容
+    ///
+    /// 给定 `file_path` 时，发送请求体时连接多次中断后改用该文件的分块上传；否则按普通的重试策略重试。
+    pub(crate) async fn put_content(
+        &self,
+        file_content: Vec<u8>,
+        content_type: String,
+        object_key: &str,
+        options: &UploadOptions,
+        file_path: Option<&Path>,
+    ) -> Result<UploadResult> {
+        let started = Instant::now();
+        let bytes = file_content.len() as u64;
+        let mut crc64 = self.hash_backend.crc64();
+        crc64.update(&file_content);
 
-        // 添加元数据头
-        if let Some(metadata) = metadata {
-            for (key, value) in metadata {
-                headers.insert(format!("x-cos-meta-{}", key), value);
-            }
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .header("Content-Type", content_type)
+            .body(file_content);
+        if options.forbid_overwrite {
+            request = request.header(FORBID_OVERWRITE_HEADER, "true");
         }
+        let request = options.apply(request);
 
-        let params = HashMap::new();
-
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "put",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
-        );
-
-        // 构建请求 headers
-        let mut request = self
-            .client
-            .put(&url)
-            .header("Authorization", authorization);
+        // 发送请求：请求体是内存中的 `Bytes`，网络错误或 5xx 后可以原样重放
+        let mut attempt = 1;
+        let mut stream_failures = 0;
+        let response = loop {
+            match self.execute(request.clone()).await {
+                Ok(response) => break response,
+                Err(e)
+                    if attempt < SIMPLE_PUT_MAX_ATTEMPTS
+                        && self.is_retryable(&e)
+                        && self.acquire_retry() =>
+                {
+                    if is_stream_error(&e) {
+                        stream_failures += 1;
+                    }
+                    // 连接反复在发送请求体时中断，改用较小的分块，每个分块单独重试
+                    if let Some(file_path) = file_path.filter(|_| {
+                        stream_failures >= STREAM_FAILURES_BEFORE_MULTIPART && bytes > 0
+                    }) {
+                        warn!("发送请求体时连接多次中断，改用分块上传: {}", e);
+                        let options = UploadOptions {
+                            adaptive_part_size: true,
+                            ..options.clone()
+                        };
+                        return Box::pin(self.multipart_upload(file_path, object_key, &options))
+                            .await;
+                    }
+                    warn!("文件上传失败，准备重试 (第 {} 次): {}", attempt, e);
+                    tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!("文件上传失败: {}", e);
+                    return Err(map_already_exists(e, object_key));
+                }
+            }
+        };
 
-        for (key, value) in headers {
-            request = request.header(key, value);
-        }
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let etag = header_of(&response, "ETag");
+        let crc = header_of(&response, CRC64_HEADER);
+        let remote_crc64 = crc.as_deref().and_then(parse_crc64);
+        self.check_crc64(object_key, crc64.finish(), remote_crc64)?;
+        info!("文件上传成功: {} (request_id: {:?})", url, request_id);
 
-        // 发送请求
-        let response = request
-            .body(file_content)
-            .send()
-            .await?;
+        self.emit(TransferEvent::UploadCompleted {
+            transfer_id: next_transfer_id(),
+            object_key: object_key.to_string(),
+            etag: etag.clone(),
+            crc,
+            request_id: request_id.clone(),
+        });
 
-        if response.status().is_success() {
-            info!("文件上传成功: {}", url);
-            Ok(url)
-        } else {
-            let error_message = response.text().await?;
-            error!("文件上传失败: {}", error_message);
-            Err(anyhow::anyhow!("上传失败: {}", error_message))
-        }
+        Ok(UploadResult {
+            url,
+            etag,
+            request_id,
+            stats: Some(TransferStats::new(started.elapsed(), bytes, Vec::new(), 0)),
+            failover: None,
+            crc64: remote_crc64,
+        })
     }
 
     /// 分块上传
+    ///
+    /// 分块由独立的任务并发上传，同时进行的分块数不超过 [`PART_CONCURRENCY`]。
+    /// 每个任务都带有包含传输 ID 与分块编号的 tracing span，在启用 `tokio_unstable`
+    /// 与 `tokio-console` feature 时还会以 `cos-part:{传输 ID}:{分块编号}` 命名。
+    ///
+    /// 没有断点可以续传，失败时终止分块上传，释放已上传的分块。
     async fn multipart_upload<P: AsRef<Path>>(
         &self,
         file_path: P,
         object_key: &str,
-        metadata: Option<Metadata>,
-    ) -> Result<String> {
-        let file_path = file_path.as_ref();
-        info!("分块上传文件: {:?}", file_path);
+        options: &UploadOptions,
+    ) -> Result<UploadResult> {
+        let upload_id = std::sync::Mutex::new(None);
+        let result = self
+            .multipart_upload_resumable(
+                file_path.as_ref(),
+                object_key,
+                options,
+                None,
+                &|checkpoint| *upload_id.lock().unwrap() = Some(checkpoint.upload_id.clone()),
+                None,
+            )
+            .await;
+        if result.is_err() {
+            if let Some(upload_id) = upload_id.into_inner().unwrap() {
+                // 已完成或已终止的分块上传会返回 404，无需处理
+                match self.abort_multipart_upload(object_key, &upload_id).await {
+                    Err(e) if !is_not_found(&e) => warn!("终止分块上传失败: {}", e),
+                    _ => {}
+                }
+            }
+        }
+        result
+    }
 
-        let base_url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}",
-            self.config.bucket, self.config.region, object_key
-        );
+    /// 可续传的分块上传
+    ///
+    /// 传入的断点与本地文件仍一致时沿用其上传 ID 并跳过已完成的分块，否则终止旧的分块上传并重新初始化。
+    /// 初始化之后以及每个分块完成之后，都会以最新的断点调用 `on_checkpoint`。
+    pub(crate) async fn multipart_upload_resumable(
+        &self,
+        file_path: &Path,
+        object_key: &str,
+        options: &UploadOptions,
+        checkpoint: Option<MultipartCheckpoint>,
+        on_checkpoint: &(dyn Fn(&MultipartCheckpoint) + Send + Sync),
+        control: Option<&TransferControl>,
+    ) -> Result<UploadResult> {
+        let transfer_id = next_transfer_id();
+        let span = info_span!("multipart_upload", transfer_id, object_key);
+        let started = Instant::now();
+
+        async {
+            info!("分块上传文件: {:?}", file_path);
+
+            let mut checkpoint = match checkpoint {
+                Some(checkpoint)
+                    if checkpoint.object_key == object_key
+                        && checkpoint.file_path == file_path
+                        && checkpoint.part_size > 0
+                        && checkpoint.matches_file().await =>
+                {
+                    info!(
+                        "从断点继续分块上传: 已完成 {} 个分块",
+                        checkpoint.completed_parts.len()
+                    );
+                    checkpoint
+                }
+                stale => {
+                    if let Some(stale) = stale {
+                        warn!("文件已变化，放弃旧的断点: {}", stale.upload_id);
+                        if let Err(e) = self
+                            .abort_multipart_upload(&stale.object_key, &stale.upload_id)
+                            .await
+                        {
+                            warn!("终止旧的分块上传失败: {}", e);
+                        }
+                    }
+
+                    // 初始化分块上传
+                    let file_metadata = tokio::fs::metadata(file_path).await?;
+                    let upload_id = self.init_multipart_upload(object_key, options).await?;
+                    MultipartCheckpoint {
+                        file_path: file_path.to_path_buf(),
+                        object_key: object_key.to_string(),
+                        upload_id,
+                        file_size: file_metadata.len(),
+                        file_mtime: file_mtime(&file_metadata).unwrap_or(0),
+                        part_size: PART_SIZE,
+                        part_sizes: Vec::new(),
+                        completed_parts: Vec::new(),
+                    }
+                }
+            };
+            on_checkpoint(&checkpoint);
+
+            // 上传分块
+            let upload_id = checkpoint.upload_id.clone();
+            let part_size = checkpoint.part_size;
+            let mut file = File::open(file_path).await?;
+            let file_size = file.metadata().await?.len();
+            // 文件在选择上传方式之后被清空时没有任何分块可以上传，COS 也不接受没有分块的完成请求
+            if file_size == 0 {
+                warn!("文件已被清空，改用普通上传: {:?}", file_path);
+                if let Err(e) = self.abort_multipart_upload(object_key, &upload_id).await {
+                    warn!("终止分块上传失败: {}", e);
+                }
+                return self.simple_upload(file_path, object_key, options).await;
+            }
+            let mut part_number = 1u32;
+            let mut start = 0u64;
+            let semaphore = Arc::new(Semaphore::new(PART_CONCURRENCY));
+            // 按顺序读取分块的同时增量计算整个文件的 CRC64
+            let mut crc64 = self.hash_backend.crc64();
+            let mut tasks: JoinSet<Result<CompletedPart>> = JoinSet::new();
+            let mut timings = PartTimings::default();
+            let mut progress = self.progress_tracker(transfer_id, object_key, file_size);
+            // 断点中已有按固定大小完成的分块时不能再改变分块边界
+            let mut tuner = (options.adaptive_part_size
+                && (!checkpoint.part_sizes.is_empty() || checkpoint.completed_parts.is_empty()))
+            .then(|| PartSizeTuner::new(MAX_PARTS));
+
+            // 出错时先停止仍在上传的分块，再把错误交给调用方处理（终止或保留断点）
+            let uploaded: Result<()> = async {
+                while start < file_size {
+                    let size = match checkpoint.part_sizes.get(part_number as usize - 1) {
+                        Some(&size) => size,
+                        None => match &mut tuner {
+                            Some(tuner) => {
+                                let size =
+                                    tuner.next_size(file_size - start, u64::from(part_number - 1));
+                                checkpoint.part_sizes.push(size);
+                                size
+                            }
+                            None => part_size,
+                        },
+                    };
+                    let end = std::cmp::min(start + size, file_size);
+
+                    // 暂停或取消时不再开始新的分块，先等已在上传的分块完成并记录到断点中
+                    if let Some(control) = control.filter(|c| c.state() != TransferState::Running) {
+                        while let Some(result) = tasks.join_next().await {
+                            record_completed(
+                                &mut checkpoint,
+                                tuner.as_mut(),
+                                &mut timings,
+                                &mut progress,
+                                result??,
+                                on_checkpoint,
+                            );
+                        }
+                        if let Err(e) = control.wait_running(object_key).await {
+                            if let Err(abort_err) =
+                                self.abort_multipart_upload(object_key, &upload_id).await
+                            {
+                                warn!("终止分块上传失败: {}", abort_err);
+                            }
+                            return Err(e);
+                        }
+                    }
+
+                    // 先获取许可再读取数据，限制同时驻留在内存中的分块数量
+                    let permit = semaphore.clone().acquire_owned().await?;
 
-        // 初始化分块上传
-        let upload_id = self.init_multipart_upload(object_key, metadata).await?;
+                    file.seek(std::io::SeekFrom::Start(start)).await?;
+                    let mut buffer = vec![0; (end - start) as usize];
+                    file.read_exact(&mut buffer).await?;
+                    crc64.update(&buffer);
 
-        // 上传分块
-        let mut file = File::open(file_path).await?;
-        let file_size = file.metadata().await?.len();
-        let mut part_number = 1u32;
-        let mut etags = Vec::new();
+                    // 断点中已完成的分块只参与校验值计算
+                    start = end;
+                    if checkpoint.is_completed(part_number) {
+                        progress.skip(buffer.len() as u64);
+                        part_number = part_number
+                            .checked_add(1)
+                            .ok_or_else(|| anyhow::anyhow!("分块编号溢出"))?;
+                        continue;
+                    }
 
-        while (u64::from(part_number - 1)) * PART_SIZE < file_size {
-            let start = u64::from(part_number - 1) * PART_SIZE;
-            let end = std::cmp::min(u64::from(part_number) * PART_SIZE, file_size);
-            let part_size = end - start;
+                    let uploader = self.clone();
+                    let object_key = object_key.to_string();
+                    let upload_id = upload_id.clone();
+                    let part_sha1 = options.part_sha1;
+                    let part_span = info_span!("upload_part", transfer_id, part_number);
 
-            file.seek(std::io::SeekFrom::Start(start)).await?;
-            let mut buffer = vec![0; part_size as usize];
-            file.read_exact(&mut buffer).await?;
+                    spawn_named(
+                        &mut tasks,
+                        &format!("cos-part:{}:{}", transfer_id, part_number),
+                        async move {
+                            let _permit = permit;
+                            let content_sha1 = part_sha1.then(|| sha1_hex(&buffer));
+                            let bytes = buffer.len() as u64;
+                            let started = Instant::now();
+                            let (etag, retries) = uploader
+                                .upload_part_with_retry(
+                                    transfer_id,
+                                    &object_key,
+                                    &upload_id,
+                                    part_number,
+                                    Bytes::from(buffer),
+                                    content_sha1.as_deref(),
+                                )
+                                .await?;
+                            Ok(CompletedPart {
+                                part_number,
+                                etag,
+                                bytes,
+                                elapsed: started.elapsed(),
+                                retries,
+                            })
+                        }
+                        .instrument(part_span),
+                    );
 
-            let etag = self
-                .upload_part(object_key, &upload_id, part_number, &buffer)
-                .await?;
-            etags.push((part_number, etag));
+                    // 尽早收集已完成的分块，以便出错时及时停止
+                    while let Some(result) = tasks.try_join_next() {
+                        record_completed(
+                            &mut checkpoint,
+                            tuner.as_mut(),
+                            &mut timings,
+                            &mut progress,
+                            result??,
+                            on_checkpoint,
+                        );
+                    }
 
-            part_number = part_number
-                .checked_add(1)
-                .ok_or_else(|| anyhow::anyhow!("分块编号溢出"))?;
+                    part_number = part_number
+                        .checked_add(1)
+                        .ok_or_else(|| anyhow::anyhow!("分块编号溢出"))?;
+                }
+
+                while let Some(result) = tasks.join_next().await {
+                    record_completed(
+                        &mut checkpoint,
+                        tuner.as_mut(),
+                        &mut timings,
+                        &mut progress,
+                        result??,
+                        on_checkpoint,
+                    );
+                }
+                Ok(())
+            }
+            .await;
+            if let Err(e) = uploaded {
+                tasks.shutdown().await;
+                return Err(e);
+            }
+
+            // 完成分块上传
+            let completed = self
+                .finish_multipart_upload(
+                    object_key,
+                    &upload_id,
+                    &checkpoint.completed_parts,
+                    Some(part_number - 1),
+                    options.forbid_overwrite,
+                )
+                .await;
+            let (mut result, crc) = match completed {
+                Ok(completed) => completed,
+                Err(e) if options.forbid_overwrite => {
+                    let e = map_already_exists(e, object_key);
+                    if matches!(e.downcast_ref(), Some(CosError::AlreadyExists { .. })) {
+                        // 对象已存在时分块不会再被使用，及时释放
+                        if let Err(abort_err) =
+                            self.abort_multipart_upload(object_key, &upload_id).await
+                        {
+                            warn!("终止分块上传失败: {}", abort_err);
+                        }
+                    }
+                    return Err(e);
+                }
+                Err(e) => return Err(e),
+            };
+            self.check_crc64(object_key, crc64.finish(), result.crc64)?;
+            result.stats = Some(timings.finish(started.elapsed()));
+            info!(
+                "分块上传成功: {} (request_id: {:?})",
+                result.url, result.request_id
+            );
+
+            self.emit(TransferEvent::UploadCompleted {
+                transfer_id,
+                object_key: object_key.to_string(),
+                etag: result.etag.clone(),
+                crc: crc.clone(),
+                request_id: result.request_id.clone(),
+            });
+
+            Ok(result)
         }
+        .instrument(span)
+        .await
+    }
 
-        // 完成分块上传
-        self.complete_multipart_upload(object_key, &upload_id, &etags)
-            .await?;
+    /// 复制一个上传器，其所有重试都从给定的预算中扣除
+    pub(crate) fn fork_with_budget(&self, budget: Arc<RetryBudget>) -> Uploader {
+        Uploader {
+            retry_budget: Some(budget),
+            ..self.clone()
+        }
+    }
 
-        Ok(base_url)
+    /// 尝试获取一次重试机会，没有设置重试预算时总是允许
+    fn acquire_retry(&self) -> bool {
+        self.retry_budget
+            .as_ref()
+            .is_none_or(|budget| budget.try_acquire())
     }
 
     /// 初始化分块上传
     ///
+    /// 与 [`Uploader::upload_part_copy`]、[`Uploader::complete_multipart_upload`] 配合，
+    /// 可以自行组织服务端拼装对象的流程。
+    ///
     /// # 参数
     ///
     /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `options` - 上传选项，其中的元数据、存储类型与有效期作用于最终的对象
     ///
     /// # 返回值
     ///
     /// 成功时返回上传 ID
-    async fn init_multipart_upload(
+    ///
+    /// # 错误
+    ///
+    /// 选项覆盖了 Bucket 或地域时返回错误：之后的分块与完成请求不带选项，无法发往同一个 Bucket，
+    /// 此时应使用 [`Uploader::start_multipart_upload`]。
+    pub async fn init_multipart_upload(
         &self,
         object_key: &str,
-        metadata: Option<Metadata>,
+        options: &UploadOptions,
     ) -> Result<String> {
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}?uploads",
-            self.config.bucket, self.config.region, object_key
-        );
-
-        let mut headers = HashMap::new();
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
-        );
-
-        if let Some(metadata) = metadata {
-            for (key, value) in metadata {
-                headers.insert(format!("x-cos-meta-{}", key), value);
-            }
+        if self.retarget(options).is_some() {
+            return Err(anyhow::anyhow!(
+                "初始化分块上传不支持覆盖 Bucket 或地域，请使用 start_multipart_upload: {}",
+                object_key
+            ));
         }
+        let request = options.apply(CosRequest::new(Method::POST, object_key).param("uploads", ""));
 
-        let params = HashMap::from([("uploads".to_string(), "".to_string())]);
-
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "post",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
-        );
-
-        // 构建请求 headers
-        let mut request = self
-            .client
-            .post(&url)
-            .header("Authorization", authorization);
-
-        for (key, value) in headers {
-            request = request.header(key, value);
-        }
+        let text = read_xml(self.execute(request).await?).await?;
+        find_tag(&text, "UploadId")
+            .map(|upload_id| upload_id.to_string())
+            .ok_or_else(|| anyhow::anyhow!("初始化分块上传响应中缺少 UploadId: {}", text))
+    }
 
-        let response = request
-            .send()
-            .await?;
+    /// 上传单个分块，失败时按指数退避重试，并发送分块事件
+    ///
+    /// 默认只有网络错误与 COS 的 5xx/429 响应会被重试（可由 [`RetryClassifier`] 调整），最多尝试 [`PART_MAX_ATTEMPTS`] 次；
+    /// 设置了重试预算时，每次重试都会从预算中扣除。
+    pub(crate) async fn upload_part_with_retry(
+        &self,
+        transfer_id: u64,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        data: Bytes,
+        content_sha1: Option<&str>,
+    ) -> Result<(String, u32)> {
+        self.emit(TransferEvent::PartStarted {
+            transfer_id,
+            object_key: object_key.to_string(),
+            part_number,
+            size: data.len() as u64,
+        });
 
-        if response.status().is_success() {
-            let text = response.text().await?;
-            // 解析 XML 响应以获取 upload_id
-            // 注意：这里使用了一个简单的字符串解析方法，在实际生产环境中应使用proper XML解析库
-            let upload_id = text
-                .split("<UploadId>")
-                .nth(1)
-                .unwrap()
-                .split("</UploadId>")
-                .next()
-                .unwrap();
-            Ok(upload_id.to_string())
-        } else {
-            Err(anyhow::anyhow!("初始化分块上传失败"))
+        let mut attempt = 1;
+        loop {
+            debug!("开始上传分块 (第 {} 次尝试)", attempt);
+            match self
+                .upload_part(
+                    object_key,
+                    upload_id,
+                    part_number,
+                    data.clone(),
+                    content_sha1,
+                )
+                .await
+            {
+                Ok((etag, crc)) => {
+                    debug!("分块上传完成");
+                    self.emit(TransferEvent::PartCompleted {
+                        transfer_id,
+                        object_key: object_key.to_string(),
+                        part_number,
+                        etag: etag.clone(),
+                        crc,
+                    });
+                    return Ok((etag, attempt - 1));
+                }
+                Err(e)
+                    if attempt < PART_MAX_ATTEMPTS
+                        && self.is_retryable(&e)
+                        && self.acquire_retry() =>
+                {
+                    warn!("分块上传失败，准备重试 (第 {} 次): {}", attempt, e);
+                    self.emit(TransferEvent::PartRetried {
+                        transfer_id,
+                        object_key: object_key.to_string(),
+                        part_number,
+                        attempt,
+                        error: e.to_string(),
+                    });
+                    tokio::time::sleep(RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
         }
     }
 
@@ -273,73 +945,32 @@ impl Uploader {
     /// * `upload_id` - 初始化分块上传时返回的上传 ID
     /// * `part_number` - 分块的编号
     /// * `data` - 分块的数据
+    /// * `content_sha1` - 分块数据的 SHA-1，设置时以 `x-cos-content-sha1` 发送由 COS 校验
     ///
     /// # 返回值
     ///
-    /// 成功时返回该分块的 ETag
+    /// 成功时返回该分块的 ETag 与 COS 返回的 CRC64
     async fn upload_part(
         &self,
         object_key: &str,
         upload_id: &str,
         part_number: u32,
-        data: &[u8],
-    ) -> Result<String> {
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}?partNumber={}&uploadId={}",
-            self.config.bucket, self.config.region, object_key, part_number, upload_id
-        );
-
-        let mut headers = HashMap::new();
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
-        );
-        headers.insert("Content-Length".to_string(), data.len().to_string());
-
-        let params = HashMap::from([
-            ("partNumber".to_string(), part_number.to_string()),
-            ("uploadId".to_string(), upload_id.to_string()),
-        ]);
-
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "put",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
-        );
+        data: Bytes,
+        content_sha1: Option<&str>,
+    ) -> Result<(String, Option<String>)> {
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .param("partNumber", part_number.to_string())
+            .param("uploadId", upload_id)
+            .body(data);
+        if let Some(sha1) = content_sha1 {
+            request = request.header("x-cos-content-sha1", sha1);
+        }
 
-        let response = self
-            .client
-            .put(&url)
-            .header("Authorization", authorization)
-            .header(
-                "Host",
-                format!(
-                    "{}.cos.{}.myqcloud.com",
-                    self.config.bucket, self.config.region
-                ),
-            )
-            .body(data.to_vec())
-            .send()
-            .await?;
+        let response = self.execute(request).await?;
 
-        if response.status().is_success() {
-            Ok(response
-                .headers()
-                .get("ETag")
-                .unwrap()
-                .to_str()
-                .unwrap()
-                .to_string())
-        } else {
-            Err(anyhow::anyhow!("上传分块失败"))
-        }
+        let etag = header_of(&response, "ETag")
+            .ok_or_else(|| anyhow::anyhow!("上传分块响应中缺少 ETag"))?;
+        Ok((etag, header_of(&response, CRC64_HEADER)))
     }
 
     /// 完成分块上传
@@ -349,141 +980,469 @@ impl Uploader {
     /// * `object_key` - COS 中的对象键（存储路径）
     /// * `upload_id` - 初始化分块上传时返回的上传 ID
     /// * `parts` - 已上传分块的信息，包含分块编号和对应的 ETag
+    /// * `expected_parts` - 预期的分块数，已知时用于发现末尾缺失的分块
+    /// * `forbid_overwrite` - 是否禁止覆盖同名对象
     ///
     /// # 返回值
     ///
-    /// 成功时返回 Ok(())
-    async fn complete_multipart_upload(
+    /// 成功时返回合并后对象的上传结果与 COS 返回的对象 CRC64
+    ///
+    /// # 错误
+    ///
+    /// 分块列表中有重复、缺失或未按升序排列的编号时返回 [`CosError::InvalidPartList`]，
+    /// 避免并发与重试中拼错的列表合并出内容错误的对象。
+    pub(crate) async fn finish_multipart_upload(
         &self,
         object_key: &str,
         upload_id: &str,
         parts: &[(u32, String)],
-    ) -> Result<()> {
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}?uploadId={}",
-            self.config.bucket, self.config.region, object_key, upload_id
-        );
+        expected_parts: Option<u32>,
+        forbid_overwrite: bool,
+    ) -> Result<(UploadResult, Option<String>)> {
+        let body = complete_multipart_body(object_key, parts)?;
+        if let Err(e) = check_part_list(object_key, parts, expected_parts) {
+            error!("{}", e);
+            return Err(e.into());
+        }
 
-        let mut headers = HashMap::new();
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
-        );
+        let mut request = CosRequest::new(Method::POST, object_key)
+            .param("uploadId", upload_id)
+            .body(body);
+        if forbid_overwrite {
+            request = request.header(FORBID_OVERWRITE_HEADER, "true");
+        }
 
-        let params = HashMap::from([("uploadId".to_string(), upload_id.to_string())]);
+        let response = self.execute(request).await?;
+        let url = object_url_of(&response);
+        let request_id = request_id_of(response.headers());
+        let crc = header_of(&response, CRC64_HEADER);
+        let text = read_xml(response).await?;
 
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "post",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
-        );
+        let result = UploadResult {
+            url,
+            etag: find_tag(&text, "ETag").map(|etag| etag.to_string()),
+            request_id,
+            stats: None,
+            failover: None,
+            crc64: crc.as_deref().and_then(parse_crc64),
+        };
+        Ok((result, crc))
+    }
 
-        let body = format!(
-            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
-            parts
-                .iter()
-                .map(|(part_number, etag)| format!(
-                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
-                    part_number, etag
-                ))
-                .collect::<Vec<_>>()
-                .join("")
+    /// 完成分块上传，按分块编号把各分块合并为对象
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `upload_id` - 初始化分块上传时返回的上传 ID
+    /// * `parts` - 分块编号与对应的 ETag，按分块编号升序
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回合并后对象的上传结果
+    ///
+    /// # 错误
+    ///
+    /// 分块编号重复、不是从 1 开始连续或未按升序排列时返回 [`CosError::InvalidPartList`]，不发出请求。
+    pub async fn complete_multipart_upload(
+        &self,
+        object_key: &str,
+        upload_id: &str,
+        parts: &[(u32, String)],
+    ) -> Result<UploadResult> {
+        let (result, _) = self
+            .finish_multipart_upload(object_key, upload_id, parts, None, false)
+            .await?;
+        Ok(result)
+    }
+
+    /// 终止分块上传，释放已上传的分块
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `upload_id` - 初始化分块上传时返回的上传 ID
+    pub async fn abort_multipart_upload(&self, object_key: &str, upload_id: &str) -> Result<()> {
+        let request = CosRequest::new(Method::DELETE, object_key).param("uploadId", upload_id);
+        self.execute(request).await?;
+        Ok(())
+    }
+}
+
+/// 上传完成的分块
+struct CompletedPart {
+    part_number: u32,
+    etag: String,
+    /// 分块大小
+    bytes: u64,
+    /// 上传耗时（包括重试）
+    elapsed: Duration,
+    /// 重试次数
+    retries: u32,
+}
+
+/// 分块上传过程中收集的耗时，上传完成后生成 [`TransferStats`]
+#[derive(Default)]
+struct PartTimings {
+    bytes: u64,
+    latencies: Vec<Duration>,
+    retries: u32,
+}
+
+impl PartTimings {
+    fn record(&mut self, part: &CompletedPart) {
+        self.bytes += part.bytes;
+        self.latencies.push(part.elapsed);
+        self.retries += part.retries;
+    }
+
+    fn finish(self, duration: Duration) -> TransferStats {
+        TransferStats::new(duration, self.bytes, self.latencies, self.retries)
+    }
+}
+
+/// 把完成的分块记录到断点中，交给分块大小调整器统计吞吐量，并更新传输进度
+fn record_completed(
+    checkpoint: &mut MultipartCheckpoint,
+    tuner: Option<&mut PartSizeTuner>,
+    timings: &mut PartTimings,
+    progress: &mut ProgressTracker,
+    part: CompletedPart,
+    on_checkpoint: &(dyn Fn(&MultipartCheckpoint) + Send + Sync),
+) {
+    if let Some(tuner) = tuner {
+        tuner.record(part.bytes, part.elapsed);
+    }
+    timings.record(&part);
+    progress.record(part.bytes);
+    checkpoint.record_part(part.part_number, part.etag);
+    on_checkpoint(checkpoint);
+}
+
+/// `x-cos-copy-source-range` 头部的值（闭区间）
+fn copy_source_range(range: &Range<u64>) -> Result<String> {
+    if range.start >= range.end {
+        return Err(anyhow::anyhow!("复制范围不能为空: {:?}", range));
+    }
+    if range.end - range.start > MAX_COPY_PART_SIZE {
+        return Err(anyhow::anyhow!("复制范围超过 5 GB: {:?}", range));
+    }
+    Ok(format!("bytes={}-{}", range.start, range.end - 1))
+}
+
+/// 检查分块列表：编号从 1 开始连续、不重复且按升序排列
+///
+/// 给出 `expected` 时，缺失的编号按预期的分块数计算，能够发现末尾缺失的分块。
+fn check_part_list(
+    object_key: &str,
+    parts: &[(u32, String)],
+    expected: Option<u32>,
+) -> Result<(), CosError> {
+    let unordered = parts.windows(2).any(|pair| pair[0].0 > pair[1].0);
+    let mut numbers: Vec<u32> = parts.iter().map(|(part_number, _)| *part_number).collect();
+    numbers.sort_unstable();
+
+    let mut duplicates: Vec<u32> = numbers
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0])
+        .collect();
+    duplicates.dedup();
+    numbers.dedup();
+
+    let last = expected.unwrap_or_else(|| numbers.last().copied().unwrap_or(0));
+    let mut present = numbers.iter().peekable();
+    let missing: Vec<u32> = (1..=last)
+        .filter(|n| {
+            while present.next_if(|p| *p < n).is_some() {}
+            present.next_if_eq(&n).is_none()
+        })
+        .collect();
+
+    if duplicates.is_empty() && missing.is_empty() && !unordered {
+        return Ok(());
+    }
+    Err(CosError::InvalidPartList {
+        object_key: object_key.to_string(),
+        duplicates,
+        missing,
+        unordered,
+    })
+}
+
+/// 完成分块上传的请求体
+///
+/// COS 拒绝没有分块的完成请求，空文件必须使用普通上传，这里提前返回错误。
+fn complete_multipart_body(object_key: &str, parts: &[(u32, String)]) -> Result<String> {
+    if parts.is_empty() {
+        return Err(anyhow::anyhow!(
+            "分块上传没有任何分块，空文件应使用普通上传: {}",
+            object_key
+        ));
+    }
+
+    Ok(format!(
+        "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+        parts
+            .iter()
+            .map(|(part_number, etag)| format!(
+                "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                part_number, etag
+            ))
+            .collect::<Vec<_>>()
+            .join("")
+    ))
+}
+
+/// 校验本地计算的 CRC64 与 COS 返回的是否一致，COS 未返回校验值时跳过
+fn verify_crc64(object_key: &str, local: u64, remote: Option<u64>) -> Result<()> {
+    let Some(remote) = remote else {
+        return Ok(());
+    };
+
+    if local != remote {
+        error!(
+            "CRC64 校验失败: {} (本地 {}, COS {})",
+            object_key, local, remote
         );
+        return Err(CosError::ChecksumMismatch {
+            object_key: object_key.to_string(),
+            local,
+            remote,
+        }
+        .into());
+    }
 
-        let response = self
-            .client
-            .post(&url)
-            .header("Authorization", authorization)
-            .header(
-                "Host",
-                format!(
-                    "{}.cos.{}.myqcloud.com",
-                    self.config.bucket, self.config.region
-                ),
-            )
-            .body(body)
-            .send()
-            .await?;
+    Ok(())
+}
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("完成分块上传失败"))
+impl Uploader {
+    /// 按兼容模式校验 CRC64，[`CompatibilityProfile::Generic`](crate::CompatibilityProfile::Generic) 下跳过
+    pub(crate) fn check_crc64(
+        &self,
+        object_key: &str,
+        local: u64,
+        remote: Option<u64>,
+    ) -> Result<()> {
+        if !self.config.compatibility.verifies_crc64() {
+            return Ok(());
         }
+        verify_crc64(object_key, local, remote)
     }
 }
 
-// 为 Uploader 结构体实现一些辅助方法
+// 服务端组合对象
 
 impl Uploader {
-    /// 获取对象的元数据
+    /// 通过服务端复制将多个对象按顺序拼接为一个新对象
+    ///
+    /// 使用分块上传配合 Upload Part - Copy，每个源对象作为一个分块，整个过程不会下载任何数据。
+    /// 适用于浏览器分片上传后各分片落为独立对象、需要在服务端合并的场景。
+    ///
+    /// 受 COS 分块上传限制，除最后一个源对象外，每个源对象必须不小于 1 MB，
+    /// 且每个源对象不能超过 5 GB，源对象数量不能超过 10000。
     ///
     /// # 参数
     ///
-    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `sources` - 按拼接顺序排列的源对象键（同一 Bucket 内）
+    /// * `dst_key` - 目标对象键
     ///
     /// # 返回值
     ///
-    /// 成功时返回对象的元数据
-    pub async fn get_object_metadata(&self, object_key: &str) -> Result<HashMap<String, String>> {
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}",
-            self.config.bucket, self.config.region, object_key
-        );
+    /// 成功时返回目标对象的上传结果
+    pub async fn compose_objects(&self, sources: &[String], dst_key: &str) -> Result<UploadResult> {
+        if sources.is_empty() {
+            return Err(anyhow::anyhow!("源对象列表不能为空"));
+        }
+        if sources.len() > MAX_PARTS {
+            return Err(anyhow::anyhow!(
+                "源对象数量超过上限 {}: {}",
+                MAX_PARTS,
+                sources.len()
+            ));
+        }
 
-        let mut headers = HashMap::new();
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
-        );
+        // 提前校验源对象大小，避免在完成分块上传时才失败
+        for (index, source) in sources.iter().enumerate() {
+            let metadata = self.get_object_metadata(source).await?;
+            let size = metadata
+                .content_length
+                .ok_or_else(|| anyhow::anyhow!("无法获取源对象大小: {}", source))?;
 
-        let params = HashMap::new();
+            if size > MAX_COPY_PART_SIZE {
+                return Err(anyhow::anyhow!("源对象超过 5 GB: {}", source));
+            }
+            if index + 1 < sources.len() && size < MIN_PART_SIZE {
+                return Err(anyhow::anyhow!(
+                    "除最后一个源对象外，源对象不能小于 1 MB: {}",
+                    source
+                ));
+            }
+        }
 
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "head",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
-        );
+        info!("组合 {} 个对象到: {}", sources.len(), dst_key);
 
-        let response = self
-            .client
-            .head(&url)
-            .header("Authorization", authorization)
-            .header(
-                "Host",
-                format!(
-                    "{}.cos.{}.myqcloud.com",
-                    self.config.bucket, self.config.region
-                ),
-            )
-            .send()
+        let upload_id = self
+            .init_multipart_upload(dst_key, &UploadOptions::default())
             .await?;
 
-        if response.status().is_success() {
-            Ok(response
-                .headers()
-                .iter()
-                .map(|(k, v)| (k.to_string(), v.to_str().unwrap_or("").to_string()))
-                .collect())
-        } else {
-            Err(anyhow::anyhow!("获取对象元数据失败"))
+        let result = async {
+            let mut etags = Vec::with_capacity(sources.len());
+            for (index, source) in sources.iter().enumerate() {
+                let part_number = index as u32 + 1;
+                let etag = self
+                    .upload_part_copy(dst_key, &upload_id, part_number, source, None)
+                    .await?;
+                etags.push((part_number, etag));
+            }
+            self.finish_multipart_upload(
+                dst_key,
+                &upload_id,
+                &etags,
+                Some(sources.len() as u32),
+                false,
+            )
+            .await
+        }
+        .await;
+
+        match result {
+            Ok((result, _)) => {
+                info!(
+                    "对象组合成功: {} (request_id: {:?})",
+                    result.url, result.request_id
+                );
+                Ok(result)
+            }
+            Err(e) => {
+                error!("对象组合失败: {}", e);
+                if let Err(abort_err) = self.abort_multipart_upload(dst_key, &upload_id).await {
+                    error!("终止分块上传失败: {}", abort_err);
+                }
+                Err(e)
+            }
         }
     }
 
+    /// 以服务端复制的方式上传单个分块（Upload Part - Copy）
+    ///
+    /// 数据在 COS 内部复制，不经过本地。配合 [`Uploader::init_multipart_upload`] 与
+    /// [`Uploader::complete_multipart_upload`] 可以自行拼装对象，例如修改大对象时只上传变化的区域，
+    /// 其余区域从原对象复制。除最后一个分块外，每个分块不能小于 1 MB，且不能超过 5 GB。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - 目标对象键
+    /// * `upload_id` - 初始化分块上传时返回的上传 ID
+    /// * `part_number` - 分块的编号（1 到 10000）
+    /// * `source_key` - 源对象键（同一 Bucket 内）
+    /// * `source_range` - 复制源对象的字节范围（左闭右开），为 `None` 时复制整个源对象
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回该分块的 ETag
+    ///
+    /// # 错误
+    ///
+    /// 分块编号或字节范围无效、请求失败时返回错误。
+    pub async fn upload_part_copy(
+        &self,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        source_key: &str,
+        source_range: Option<Range<u64>>,
+    ) -> Result<String> {
+        if part_number == 0 || part_number as usize > MAX_PARTS {
+            return Err(anyhow::anyhow!("分块编号超出范围: {}", part_number));
+        }
+        let mut request = CosRequest::new(Method::PUT, object_key)
+            .param("partNumber", part_number.to_string())
+            .param("uploadId", upload_id)
+            .header("x-cos-copy-source", self.copy_source(source_key));
+        if let Some(range) = source_range {
+            request = request.header("x-cos-copy-source-range", copy_source_range(&range)?);
+        }
+
+        let text = read_xml(self.execute(request).await?).await?;
+
+        // 复制请求可能在返回 200 的同时在响应体中携带错误
+        if text.contains("<Error>") {
+            return Err(anyhow::anyhow!(
+                "复制分块失败: {} (分块 {}): {}",
+                source_key,
+                part_number,
+                text
+            ));
+        }
+
+        find_tag(&text, "ETag")
+            .map(|etag| etag.to_string())
+            .ok_or_else(|| anyhow::anyhow!("复制分块响应中缺少 ETag: {}", text))
+    }
+
+    /// 上传单个分块的数据
+    ///
+    /// 与 [`Uploader::init_multipart_upload`]、[`Uploader::complete_multipart_upload`] 配合，
+    /// 可以边生成边上传数据，例如上传事先不知道总大小的流。除最后一个分块外，每个分块不能小于 1 MB。
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    /// * `upload_id` - 初始化分块上传时返回的上传 ID
+    /// * `part_number` - 分块的编号（1 到 10000）
+    /// * `data` - 分块的数据
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回该分块的 ETag
+    pub async fn upload_part_bytes(
+        &self,
+        object_key: &str,
+        upload_id: &str,
+        part_number: u32,
+        data: impl Into<Bytes>,
+    ) -> Result<String> {
+        if part_number == 0 || part_number as usize > MAX_PARTS {
+            return Err(anyhow::anyhow!("分块编号超出范围: {}", part_number));
+        }
+        let (etag, _) = self
+            .upload_part(object_key, upload_id, part_number, data.into(), None)
+            .await?;
+        Ok(etag)
+    }
+
+    /// 同一 Bucket 内对象的 `x-cos-copy-source` 头部的值
+    pub(crate) fn copy_source(&self, source_key: &str) -> String {
+        let encoded_path = self
+            .config
+            .object_path(source_key)
+            .split('/')
+            .map(|segment| url_encode(segment))
+            .collect::<Vec<_>>()
+            .join("/");
+        format!("{}{}", self.host(&self.config.region), encoded_path)
+    }
+}
+
+// 为 Uploader 结构体实现一些辅助方法
+
+impl Uploader {
+    /// 获取对象的元数据
+    ///
+    /// # 参数
+    ///
+    /// * `object_key` - COS 中的对象键（存储路径）
+    ///
+    /// # 返回值
+    ///
+    /// 成功时返回对象的元数据，其中包含 HEAD 请求的 `x-cos-request-id`
+    pub async fn get_object_metadata(&self, object_key: &str) -> Result<ObjectMetadata> {
+        let request = CosRequest::new(Method::HEAD, object_key);
+        let response = self.execute(request).await?;
+        Ok(ObjectMetadata::from_headers(response.headers()))
+    }
+
     /// 删除对象
     ///
     /// # 参数
@@ -492,52 +1451,82 @@ impl Uploader {
     ///
     /// # 返回值
     ///
-    /// 成功时返回 Ok(())
-    pub async fn delete_object(&self, object_key: &str) -> Result<()> {
-        let url = format!(
-            "https://{}.cos.{}.myqcloud.com/{}",
-            self.config.bucket, self.config.region, object_key
+    /// 成功时返回删除结果，其中包含删除请求的 `x-cos-request-id`
+    pub async fn delete_object(&self, object_key: &str) -> Result<DeleteResult> {
+        let request = CosRequest::new(Method::DELETE, object_key);
+        let response = self.execute(request).await?;
+        self.invalidate_cached(object_key);
+        let request_id = request_id_of(response.headers());
+        info!(
+            "对象删除成功: {} (request_id: {:?})",
+            object_key, request_id
         );
+        Ok(DeleteResult { request_id })
+    }
+}
 
-        let mut headers = HashMap::new();
-        headers.insert(
-            "Host".to_string(),
-            format!(
-                "{}.cos.{}.myqcloud.com",
-                self.config.bucket, self.config.region
-            ),
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hash::SoftwareHashBackend;
+
+    #[test]
+    fn test_complete_multipart_body() {
+        assert!(complete_multipart_body("empty.bin", &[]).is_err());
+
+        let body = complete_multipart_body("a.bin", &[(1, "\"e1\"".to_string())]).unwrap();
+        assert_eq!(
+            body,
+            "<CompleteMultipartUpload><Part><PartNumber>1</PartNumber><ETag>\"e1\"</ETag></Part>\
+             </CompleteMultipartUpload>"
         );
+    }
 
-        let params = HashMap::new();
+    #[test]
+    fn test_check_part_list() {
+        let parts = |numbers: &[u32]| -> Vec<(u32, String)> {
+            numbers
+                .iter()
+                .map(|n| (*n, format!("\"e{}\"", n)))
+                .collect()
+        };
+        assert!(check_part_list("a.bin", &parts(&[1, 2, 3]), Some(3)).is_ok());
 
-        let authorization = generate_authorization(
-            &self.config.secret_id,
-            &self.config.secret_key,
-            "delete",
-            &format!("/{}", object_key),
-            &params,
-            &headers,
-            3600,
+        let err = check_part_list("a.bin", &parts(&[1, 2, 2, 6, 8]), None).unwrap_err();
+        assert_eq!(
+            err,
+            CosError::InvalidPartList {
+                object_key: "a.bin".to_string(),
+                duplicates: vec![2],
+                missing: vec![3, 4, 5, 7],
+                unordered: false,
+            }
         );
+        assert!(err.to_string().contains("缺失的分块 3-5, 7"));
 
-        let response = self
-            .client
-            .delete(&url)
-            .header("Authorization", authorization)
-            .header(
-                "Host",
-                format!(
-                    "{}.cos.{}.myqcloud.com",
-                    self.config.bucket, self.config.region
-                ),
-            )
-            .send()
-            .await?;
+        // 末尾缺失的分块只有在给出预期的分块数时才能发现
+        assert!(check_part_list("a.bin", &parts(&[1, 2]), None).is_ok());
+        assert!(check_part_list("a.bin", &parts(&[1, 2]), Some(3)).is_err());
+        assert!(matches!(
+            check_part_list("a.bin", &parts(&[2, 1]), None),
+            Err(CosError::InvalidPartList {
+                unordered: true,
+                ..
+            })
+        ));
+    }
 
-        if response.status().is_success() {
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("删除对象失败"))
-        }
+    #[test]
+    fn test_copy_source_range() {
+        assert_eq!(copy_source_range(&(0..1024)).unwrap(), "bytes=0-1023");
+        assert!(copy_source_range(&(10..10)).is_err());
+        assert!(copy_source_range(&(0..MAX_COPY_PART_SIZE + 1)).is_err());
+    }
+
+    #[test]
+    fn test_verify_empty_crc64() {
+        // 空文件的 CRC64 为 0，COS 对空对象同样返回 0
+        assert_eq!(SoftwareHashBackend.crc64().finish(), 0);
+        assert!(verify_crc64("empty.bin", 0, Some(0)).is_ok());
     }
 }
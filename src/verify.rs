@@ -0,0 +1,178 @@
+use crate::error::CosError;
+use crate::options::{UploadOptions, UploadVerification};
+use crate::types::ObjectMetadata;
+use crate::uploader::Uploader;
+use anyhow::{anyhow, Result};
+use std::ops::Range;
+use std::path::Path;
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tracing::{error, info};
+
+/// 抽样读取时每个片段的字节数
+const SAMPLE_LEN: u64 = 64 * 1024;
+
+/// 计算本地文件 CRC64 时每次读取的字节数
+const READ_CHUNK: usize = 1024 * 1024;
+
+/// 抽样读取的片段：文件的开头、中间与末尾，小文件只有一个覆盖整个文件的片段
+fn sample_ranges(size: u64) -> Vec<Range<u64>> {
+    if size <= SAMPLE_LEN * 3 {
+        let whole = 0..size;
+        return if whole.is_empty() {
+            Vec::new()
+        } else {
+            vec![whole]
+        };
+    }
+    let middle = size / 2 - SAMPLE_LEN / 2;
+    vec![
+        0..SAMPLE_LEN,
+        middle..middle + SAMPLE_LEN,
+        size - SAMPLE_LEN..size,
+    ]
+}
+
+/// 比对对象的元数据与上传选项，返回第一个不一致的原因
+fn metadata_mismatch(metadata: &ObjectMetadata, options: &UploadOptions) -> Option<String> {
+    if let Some(expected) = &options.metadata {
+        for (key, value) in expected {
+            match metadata.user_metadata.get(&key.to_ascii_lowercase()) {
+                Some(actual) if actual == value => {}
+                actual => {
+                    return Some(format!(
+                        "自定义元数据 {} 不一致 (期望 {:?}, COS {:?})",
+                        key, value, actual
+                    ))
+                }
+            }
+        }
+    }
+    if let Some(expected) = &options.content_language {
+        if metadata.content_language.as_ref() != Some(expected) {
+            return Some(format!(
+                "Content-Language 不一致 (期望 {:?}, COS {:?})",
+                expected, metadata.content_language
+            ));
+        }
+    }
+    None
+}
+
+/// 校验失败的错误
+fn verification_failed(object_key: &str, reason: String) -> anyhow::Error {
+    error!("上传后校验失败: {}: {}", object_key, reason);
+    CosError::VerificationFailed {
+        object_key: object_key.to_string(),
+        reason,
+    }
+    .into()
+}
+
+impl Uploader {
+    /// 按上传选项对刚上传的对象进行读后校验，未开启时直接返回
+    ///
+    /// # 错误
+    ///
+    /// 对象与本地文件或上传选项不一致时返回 [`CosError::VerificationFailed`]，
+    /// CRC64 不一致时返回 [`CosError::ChecksumMismatch`]。
+    pub(crate) async fn verify_upload(
+        &self,
+        file_path: &Path,
+        object_key: &str,
+        options: &UploadOptions,
+    ) -> Result<()> {
+        let Some(verification) = options.verify_after_upload else {
+            return Ok(());
+        };
+
+        let file_size = tokio::fs::metadata(file_path).await?.len();
+        let metadata = self.get_object_metadata(object_key).await?;
+        match metadata.content_length {
+            Some(size) if size == file_size => {}
+            size => {
+                return Err(verification_failed(
+                    object_key,
+                    format!("大小不一致 (本地 {}, COS {:?})", file_size, size),
+                ))
+            }
+        }
+        if let Some(reason) = metadata_mismatch(&metadata, options) {
+            return Err(verification_failed(object_key, reason));
+        }
+
+        if self.config.compatibility.verifies_crc64() {
+            let remote = metadata.crc64.ok_or_else(|| {
+                verification_failed(object_key, "COS 未返回对象的 CRC64".to_string())
+            })?;
+            let local = self.file_crc64(file_path).await?;
+            self.check_crc64(object_key, local, Some(remote))?;
+        }
+
+        if verification == UploadVerification::SpotCheck {
+            let mut file = File::open(file_path).await?;
+            for range in sample_ranges(file_size) {
+                let mut local = vec![0u8; (range.end - range.start) as usize];
+                file.seek(std::io::SeekFrom::Start(range.start)).await?;
+                file.read_exact(&mut local).await?;
+
+                // 直接请求 COS，不经过对象缓存
+                let (remote, _) = self
+                    .fetch_object_bytes(object_key, None, Some(&range), None)
+                    .await?
+                    .ok_or_else(|| anyhow!("未带条件的请求不应返回 304: {}", object_key))?;
+                if remote.as_ref() != local.as_slice() {
+                    return Err(verification_failed(
+                        object_key,
+                        format!("字节范围 {}-{} 的内容不一致", range.start, range.end - 1),
+                    ));
+                }
+            }
+        }
+
+        info!("上传后校验通过: {} ({:?})", object_key, verification);
+        Ok(())
+    }
+
+    /// 计算本地文件的 CRC64
+    pub(crate) async fn file_crc64(&self, file_path: &Path) -> Result<u64> {
+        let mut file = File::open(file_path).await?;
+        let mut crc64 = self.hash_backend.crc64();
+        let mut buf = vec![0u8; READ_CHUNK];
+        loop {
+            let n = file.read(&mut buf).await?;
+            if n == 0 {
+                break;
+            }
+            crc64.update(&buf[..n]);
+        }
+        Ok(crc64.finish())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sample_ranges() {
+        assert!(sample_ranges(0).is_empty());
+        assert_eq!(sample_ranges(100), vec![0..100]);
+        let size = 10 * 1024 * 1024;
+        let ranges = sample_ranges(size);
+        assert_eq!(ranges.len(), 3);
+        assert_eq!(ranges[0], 0..SAMPLE_LEN);
+        assert_eq!(ranges[2], size - SAMPLE_LEN..size);
+        assert_eq!(ranges[1].end - ranges[1].start, SAMPLE_LEN);
+
+        let mut metadata = ObjectMetadata::default();
+        let mut expected = crate::Metadata::new();
+        expected.insert("Owner".to_string(), "finance".to_string());
+        let options = UploadOptions::new().with_metadata(expected);
+        assert!(metadata_mismatch(&metadata, &options).is_some());
+        metadata
+            .user_metadata
+            .insert("owner".to_string(), "finance".to_string());
+        assert!(metadata_mismatch(&metadata, &options).is_none());
+    }
+}
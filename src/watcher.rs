@@ -0,0 +1,340 @@
+use crate::keymap::relative_key;
+use crate::types::ObjectMetadata;
+use crate::uploader::{Metadata, Uploader};
+use anyhow::Result;
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::{debug, error, info, warn};
+
+/// 键映射函数，参数为相对于监听目录的路径，返回 `None` 表示忽略该文件
+pub type KeyMapper = Arc<dyn Fn(&Path) -> Option<String> + Send + Sync>;
+
+/// 遇到对象键冲突时采取的动作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConflictAction {
+    /// 覆盖远端对象
+    Overwrite,
+    /// 跳过本次上传
+    Skip,
+    /// 改为上传到指定的对象键
+    Rename(String),
+}
+
+/// 冲突处理策略
+///
+/// 在每次上传前被调用，决定如何处理已存在的远端对象。
+pub trait ConflictPolicy: Send + Sync {
+    /// 是否需要在决策前查询远端对象元数据
+    ///
+    /// 返回 `false` 时，`resolve` 的 `remote` 参数总是 `None`，可以省去一次 HEAD 请求。
+    fn check_remote(&self) -> bool {
+        true
+    }
+
+    /// 决定如何处理本次上传
+    ///
+    /// # 参数
+    ///
+    /// * `local` - 本地文件路径
+    /// * `object_key` - 映射后的对象键
+    /// * `remote` - 远端对象的元数据，对象不存在时为 `None`
+    fn resolve(
+        &self,
+        local: &Path,
+        object_key: &str,
+        remote: Option<&ObjectMetadata>,
+    ) -> ConflictAction;
+}
+
+/// 总是覆盖远端对象
+pub struct OverwritePolicy;
+
+impl ConflictPolicy for OverwritePolicy {
+    fn check_remote(&self) -> bool {
+        false
+    }
+
+    fn resolve(&self, _: &Path, _: &str, _: Option<&ObjectMetadata>) -> ConflictAction {
+        ConflictAction::Overwrite
+    }
+}
+
+/// 远端对象已存在时跳过
+pub struct SkipExistingPolicy;
+
+impl ConflictPolicy for SkipExistingPolicy {
+    fn resolve(&self, _: &Path, _: &str, remote: Option<&ObjectMetadata>) -> ConflictAction {
+        match remote {
+            Some(_) => ConflictAction::Skip,
+            None => ConflictAction::Overwrite,
+        }
+    }
+}
+
+/// 远端对象已存在时，在对象键后追加时间戳后缀
+pub struct RenameWithTimestampPolicy;
+
+impl ConflictPolicy for RenameWithTimestampPolicy {
+    fn resolve(
+        &self,
+        _: &Path,
+        object_key: &str,
+        remote: Option<&ObjectMetadata>,
+    ) -> ConflictAction {
+        match remote {
+            Some(_) => ConflictAction::Rename(format!(
+                "{}.{}",
+                object_key,
+                chrono::Utc::now().format("%Y%m%d%H%M%S")
+            )),
+            None => ConflictAction::Overwrite,
+        }
+    }
+}
+
+/// 目录监听上传的选项
+#[derive(Clone)]
+pub struct WatchOptions {
+    /// 去抖时间，文件在该时间内没有新的变更事件才会被上传
+    pub debounce: Duration,
+    /// 是否递归监听子目录
+    pub recursive: bool,
+    /// 自定义键映射，为 `None` 时使用 `prefix` + 相对路径
+    pub key_mapper: Option<KeyMapper>,
+    /// 冲突处理策略
+    pub conflict_policy: Arc<dyn ConflictPolicy>,
+    /// 附加到每个对象上的元数据
+    pub metadata: Option<Metadata>,
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self {
+            debounce: Duration::from_millis(500),
+            recursive: true,
+            key_mapper: None,
+            conflict_policy: Arc::new(OverwritePolicy),
+            metadata: None,
+        }
+    }
+}
+
+/// 把监听到的文件映射为对象键，返回 `None` 表示忽略该文件
+///
+/// 设置了 `key_mapper` 时以相对于监听目录的路径调用它，否则使用 `prefix` + 以 `/` 分隔的相对路径。
+fn watched_key(
+    dir: &Path,
+    path: &Path,
+    prefix: &str,
+    key_mapper: Option<&KeyMapper>,
+) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?;
+    match key_mapper {
+        Some(mapper) => {
+            let key = mapper(relative);
+            if key.is_none() {
+                debug!("忽略文件: {:?}", path);
+            }
+            key
+        }
+        None => match relative_key(dir, path) {
+            Ok(relative) => Some(format!("{}{}", prefix, relative)),
+            Err(e) => {
+                warn!("无法把文件映射为对象键: {:?}: {}", path, e);
+                None
+            }
+        },
+    }
+}
+
+impl Uploader {
+    /// 监听目录并持续上传新增或修改的文件
+    ///
+    /// 该方法会一直运行，直到底层监听器出错；需要停止时可以直接丢弃返回的 future
+    /// （例如通过 `tokio::select!` 或 `JoinHandle::abort`）。单个文件上传失败只会记录日志，
+    /// 不会中断监听。
+    ///
+    /// # 参数
+    ///
+    /// * `dir` - 要监听的目录
+    /// * `prefix` - 对象键前缀，例如 `"uploads/"`
+    /// * `opts` - 监听选项
+    pub async fn watch_and_upload<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        prefix: &str,
+        opts: WatchOptions,
+    ) -> Result<()> {
+        let dir = tokio::fs::canonicalize(dir.as_ref()).await?;
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let _ = tx.send(res);
+        })?;
+        let mode = if opts.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        };
+        watcher.watch(&dir, mode)?;
+        info!("开始监听目录: {:?}", dir);
+
+        let mut pending: HashMap<PathBuf, Instant> = HashMap::new();
+
+        loop {
+            let deadline = pending.values().min().map(|t| *t + opts.debounce);
+
+            tokio::select! {
+                event = rx.recv() => {
+                    let event = match event {
+                        Some(event) => event?,
+                        None => return Err(anyhow::anyhow!("目录监听器已关闭")),
+                    };
+                    if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                        for path in event.paths {
+                            pending.insert(path, Instant::now());
+                        }
+                    }
+                }
+                _ = tokio::time::sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    let now = Instant::now();
+                    let ready: Vec<PathBuf> = pending
+                        .iter()
+                        .filter(|(_, t)| **t + opts.debounce <= now)
+                        .map(|(p, _)| p.clone())
+                        .collect();
+                    for path in ready {
+                        pending.remove(&path);
+                        self.upload_watched_file(&dir, &path, prefix, &opts).await;
+                    }
+                }
+            }
+        }
+    }
+
+    /// 上传一个监听到变更的文件，错误只记录日志
+    async fn upload_watched_file(
+        &self,
+        dir: &Path,
+        path: &Path,
+        prefix: &str,
+        opts: &WatchOptions,
+    ) {
+        match tokio::fs::metadata(path).await {
+            Ok(meta) if meta.is_file() => {}
+            _ => return,
+        }
+
+        let Some(object_key) = watched_key(dir, path, prefix, opts.key_mapper.as_ref()) else {
+            return;
+        };
+
+        let remote = if opts.conflict_policy.check_remote() {
+            match self.get_object_metadata(&object_key).await {
+                Ok(metadata) => Some(metadata),
+                Err(e) if crate::error::is_not_found(&e) => None,
+                Err(e) => {
+                    // 无法确认远端对象是否存在时不上传，避免覆盖策略本应保护的对象
+                    error!(
+                        "查询远端对象失败，跳过本次上传，将在文件下次变更时重试: {}: {}",
+                        object_key, e
+                    );
+                    return;
+                }
+            }
+        } else {
+            None
+        };
+
+        let object_key = match opts
+            .conflict_policy
+            .resolve(path, &object_key, remote.as_ref())
+        {
+            ConflictAction::Overwrite => object_key,
+            ConflictAction::Skip => {
+                debug!("对象已存在，跳过: {}", object_key);
+                return;
+            }
+            ConflictAction::Rename(key) => key,
+        };
+
+        match self
+            .upload_file(path, &object_key, opts.metadata.clone())
+            .await
+        {
+            Ok(result) => info!(
+                "监听上传成功: {:?} -> {} (request_id: {:?})",
+                path, result.url, result.request_id
+            ),
+            Err(e) => error!("监听上传失败，将在文件下次变更时重试: {:?}: {}", path, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conflict_policies() {
+        let local = Path::new("/data/a.txt");
+        let remote = ObjectMetadata::default();
+
+        assert!(!OverwritePolicy.check_remote());
+        assert_eq!(
+            OverwritePolicy.resolve(local, "a.txt", Some(&remote)),
+            ConflictAction::Overwrite
+        );
+
+        assert!(SkipExistingPolicy.check_remote());
+        assert_eq!(
+            SkipExistingPolicy.resolve(local, "a.txt", Some(&remote)),
+            ConflictAction::Skip
+        );
+        assert_eq!(
+            SkipExistingPolicy.resolve(local, "a.txt", None),
+            ConflictAction::Overwrite
+        );
+
+        assert_eq!(
+            RenameWithTimestampPolicy.resolve(local, "a.txt", None),
+            ConflictAction::Overwrite
+        );
+        match RenameWithTimestampPolicy.resolve(local, "a.txt", Some(&remote)) {
+            ConflictAction::Rename(key) => {
+                let suffix = key.strip_prefix("a.txt.").unwrap();
+                assert_eq!(suffix.len(), 14);
+                assert!(suffix.bytes().all(|b| b.is_ascii_digit()));
+            }
+            action => panic!("期望重命名，实际为 {:?}", action),
+        }
+    }
+
+    #[test]
+    fn test_watched_key() {
+        let dir = Path::new("/data");
+        let path = Path::new("/data/sub/a.txt");
+        assert_eq!(
+            watched_key(dir, path, "uploads/", None).as_deref(),
+            Some("uploads/sub/a.txt")
+        );
+        assert_eq!(watched_key(dir, Path::new("/other/a.txt"), "", None), None);
+
+        let mapper: KeyMapper = Arc::new(|relative: &Path| {
+            let name = relative.to_str()?;
+            (!name.ends_with(".tmp")).then(|| format!("mapped/{}", name))
+        });
+        assert_eq!(
+            watched_key(dir, path, "uploads/", Some(&mapper)).as_deref(),
+            Some("mapped/sub/a.txt")
+        );
+        assert_eq!(
+            watched_key(dir, Path::new("/data/b.tmp"), "", Some(&mapper)),
+            None
+        );
+    }
+}
@@ -0,0 +1,55 @@
+//! 简单的 XML 取值工具
+//!
+//! COS 控制面的响应结构都很扁平，这里使用字符串查找提取标签内容，避免引入完整的 XML 解析库。
+
+/// 取出第一个 `<tag>...</tag>` 之间的内容
+pub(crate) fn find_tag<'a>(text: &'a str, tag: &str) -> Option<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let start = text.find(&open)? + open.len();
+    let end = text[start..].find(&close)? + start;
+    Some(&text[start..end])
+}
+
+/// 按文档顺序取出所有 `<tag>...</tag>` 之间的内容
+#[cfg(feature = "runtime")]
+pub(crate) fn find_all_tags<'a>(text: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find(&open) {
+        let start = start + open.len();
+        let Some(end) = rest[start..].find(&close) else {
+            break;
+        };
+        found.push(&rest[start..start + end]);
+        rest = &rest[start + end + close.len()..];
+    }
+
+    found
+}
+
+/// 还原 XML 文本中的实体引用
+#[cfg(feature = "runtime")]
+pub(crate) fn unescape(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+/// 转义 XML 文本中的特殊字符
+#[cfg(feature = "runtime")]
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
@@ -0,0 +1,852 @@
+//! 基于进程内模拟 COS 服务器的集成测试
+
+use cos_upload::testing::{MockCos, MOCK_BUCKET, MOCK_REGION};
+use cos_upload::{
+    BatchOptions, Config, CosError, DownloadOptions, DuplicatePolicy, DuplicateUploadError,
+    ListOptions, Metadata, TransferEvent, TransferManager, UploadJournal, UploadManifest, Uploader,
+};
+use std::io::Write;
+use std::sync::Arc;
+
+fn temp_file(content: &[u8]) -> tempfile::NamedTempFile {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(content).unwrap();
+    file
+}
+
+#[tokio::test]
+async fn test_simple_upload_round_trip() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let file = temp_file(b"hello mock cos");
+
+    let mut metadata = Metadata::new();
+    metadata.insert("owner".to_string(), "tests".to_string());
+    let result = uploader
+        .upload_file(file.path(), "docs/hello 世界.txt", Some(metadata))
+        .await
+        .unwrap();
+    assert!(result.etag.is_some());
+    assert_eq!(
+        mock.object("docs/hello 世界.txt").unwrap().as_ref(),
+        b"hello mock cos"
+    );
+
+    let head = uploader
+        .get_object_metadata("docs/hello 世界.txt")
+        .await
+        .unwrap();
+    assert_eq!(head.content_length, Some(14));
+    assert_eq!(head.user_metadata.get("owner").unwrap(), "tests");
+
+    let range = uploader
+        .get_object_bytes("docs/hello 世界.txt", Some(6..10))
+        .await
+        .unwrap();
+    assert_eq!(range.as_ref(), b"mock");
+
+    uploader.delete_object("docs/hello 世界.txt").await.unwrap();
+    assert!(mock.object_keys().is_empty());
+}
+
+#[tokio::test]
+async fn test_multipart_upload_and_listing() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let content: Vec<u8> = (0..12 * 1024 * 1024u32).map(|i| (i % 251) as u8).collect();
+    let file = temp_file(&content);
+
+    uploader
+        .upload_file(file.path(), "data/large.bin", None)
+        .await
+        .unwrap();
+    assert_eq!(
+        mock.object("data/large.bin").unwrap().as_ref(),
+        &content[..]
+    );
+    assert_eq!(mock.pending_uploads(), 0);
+
+    let small = temp_file(b"x");
+    for key in ["data/a/1.txt", "data/a/2.txt", "data/b.txt"] {
+        uploader.upload_file(small.path(), key, None).await.unwrap();
+    }
+    let page = uploader
+        .list_objects(&ListOptions::new("data/").with_delimiter("/"), None)
+        .await
+        .unwrap();
+    let keys: Vec<_> = page.items.iter().map(|item| item.key.as_str()).collect();
+    assert_eq!(keys, ["data/b.txt", "data/large.bin"]);
+    assert_eq!(page.common_prefixes, ["data/a/"]);
+}
+
+#[tokio::test]
+async fn test_multipart_upload_aborts_on_part_failure() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let file = temp_file(&vec![7u8; 22 * 1024 * 1024]);
+
+    // 403 不会重试，第一个失败的分块就让上传失败，其余并发的分块随之停止
+    mock.fail_next_parts(1, 403);
+    let error = uploader
+        .upload_file(file.path(), "data/failed.bin", None)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(CosError::Service { status: 403, .. })
+    ));
+    assert_eq!(mock.pending_uploads(), 0);
+    assert!(mock.object("data/failed.bin").is_none());
+}
+
+#[tokio::test]
+async fn test_multipart_upload_events() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader().with_event_channel(256);
+    let mut events = uploader.subscribe_events().unwrap();
+    let file = temp_file(&vec![3u8; 12 * 1024 * 1024]);
+
+    mock.fail_next_parts(1, 503);
+    let result = uploader
+        .upload_file(file.path(), "data/events.bin", None)
+        .await
+        .unwrap();
+
+    let mut received = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if !matches!(event, TransferEvent::Progress { .. }) {
+            received.push(event);
+        }
+    }
+    // 每个分块依次经历开始、（重试、）完成，全部分块完成后才是对象上传完成
+    let part_events: Vec<(&str, u32)> = received
+        .iter()
+        .filter_map(|event| match event {
+            TransferEvent::PartStarted { part_number, .. } => Some(("started", *part_number)),
+            TransferEvent::PartRetried {
+                part_number,
+                attempt,
+                ..
+            } => {
+                assert_eq!(*attempt, 1);
+                Some(("retried", *part_number))
+            }
+            TransferEvent::PartCompleted { part_number, .. } => Some(("completed", *part_number)),
+            _ => None,
+        })
+        .collect();
+    for part in 1..=3 {
+        let stages: Vec<&str> = part_events
+            .iter()
+            .filter(|(_, number)| *number == part)
+            .map(|(stage, _)| *stage)
+            .collect();
+        assert!(
+            stages == ["started", "completed"] || stages == ["started", "retried", "completed"],
+            "分块 {} 的事件: {:?}",
+            part,
+            stages
+        );
+    }
+    let retried = part_events.iter().filter(|(stage, _)| *stage == "retried");
+    assert_eq!(retried.count(), 1);
+    assert_eq!(received.len(), part_events.len() + 1);
+    match received.last().unwrap() {
+        TransferEvent::UploadCompleted {
+            object_key, etag, ..
+        } => {
+            assert_eq!(object_key, "data/events.bin");
+            assert_eq!(*etag, result.etag);
+        }
+        event => panic!("最后一个事件应为上传完成: {:?}", event),
+    }
+}
+
+#[tokio::test]
+async fn test_download_resume_checks_etag() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let dir = tempfile::tempdir().unwrap();
+    let target = dir.path().join("report.txt");
+    let partial = dir.path().join("report.txt.part");
+    let etag_record = dir.path().join("report.txt.part.etag");
+    let options = DownloadOptions::new().with_keep_partial(true);
+
+    let old_etag = uploader
+        .upload_file(temp_file(b"old report").path(), "report.txt", None)
+        .await
+        .unwrap()
+        .etag
+        .unwrap();
+    std::fs::write(&partial, b"old ").unwrap();
+    std::fs::write(&etag_record, &old_etag).unwrap();
+    uploader
+        .download_object_with_options("report.txt", &target, &options)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&target).unwrap(), b"old report");
+    assert!(!etag_record.exists());
+
+    // 对象在两次下载之间被替换，已有的内容不能拼接到新对象上
+    std::fs::write(&partial, b"old ").unwrap();
+    std::fs::write(&etag_record, &old_etag).unwrap();
+    uploader
+        .upload_file(temp_file(b"new report, longer").path(), "report.txt", None)
+        .await
+        .unwrap();
+    uploader
+        .download_object_with_options("report.txt", &target, &options)
+        .await
+        .unwrap();
+    assert_eq!(std::fs::read(&target).unwrap(), b"new report, longer");
+    assert!(!partial.exists());
+}
+
+#[tokio::test]
+async fn test_retry_and_signature_errors() {
+    let mock = MockCos::start().await.unwrap();
+    let file = temp_file(b"retry me");
+
+    mock.fail_next(1, 503);
+    mock.uploader()
+        .upload_file(file.path(), "retry.txt", None)
+        .await
+        .unwrap();
+    assert!(mock.object("retry.txt").is_some());
+
+    let wrong_key = Config::new(
+        mock.config().secret_id,
+        "wrong-secret-key".to_string(),
+        MOCK_REGION.to_string(),
+        MOCK_BUCKET.to_string(),
+    )
+    .with_custom_endpoint(mock.endpoint().to_string());
+    let err = Uploader::new(wrong_key)
+        .upload_file(file.path(), "denied.txt", None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<CosError>().and_then(CosError::code),
+        Some("SignatureDoesNotMatch")
+    );
+    assert!(mock.object("denied.txt").is_none());
+}
+
+#[tokio::test]
+async fn test_caller_driven_multipart_upload() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+
+    let mut upload = uploader
+        .start_multipart_upload("chunks/video.bin", &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(upload.write_part(vec![1u8; 1024 * 1024]).await.unwrap(), 1);
+    assert_eq!(upload.write_part(vec![2u8; 10]).await.unwrap(), 2);
+    // 末尾分块之后不能再写入
+    assert!(upload.write_part(vec![3u8; 10]).await.is_err());
+
+    let result = upload.complete().await.unwrap();
+    assert_eq!(result.stats.unwrap().parts, 2);
+    let object = mock.object("chunks/video.bin").unwrap();
+    assert_eq!(object.len(), 1024 * 1024 + 10);
+    assert_eq!(mock.pending_uploads(), 0);
+
+    let upload = uploader
+        .start_multipart_upload("chunks/abandoned.bin", &Default::default())
+        .await
+        .unwrap();
+    assert_eq!(mock.pending_uploads(), 1);
+    upload.abort().await.unwrap();
+    assert_eq!(mock.pending_uploads(), 0);
+}
+
+#[tokio::test]
+async fn test_finalize_with_metadata() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let mut metadata = Metadata::new();
+    metadata.insert("rows".to_string(), "2".to_string());
+    let options = cos_upload::UploadOptions::new()
+        .with_metadata(metadata)
+        .with_expires_in(std::time::Duration::from_secs(3 * 86400));
+
+    let upload_id = uploader
+        .init_multipart_upload("exports/rows.csv", &Default::default())
+        .await
+        .unwrap();
+    let etag = uploader
+        .upload_part_bytes("exports/rows.csv", &upload_id, 1, b"a,b\n1,2\n".to_vec())
+        .await
+        .unwrap();
+    let result = uploader
+        .finalize_with_metadata("exports/rows.csv", &upload_id, &[(1, etag)], &options)
+        .await
+        .unwrap();
+    assert!(result.etag.is_some());
+    assert_eq!(mock.pending_uploads(), 0);
+    assert_eq!(
+        mock.object("exports/rows.csv").unwrap().as_ref(),
+        b"a,b\n1,2\n"
+    );
+
+    // 复制到自身后元数据与过期标签都已替换
+    let head = uploader
+        .get_object_metadata("exports/rows.csv")
+        .await
+        .unwrap();
+    assert_eq!(head.user_metadata.get("rows").unwrap(), "2");
+    assert_eq!(
+        mock.object_tagging("exports/rows.csv").as_deref(),
+        Some("cos-upload-expiry-days=3")
+    );
+}
+
+#[tokio::test]
+async fn test_upload_dir_with_manifest() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"alpha").unwrap();
+    std::fs::write(dir.path().join("b.txt"), b"beta").unwrap();
+
+    let opts = BatchOptions {
+        previous_manifest: Some(Arc::new(UploadManifest::new())),
+        ..BatchOptions::default()
+    };
+    let report = uploader
+        .upload_dir(dir.path(), "site/", opts)
+        .await
+        .unwrap();
+    assert_eq!(report.uploaded.len(), 2);
+    let manifest = report.manifest.unwrap();
+    assert_eq!(manifest.get("site/a.txt").unwrap().size, 5);
+
+    std::fs::write(dir.path().join("b.txt"), b"BETA").unwrap();
+    let requests = mock.request_count();
+    let opts = BatchOptions {
+        previous_manifest: Some(Arc::new(manifest.clone())),
+        ..BatchOptions::default()
+    };
+    let report = uploader
+        .upload_dir(dir.path(), "site/", opts)
+        .await
+        .unwrap();
+    assert_eq!(report.uploaded.len(), 1);
+    assert_eq!(report.uploaded[0].0, dir.path().join("b.txt"));
+    assert_eq!(report.unchanged, vec![dir.path().join("a.txt")]);
+    assert_eq!(mock.request_count(), requests + 1);
+    assert_eq!(mock.object("site/b.txt").unwrap().as_ref(), b"BETA");
+
+    let updated = report.manifest.unwrap();
+    assert_eq!(updated.get("site/a.txt"), manifest.get("site/a.txt"));
+    assert_ne!(updated.get("site/b.txt"), manifest.get("site/b.txt"));
+}
+
+#[tokio::test]
+async fn test_upload_dir_dedupe_content() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let dir = tempfile::tempdir().unwrap();
+    std::fs::write(dir.path().join("a.txt"), b"same").unwrap();
+    std::fs::write(dir.path().join("b.txt"), b"same").unwrap();
+    std::fs::write(dir.path().join("c.txt"), b"other").unwrap();
+
+    let opts = BatchOptions {
+        dedupe_content: true,
+        ..BatchOptions::default()
+    };
+    let report = uploader
+        .upload_dir(dir.path(), "site/", opts)
+        .await
+        .unwrap();
+    assert_eq!(report.uploaded.len(), 3);
+    assert_eq!(report.deduplicated.len(), 1);
+    let (path, source_key) = &report.deduplicated[0];
+    assert!(path.ends_with("a.txt") || path.ends_with("b.txt"));
+    assert!(source_key == "site/a.txt" || source_key == "site/b.txt");
+    assert_eq!(mock.object("site/a.txt").unwrap().as_ref(), b"same");
+    assert_eq!(mock.object("site/b.txt").unwrap().as_ref(), b"same");
+    assert_eq!(mock.object("site/c.txt").unwrap().as_ref(), b"other");
+}
+
+#[cfg(feature = "presign")]
+#[tokio::test]
+async fn test_client_multipart_upload() {
+    use cos_upload::{ClientMultipartOptions, PresignWindow, UploadOptions};
+
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let policy = ClientMultipartOptions::new()
+        .with_wave_size(1)
+        .with_window(PresignWindow::new(std::time::Duration::from_secs(60)))
+        .with_refresh_margin(std::time::Duration::from_secs(120));
+    let upload = uploader
+        .start_client_multipart_upload("big.bin", 2, &UploadOptions::new(), policy)
+        .await
+        .unwrap();
+
+    let refreshed = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let sink = refreshed.clone();
+    upload.set_refresh_callback(Arc::new(move |tickets| {
+        sink.lock()
+            .unwrap()
+            .extend(tickets.iter().map(|t| t.part_number));
+    }));
+
+    let client = reqwest::Client::new();
+    let parts = [vec![b'a'; 1024 * 1024], b"tail".to_vec()];
+    for (i, data) in parts.iter().enumerate() {
+        let wave = upload.next_wave();
+        assert_eq!(wave.len(), 1);
+        assert_eq!(wave[0].part_number, i as u32 + 1);
+        assert!(upload.next_wave().is_empty());
+
+        // URL 在刷新提前量之内，立即重新签发
+        let ticket = upload.refresh_expiring().remove(0);
+        let response = client
+            .put(&ticket.url)
+            .body(data.clone())
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        let etag = response.headers()["etag"].to_str().unwrap().to_string();
+        upload.part_completed(ticket.part_number, &etag).unwrap();
+    }
+    assert!(upload.part_completed(3, "\"x\"").is_err());
+    assert_eq!(*refreshed.lock().unwrap(), vec![1, 2]);
+
+    // 完成请求失败后上传仍未结束，可以通过克隆的句柄重试
+    let retry = upload.clone();
+    mock.fail_next(1, 503);
+    assert!(upload.complete().await.is_err());
+    assert_eq!(mock.pending_uploads(), 1);
+    retry.clone().complete().await.unwrap();
+    assert!(retry.abort().await.is_err());
+    assert_eq!(mock.pending_uploads(), 0);
+    let object = mock.object("big.bin").unwrap();
+    assert_eq!(object.len(), 1024 * 1024 + 4);
+    assert!(object.ends_with(b"tail"));
+}
+
+#[tokio::test]
+async fn test_journaled_upload_queue() {
+    let mock = MockCos::start().await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let journal_path = dir.path().join("uploads.journal");
+    let file = temp_file(b"captured frame");
+
+    let journal = UploadJournal::open(&journal_path).await.unwrap();
+    let manager = TransferManager::new(Arc::new(mock.uploader())).with_journal(journal);
+    manager
+        .enqueue_durable(file.path(), "frames/1.jpg", None)
+        .await
+        .unwrap();
+    manager
+        .enqueue_durable(dir.path().join("missing.jpg"), "frames/2.jpg", None)
+        .await
+        .unwrap();
+    drop(manager);
+
+    // 重启后从日志恢复两个条目，成功的一个记为完成
+    let journal = UploadJournal::open(&journal_path).await.unwrap();
+    assert_eq!(journal.pending_len(), 2);
+    let manager = TransferManager::new(Arc::new(mock.uploader())).with_journal(journal);
+    let report = manager.run_queue().await;
+    assert_eq!(report.completed.len(), 1);
+    assert_eq!(report.failed.len(), 1);
+    assert_eq!(
+        mock.object("frames/1.jpg").unwrap().as_ref(),
+        b"captured frame"
+    );
+    drop(manager);
+
+    let journal = UploadJournal::open(&journal_path).await.unwrap();
+    assert_eq!(journal.pending_len(), 1);
+}
+
+#[tokio::test]
+async fn test_upload_queue_failover() {
+    let primary = MockCos::start().await.unwrap();
+    let fallback = MockCos::start().await.unwrap();
+    let dir = tempfile::tempdir().unwrap();
+    let journal = UploadJournal::open(dir.path().join("uploads.journal"))
+        .await
+        .unwrap();
+    let uploader = primary.uploader().with_failover(fallback.config());
+    let manager = TransferManager::new(Arc::new(uploader)).with_journal(journal);
+    let file = temp_file(b"regional incident");
+
+    manager
+        .enqueue_durable(file.path(), "events/1.json", None)
+        .await
+        .unwrap();
+    primary.fail_next(10, 503);
+    let report = manager.run_queue().await;
+    assert!(report.is_complete());
+    assert!(report.completed[0].1.failover.is_some());
+    assert_eq!(
+        fallback.object("events/1.json").unwrap().as_ref(),
+        b"regional incident"
+    );
+}
+
+#[tokio::test]
+async fn test_failover_keeps_uploader_settings() {
+    let primary = MockCos::start().await.unwrap();
+    let fallback = MockCos::start().await.unwrap();
+    let uploader = primary
+        .uploader()
+        .with_event_channel(16)
+        .with_failover(fallback.config());
+    let mut events = uploader.subscribe_events().unwrap();
+    let file = temp_file(b"failover events");
+
+    primary.fail_next(10, 503);
+    let result = uploader
+        .upload_file(file.path(), "events.txt", None)
+        .await
+        .unwrap();
+    assert!(result.failover.is_some());
+    // 主 Bucket 上的上传失败，完成事件只能来自备用上传器
+    let mut completed = Vec::new();
+    while let Ok(event) = events.try_recv() {
+        if let TransferEvent::UploadCompleted { object_key, .. } = event {
+            completed.push(object_key);
+        }
+    }
+    assert_eq!(completed, vec!["events.txt"]);
+}
+
+/// 让第一个请求失败，使上传停在重试的退避中，返回时上传已经开始
+async fn start_stalled_upload(
+    mock: &MockCos,
+    manager: &TransferManager,
+    file_path: std::path::PathBuf,
+    object_key: &'static str,
+) -> tokio::task::JoinHandle<anyhow::Result<cos_upload::UploadResult>> {
+    let requests = mock.request_count();
+    mock.fail_next(1, 503);
+    let manager = manager.clone();
+    let handle =
+        tokio::spawn(async move { manager.upload_file(file_path, object_key, None).await });
+    while mock.request_count() == requests {
+        tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    }
+    handle
+}
+
+#[tokio::test]
+async fn test_upload_queue_waits_for_same_key() {
+    let mock = MockCos::start().await.unwrap();
+    let manager = TransferManager::new(Arc::new(mock.uploader()));
+    let first = temp_file(b"first");
+    let second = temp_file(b"second");
+
+    let running = start_stalled_upload(&mock, &manager, first.path().to_path_buf(), "k.txt").await;
+    manager.enqueue(second.path(), "k.txt", None);
+    let report = manager.run_queue().await;
+    assert!(report.is_complete());
+    running.await.unwrap().unwrap();
+
+    // 队列中的上传排在进行中的上传之后，最终内容是后入队的文件
+    assert_eq!(mock.object("k.txt").unwrap().as_ref(), b"second");
+}
+
+#[tokio::test]
+async fn test_duplicate_policies() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = Arc::new(mock.uploader());
+    let first = temp_file(b"first");
+    let second = temp_file(b"second");
+
+    // 排队：后来的上传在前一个结束后才开始，最终内容是后来的文件
+    let manager = TransferManager::new(uploader.clone());
+    let running =
+        start_stalled_upload(&mock, &manager, first.path().to_path_buf(), "queue.txt").await;
+    manager
+        .upload_file(second.path(), "queue.txt", None)
+        .await
+        .unwrap();
+    assert!(running.is_finished());
+    running.await.unwrap().unwrap();
+    assert_eq!(mock.object("queue.txt").unwrap().as_ref(), b"second");
+
+    // 合并：后来者共享进行中上传的结果，它的文件不会被上传
+    let manager =
+        TransferManager::new(uploader.clone()).with_duplicate_policy(DuplicatePolicy::Coalesce);
+    let running =
+        start_stalled_upload(&mock, &manager, first.path().to_path_buf(), "coalesce.txt").await;
+    let shared = manager
+        .upload_file(second.path(), "coalesce.txt", None)
+        .await
+        .unwrap();
+    assert_eq!(running.await.unwrap().unwrap(), shared);
+    assert_eq!(mock.object("coalesce.txt").unwrap().as_ref(), b"first");
+
+    // 拒绝：立即返回错误，进行中的上传结束后可以再次上传
+    let manager = TransferManager::new(uploader).with_duplicate_policy(DuplicatePolicy::Reject);
+    let running =
+        start_stalled_upload(&mock, &manager, first.path().to_path_buf(), "reject.txt").await;
+    let error = manager
+        .upload_file(second.path(), "reject.txt", None)
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<DuplicateUploadError>(),
+        Some(&DuplicateUploadError {
+            object_key: "reject.txt".to_string()
+        })
+    );
+    running.await.unwrap().unwrap();
+    manager
+        .upload_file(second.path(), "reject.txt", None)
+        .await
+        .unwrap();
+    assert_eq!(mock.object("reject.txt").unwrap().as_ref(), b"second");
+}
+
+#[tokio::test]
+async fn test_upload_open_file() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let options = cos_upload::UploadOptions::new();
+
+    let small = temp_file(b"hello");
+    let file = std::fs::File::open(small.path()).unwrap();
+    uploader
+        .upload_open_file(file, 5, "small.txt", &options)
+        .await
+        .unwrap();
+    assert_eq!(mock.object("small.txt").unwrap().as_ref(), b"hello");
+
+    let content: Vec<u8> = (0..6 * 1024 * 1024).map(|i| (i % 251) as u8).collect();
+    let large = temp_file(&content);
+    let file = tokio::fs::File::open(large.path()).await.unwrap();
+    let len = content.len() as u64;
+    uploader
+        .upload_open_file(file, len, "large.bin", &options)
+        .await
+        .unwrap();
+    assert_eq!(
+        mock.object("large.bin").unwrap().as_ref(),
+        content.as_slice()
+    );
+
+    let file = std::fs::File::open(small.path()).unwrap();
+    assert!(uploader
+        .upload_open_file(file, 10, "short.txt", &options)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn test_failover_and_shadow_ignore_primary_target() {
+    let primary = MockCos::start().await.unwrap();
+    let fallback = MockCos::start().await.unwrap();
+    let shadow = MockCos::start().await.unwrap();
+    // 路径风格下 Bucket 出现在服务器收到的对象键中，可以看出实际写入的 Bucket
+    let secondary = |mock: &MockCos, bucket: &str| Config {
+        bucket: bucket.to_string(),
+        ..mock
+            .config()
+            .with_addressing_style(cos_upload::AddressingStyle::Path)
+    };
+    let uploader = primary
+        .uploader()
+        .with_failover(secondary(&fallback, "fallback-1250000000"))
+        .with_shadow(secondary(&shadow, "shadow-1250000000"));
+    // 覆盖的 Bucket 就是主 Bucket
+    let options = cos_upload::UploadOptions::new().with_bucket(MOCK_BUCKET.to_string());
+    let file = temp_file(b"failover");
+
+    uploader
+        .upload_file_with_options(file.path(), "mirrored.txt", &options)
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if !shadow.object_keys().is_empty() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(shadow.object_keys(), vec!["shadow-1250000000/mirrored.txt"]);
+
+    primary.fail_next(10, 503);
+    let result = uploader
+        .upload_file_with_options(file.path(), "failover.txt", &options)
+        .await
+        .unwrap();
+    assert_eq!(result.failover.unwrap().bucket, "fallback-1250000000");
+    assert_eq!(
+        fallback.object_keys(),
+        vec!["fallback-1250000000/failover.txt"]
+    );
+}
+
+#[tokio::test]
+async fn test_upload_group_sidecar() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let a = temp_file(b"first");
+    let b = temp_file(b"second");
+    let files = [
+        (a.path(), "group/a.txt".to_string()),
+        (b.path(), "group/b.txt".to_string()),
+    ];
+    let options = cos_upload::UploadOptions::new().with_checksum_sidecar(true);
+
+    let result = uploader
+        .upload_group_with_options(&files, &options)
+        .await
+        .unwrap();
+    assert_eq!(result.published.len(), 2);
+    // 旁路文件只为最终的对象写入，暂存对象全部清理
+    assert_eq!(
+        mock.object_keys(),
+        vec![
+            "group/a.txt",
+            "group/a.txt.crc64",
+            "group/b.txt",
+            "group/b.txt.crc64"
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_upload_open_file_failover_and_shadow() {
+    let primary = MockCos::start().await.unwrap();
+    let fallback = MockCos::start().await.unwrap();
+    let shadow = MockCos::start().await.unwrap();
+    let uploader = primary
+        .uploader()
+        .with_failover(fallback.config())
+        .with_shadow(shadow.config());
+    let options = cos_upload::UploadOptions::new();
+    let source = temp_file(b"open handle");
+
+    let file = std::fs::File::open(source.path()).unwrap();
+    uploader
+        .upload_open_file(file, 11, "mirrored.txt", &options)
+        .await
+        .unwrap();
+    for _ in 0..100 {
+        if shadow.object("mirrored.txt").is_some() {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    }
+    assert_eq!(
+        shadow.object("mirrored.txt").unwrap().as_ref(),
+        b"open handle"
+    );
+
+    primary.fail_next(10, 503);
+    let file = std::fs::File::open(source.path()).unwrap();
+    let result = uploader
+        .upload_open_file(file, 11, "failover.txt", &options)
+        .await
+        .unwrap();
+    assert!(result.failover.is_some());
+    assert_eq!(
+        fallback.object("failover.txt").unwrap().as_ref(),
+        b"open handle"
+    );
+}
+
+#[cfg(feature = "gzip")]
+#[tokio::test]
+async fn test_json_gzip_round_trip() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let value = serde_json::json!({ "name": "状态", "items": [1, 2, 3] });
+
+    uploader.put_json("plain.json", &value).await.unwrap();
+    assert_eq!(
+        mock.object("plain.json").unwrap().as_ref(),
+        serde_json::to_vec(&value).unwrap().as_slice()
+    );
+    let plain = uploader
+        .get_json::<serde_json::Value>("plain.json")
+        .await
+        .unwrap();
+    assert_eq!(plain.value, value);
+
+    uploader.put_json_gzip("state.json", &value).await.unwrap();
+    let stored = mock.object("state.json").unwrap();
+    assert_eq!(&stored[..2], &[0x1f, 0x8b]);
+    let document = uploader
+        .get_json::<serde_json::Value>("state.json")
+        .await
+        .unwrap();
+    assert_eq!(document.value, value);
+    assert!(document.etag.is_some());
+}
+
+#[cfg(feature = "zip")]
+#[tokio::test]
+async fn test_download_as_zip() {
+    use std::io::Read;
+
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let large = vec![7u8; 300 * 1024];
+    uploader
+        .upload_file(temp_file(b"hello").path(), "export/a.txt", None)
+        .await
+        .unwrap();
+    uploader
+        .upload_file(temp_file(&large).path(), "export/sub/b.bin", None)
+        .await
+        .unwrap();
+
+    let selection = cos_upload::ArchiveSelection::Prefix("export/".to_string());
+    // 打包的 future 可以交给 tokio 在后台执行
+    let count = tokio::spawn(async move {
+        let mut archive = Vec::new();
+        let count = uploader
+            .download_as_zip(&selection, &mut archive)
+            .await
+            .unwrap();
+        (count, archive)
+    });
+    let (count, archive) = count.await.unwrap();
+    assert_eq!(count, 2);
+
+    let mut zip = zip::ZipArchive::new(std::io::Cursor::new(archive)).unwrap();
+    assert_eq!(zip.len(), 2);
+    let mut content = String::new();
+    zip.by_name("export/a.txt")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+    assert_eq!(content, "hello");
+    let mut data = Vec::new();
+    zip.by_name("export/sub/b.bin")
+        .unwrap()
+        .read_to_end(&mut data)
+        .unwrap();
+    assert_eq!(data, large);
+}
+
+#[tokio::test]
+async fn test_upload_group_rejects_long_staging_key() {
+    let mock = MockCos::start().await.unwrap();
+    let uploader = mock.uploader();
+    let a = temp_file(b"first");
+    let b = temp_file(b"second");
+    // 最终的对象键在长度上限之内，加上暂存前缀后超出
+    let files = [
+        (a.path(), "group/a.txt".to_string()),
+        (b.path(), format!("group/{}", "b".repeat(830))),
+    ];
+
+    let error = uploader.upload_group(&files).await.unwrap_err();
+    assert!(matches!(
+        error.downcast_ref(),
+        Some(CosError::InvalidObjectKey { .. })
+    ));
+    assert_eq!(mock.request_count(), 0);
+    assert!(mock.object_keys().is_empty());
+}